@@ -0,0 +1,68 @@
+use aptos_crypto::HashValue;
+use aptos_types::transaction::Version;
+use std::fmt;
+
+/// Why the store refused an update or a proof.
+#[derive(Debug)]
+pub enum LightClientError {
+    /// The bootstrap ledger info does not hash to the trusted waypoint.
+    WaypointMismatch(anyhow::Error),
+    /// A ledger info that must end an epoch does not carry the next
+    /// validator set.
+    NotEpochEnding { epoch: u64, version: Version },
+    /// A ledger info from a later epoch arrived without the epoch changes
+    /// leading up to it.
+    EpochMismatch { trusted: u64, update: u64 },
+    /// The quorum signature does not verify against the trusted validators.
+    InvalidSignatures(anyhow::Error),
+    /// The update is older than the latest trusted ledger info.
+    Stale {
+        trusted_version: Version,
+        update_version: Version,
+    },
+    /// Validators signed two different ledger infos for the same version,
+    /// or a different end to an epoch the store already crossed.
+    Fork {
+        version: Version,
+        trusted: HashValue,
+        update: HashValue,
+    },
+    /// A transaction or state proof does not verify against the trusted
+    /// ledger info.
+    InvalidProof(anyhow::Error),
+}
+
+impl fmt::Display for LightClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WaypointMismatch(err) => write!(f, "waypoint mismatch: {err}"),
+            Self::NotEpochEnding { epoch, version } => write!(
+                f,
+                "ledger info at version {version} does not end epoch {epoch}"
+            ),
+            Self::EpochMismatch { trusted, update } => write!(
+                f,
+                "update is for epoch {update} but the trusted epoch is {trusted}"
+            ),
+            Self::InvalidSignatures(err) => write!(f, "invalid quorum signature: {err}"),
+            Self::Stale {
+                trusted_version,
+                update_version,
+            } => write!(
+                f,
+                "stale update: version {update_version} is older than trusted version {trusted_version}"
+            ),
+            Self::Fork {
+                version,
+                trusted,
+                update,
+            } => write!(
+                f,
+                "fork at version {version}: trusted ledger info {trusted}, update {update}"
+            ),
+            Self::InvalidProof(err) => write!(f, "invalid proof: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for LightClientError {}
@@ -0,0 +1,13 @@
+//! Native Aptos light client.
+//!
+//! [`LightClientStore`] follows a chain from a trusted waypoint by verifying
+//! quorum-signed ledger infos and epoch changes, and checks transaction and
+//! state proofs against the latest ledger info it trusts. It needs no ZK
+//! machinery; the halo2 circuits take their witnesses from it and must
+//! accept exactly the updates it accepts.
+
+mod error;
+mod store;
+
+pub use error::LightClientError;
+pub use store::LightClientStore;
@@ -0,0 +1,229 @@
+use crate::error::LightClientError;
+use aptos_crypto::hash::CryptoHash;
+use aptos_types::{
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    proof::{SparseMerkleProof, TransactionInfoWithProof},
+    state_proof::StateProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+    waypoint::Waypoint,
+};
+
+/// Trusted view of an Aptos chain, ratcheted forward by signed updates.
+///
+/// The store holds the validator set of the current epoch, the waypoint of
+/// the ledger info that installed it, and the latest ledger info the store
+/// has accepted. Every update must be signed by a quorum of the current
+/// validators; an epoch-ending ledger info hands trust to the next set.
+#[derive(Clone, Debug)]
+pub struct LightClientStore {
+    waypoint: Waypoint,
+    epoch_state: EpochState,
+    latest: LedgerInfoWithSignatures,
+}
+
+impl LightClientStore {
+    /// Bootstraps from a waypoint and the epoch-ending ledger info it
+    /// commits to. Its signatures are not checked: the waypoint is the root
+    /// of trust.
+    pub fn new(
+        waypoint: Waypoint,
+        epoch_ending: LedgerInfoWithSignatures,
+    ) -> Result<Self, LightClientError> {
+        let ledger_info = epoch_ending.ledger_info();
+        waypoint
+            .verify(ledger_info)
+            .map_err(LightClientError::WaypointMismatch)?;
+        let epoch_state = ledger_info
+            .next_epoch_state()
+            .cloned()
+            .ok_or_else(|| not_epoch_ending(ledger_info))?;
+        Ok(Self {
+            waypoint,
+            epoch_state,
+            latest: epoch_ending,
+        })
+    }
+
+    /// Waypoint of the epoch boundary that installed the current validators.
+    pub fn waypoint(&self) -> Waypoint {
+        self.waypoint
+    }
+
+    pub fn epoch_state(&self) -> &EpochState {
+        &self.epoch_state
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch_state.epoch
+    }
+
+    pub fn latest_ledger_info(&self) -> &LedgerInfoWithSignatures {
+        &self.latest
+    }
+
+    pub fn version(&self) -> Version {
+        self.latest.ledger_info().version()
+    }
+
+    /// Walks a chain of epoch-ending ledger infos, each signed by the
+    /// validators the previous one installed.
+    ///
+    /// Ledger infos for epochs the store has already crossed are skipped,
+    /// except that the one ending the previous epoch must match the trusted
+    /// waypoint.
+    pub fn verify_epoch_change(
+        &mut self,
+        proof: &EpochChangeProof,
+    ) -> Result<(), LightClientError> {
+        for epoch_ending in &proof.ledger_info_with_sigs {
+            let ledger_info = epoch_ending.ledger_info();
+            if ledger_info.epoch() < self.epoch() {
+                if ledger_info.epoch() + 1 == self.epoch() {
+                    self.check_waypoint(ledger_info)?;
+                }
+                continue;
+            }
+            if !ledger_info.ends_epoch() {
+                return Err(not_epoch_ending(ledger_info));
+            }
+            self.verify_signatures(epoch_ending)?;
+            self.check_version(ledger_info)?;
+            self.ratchet(epoch_ending);
+        }
+        Ok(())
+    }
+
+    /// Accepts a ledger info signed by the current validators. If it ends
+    /// the epoch, the store moves on to the next one.
+    ///
+    /// Re-submitting the latest trusted ledger info is a no-op.
+    pub fn update(&mut self, update: &LedgerInfoWithSignatures) -> Result<(), LightClientError> {
+        let ledger_info = update.ledger_info();
+        if ledger_info == self.latest.ledger_info() {
+            return Ok(());
+        }
+        if ledger_info.epoch() < self.epoch() {
+            return Err(LightClientError::Stale {
+                trusted_version: self.version(),
+                update_version: ledger_info.version(),
+            });
+        }
+        self.verify_signatures(update)?;
+        self.check_version(ledger_info)?;
+        self.ratchet(update);
+        Ok(())
+    }
+
+    /// Applies the epoch changes of a state proof, then its latest ledger
+    /// info.
+    pub fn verify_state_proof(&mut self, proof: &StateProof) -> Result<(), LightClientError> {
+        self.verify_epoch_change(proof.epoch_changes())?;
+        self.update(proof.latest_ledger_info_w_sigs())
+    }
+
+    /// Checks that the transaction at `version` is committed under the
+    /// latest trusted ledger info.
+    pub fn verify_transaction_info(
+        &self,
+        version: Version,
+        proof: &TransactionInfoWithProof,
+    ) -> Result<(), LightClientError> {
+        proof
+            .verify(self.latest.ledger_info(), version)
+            .map_err(LightClientError::InvalidProof)
+    }
+
+    /// Checks `value` (or its absence) under `key` in the state checkpoint
+    /// committed by the transaction at `version`.
+    ///
+    /// Account resources are state values at `StateKey::resource`, so this
+    /// also verifies account proofs.
+    pub fn verify_state_value(
+        &self,
+        key: &StateKey,
+        value: Option<&StateValue>,
+        version: Version,
+        transaction_info: &TransactionInfoWithProof,
+        proof: &SparseMerkleProof,
+    ) -> Result<(), LightClientError> {
+        self.verify_transaction_info(version, transaction_info)?;
+        let state_root = transaction_info
+            .transaction_info()
+            .state_checkpoint_hash()
+            .ok_or_else(|| {
+                LightClientError::InvalidProof(anyhow::anyhow!(
+                    "transaction {version} is not a state checkpoint"
+                ))
+            })?;
+        proof
+            .verify(state_root, key.hash(), value)
+            .map_err(LightClientError::InvalidProof)
+    }
+
+    fn verify_signatures(&self, update: &LedgerInfoWithSignatures) -> Result<(), LightClientError> {
+        let epoch = update.ledger_info().epoch();
+        if epoch != self.epoch() {
+            return Err(LightClientError::EpochMismatch {
+                trusted: self.epoch(),
+                update: epoch,
+            });
+        }
+        update
+            .verify_signatures(&self.epoch_state.verifier)
+            .map_err(|err| LightClientError::InvalidSignatures(err.into()))
+    }
+
+    /// Rejects updates older than the latest trusted ledger info, and a
+    /// second ledger info for the same version that commits to a different
+    /// history.
+    fn check_version(&self, update: &LedgerInfo) -> Result<(), LightClientError> {
+        let trusted = self.latest.ledger_info();
+        if update.version() < trusted.version() {
+            return Err(LightClientError::Stale {
+                trusted_version: trusted.version(),
+                update_version: update.version(),
+            });
+        }
+        if update.version() == trusted.version()
+            && update.transaction_accumulator_hash() != trusted.transaction_accumulator_hash()
+        {
+            return Err(LightClientError::Fork {
+                version: update.version(),
+                trusted: trusted.transaction_accumulator_hash(),
+                update: update.transaction_accumulator_hash(),
+            });
+        }
+        Ok(())
+    }
+
+    fn check_waypoint(&self, epoch_ending: &LedgerInfo) -> Result<(), LightClientError> {
+        if self.waypoint.verify(epoch_ending).is_ok() {
+            return Ok(());
+        }
+        Err(LightClientError::Fork {
+            version: epoch_ending.version(),
+            trusted: self.waypoint.value(),
+            update: Waypoint::new_any(epoch_ending).value(),
+        })
+    }
+
+    fn ratchet(&mut self, update: &LedgerInfoWithSignatures) {
+        let ledger_info = update.ledger_info();
+        if let Some(next_epoch_state) = ledger_info.next_epoch_state() {
+            self.epoch_state = next_epoch_state.clone();
+            self.waypoint = Waypoint::new_epoch_boundary(ledger_info)
+                .expect("ledger info carries the next epoch state");
+        }
+        self.latest = update.clone();
+    }
+}
+
+fn not_epoch_ending(ledger_info: &LedgerInfo) -> LightClientError {
+    LightClientError::NotEpochEnding {
+        epoch: ledger_info.epoch(),
+        version: ledger_info.version(),
+    }
+}
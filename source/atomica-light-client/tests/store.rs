@@ -0,0 +1,340 @@
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_types::{
+    aggregate_signature::{AggregateSignature, PartialSignatures},
+    block_info::BlockInfo,
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    proof::{
+        SparseMerkleLeafNode, SparseMerkleProof, TransactionAccumulatorProof,
+        TransactionInfoWithProof,
+    },
+    state_proof::StateProof,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::{ExecutionStatus, TransactionInfo, Version},
+    validator_signer::ValidatorSigner,
+    validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier},
+    waypoint::Waypoint,
+};
+use atomica_light_client::{LightClientError, LightClientStore};
+use std::sync::Arc;
+
+fn validators(seed: u8) -> Vec<ValidatorSigner> {
+    (0..4)
+        .map(|i| ValidatorSigner::random([seed * 16 + i; 32]))
+        .collect()
+}
+
+fn epoch_state(epoch: u64, signers: &[ValidatorSigner]) -> EpochState {
+    let infos = signers
+        .iter()
+        .map(|signer| ValidatorConsensusInfo::new(signer.author(), signer.public_key(), 1))
+        .collect();
+    EpochState::new(epoch, Arc::new(ValidatorVerifier::new(infos)))
+}
+
+/// A ledger info at `version`, signed by every validator in `signers`.
+fn signed(
+    epoch: u64,
+    version: Version,
+    accumulator_root: HashValue,
+    next_epoch_state: Option<EpochState>,
+    signers: &[ValidatorSigner],
+) -> LedgerInfoWithSignatures {
+    let block = BlockInfo::new(
+        epoch,
+        0,
+        HashValue::sha3_256_of(&version.to_le_bytes()),
+        accumulator_root,
+        version,
+        version * 1_000,
+        next_epoch_state,
+    );
+    let ledger_info = LedgerInfo::new(block, HashValue::zero());
+    if signers.is_empty() {
+        return LedgerInfoWithSignatures::new(ledger_info, AggregateSignature::empty());
+    }
+    let mut partial = PartialSignatures::empty();
+    for signer in signers {
+        partial.add_signature(signer.author(), signer.sign(&ledger_info).unwrap());
+    }
+    let verifier = ValidatorVerifier::new(
+        signers
+            .iter()
+            .map(|signer| ValidatorConsensusInfo::new(signer.author(), signer.public_key(), 1))
+            .collect(),
+    );
+    let aggregated = verifier
+        .aggregate_signatures(partial.signatures_iter())
+        .unwrap();
+    LedgerInfoWithSignatures::new(ledger_info, aggregated)
+}
+
+/// A stand-in transaction accumulator root for `version`.
+fn root(version: Version) -> HashValue {
+    HashValue::sha3_256_of(&[&b"accumulator"[..], &version.to_le_bytes()].concat())
+}
+
+/// Genesis hands over to validator set A for epoch 1, whose last ledger info
+/// hands over to set B for epoch 2.
+struct Chain {
+    set_a: Vec<ValidatorSigner>,
+    set_b: Vec<ValidatorSigner>,
+    genesis: LedgerInfoWithSignatures,
+    epoch_1_end: LedgerInfoWithSignatures,
+}
+
+impl Chain {
+    fn new() -> Self {
+        let set_a = validators(1);
+        let set_b = validators(2);
+        // Genesis is trusted through the waypoint, not through signatures.
+        let genesis = signed(0, 0, root(0), Some(epoch_state(1, &set_a)), &[]);
+        let epoch_1_end = signed(1, 10, root(10), Some(epoch_state(2, &set_b)), &set_a);
+        Self {
+            set_a,
+            set_b,
+            genesis,
+            epoch_1_end,
+        }
+    }
+
+    fn waypoint(&self) -> Waypoint {
+        Waypoint::new_epoch_boundary(self.genesis.ledger_info()).unwrap()
+    }
+
+    fn store(&self) -> LightClientStore {
+        LightClientStore::new(self.waypoint(), self.genesis.clone()).unwrap()
+    }
+
+    /// A store that has crossed into epoch 2.
+    fn store_in_epoch_2(&self) -> LightClientStore {
+        let mut store = self.store();
+        store
+            .verify_epoch_change(&EpochChangeProof::new(
+                vec![self.epoch_1_end.clone()],
+                false,
+            ))
+            .unwrap();
+        store
+    }
+}
+
+#[test]
+fn test_follows_epoch_changes() {
+    let chain = Chain::new();
+    let mut store = chain.store();
+    assert_eq!(store.epoch(), 1);
+    assert_eq!(store.waypoint(), chain.waypoint());
+
+    store
+        .verify_epoch_change(&EpochChangeProof::new(
+            vec![chain.epoch_1_end.clone()],
+            false,
+        ))
+        .unwrap();
+    assert_eq!(store.epoch(), 2);
+    assert_eq!(
+        store.waypoint(),
+        Waypoint::new_epoch_boundary(chain.epoch_1_end.ledger_info()).unwrap()
+    );
+
+    let latest = signed(2, 20, root(20), None, &chain.set_b);
+    store.update(&latest).unwrap();
+    assert_eq!(store.version(), 20);
+    assert_eq!(store.latest_ledger_info(), &latest);
+    // Replaying what the store already trusts changes nothing.
+    store.update(&latest).unwrap();
+    store
+        .verify_epoch_change(&EpochChangeProof::new(
+            vec![chain.epoch_1_end.clone()],
+            false,
+        ))
+        .unwrap();
+    assert_eq!(store.version(), 20);
+}
+
+#[test]
+fn test_rejects_wrong_waypoint() {
+    let chain = Chain::new();
+    let waypoint = Waypoint::new_epoch_boundary(chain.epoch_1_end.ledger_info()).unwrap();
+    let err = LightClientStore::new(waypoint, chain.genesis.clone()).unwrap_err();
+    assert!(matches!(err, LightClientError::WaypointMismatch(_)));
+}
+
+#[test]
+fn test_rejects_bootstrap_from_non_epoch_ending_ledger_info() {
+    let chain = Chain::new();
+    let mid_epoch = signed(1, 5, root(5), None, &chain.set_a);
+    let waypoint = Waypoint::new_any(mid_epoch.ledger_info());
+    let err = LightClientStore::new(waypoint, mid_epoch).unwrap_err();
+    assert!(matches!(
+        err,
+        LightClientError::NotEpochEnding {
+            epoch: 1,
+            version: 5
+        }
+    ));
+}
+
+#[test]
+fn test_rejects_signatures_from_other_validators() {
+    let chain = Chain::new();
+    let mut store = chain.store();
+    let forged = signed(
+        1,
+        10,
+        root(10),
+        Some(epoch_state(2, &chain.set_b)),
+        &chain.set_b,
+    );
+    let err = store
+        .verify_epoch_change(&EpochChangeProof::new(vec![forged], false))
+        .unwrap_err();
+    assert!(matches!(err, LightClientError::InvalidSignatures(_)));
+    assert_eq!(store.epoch(), 1);
+}
+
+#[test]
+fn test_rejects_minority_signatures() {
+    let chain = Chain::new();
+    let mut store = chain.store();
+    // Two of four equal-power validators are short of a 2f + 1 quorum.
+    let update = signed(1, 5, root(5), None, &chain.set_a[..2]);
+    let err = store.update(&update).unwrap_err();
+    assert!(matches!(err, LightClientError::InvalidSignatures(_)));
+}
+
+#[test]
+fn test_rejects_future_epoch_without_epoch_change() {
+    let chain = Chain::new();
+    let mut store = chain.store();
+    let err = store
+        .update(&signed(2, 20, root(20), None, &chain.set_b))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        LightClientError::EpochMismatch {
+            trusted: 1,
+            update: 2
+        }
+    ));
+}
+
+#[test]
+fn test_rejects_non_epoch_ending_epoch_change() {
+    let chain = Chain::new();
+    let mut store = chain.store();
+    let mid_epoch = signed(1, 5, root(5), None, &chain.set_a);
+    let err = store
+        .verify_epoch_change(&EpochChangeProof::new(vec![mid_epoch], false))
+        .unwrap_err();
+    assert!(matches!(err, LightClientError::NotEpochEnding { .. }));
+}
+
+#[test]
+fn test_rejects_stale_updates() {
+    let chain = Chain::new();
+    let mut store = chain.store_in_epoch_2();
+    store
+        .update(&signed(2, 20, root(20), None, &chain.set_b))
+        .unwrap();
+
+    let older = signed(2, 15, root(15), None, &chain.set_b);
+    let err = store.update(&older).unwrap_err();
+    assert!(matches!(
+        err,
+        LightClientError::Stale {
+            trusted_version: 20,
+            update_version: 15
+        }
+    ));
+
+    let previous_epoch = signed(1, 8, root(8), None, &chain.set_a);
+    let err = store.update(&previous_epoch).unwrap_err();
+    assert!(matches!(err, LightClientError::Stale { .. }));
+}
+
+#[test]
+fn test_rejects_conflicting_ledger_info_at_trusted_version() {
+    let chain = Chain::new();
+    let mut store = chain.store_in_epoch_2();
+    store
+        .update(&signed(2, 20, root(20), None, &chain.set_b))
+        .unwrap();
+
+    let conflicting = signed(2, 20, root(21), None, &chain.set_b);
+    let err = store.update(&conflicting).unwrap_err();
+    assert!(matches!(err, LightClientError::Fork { version: 20, .. }));
+}
+
+#[test]
+fn test_rejects_conflicting_epoch_end() {
+    let chain = Chain::new();
+    let mut store = chain.store_in_epoch_2();
+    // Validator set A also signed a different end to epoch 1.
+    let conflicting = signed(
+        1,
+        11,
+        root(11),
+        Some(epoch_state(2, &chain.set_b)),
+        &chain.set_a,
+    );
+    let err = store
+        .verify_epoch_change(&EpochChangeProof::new(vec![conflicting], false))
+        .unwrap_err();
+    assert!(matches!(err, LightClientError::Fork { version: 11, .. }));
+}
+
+#[test]
+fn test_verifies_state_proof() {
+    let chain = Chain::new();
+    let mut store = chain.store();
+    let latest = signed(2, 20, root(20), None, &chain.set_b);
+    let proof = StateProof::new(
+        latest.clone(),
+        EpochChangeProof::new(vec![chain.epoch_1_end.clone()], false),
+    );
+    store.verify_state_proof(&proof).unwrap();
+    assert_eq!(store.epoch(), 2);
+    assert_eq!(store.latest_ledger_info(), &latest);
+}
+
+#[test]
+fn test_verifies_state_values_against_latest_ledger_info() {
+    let chain = Chain::new();
+    let mut store = chain.store_in_epoch_2();
+
+    // A one-leaf state tree committed by a one-transaction accumulator.
+    let key = StateKey::raw(b"atomica::bridge::Vault");
+    let value = StateValue::from(b"locked: 1000".to_vec());
+    let leaf = SparseMerkleLeafNode::new(key.hash(), value.hash());
+    let state_root = leaf.hash();
+    let transaction_info =
+        TransactionInfo::new_placeholder(100, Some(state_root), ExecutionStatus::Success);
+    let transaction_proof = TransactionInfoWithProof::new(
+        TransactionAccumulatorProof::new(vec![]),
+        transaction_info.clone(),
+    );
+    store
+        .update(&signed(2, 20, transaction_info.hash(), None, &chain.set_b))
+        .unwrap();
+
+    let proof = SparseMerkleProof::new(Some(leaf), vec![]);
+    store
+        .verify_state_value(&key, Some(&value), 0, &transaction_proof, &proof)
+        .unwrap();
+
+    let forged = StateValue::from(b"locked: 9999".to_vec());
+    let err = store
+        .verify_state_value(&key, Some(&forged), 0, &transaction_proof, &proof)
+        .unwrap_err();
+    assert!(matches!(err, LightClientError::InvalidProof(_)));
+
+    // The same proof does not verify against another ledger info.
+    let other_store = Chain::new().store_in_epoch_2();
+    let err = other_store
+        .verify_state_value(&key, Some(&value), 0, &transaction_proof, &proof)
+        .unwrap_err();
+    assert!(matches!(err, LightClientError::InvalidProof(_)));
+}
@@ -0,0 +1,200 @@
+//! Human-readable Move abort codes.
+//!
+//! Aptos packages carry an error map in each module's metadata, built from
+//! the doc comments on `E*` constants. [`ErrorMaps`] collects those maps
+//! from a [`ReleaseBundle`] or from published packages, plus the module
+//! sources when a package was published with them, and decodes an abort
+//! code into its constant, category and description.
+
+use std::{collections::BTreeMap, fmt};
+
+use aptos_framework::{natives::code::PackageMetadata, unzip_metadata_str, ReleaseBundle};
+use aptos_types::vm::module_metadata::get_metadata_from_compiled_code;
+use move_binary_format::CompiledModule;
+use move_core_types::{errmap::ErrorDescription, language_storage::ModuleId};
+use serde::Serialize;
+
+/// Categories from `std::error`, by their code in the top bits of an abort
+/// code.
+const CATEGORIES: [(u64, &str); 13] = [
+    (0x1, "invalid_argument"),
+    (0x2, "out_of_range"),
+    (0x3, "invalid_state"),
+    (0x4, "unauthenticated"),
+    (0x5, "permission_denied"),
+    (0x6, "not_found"),
+    (0x7, "aborted"),
+    (0x8, "already_exists"),
+    (0x9, "resource_exhausted"),
+    (0xA, "cancelled"),
+    (0xB, "internal"),
+    (0xC, "not_implemented"),
+    (0xD, "unavailable"),
+];
+
+/// Where an error constant is declared.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SourceLocation {
+    pub file: String,
+    /// 1-based
+    pub line: usize,
+}
+
+/// A decoded abort code.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AbortExplanation {
+    pub module: String,
+    pub code: u64,
+    /// The `std::error` category, e.g. `invalid_argument`
+    pub category: Option<&'static str>,
+    /// The code without its category
+    pub reason: u64,
+    /// The error constant, e.g. `E_EXCEEDS_MAX_MINT`
+    pub name: Option<String>,
+    /// The constant's doc comment
+    pub description: Option<String>,
+    pub source: Option<SourceLocation>,
+}
+
+impl fmt::Display for AbortExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}::", self.module)?;
+        match &self.name {
+            Some(name) => write!(f, "{}", name)?,
+            None => write!(f, "{}", self.reason)?,
+        }
+        if let Some(category) = self.category {
+            write!(f, " ({})", category)?;
+        }
+        if let Some(description) = &self.description {
+            write!(f, ": {}", description)?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, " [{}:{}]", source.file, source.line)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct ModuleErrors {
+    codes: BTreeMap<u64, ErrorDescription>,
+    /// Source path and text
+    source: Option<(String, String)>,
+}
+
+/// Error maps of every module added so far, by module id.
+#[derive(Default)]
+pub struct ErrorMaps {
+    modules: BTreeMap<ModuleId, ModuleErrors>,
+}
+
+impl ErrorMaps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Error maps of every module in the bundle's packages
+    pub fn from_release_bundle(bundle: &ReleaseBundle) -> Self {
+        let mut maps = Self::new();
+        for package in &bundle.packages {
+            let modules = package
+                .code()
+                .iter()
+                .filter_map(|code| CompiledModule::deserialize(code).ok());
+            maps.add_package(package.package_metadata(), modules);
+        }
+        maps
+    }
+
+    /// Add a module's error map from its metadata
+    pub fn add_module(&mut self, module: &CompiledModule) {
+        let entry = self.modules.entry(module.self_id()).or_default();
+        if let Some(metadata) = get_metadata_from_compiled_code(module) {
+            entry.codes.extend(metadata.error_map);
+        }
+    }
+
+    /// Add a package's modules, with their sources when the package was
+    /// published with them
+    pub fn add_package(
+        &mut self,
+        metadata: &PackageMetadata,
+        modules: impl IntoIterator<Item = CompiledModule>,
+    ) {
+        for module in modules {
+            self.add_module(&module);
+            let id = module.self_id();
+            let source = metadata
+                .modules
+                .iter()
+                .find(|m| m.name == id.name().as_str())
+                .filter(|m| !m.source.is_empty())
+                .and_then(|m| unzip_metadata_str(&m.source).ok());
+            if let Some(source) = source {
+                let path = format!("{}/sources/{}.move", metadata.name, id.name());
+                self.add_source(&id, path, source);
+            }
+        }
+    }
+
+    /// Attach source text to a module, for locating its error constants
+    pub fn add_source(&mut self, module: &ModuleId, path: String, source: String) {
+        self.modules.entry(module.clone()).or_default().source = Some((path, source));
+    }
+
+    pub fn contains(&self, module: &ModuleId) -> bool {
+        self.modules.contains_key(module)
+    }
+
+    /// Decode `code` aborted with in `module`
+    ///
+    /// Framework modules key their error maps by reason (the code without
+    /// its category); codes raised without a category are looked up as is.
+    pub fn decode(&self, module: &ModuleId, code: u64) -> AbortExplanation {
+        let category = code >> 16;
+        let reason = code & 0xFFFF;
+        let errors = self.modules.get(module);
+        let description = errors.and_then(|errors| {
+            errors
+                .codes
+                .get(&reason)
+                .or_else(|| errors.codes.get(&code))
+        });
+        let source = errors.zip(description).and_then(|(errors, description)| {
+            let (path, text) = errors.source.as_ref()?;
+            let line = constant_line(text, &description.code_name)?;
+            Some(SourceLocation {
+                file: path.clone(),
+                line,
+            })
+        });
+        AbortExplanation {
+            module: module.short_str_lossless(),
+            code,
+            category: CATEGORIES
+                .iter()
+                .find(|(id, _)| *id == category)
+                .map(|(_, name)| *name),
+            reason,
+            name: description.map(|d| d.code_name.clone()),
+            description: description
+                .map(|d| d.code_description.clone())
+                .filter(|d| !d.is_empty()),
+            source,
+        }
+    }
+}
+
+/// The 1-based line declaring `const <name>`
+fn constant_line(source: &str, name: &str) -> Option<usize> {
+    source
+        .lines()
+        .position(|line| {
+            line.trim_start()
+                .strip_prefix("const ")
+                .and_then(|rest| rest.trim_start().strip_prefix(name))
+                .is_some_and(|rest| rest.trim_start().starts_with(':'))
+        })
+        .map(|idx| idx + 1)
+}
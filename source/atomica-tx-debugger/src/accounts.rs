@@ -0,0 +1,83 @@
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    HashValue,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    function_info::FunctionInfo,
+    transaction::authenticator::{AuthenticationKey, MultiKey},
+};
+
+/// Address of the APT fungible asset metadata object.
+pub const APT_METADATA: AccountAddress = AccountAddress::TEN;
+
+/// Domain separators from `0x1::object` and `0x1::account_abstraction`.
+const DERIVABLE_ABSTRACTION_SCHEME: u8 = 0x05;
+const USER_DERIVED_OBJECT_SCHEME: u8 = 0xFC;
+const NAMED_OBJECT_SCHEME: u8 = 0xFE;
+
+/// The key an account is seeded with, which fixes its authentication key and
+/// default address.
+#[derive(Clone, Debug)]
+pub enum AccountKey {
+    Ed25519(Ed25519PublicKey),
+    MultiKey(MultiKey),
+    /// A derivable abstracted account: `function_info` authenticates for
+    /// every account derived from it, `identity` is the abstract public key.
+    Derivable {
+        function_info: FunctionInfo,
+        identity: Vec<u8>,
+    },
+}
+
+impl AccountKey {
+    pub fn authentication_key(&self) -> AuthenticationKey {
+        match self {
+            Self::Ed25519(public_key) => AuthenticationKey::ed25519(public_key),
+            Self::MultiKey(public_keys) => AuthenticationKey::multi_key(public_keys.clone()),
+            Self::Derivable { .. } => AuthenticationKey::new(self.address().into_bytes()),
+        }
+    }
+
+    /// The address the account lives at before any key rotation.
+    pub fn address(&self) -> AccountAddress {
+        match self {
+            Self::Derivable {
+                function_info,
+                identity,
+            } => {
+                // Mirrors `account_abstraction::derive_account_address`.
+                let mut bytes = bcs::to_bytes(function_info).expect("FunctionInfo serializes");
+                bytes.extend(bcs::to_bytes(identity).expect("identity serializes"));
+                bytes.push(DERIVABLE_ABSTRACTION_SCHEME);
+                AccountAddress::new(*HashValue::sha3_256_of(&bytes))
+            }
+            _ => self.authentication_key().account_address(),
+        }
+    }
+}
+
+/// Ed25519 private key of the named local account `name`, derived from the
+/// name so its address is the same from run to run.
+pub fn local_account_key(name: &str) -> Ed25519PrivateKey {
+    let seed = HashValue::sha3_256_of(format!("atomica-scenario:{}", name).as_bytes());
+    Ed25519PrivateKey::try_from(seed.as_ref()).expect("any 32 bytes are an Ed25519 private key")
+}
+
+/// Address of the object `object::create_named_object(creator, seed)` creates,
+/// e.g. the FAKEETH metadata at `object_address(@atomica, b"FAKEETH")`.
+pub fn object_address(creator: AccountAddress, seed: &[u8]) -> AccountAddress {
+    derive(creator, seed, NAMED_OBJECT_SCHEME)
+}
+
+/// Address of `owner`'s primary fungible store for the asset at `metadata`.
+pub fn primary_store_address(owner: AccountAddress, metadata: AccountAddress) -> AccountAddress {
+    derive(owner, metadata.as_ref(), USER_DERIVED_OBJECT_SCHEME)
+}
+
+fn derive(source: AccountAddress, seed: &[u8], scheme: u8) -> AccountAddress {
+    let mut bytes = source.to_vec();
+    bytes.extend_from_slice(seed);
+    bytes.push(scheme);
+    AccountAddress::new(*HashValue::sha3_256_of(&bytes))
+}
@@ -1,39 +1,297 @@
 
-use clap::Parser;
-use std::path::PathBuf;
+use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
 use std::fs;
-use aptos_types::transaction::{SignedTransaction, TransactionStatus};
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{
+        authenticator::AuthenticationKey, RawTransaction, SignedTransaction, TransactionOutput,
+        TransactionStatus,
+    },
+    vm_status::AbortLocation,
+};
 
-use aptos_framework::ReleaseBundle;
-
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use aptos_logger::Logger;
 
-use atomica_tx_debugger::offline_runner::OfflineTxnRunner;
+use aptos_crypto::ed25519::Ed25519PublicKey;
+use atomica_tx_debugger::{
+    accounts::AccountKey, call::{self, LocalAccounts}, genesis::GenesisOptions,
+    offline_runner::OfflineTxnRunner, rest_fork::RestForkStateView, scenario::Scenario,
+    simulate::into_simulation,
+};
 
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to the genesis .mrb file (ReleaseBundle)
+    #[arg(long, required_unless_present_any = ["state", "fork_url", "fork_cache"])]
+    mrb_path: Option<PathBuf>,
+
+    /// Start from a state snapshot instead of running genesis
+    #[arg(long, conflicts_with = "mrb_path")]
+    state: Option<PathBuf>,
+
+    /// Fork a live network through its REST API instead of running genesis,
+    /// e.g. https://fullnode.testnet.aptoslabs.com/v1
+    #[arg(long, requires = "fork_version", conflicts_with_all = ["mrb_path", "state"])]
+    fork_url: Option<String>,
+
+    /// Ledger version to fork at
     #[arg(long)]
-    mrb_path: PathBuf,
+    fork_version: Option<u64>,
 
-    /// Path to the signed transaction file
+    /// Cache of state read from the fork; without --fork-url, replay from it offline
+    #[arg(long, conflicts_with_all = ["mrb_path", "state"])]
+    fork_cache: Option<PathBuf>,
+
+    /// Save the state the transaction runs against (after genesis and
+    /// sender seeding) to this path, for replaying it with --state
+    #[arg(long)]
+    save_pre_state: Option<PathBuf>,
+
+    /// Save the state after the transaction to this path
     #[arg(long)]
-    txn_path: PathBuf,
+    save_post_state: Option<PathBuf>,
+
+    /// Path to the signed transaction file
+    #[arg(long, required = true)]
+    txn_path: Option<PathBuf>,
 
     /// Treat the transaction file content as hex string instead of raw bytes
     #[arg(long)]
     is_hex: bool,
 
-    /// Enable verbose opcode tracing to a directory
+    /// Write a structured execution trace (calls, instructions with pc and
+    /// gas, locals on abort) to this file as JSON lines
     #[arg(long)]
     trace: Option<PathBuf>,
 
+    /// Write a gas profile (folded stacks, flamegraphs and a markdown
+    /// report) into this directory
+    #[arg(long, conflicts_with = "trace")]
+    gas_profile: Option<PathBuf>,
+
+    /// Simulate like the `/simulate` endpoint: skip signature verification,
+    /// and accept a RawTransaction as well as a signed one
+    #[arg(long, conflicts_with_all = ["trace", "gas_profile"])]
+    simulate: bool,
+
+    /// Public key the sender claims when simulating, as hex: an Ed25519 key,
+    /// or a BCS MultiKey with --auth-scheme multi-key. Without it, a
+    /// RawTransaction is simulated without checking the authentication key
+    #[arg(long, requires = "simulate")]
+    public_key: Option<String>,
+
+    /// Scheme of --public-key
+    #[arg(long, value_enum, default_value_t = AuthScheme::Ed25519)]
+    auth_scheme: AuthScheme,
+
+    /// Print a before/after diff of every written key and the balance
+    /// changes per account, instead of the plain write set
+    #[arg(long)]
+    diff: bool,
+
+    /// Write the before/after diff to this file as JSON
+    #[arg(long)]
+    diff_json: Option<PathBuf>,
+
     /// Enable verbose logging (shows internal VM and framework logs)
-    #[arg(long, short)]
+    #[arg(long, short, global = true)]
     verbose: bool,
+
+    /// Chain id used by genesis and expected in transactions
+    #[arg(long, default_value_t = ChainId::test().id())]
+    chain_id: u8,
+
+    /// Number of genesis validators
+    #[arg(long, default_value_t = 1)]
+    validators: usize,
+
+    /// Epoch interval in seconds
+    #[arg(long, default_value_t = 3600)]
+    epoch_interval_secs: u64,
+
+    /// Feature flag id to enable at genesis (repeatable)
+    #[arg(long = "enable-feature")]
+    enable_features: Vec<u64>,
+
+    /// Feature flag id to disable at genesis (repeatable)
+    #[arg(long = "disable-feature")]
+    disable_features: Vec<u64>,
+
+    /// Initial on-chain time in microseconds
+    #[arg(long, default_value_t = 0)]
+    timestamp_usecs: u64,
+
+    /// Pre-fund an account at genesis, as ADDRESS=AMOUNT (repeatable)
+    #[arg(long = "fund", value_parser = parse_funded_account)]
+    funded_accounts: Vec<(AccountAddress, u64)>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum AuthScheme {
+    Ed25519,
+    MultiKey,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run a multi-step YAML or JSON scenario with expectations
+    Scenario(ScenarioArgs),
+    /// Call an entry function as a named local account
+    Call(CallArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct ScenarioArgs {
+    /// Path to the scenario file (.yaml, .yml or .json)
+    scenario: PathBuf,
+
+    /// Genesis .mrb file; defaults to the framework built into this binary
+    #[arg(long)]
+    mrb_path: Option<PathBuf>,
+
+    /// Start from a state snapshot instead of running genesis
+    #[arg(long, conflicts_with = "mrb_path")]
+    state: Option<PathBuf>,
+
+    /// Save the state after the last step to this path
+    #[arg(long)]
+    save_post_state: Option<PathBuf>,
+}
+
+/// A transaction to simulate: a signed transaction as is, unless a key is
+/// claimed, or a RawTransaction wrapped for simulation
+fn load_simulation(bytes: &[u8], key: Option<&AccountKey>) -> Result<SignedTransaction> {
+    let raw = match bcs::from_bytes::<SignedTransaction>(bytes) {
+        Ok(txn) if key.is_none() => return Ok(txn),
+        Ok(txn) => txn.into_raw_transaction(),
+        Err(_) => bcs::from_bytes::<RawTransaction>(bytes)
+            .context("Failed to deserialize SignedTransaction or RawTransaction")?,
+    };
+    into_simulation(raw, key)
+}
+
+#[derive(clap::Args, Debug)]
+struct CallArgs {
+    /// Entry function, e.g. 0x1::aptos_account::transfer
+    function: String,
+
+    /// Argument, as `<type>:<value>` or a plain value of the parameter's
+    /// type on chain (repeatable)
+    #[arg(long = "arg")]
+    args: Vec<String>,
+
+    /// Type argument (repeatable)
+    #[arg(long = "type-arg")]
+    type_args: Vec<String>,
+
+    /// Named local account sending the call
+    #[arg(long)]
+    sender: String,
+
+    /// Another named local account, usable by name in arguments (repeatable)
+    #[arg(long = "account")]
+    accounts: Vec<String>,
+
+    /// APT in octas for local accounts the state doesn't have yet
+    #[arg(long, default_value_t = 100_000_000_000)]
+    balance: u64,
+
+    #[arg(long, default_value_t = 2_000_000)]
+    max_gas: u64,
+
+    /// Genesis .mrb file; defaults to the framework built into this binary
+    #[arg(long)]
+    mrb_path: Option<PathBuf>,
+
+    /// Start from a state snapshot instead of running genesis
+    #[arg(long, conflicts_with = "mrb_path")]
+    state: Option<PathBuf>,
+
+    /// Save the state the call runs against (after account setup) to this
+    /// path, for replaying it with --state and --txn-path
+    #[arg(long)]
+    save_pre_state: Option<PathBuf>,
+
+    /// Save the state after the call to this path
+    #[arg(long)]
+    save_post_state: Option<PathBuf>,
+
+    /// Write the signed transaction (BCS) to this path, for --txn-path
+    #[arg(long)]
+    save_txn: Option<PathBuf>,
+
+    /// Print a before/after diff instead of the plain write set
+    #[arg(long)]
+    diff: bool,
+
+    /// Write the before/after diff to this file as JSON
+    #[arg(long)]
+    diff_json: Option<PathBuf>,
+}
+
+fn parse_funded_account(value: &str) -> Result<(AccountAddress, u64)> {
+    let (address, amount) = value
+        .split_once('=')
+        .context("expected ADDRESS=AMOUNT")?;
+    let address = AccountAddress::from_hex_literal(address)
+        .with_context(|| format!("invalid address {}", address))?;
+    let amount = amount
+        .parse()
+        .with_context(|| format!("invalid amount {}", amount))?;
+    Ok((address, amount))
+}
+
+impl Args {
+    /// The key given by --public-key and --auth-scheme
+    fn claimed_key(&self) -> Result<Option<AccountKey>> {
+        let Some(public_key) = &self.public_key else {
+            return Ok(None);
+        };
+        let bytes = hex::decode(public_key.trim_start_matches("0x"))
+            .context("Failed to decode --public-key hex")?;
+        let key = match self.auth_scheme {
+            AuthScheme::Ed25519 => AccountKey::Ed25519(
+                Ed25519PublicKey::try_from(bytes.as_slice()).context("Invalid Ed25519 public key")?,
+            ),
+            AuthScheme::MultiKey => {
+                AccountKey::MultiKey(bcs::from_bytes(&bytes).context("Invalid BCS MultiKey")?)
+            },
+        };
+        Ok(Some(key))
+    }
+
+    fn genesis_options(&self) -> GenesisOptions {
+        let mut options = GenesisOptions::new()
+            .chain_id(ChainId::new(self.chain_id))
+            .epoch_duration_secs(self.epoch_interval_secs)
+            .initial_timestamp_usecs(self.timestamp_usecs);
+        options.validators = self.validators;
+        for &id in &self.enable_features {
+            options = options.enable_feature_id(id);
+        }
+        for &id in &self.disable_features {
+            options = options.disable_feature_id(id);
+        }
+        for &(address, amount) in &self.funded_accounts {
+            options = options.fund_account(address, amount);
+        }
+        options
+    }
 }
 
 fn main() -> Result<()> {
@@ -47,37 +305,47 @@ fn main() -> Result<()> {
     };
     Logger::new().level(log_level).init();
 
-    // 1. Load Genesis from .mrb
-    println!("Loading genesis from {:?}", args.mrb_path);
-    let mrb_content = fs::read(&args.mrb_path)
-        .with_context(|| format!("Failed to read mrb file: {:?}", args.mrb_path))?;
-    let release_bundle: ReleaseBundle = bcs::from_bytes(&mrb_content)
-        .context("Failed to deserialize ReleaseBundle from .mrb")?;
-
-    // 2. Setup Runner
-    println!("Initializing FakeExecutor...");
-    let mut runner = OfflineTxnRunner::new(&release_bundle);
-
-    if let Some(trace_path) = args.trace {
-        println!("Opcode tracing enabled. Output will be saved to {:?}", trace_path);
-        fs::create_dir_all(&trace_path).context("Failed to create trace directory")?;
-        runner.enable_tracing(trace_path.clone());
+    match &args.command {
+        Some(Command::Scenario(scenario_args)) => return run_scenario(scenario_args),
+        Some(Command::Call(call_args)) => return run_call(call_args),
+        None => {},
     }
 
+    // 1. Boot from a snapshot, a fork or the .mrb genesis
+    let mut runner = if let Some(state_path) = &args.state {
+        println!("Loading state snapshot from {:?}", state_path);
+        OfflineTxnRunner::load_snapshot(state_path)?
+    } else if let Some(url) = &args.fork_url {
+        let version = args.fork_version.context("--fork-url requires --fork-version")?;
+        println!("Forking {} at version {}", url, version);
+        OfflineTxnRunner::fork(RestForkStateView::new(url, version, args.fork_cache.clone())?)?
+    } else if let Some(cache_path) = &args.fork_cache {
+        println!("Replaying fork cache {:?} offline", cache_path);
+        OfflineTxnRunner::fork(RestForkStateView::offline(cache_path)?)?
+    } else {
+        let mrb_path = args.mrb_path.as_ref().context("--mrb-path is required")?;
+        println!("Loading genesis from {:?}", mrb_path);
+        OfflineTxnRunner::from_mrb(mrb_path, &args.genesis_options())?
+    };
+
     // 3. Load Transaction
-    println!("Loading transaction from {:?}", args.txn_path);
+    let txn_path = args.txn_path.as_ref().context("--txn-path is required")?;
+    println!("Loading transaction from {:?}", txn_path);
     let txn_bytes = if args.is_hex {
-        let content = fs::read_to_string(&args.txn_path)
+        let content = fs::read_to_string(txn_path)
             .context("Failed to read txn file as string")?;
         let trimmed = content.trim().trim_start_matches("0x");
         hex::decode(trimmed).context("Failed to decode hex string")?
     } else {
-        fs::read(&args.txn_path)
+        fs::read(txn_path)
             .context("Failed to read txn file as bytes")?
     };
 
-    let signed_txn: SignedTransaction = bcs::from_bytes(&txn_bytes)
-        .context("Failed to deserialize SignedTransaction")?;
+    let signed_txn = if args.simulate {
+        load_simulation(&txn_bytes, args.claimed_key()?.as_ref())?
+    } else {
+        bcs::from_bytes(&txn_bytes).context("Failed to deserialize SignedTransaction")?
+    };
 
     let sender = signed_txn.sender();
     let sequence_number = signed_txn.sequence_number();
@@ -85,55 +353,128 @@ fn main() -> Result<()> {
     println!("Transaction Sender: {}", sender);
     println!("Sequence Number: {}", sequence_number);
 
-    // 4. Fund Sender
-    println!("Funding sender account...");
-    runner.fund_account(sender, 100_000_000_000, sequence_number);
+    // 4. Seed Sender, unless the state already has it
+    if runner.account(sender)?.is_none() {
+        // Abstracted accounts carry no authentication key; fall back to the address
+        let authentication_key = signed_txn
+            .authenticator_ref()
+            .sender()
+            .authentication_key()
+            .unwrap_or_else(|| AuthenticationKey::new(sender.into_bytes()));
+        println!("Seeding sender account...");
+        runner.create_account(sender, authentication_key, sequence_number)?;
+        runner.set_apt_balance(sender, 100_000_000_000)?;
+    }
+
+    if let Some(save_path) = &args.save_pre_state {
+        println!("Saving pre-transaction state snapshot to {:?}", save_path);
+        runner.save_snapshot(save_path)?;
+    }
 
-    // 5. Execute
-    println!("Executing transaction...");
-    let tx_out = runner.execute_transaction(signed_txn)?;
+    // 5. Execute, keeping what the fork fetched even if execution fails
+    let executed = execute(&mut runner, &args, signed_txn);
+    runner.save_fork_cache()?;
+    let tx_out = executed?;
+    if let Some(save_path) = &args.save_post_state {
+        println!("Saving post-transaction state snapshot to {:?}", save_path);
+        runner.save_snapshot(save_path)?;
+    }
 
     // 6. Report Results
+    report(&runner, &tx_out, args.diff, args.diff_json.as_deref())
+}
+
+/// Execute `signed_txn` the way `args` asks: traced, gas profiled, simulated
+/// or plain
+fn execute(
+    runner: &mut OfflineTxnRunner,
+    args: &Args,
+    signed_txn: SignedTransaction,
+) -> Result<TransactionOutput> {
+    println!("Executing transaction...");
+    let tx_out = if let Some(trace_path) = &args.trace {
+        let (tx_out, trace) = runner.execute_transaction_traced(signed_txn)?;
+        let file = fs::File::create(trace_path)
+            .with_context(|| format!("Failed to create trace file {:?}", trace_path))?;
+        trace.write_jsonl(std::io::BufWriter::new(file))?;
+        println!("Trace: {} events written to {:?}", trace.events.len(), trace_path);
+        if let Some(stack) = trace.abort() {
+            println!("Stack at abort:");
+            for frame in stack {
+                println!("  {} at pc {:?}", frame.function, frame.pc);
+                for (idx, value) in &frame.locals {
+                    println!("    local {}: {}", idx, value);
+                }
+            }
+        }
+        tx_out
+    } else if let Some(profile_dir) = &args.gas_profile {
+        let title = format!("{} #{}", signed_txn.sender(), signed_txn.sequence_number());
+        let (tx_out, profile) = runner.execute_transaction_profiled(signed_txn)?;
+        profile.write_to(profile_dir, &title)?;
+        println!("Gas profile written to {:?}", profile_dir);
+        println!("Most expensive functions (gas units, inclusive):");
+        for function in profile.functions.iter().take(5) {
+            println!(
+                "  {:>12.2}  {}",
+                function.inclusive as f64 / profile.scaling_factor() as f64,
+                function.function
+            );
+        }
+        tx_out
+    } else if args.simulate {
+        println!("Simulating without signature verification");
+        runner.simulate_transaction(signed_txn)?
+    } else {
+        runner.execute_transaction(signed_txn)?
+    };
+    Ok(tx_out)
+}
+
+/// Print the status, state changes and events of an executed transaction
+fn report(
+    runner: &OfflineTxnRunner,
+    tx_out: &TransactionOutput,
+    show_diff: bool,
+    diff_json: Option<&Path>,
+) -> Result<()> {
     println!("\n=== Execution Result ===");
     println!("Status: {:?}", tx_out.status());
     println!("Gas Used: {}", tx_out.gas_used());
 
-    // Print write set (state changes)
-    let write_set = tx_out.write_set();
-    let write_set_v0 = write_set.as_v0();
-    let changes_count = write_set_v0.iter().count();
-    if changes_count > 0 {
-        println!("\n=== Write Set ({} changes) ===", changes_count);
-        for (state_key, write_op) in write_set_v0.iter() {
-            println!("State Key: {:?}", state_key);
-            if let Some(state_value) = write_op.as_state_value_opt() {
-                let bytes = state_value.bytes();
-                if bytes.len() <= 64 {
-                    println!("  Value (hex): {}", hex::encode(bytes));
-                } else {
-                    println!("  Value (hex, {} bytes): {}...", bytes.len(), hex::encode(&bytes[..64]));
-                }
-                if write_op.is_delete() {
-                    println!("  Operation: DELETION");
-                } else {
-                    println!("  Operation: CREATE/MODIFY");
-                }
-            } else {
-                println!("  Operation: DELETION");
+    if let Some(diff_path) = diff_json {
+        let diff = runner.last_diff()?;
+        fs::write(diff_path, serde_json::to_string_pretty(&diff)?)
+            .with_context(|| format!("Failed to write diff to {:?}", diff_path))?;
+        println!("State diff written to {:?}", diff_path);
+    }
+
+    // Print write set (state changes), decoded against the post-transaction state
+    let writes = runner.decode_write_set(tx_out.write_set());
+    if show_diff {
+        let diff = runner.last_diff()?;
+        if !diff.is_empty() {
+            println!("\n=== State Diff ({} changes) ===", diff.changes.len());
+            print!("{}", diff);
+        }
+    } else if !writes.is_empty() {
+        println!("\n=== Write Set ({} changes) ===", writes.len());
+        for write in &writes {
+            println!("{} ({:?})", write.key, write.kind);
+            if let Some(value) = &write.value {
+                println!("{}", serde_json::to_string_pretty(value)?);
             }
             println!();
         }
     }
 
     // Print events
-    let events = tx_out.events();
+    let events = runner.decode_events(tx_out.events());
     if !events.is_empty() {
         println!("\n=== Events ({}) ===", events.len());
         for (idx, event) in events.iter().enumerate() {
-            println!("Event #{}: {:?}", idx, event.type_tag());
-            if let Ok(event_data) = bcs::to_bytes(event.event_data()) {
-                println!("  Data (hex): {}", hex::encode(&event_data));
-            }
+            println!("Event #{}: {}", idx, event.type_);
+            println!("{}", serde_json::to_string_pretty(&event.data)?);
         }
     }
 
@@ -157,10 +498,30 @@ fn main() -> Result<()> {
                     println!("\n✗ Move abort:");
                     println!("  Location: {:?}", location);
                     println!("  Abort code: {} (0x{:x})", code, code);
-                    if let Some(abort_info) = info {
-                        println!("\n  Error details:");
-                        println!("    Reason: {}", abort_info.reason_name);
-                        println!("    Description: {}", abort_info.description);
+                    let explanation = match location {
+                        AbortLocation::Module(module) => Some(runner.decode_abort(module, *code)?),
+                        AbortLocation::Script => None,
+                    };
+                    match (explanation, info) {
+                        (Some(explanation), _) if explanation.name.is_some() => {
+                            println!("\n  Error details:");
+                            println!("    Reason: {}", explanation.name.as_deref().unwrap_or_default());
+                            if let Some(category) = explanation.category {
+                                println!("    Category: error::{}", category);
+                            }
+                            if let Some(description) = &explanation.description {
+                                println!("    Description: {}", description);
+                            }
+                            if let Some(source) = &explanation.source {
+                                println!("    Declared at: {}:{}", source.file, source.line);
+                            }
+                        },
+                        (_, Some(abort_info)) => {
+                            println!("\n  Error details:");
+                            println!("    Reason: {}", abort_info.reason_name);
+                            println!("    Description: {}", abort_info.description);
+                        },
+                        _ => {},
                     }
                 },
                 aptos_types::transaction::ExecutionStatus::MiscellaneousError(maybe_code) => {
@@ -181,3 +542,95 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+fn run_scenario(args: &ScenarioArgs) -> Result<()> {
+    let scenario = Scenario::load(&args.scenario)?;
+    let mut runner = if let Some(state_path) = &args.state {
+        println!("Loading state snapshot from {:?}", state_path);
+        OfflineTxnRunner::load_snapshot(state_path)?
+    } else if let Some(mrb_path) = &args.mrb_path {
+        println!("Loading genesis from {:?}", mrb_path);
+        OfflineTxnRunner::from_mrb(mrb_path, &scenario.genesis.options())?
+    } else {
+        OfflineTxnRunner::with_genesis(
+            aptos_cached_packages::head_release_bundle(),
+            &scenario.genesis.options(),
+        )?
+    };
+
+    let title = scenario.name.as_deref().unwrap_or("scenario");
+    println!("\n=== {} ({:?}) ===", title, args.scenario);
+    let base_dir = args.scenario.parent().unwrap_or(Path::new("."));
+    let report = scenario.run(&mut runner, base_dir)?;
+    for step in &report.steps {
+        let gas = step
+            .gas_used
+            .map(|gas| format!(" (gas {})", gas))
+            .unwrap_or_default();
+        if step.failures.is_empty() {
+            println!("✓ {}{}", step.name, gas);
+        } else {
+            println!("✗ {}{}", step.name, gas);
+            for failure in &step.failures {
+                println!("    {}", failure);
+            }
+        }
+    }
+    if report.skipped > 0 {
+        println!("Skipped {} remaining step(s)", report.skipped);
+    }
+
+    if let Some(save_path) = &args.save_post_state {
+        println!("Saving post-scenario state snapshot to {:?}", save_path);
+        runner.save_snapshot(save_path)?;
+    }
+    if !report.passed() {
+        bail!("Scenario {} failed", title);
+    }
+    println!("\n✓ All {} steps passed", report.steps.len());
+    Ok(())
+}
+
+fn run_call(args: &CallArgs) -> Result<()> {
+    let mut runner = if let Some(state_path) = &args.state {
+        println!("Loading state snapshot from {:?}", state_path);
+        OfflineTxnRunner::load_snapshot(state_path)?
+    } else if let Some(mrb_path) = &args.mrb_path {
+        println!("Loading genesis from {:?}", mrb_path);
+        OfflineTxnRunner::from_mrb(mrb_path, &GenesisOptions::default())?
+    } else {
+        OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle())
+    };
+
+    let mut accounts = LocalAccounts::new();
+    for name in std::iter::once(&args.sender).chain(&args.accounts) {
+        let address = accounts.ensure(&mut runner, name, args.balance)?;
+        println!("Account {}: {}", name, address);
+    }
+    let entry_function = call::entry_function(
+        &runner,
+        accounts.names(),
+        &args.function,
+        &args.type_args,
+        &args.args,
+    )?;
+    let signed_txn = accounts.sign(&runner, &args.sender, entry_function, args.max_gas)?;
+    if let Some(txn_path) = &args.save_txn {
+        fs::write(txn_path, bcs::to_bytes(&signed_txn)?)
+            .with_context(|| format!("Failed to write transaction to {:?}", txn_path))?;
+        println!("Signed transaction written to {:?}", txn_path);
+    }
+    if let Some(save_path) = &args.save_pre_state {
+        println!("Saving pre-call state snapshot to {:?}", save_path);
+        runner.save_snapshot(save_path)?;
+    }
+
+    println!("Executing {} as {}...", args.function, args.sender);
+    let tx_out = runner.execute_transaction(signed_txn)?;
+    report(&runner, &tx_out, args.diff, args.diff_json.as_deref())?;
+
+    if let Some(save_path) = &args.save_post_state {
+        println!("Saving post-call state snapshot to {:?}", save_path);
+        runner.save_snapshot(save_path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,121 @@
+//! Entry function transactions built from text, as the `call` subcommand
+//! takes them.
+//!
+//! Accounts are named and local: each gets the key [`local_account_key`]
+//! derives from its name, the same key a scenario account of that name
+//! gets, and is created on first use unless the state already has it.
+
+use anyhow::{anyhow, ensure, Context, Result};
+use aptos_crypto::ed25519::Ed25519PublicKey;
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{EntryFunction, RawTransaction, SignedTransaction},
+};
+
+use crate::{
+    accounts::{local_account_key, AccountKey},
+    move_args::{parse_arg_as, NamedAddresses},
+    offline_runner::OfflineTxnRunner,
+};
+
+const GAS_UNIT_PRICE: u64 = 100;
+
+/// Named local accounts used so far, usable in arguments by name.
+#[derive(Default)]
+pub struct LocalAccounts {
+    names: NamedAddresses,
+}
+
+impl LocalAccounts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn names(&self) -> &NamedAddresses {
+        &self.names
+    }
+
+    /// Address of the account `name`, creating it with `balance` octas of
+    /// APT if the state doesn't have it yet
+    pub fn ensure(
+        &mut self,
+        runner: &mut OfflineTxnRunner,
+        name: &str,
+        balance: u64,
+    ) -> Result<AccountAddress> {
+        let key = AccountKey::Ed25519(Ed25519PublicKey::from(&local_account_key(name)));
+        let address = key.address();
+        if runner.account(address)?.is_none() {
+            runner.create_account(address, key.authentication_key(), 0)?;
+            runner.set_apt_balance(address, balance)?;
+        }
+        self.names.insert(name, address);
+        Ok(address)
+    }
+
+    /// Sign `entry_function` as `sender` at its current sequence number
+    pub fn sign(
+        &self,
+        runner: &OfflineTxnRunner,
+        sender: &str,
+        entry_function: EntryFunction,
+        max_gas: u64,
+    ) -> Result<SignedTransaction> {
+        let address = self
+            .names
+            .get(sender)
+            .with_context(|| format!("{} is not a local account", sender))?;
+        let sequence_number = runner
+            .account(address)?
+            .with_context(|| format!("Account {} does not exist", sender))?
+            .sequence_number();
+        let private_key = local_account_key(sender);
+        let public_key = Ed25519PublicKey::from(&private_key);
+        Ok(RawTransaction::new_entry_function(
+            address,
+            sequence_number,
+            entry_function,
+            max_gas,
+            GAS_UNIT_PRICE,
+            u64::MAX,
+            runner.chain_id(),
+        )
+        .sign(&private_key, public_key)
+        .map_err(|err| anyhow!("Failed to sign transaction: {:?}", err))?
+        .into_inner())
+    }
+}
+
+/// Build a call to `function`, parsing `args` against its parameter types in
+/// the runner's state (see [`parse_arg_as`])
+pub fn entry_function(
+    runner: &OfflineTxnRunner,
+    names: &NamedAddresses,
+    function: &str,
+    type_args: &[String],
+    args: &[String],
+) -> Result<EntryFunction> {
+    let (module, name) = names.function(function)?;
+    let type_args = type_args
+        .iter()
+        .map(|ty| names.type_tag(ty))
+        .collect::<Result<Vec<_>>>()?;
+    let params = runner.entry_function_params(&module, &name, &type_args)?;
+    ensure!(
+        params.len() == args.len(),
+        "{} takes {} argument(s), got {}",
+        function,
+        params.len(),
+        args.len()
+    );
+    let args = params
+        .iter()
+        .zip(args)
+        .enumerate()
+        .map(|(idx, (param, arg))| {
+            parse_arg_as(param, arg, names)
+                .with_context(|| format!("Invalid argument {} of {}", idx + 1, function))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(EntryFunction::new(module, name, type_args, args))
+}
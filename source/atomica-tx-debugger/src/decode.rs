@@ -0,0 +1,120 @@
+use aptos_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue};
+use aptos_types::{
+    access_path::Path,
+    state_store::state_key::{inner::StateKeyInner, StateKey},
+};
+use move_core_types::language_storage::CORE_CODE_ADDRESS;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// How a write changed its state key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WriteKind {
+    Create,
+    Modify,
+    Delete,
+}
+
+/// A write set entry with its new value decoded.
+#[derive(Clone, Debug, Serialize)]
+pub struct DecodedWrite {
+    /// See [`describe_state_key`]
+    pub key: String,
+    pub kind: WriteKind,
+    /// The new value, absent for deletions
+    pub value: Option<Value>,
+}
+
+/// An event with its payload decoded.
+#[derive(Clone, Debug, Serialize)]
+pub struct DecodedEvent {
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub data: Value,
+}
+
+/// A decoded Move value as JSON, in the conventions of the Aptos REST API:
+/// `u64` and wider integers are strings, byte vectors are `0x`-hex,
+/// `0x1::string::String` is a plain string and other structs are objects
+/// keyed by field name.
+pub fn value_to_json(value: &AnnotatedMoveValue) -> Value {
+    #[allow(unreachable_patterns)]
+    match value {
+        AnnotatedMoveValue::Bool(b) => json!(b),
+        AnnotatedMoveValue::U8(n) => json!(n),
+        AnnotatedMoveValue::U16(n) => json!(n),
+        AnnotatedMoveValue::U32(n) => json!(n),
+        AnnotatedMoveValue::U64(n) => json!(n.to_string()),
+        AnnotatedMoveValue::U128(n) => json!(n.to_string()),
+        AnnotatedMoveValue::U256(n) => json!(n.to_string()),
+        AnnotatedMoveValue::Address(address) => json!(address.to_hex_literal()),
+        AnnotatedMoveValue::Bytes(bytes) => json!(format!("0x{}", hex::encode(bytes))),
+        AnnotatedMoveValue::Vector(_, values) => {
+            Value::Array(values.iter().map(value_to_json).collect())
+        }
+        AnnotatedMoveValue::Struct(value) => struct_to_json(value),
+        // Closures and any later additions are shown as Move prints them.
+        other => json!(other.to_string()),
+    }
+}
+
+pub fn struct_to_json(value: &AnnotatedMoveStruct) -> Value {
+    if value.ty_tag.address == CORE_CODE_ADDRESS
+        && value.ty_tag.module.as_str() == "string"
+        && value.ty_tag.name.as_str() == "String"
+    {
+        if let [(_, AnnotatedMoveValue::Bytes(bytes))] = value.value.as_slice() {
+            return json!(String::from_utf8_lossy(bytes));
+        }
+    }
+    Value::Object(
+        value
+            .value
+            .iter()
+            .map(|(name, field)| (name.to_string(), value_to_json(field)))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+/// Whether `actual` contains everything in `expected`: objects match on the
+/// listed keys only, arrays and scalars must match exactly, and an expected
+/// number also matches the same number written as a string.
+pub fn json_contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected.iter().all(|(key, value)| {
+            actual
+                .get(key)
+                .is_some_and(|actual| json_contains(actual, value))
+        }),
+        (Value::Array(actual), Value::Array(expected)) => {
+            actual.len() == expected.len()
+                && actual
+                    .iter()
+                    .zip(expected)
+                    .all(|(actual, expected)| json_contains(actual, expected))
+        }
+        (Value::String(actual), Value::Number(expected)) => *actual == expected.to_string(),
+        (Value::String(actual), Value::Bool(expected)) => *actual == expected.to_string(),
+        _ => actual == expected,
+    }
+}
+
+/// A state key as a short human-readable label, e.g.
+/// `0xcafe::0x1::account::Account` or `code 0x1::coin`.
+pub fn describe_state_key(key: &StateKey) -> String {
+    match key.inner() {
+        StateKeyInner::AccessPath(access_path) => match access_path.get_path() {
+            Path::Resource(tag) | Path::ResourceGroup(tag) => format!(
+                "{}::{}",
+                access_path.address.to_hex_literal(),
+                tag.to_canonical_string()
+            ),
+            Path::Code(module) => format!("code {}", module.short_str_lossless()),
+        },
+        StateKeyInner::TableItem { handle, key } => {
+            format!("table {}[0x{}]", handle.0.to_hex_literal(), hex::encode(key))
+        }
+        StateKeyInner::Raw(bytes) => format!("raw 0x{}", hex::encode(bytes)),
+    }
+}
@@ -0,0 +1,110 @@
+//! What a transaction changed: old and new values of every key it wrote,
+//! and the resulting balance changes per account.
+
+use std::fmt;
+
+use aptos_types::account_address::AccountAddress;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{accounts::APT_METADATA, decode::WriteKind};
+
+/// A written key with its decoded value before and after the transaction.
+#[derive(Clone, Debug, Serialize)]
+pub struct StateChange {
+    /// See [`crate::decode::describe_state_key`]
+    pub key: String,
+    pub kind: WriteKind,
+    /// Absent for created keys
+    pub before: Option<Value>,
+    /// Absent for deleted keys
+    pub after: Option<Value>,
+}
+
+/// Change of an account's balance of one asset, summed over the stores the
+/// transaction wrote: its legacy coin store and any fungible stores it owns.
+/// Untouched stores of the same asset are not counted in `before`/`after`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BalanceChange {
+    pub owner: AccountAddress,
+    /// Metadata object of the fungible asset; APT is `0xa`
+    pub asset: AccountAddress,
+    pub before: u64,
+    pub after: u64,
+    pub delta: i128,
+}
+
+impl BalanceChange {
+    pub fn new(owner: AccountAddress, asset: AccountAddress, before: u64, after: u64) -> Self {
+        Self {
+            owner,
+            asset,
+            before,
+            after,
+            delta: i128::from(after) - i128::from(before),
+        }
+    }
+}
+
+/// Before/after report of a transaction's write set.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct StateDiff {
+    pub changes: Vec<StateChange>,
+    /// Only balances that actually changed, by owner then asset
+    pub balances: Vec<BalanceChange>,
+}
+
+impl StateDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn balance_change(
+        &self,
+        owner: AccountAddress,
+        asset: AccountAddress,
+    ) -> Option<&BalanceChange> {
+        self.balances
+            .iter()
+            .find(|b| b.owner == owner && b.asset == asset)
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in &self.changes {
+            let marker = match change.kind {
+                WriteKind::Create => '+',
+                WriteKind::Modify => '~',
+                WriteKind::Delete => '-',
+            };
+            writeln!(f, "{} {}", marker, change.key)?;
+            if let Some(before) = &change.before {
+                writeln!(f, "    before: {}", before)?;
+            }
+            if let Some(after) = &change.after {
+                writeln!(f, "    after:  {}", after)?;
+            }
+        }
+        if !self.balances.is_empty() {
+            writeln!(f, "Balance changes:")?;
+        }
+        for balance in &self.balances {
+            let asset = if balance.asset == APT_METADATA {
+                "APT".to_string()
+            } else {
+                balance.asset.to_hex_literal()
+            };
+            writeln!(
+                f,
+                "  {} {}: {} -> {} ({:+})",
+                balance.owner.to_hex_literal(),
+                asset,
+                balance.before,
+                balance.after,
+                balance.delta
+            )?;
+        }
+        Ok(())
+    }
+}
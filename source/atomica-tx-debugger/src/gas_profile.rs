@@ -0,0 +1,412 @@
+//! Gas profiles built on the Aptos VM's gas profiler.
+//!
+//! [`GasProfile`] summarises a [`TransactionGasLog`] per Move function
+//! (execution and storage reads) and per written resource (IO and storage
+//! fee), and renders it as folded stacks for flamegraph tools and as a
+//! markdown report.
+
+use std::{collections::BTreeMap, fmt::Write as _, fs, path::Path};
+
+use anyhow::{Context, Result};
+use aptos_gas_profiling::{CallFrame, ExecutionGasEvent, FrameName, TransactionGasLog};
+
+use crate::decode::describe_state_key;
+
+/// Gas spent in one Move function over the whole transaction.
+///
+/// Execution and storage-read gas are in internal gas units.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FunctionGas {
+    pub function: String,
+    pub calls: u64,
+    pub native: bool,
+    /// Instructions executed in the function itself
+    pub execution: u64,
+    /// Resources the function itself loaded from storage
+    pub storage_reads: u64,
+    /// Everything spent while the function was on the stack
+    pub inclusive: u64,
+}
+
+/// What writing one state key cost.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResourceGas {
+    pub resource: String,
+    pub op: String,
+    /// IO gas for the write, in internal gas units
+    pub io: u64,
+    /// Storage fee in octas
+    pub storage_fee: u64,
+    /// Storage refund in octas
+    pub storage_refund: u64,
+}
+
+pub struct GasProfile {
+    pub log: TransactionGasLog,
+    /// Functions, most expensive (inclusive) first
+    pub functions: Vec<FunctionGas>,
+    /// Written state keys, highest storage fee first
+    pub resources: Vec<ResourceGas>,
+}
+
+impl GasProfile {
+    pub fn new(log: TransactionGasLog) -> Self {
+        let mut functions = BTreeMap::new();
+        walk(&log.exec_io.call_graph, &mut vec![], &mut functions);
+        let mut functions: Vec<FunctionGas> = functions.into_values().collect();
+        functions.sort_by(|a, b| {
+            b.inclusive
+                .cmp(&a.inclusive)
+                .then(a.function.cmp(&b.function))
+        });
+
+        let mut resources: BTreeMap<String, ResourceGas> = BTreeMap::new();
+        for write in &log.exec_io.write_set_transient {
+            let resource = describe_state_key(&write.key);
+            let entry = resources
+                .entry(resource.clone())
+                .or_insert_with(|| ResourceGas {
+                    resource,
+                    op: format!("{:?}", write.op_type),
+                    ..ResourceGas::default()
+                });
+            entry.io += u64::from(write.cost);
+        }
+        for write in &log.storage.write_set_storage {
+            let resource = describe_state_key(&write.key);
+            let entry = resources
+                .entry(resource.clone())
+                .or_insert_with(|| ResourceGas {
+                    resource,
+                    op: format!("{:?}", write.op_type),
+                    ..ResourceGas::default()
+                });
+            entry.storage_fee += u64::from(write.cost);
+            entry.storage_refund += u64::from(write.refund);
+        }
+        let mut resources: Vec<ResourceGas> = resources.into_values().collect();
+        resources.sort_by(|a, b| {
+            b.storage_fee
+                .cmp(&a.storage_fee)
+                .then(b.io.cmp(&a.io))
+                .then(a.resource.cmp(&b.resource))
+        });
+
+        Self {
+            log,
+            functions,
+            resources,
+        }
+    }
+
+    /// Internal gas units per gas unit
+    pub fn scaling_factor(&self) -> u64 {
+        self.log.exec_io.gas_scaling_factor.max(1)
+    }
+
+    /// Execution and IO gas in folded-stack format (`a;b;c <internal gas>`
+    /// per line), as read by `flamegraph.pl` and `inferno-flamegraph`
+    pub fn folded_execution(&self) -> String {
+        let exec_io = &self.log.exec_io;
+        let mut lines = vec![];
+        push_line(&mut lines, "intrinsic", u64::from(exec_io.intrinsic_cost));
+        push_line(&mut lines, "keyless", u64::from(exec_io.keyless_cost));
+        for dependency in &exec_io.dependencies {
+            push_line(
+                &mut lines,
+                &format!("dependencies;{}", dependency.id.short_str_lossless()),
+                u64::from(dependency.cost),
+            );
+        }
+        fold(&exec_io.call_graph, &mut vec![], &mut lines);
+        if let Some(cost) = exec_io.transaction_transient {
+            push_line(&mut lines, "io;transaction", u64::from(cost));
+        }
+        for event in &exec_io.events_transient {
+            push_line(
+                &mut lines,
+                &format!("io;events;{}", event.ty.to_canonical_string()),
+                u64::from(event.cost),
+            );
+        }
+        for write in &exec_io.write_set_transient {
+            push_line(
+                &mut lines,
+                &format!("io;writes;{}", describe_state_key(&write.key)),
+                u64::from(write.cost),
+            );
+        }
+        lines.concat()
+    }
+
+    /// Storage fees in folded-stack format, in octas
+    pub fn folded_storage(&self) -> String {
+        let storage = &self.log.storage;
+        let mut lines = vec![];
+        push_line(&mut lines, "transaction", u64::from(storage.txn_storage));
+        for write in &storage.write_set_storage {
+            push_line(
+                &mut lines,
+                &format!("writes;{}", describe_state_key(&write.key)),
+                u64::from(write.cost),
+            );
+        }
+        for event in &storage.events {
+            push_line(
+                &mut lines,
+                &format!("events;{}", event.ty.to_canonical_string()),
+                u64::from(event.cost),
+            );
+        }
+        lines.concat()
+    }
+
+    /// A markdown report with totals, the per-function table and the
+    /// per-resource table
+    pub fn markdown_report(&self, title: &str) -> String {
+        let exec_io = &self.log.exec_io;
+        let storage = &self.log.storage;
+        let scale = self.scaling_factor();
+        let units = |internal: u64| format!("{:.2}", internal as f64 / scale as f64);
+        let execution: u64 = self.functions.iter().map(|f| f.execution).sum();
+        let storage_reads: u64 = self.functions.iter().map(|f| f.storage_reads).sum();
+        let io: u64 = exec_io.transaction_transient.map_or(0, u64::from)
+            + exec_io
+                .events_transient
+                .iter()
+                .map(|e| u64::from(e.cost))
+                .sum::<u64>()
+            + exec_io
+                .write_set_transient
+                .iter()
+                .map(|w| u64::from(w.cost))
+                .sum::<u64>();
+
+        let mut out = String::new();
+        let _ = writeln!(out, "# Gas profile: {}\n", title);
+        let _ = writeln!(out, "| | Gas units | Internal gas |");
+        let _ = writeln!(out, "|---|---:|---:|");
+        let _ = writeln!(
+            out,
+            "| Intrinsic | {} | {} |",
+            units(exec_io.intrinsic_cost.into()),
+            u64::from(exec_io.intrinsic_cost)
+        );
+        let _ = writeln!(out, "| Execution | {} | {} |", units(execution), execution);
+        let _ = writeln!(
+            out,
+            "| Storage reads | {} | {} |",
+            units(storage_reads),
+            storage_reads
+        );
+        let _ = writeln!(out, "| IO (writes, events) | {} | {} |", units(io), io);
+        let _ = writeln!(
+            out,
+            "| **Execution + IO total** | **{}** | **{}** |\n",
+            units(exec_io.total.into()),
+            u64::from(exec_io.total)
+        );
+        let _ = writeln!(
+            out,
+            "Storage fee: {} octas, refund: {} octas.\n",
+            u64::from(storage.total),
+            u64::from(storage.total_refund)
+        );
+
+        let _ = writeln!(out, "## Functions\n");
+        let _ = writeln!(
+            out,
+            "| Function | Calls | Execution | Storage reads | Inclusive | Inclusive % |"
+        );
+        let _ = writeln!(out, "|---|---:|---:|---:|---:|---:|");
+        let total = u64::from(exec_io.total).max(1);
+        for function in &self.functions {
+            let _ = writeln!(
+                out,
+                "| `{}`{} | {} | {} | {} | {} | {:.1}% |",
+                function.function,
+                if function.native { " (native)" } else { "" },
+                function.calls,
+                units(function.execution),
+                units(function.storage_reads),
+                units(function.inclusive),
+                function.inclusive as f64 * 100.0 / total as f64
+            );
+        }
+
+        let _ = writeln!(out, "\n## Resources written\n");
+        let _ = writeln!(
+            out,
+            "| Resource | Operation | IO (gas units) | Storage fee (octas) | Refund (octas) |"
+        );
+        let _ = writeln!(out, "|---|---|---:|---:|---:|");
+        for resource in &self.resources {
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {} | {} | {} |",
+                resource.resource,
+                resource.op,
+                units(resource.io),
+                resource.storage_fee,
+                resource.storage_refund
+            );
+        }
+        if !storage.events.is_empty() {
+            let _ = writeln!(out, "\n## Events\n");
+            let _ = writeln!(out, "| Event | Storage fee (octas) |");
+            let _ = writeln!(out, "|---|---:|");
+            for event in &storage.events {
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} |",
+                    event.ty.to_canonical_string(),
+                    u64::from(event.cost)
+                );
+            }
+        }
+        out
+    }
+
+    /// Write `execution.folded`, `storage.folded`, `report.md` and, where
+    /// the profiler can render them, `execution.svg` and `storage.svg`
+    /// into `dir`.
+    pub fn write_to(&self, dir: &Path, title: &str) -> Result<()> {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+        fs::write(dir.join("execution.folded"), self.folded_execution())?;
+        fs::write(dir.join("storage.folded"), self.folded_storage())?;
+        fs::write(dir.join("report.md"), self.markdown_report(title))?;
+        if let Some(svg) = self.log.exec_io.to_flamegraph(format!(
+            "Execution & IO (gas unit = {} internal gas)",
+            self.scaling_factor()
+        ))? {
+            fs::write(dir.join("execution.svg"), svg)?;
+        }
+        if let Some(svg) = self
+            .log
+            .storage
+            .to_flamegraph("Storage fee (octas)".to_string())?
+        {
+            fs::write(dir.join("storage.svg"), svg)?;
+        }
+        Ok(())
+    }
+}
+
+fn frame_name(frame: &CallFrame) -> String {
+    match &frame.name {
+        FrameName::Script => "script".to_string(),
+        FrameName::Function {
+            module_id, name, ..
+        } => format!("{}::{}", module_id.short_str_lossless(), name),
+    }
+}
+
+/// Accumulate `frame` and its callees into `functions`, returning the
+/// frame's inclusive cost. `stack` holds the callers' names so recursive
+/// calls are not counted twice towards a function's inclusive cost.
+fn walk(
+    frame: &CallFrame,
+    stack: &mut Vec<String>,
+    functions: &mut BTreeMap<String, FunctionGas>,
+) -> u64 {
+    let name = frame_name(frame);
+    stack.push(name.clone());
+    let mut execution = 0;
+    let mut storage_reads = 0;
+    let mut callees = 0;
+    for event in &frame.events {
+        #[allow(unreachable_patterns)]
+        match event {
+            ExecutionGasEvent::Bytecode { cost, .. } | ExecutionGasEvent::CreateTy { cost } => {
+                execution += u64::from(*cost)
+            }
+            ExecutionGasEvent::LoadResource { cost, .. } => storage_reads += u64::from(*cost),
+            ExecutionGasEvent::CallNative {
+                module_id,
+                fn_name,
+                cost,
+                ..
+            } => {
+                let native = format!("{}::{}", module_id.short_str_lossless(), fn_name);
+                let entry = functions
+                    .entry(native.clone())
+                    .or_insert_with(|| FunctionGas {
+                        function: native,
+                        native: true,
+                        ..FunctionGas::default()
+                    });
+                entry.calls += 1;
+                entry.execution += u64::from(*cost);
+                entry.inclusive += u64::from(*cost);
+                callees += u64::from(*cost);
+            }
+            ExecutionGasEvent::Call(callee) => callees += walk(callee, stack, functions),
+            _ => {}
+        }
+    }
+    stack.pop();
+
+    let inclusive = execution + storage_reads + callees;
+    let recursive = stack.contains(&name);
+    let entry = functions
+        .entry(name.clone())
+        .or_insert_with(|| FunctionGas {
+            function: name,
+            ..FunctionGas::default()
+        });
+    entry.calls += 1;
+    entry.execution += execution;
+    entry.storage_reads += storage_reads;
+    if !recursive {
+        entry.inclusive += inclusive;
+    }
+    inclusive
+}
+
+/// Folded-stack lines for `frame`, with each frame's own cost on its stack.
+fn fold(frame: &CallFrame, stack: &mut Vec<String>, lines: &mut Vec<String>) {
+    stack.push(frame_name(frame));
+    let mut own = 0;
+    for event in &frame.events {
+        #[allow(unreachable_patterns)]
+        match event {
+            ExecutionGasEvent::Bytecode { cost, .. } | ExecutionGasEvent::CreateTy { cost } => {
+                own += u64::from(*cost)
+            }
+            ExecutionGasEvent::LoadResource { ty, cost, .. } => push_line(
+                lines,
+                &format!(
+                    "{};storage_reads;{}",
+                    stack.join(";"),
+                    ty.to_canonical_string()
+                ),
+                u64::from(*cost),
+            ),
+            ExecutionGasEvent::CallNative {
+                module_id,
+                fn_name,
+                cost,
+                ..
+            } => push_line(
+                lines,
+                &format!(
+                    "{};{}::{}",
+                    stack.join(";"),
+                    module_id.short_str_lossless(),
+                    fn_name
+                ),
+                u64::from(*cost),
+            ),
+            ExecutionGasEvent::Call(callee) => fold(callee, stack, lines),
+            _ => {}
+        }
+    }
+    push_line(lines, &stack.join(";"), own);
+    stack.pop();
+}
+
+fn push_line(lines: &mut Vec<String>, stack: &str, cost: u64) {
+    if cost > 0 {
+        lines.push(format!("{} {}\n", stack, cost));
+    }
+}
@@ -0,0 +1,282 @@
+use std::{
+    any::Any,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use aptos_framework::ReleaseBundle;
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    on_chain_config::{FeatureFlag, Features, OnChainConsensusConfig, OnChainExecutionConfig},
+    transaction::ChangeSet,
+};
+use aptos_vm_genesis::{
+    default_gas_schedule, encode_genesis_change_set, GenesisConfiguration, TestValidator,
+    GENESIS_KEYPAIR,
+};
+use move_binary_format::CompiledModule;
+
+/// Reads a Move release bundle (`.mrb`), as written by
+/// `aptos-framework release`.
+pub fn load_release_bundle(path: &Path) -> Result<ReleaseBundle> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read mrb file: {:?}", path))?;
+    let bundle: ReleaseBundle = bcs::from_bytes(&bytes)
+        .with_context(|| format!("Failed to deserialize ReleaseBundle from {:?}", path))?;
+    if bundle
+        .packages
+        .iter()
+        .all(|package| package.code().is_empty())
+    {
+        bail!("Release bundle {:?} contains no modules", path);
+    }
+    Ok(bundle)
+}
+
+/// Every `head-<hash>.mrb` fixture in `dir`, sorted by name.
+pub fn release_bundle_fixtures(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(dir)
+        .with_context(|| format!("Failed to list fixtures in {:?}", dir))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("head-") && name.ends_with(".mrb"))
+        })
+        .collect();
+    fixtures.sort();
+    Ok(fixtures)
+}
+
+/// An account funded with APT right after genesis.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FundedAccount {
+    pub address: AccountAddress,
+    pub balance: u64,
+}
+
+/// Parameters of the genesis the offline runner boots from.
+///
+/// The defaults match `aptos_vm_genesis::generate_test_genesis` with a single
+/// validator, so existing callers see the same state as before.
+#[derive(Clone, Debug)]
+pub struct GenesisOptions {
+    pub chain_id: ChainId,
+    pub validators: usize,
+    pub validator_stake: u64,
+    pub epoch_duration_secs: u64,
+    /// Feature flag ids enabled on top of `Features::default()`.
+    pub enabled_features: Vec<u64>,
+    /// Feature flag ids disabled from `Features::default()`.
+    pub disabled_features: Vec<u64>,
+    /// Written to `0x1::timestamp::CurrentTimeMicroseconds` after genesis.
+    pub initial_timestamp_usecs: u64,
+    pub funded_accounts: Vec<FundedAccount>,
+}
+
+impl Default for GenesisOptions {
+    fn default() -> Self {
+        Self {
+            chain_id: ChainId::test(),
+            validators: 1,
+            validator_stake: 100_000_000,
+            epoch_duration_secs: 3600,
+            enabled_features: vec![],
+            disabled_features: vec![],
+            initial_timestamp_usecs: 0,
+            funded_accounts: vec![],
+        }
+    }
+}
+
+impl GenesisOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn chain_id(mut self, chain_id: ChainId) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    pub fn validators(mut self, count: usize, stake: u64) -> Self {
+        self.validators = count;
+        self.validator_stake = stake;
+        self
+    }
+
+    pub fn epoch_duration_secs(mut self, secs: u64) -> Self {
+        self.epoch_duration_secs = secs;
+        self
+    }
+
+    pub fn enable_feature(self, flag: FeatureFlag) -> Self {
+        self.enable_feature_id(flag as u64)
+    }
+
+    pub fn disable_feature(self, flag: FeatureFlag) -> Self {
+        self.disable_feature_id(flag as u64)
+    }
+
+    /// Enables a feature by its on-chain id, e.g. one newer than this build
+    /// of `aptos-types` knows about.
+    pub fn enable_feature_id(mut self, id: u64) -> Self {
+        self.disabled_features.retain(|&disabled| disabled != id);
+        self.enabled_features.push(id);
+        self
+    }
+
+    pub fn disable_feature_id(mut self, id: u64) -> Self {
+        self.enabled_features.retain(|&enabled| enabled != id);
+        self.disabled_features.push(id);
+        self
+    }
+
+    pub fn initial_timestamp_usecs(mut self, usecs: u64) -> Self {
+        self.initial_timestamp_usecs = usecs;
+        self
+    }
+
+    pub fn fund_account(mut self, address: AccountAddress, balance: u64) -> Self {
+        self.funded_accounts
+            .push(FundedAccount { address, balance });
+        self
+    }
+
+    /// The on-chain feature set genesis is initialised with.
+    pub fn features(&self) -> Features {
+        let mut features = Features::default();
+        for &id in &self.enabled_features {
+            set_feature(&mut features, id, true);
+        }
+        for &id in &self.disabled_features {
+            set_feature(&mut features, id, false);
+        }
+        features
+    }
+
+    /// Runs genesis for `framework` and returns its change set.
+    ///
+    /// Packages this VM cannot load are left out, see [`loadable_framework`].
+    /// `aptos_vm_genesis` panics when the framework cannot be published or
+    /// initialised; the panic is reported as an error instead.
+    pub fn build(&self, framework: &ReleaseBundle) -> Result<ChangeSet> {
+        if self.validators == 0 {
+            bail!("Genesis needs at least one validator");
+        }
+        let framework = loadable_framework(framework)?;
+        let validators: Vec<_> =
+            TestValidator::new_test_set(Some(self.validators), Some(self.validator_stake))
+                .into_iter()
+                .map(|validator| validator.data)
+                .collect();
+        let genesis_config = GenesisConfiguration {
+            allow_new_validators: true,
+            epoch_duration_secs: self.epoch_duration_secs,
+            is_test: true,
+            min_stake: 0,
+            min_voting_threshold: 0,
+            // 1M APT
+            max_stake: 100_000_000_000_000,
+            recurring_lockup_duration_secs: 7200,
+            required_proposer_stake: 0,
+            rewards_apy_percentage: 10,
+            voting_duration_secs: 3600,
+            voting_power_increase_limit: 50,
+            employee_vesting_start: 1663456089,
+            employee_vesting_period_duration: 5 * 60,
+            initial_features_override: Some(self.features()),
+            randomness_config_override: None,
+            jwk_consensus_config_override: None,
+            initial_jwks: vec![],
+            keyless_groth16_vk: None,
+        };
+
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            encode_genesis_change_set(
+                &GENESIS_KEYPAIR.1,
+                &validators,
+                &framework,
+                self.chain_id,
+                &genesis_config,
+                &OnChainConsensusConfig::default_for_genesis(),
+                &OnChainExecutionConfig::default_for_genesis(),
+                &default_gas_schedule(),
+            )
+        }))
+        .map_err(|payload| anyhow!("Genesis failed: {}", panic_message(&*payload)))
+    }
+}
+
+/// The packages of `framework` whose modules this VM can deserialize.
+///
+/// `build-framework.sh` releases `AptosExperimental` with the unstable
+/// language version, which emits a newer bytecode version than the stable
+/// packages. `aptos_vm_genesis` unwraps module deserialization, so genesis
+/// from such a bundle panics on a VM that does not read that version yet.
+/// Packages nothing else depends on are left out of genesis; a package the
+/// rest of the framework needs is an error.
+pub fn loadable_framework(framework: &ReleaseBundle) -> Result<ReleaseBundle> {
+    let unloadable: Vec<String> = framework
+        .packages
+        .iter()
+        .filter(|package| {
+            package
+                .code()
+                .iter()
+                .any(|code| CompiledModule::deserialize(code).is_err())
+        })
+        .map(|package| package.package_metadata().name.clone())
+        .collect();
+
+    let mut loadable = framework.clone();
+    loadable
+        .packages
+        .retain(|package| !unloadable.contains(&package.package_metadata().name));
+    for package in &loadable.packages {
+        let metadata = package.package_metadata();
+        if let Some(dep) = metadata
+            .deps
+            .iter()
+            .find(|dep| unloadable.contains(&dep.package_name))
+        {
+            bail!(
+                "Package {} depends on {}, which this VM cannot deserialize",
+                metadata.name,
+                dep.package_name
+            );
+        }
+    }
+    for name in &unloadable {
+        aptos_logger::warn!(
+            "Leaving {} out of genesis: this VM cannot deserialize it",
+            name
+        );
+    }
+    Ok(loadable)
+}
+
+fn set_feature(features: &mut Features, id: u64, enabled: bool) {
+    let byte = (id / 8) as usize;
+    let mask = 1u8 << (id % 8);
+    if features.features.len() <= byte {
+        features.features.resize(byte + 1, 0);
+    }
+    if enabled {
+        features.features[byte] |= mask;
+    } else {
+        features.features[byte] &= !mask;
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -1 +1,14 @@
+pub mod abort;
+pub mod accounts;
+pub mod call;
+pub mod decode;
+pub mod diff;
+pub mod gas_profile;
+pub mod genesis;
+pub mod move_args;
 pub mod offline_runner;
+pub mod rest_fork;
+pub mod scenario;
+pub mod simulate;
+pub mod snapshot;
+pub mod trace;
@@ -0,0 +1,309 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use anyhow::{bail, ensure, Context, Result};
+use aptos_types::account_address::AccountAddress;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{SignatureToken, StructHandleIndex},
+    CompiledModule,
+};
+use move_core_types::{
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, StructTag, TypeTag},
+    u256::U256,
+};
+
+use crate::accounts::object_address;
+
+/// Human-readable names for addresses, usable wherever an address, a type
+/// or a function id is written as text.
+///
+/// An address expression is one of:
+/// - a hex literal, e.g. `0xcafe`;
+/// - a name, e.g. `alice`;
+/// - `object:<creator>:<seed>`, the named object `creator` created with
+///   `seed`, e.g. `object:atomica:FAKEETH`.
+#[derive(Clone, Debug, Default)]
+pub struct NamedAddresses(BTreeMap<String, AccountAddress>);
+
+impl NamedAddresses {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, address: AccountAddress) {
+        self.0.insert(name.into(), address);
+    }
+
+    pub fn get(&self, name: &str) -> Option<AccountAddress> {
+        self.0.get(name).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &AccountAddress)> {
+        self.0.iter()
+    }
+
+    /// Resolve an address expression.
+    pub fn address(&self, expr: &str) -> Result<AccountAddress> {
+        let expr = expr.trim();
+        if let Some(object) = expr.strip_prefix("object:") {
+            let (creator, seed) = object
+                .split_once(':')
+                .with_context(|| format!("expected object:<creator>:<seed>, got {}", expr))?;
+            return Ok(object_address(self.address(creator)?, seed.as_bytes()));
+        }
+        if let Some(address) = self.get(expr) {
+            return Ok(address);
+        }
+        AccountAddress::from_hex_literal(expr)
+            .or_else(|_| AccountAddress::from_str(expr))
+            .with_context(|| format!("{} is neither an address nor a known name", expr))
+    }
+
+    /// Parse a type, replacing known names in address position, e.g.
+    /// `vector<atomica::auction::Bid>`.
+    pub fn type_tag(&self, text: &str) -> Result<TypeTag> {
+        TypeTag::from_str(&self.substitute(text)).with_context(|| format!("Invalid type {}", text))
+    }
+
+    /// Parse `<address>::<module>::<function>`.
+    pub fn function(&self, text: &str) -> Result<(ModuleId, Identifier)> {
+        let mut parts = text.trim().splitn(3, "::");
+        let (Some(address), Some(module), Some(function)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            bail!("expected <address>::<module>::<function>, got {}", text);
+        };
+        Ok((
+            ModuleId::new(
+                self.address(address)?,
+                Identifier::new(module).with_context(|| format!("Invalid module in {}", text))?,
+            ),
+            Identifier::new(function).with_context(|| format!("Invalid function in {}", text))?,
+        ))
+    }
+
+    /// Replace names that lead a `name::module::Struct` path with their
+    /// address.
+    fn substitute(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut token = String::new();
+        for c in text.chars().chain(std::iter::once('\0')) {
+            if c.is_alphanumeric() || c == '_' || c == ':' {
+                token.push(c);
+                continue;
+            }
+            match token.split_once("::") {
+                Some((name, rest)) if self.0.contains_key(name) => {
+                    out.push_str(&format!("{}::{}", self.0[name].to_hex_literal(), rest));
+                }
+                _ => out.push_str(&token),
+            }
+            token.clear();
+            if c != '\0' {
+                out.push(c);
+            }
+        }
+        out
+    }
+}
+
+/// BCS-encode a typed transaction argument written as `<type>:<value>`.
+///
+/// Supported types are `bool`, `u8` to `u256`, `address` (any address
+/// expression), `string`, `hex` (raw bytes as `vector<u8>`) and
+/// `vector<T>` of any of those with comma-separated values, e.g.
+/// `vector<u64>:1,2,3`.
+pub fn parse_arg(spec: &str, names: &NamedAddresses) -> Result<Vec<u8>> {
+    let (ty, value) = spec
+        .split_once(':')
+        .with_context(|| format!("expected <type>:<value>, got {}", spec))?;
+    let ty = ty.trim();
+    if let Some(inner) = ty
+        .strip_prefix("vector<")
+        .and_then(|ty| ty.strip_suffix('>'))
+    {
+        let values: Vec<&str> = if value.trim().is_empty() {
+            vec![]
+        } else {
+            value.split(',').collect()
+        };
+        // A vector is its ULEB128 length followed by the elements.
+        let mut bytes = bcs::to_bytes(&vec![(); values.len()])?;
+        for value in values {
+            bytes.extend(parse_arg(&format!("{}:{}", inner, value), names)?);
+        }
+        return Ok(bytes);
+    }
+    if ty == "string" {
+        return Ok(bcs::to_bytes(value)?);
+    }
+    let value = value.trim();
+    let invalid = || format!("Invalid {} argument {}", ty, value);
+    Ok(match ty {
+        "bool" => bcs::to_bytes(&bool::from_str(value).with_context(invalid)?)?,
+        "u8" => bcs::to_bytes(&u8::from_str(value).with_context(invalid)?)?,
+        "u16" => bcs::to_bytes(&u16::from_str(value).with_context(invalid)?)?,
+        "u32" => bcs::to_bytes(&u32::from_str(value).with_context(invalid)?)?,
+        "u64" => bcs::to_bytes(&u64::from_str(value).with_context(invalid)?)?,
+        "u128" => bcs::to_bytes(&u128::from_str(value).with_context(invalid)?)?,
+        "u256" => bcs::to_bytes(&U256::from_str(value).with_context(invalid)?)?,
+        "address" => bcs::to_bytes(&names.address(value)?)?,
+        "hex" => {
+            bcs::to_bytes(&hex::decode(value.trim_start_matches("0x")).with_context(invalid)?)?
+        }
+        _ => bail!("Unsupported argument type {} in {}", ty, spec),
+    })
+}
+
+/// Encode `arg` as a value of the parameter type `param`.
+///
+/// The `<type>:` prefix of [`parse_arg`] is optional here; when given, it
+/// must agree with `param`. Without one, `vector<u8>` takes hex, and
+/// `0x1::string::String` and `0x1::object::Object<T>` take a string and an
+/// address expression.
+pub fn parse_arg_as(param: &TypeTag, arg: &str, names: &NamedAddresses) -> Result<Vec<u8>> {
+    let ty = arg_type(param)
+        .with_context(|| format!("Unsupported parameter type {}", param.to_canonical_string()))?;
+    if let Some((prefix, _)) = arg.split_once(':') {
+        let prefix = prefix.trim();
+        if is_arg_type(prefix) {
+            ensure!(
+                prefix == ty || (prefix == "vector<u8>" && ty == "hex"),
+                "Argument {} does not match parameter type {}",
+                arg,
+                param.to_canonical_string()
+            );
+            return parse_arg(arg, names);
+        }
+    }
+    parse_arg(&format!("{}:{}", ty, arg), names)
+}
+
+/// The [`parse_arg`] type for values of `tag`, if it has one
+fn arg_type(tag: &TypeTag) -> Option<String> {
+    Some(match tag {
+        TypeTag::Bool => "bool".to_string(),
+        TypeTag::U8 => "u8".to_string(),
+        TypeTag::U16 => "u16".to_string(),
+        TypeTag::U32 => "u32".to_string(),
+        TypeTag::U64 => "u64".to_string(),
+        TypeTag::U128 => "u128".to_string(),
+        TypeTag::U256 => "u256".to_string(),
+        TypeTag::Address => "address".to_string(),
+        TypeTag::Vector(inner) if **inner == TypeTag::U8 => "hex".to_string(),
+        TypeTag::Vector(inner) => format!("vector<{}>", arg_type(inner)?),
+        TypeTag::Struct(tag) if tag.address == AccountAddress::ONE => {
+            match (tag.module.as_str(), tag.name.as_str()) {
+                ("string", "String") => "string".to_string(),
+                // `Object<T> { inner: address }` encodes as its address
+                ("object", "Object") => "address".to_string(),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    })
+}
+
+fn is_arg_type(ty: &str) -> bool {
+    match ty
+        .strip_prefix("vector<")
+        .and_then(|ty| ty.strip_suffix('>'))
+    {
+        Some(inner) => is_arg_type(inner),
+        None => matches!(
+            ty,
+            "bool" | "u8" | "u16" | "u32" | "u64" | "u128" | "u256" | "address" | "string" | "hex"
+        ),
+    }
+}
+
+/// Types of the non-signer parameters of the entry function `function` in
+/// `module`, instantiated with `ty_args`.
+pub fn entry_function_params(
+    module: &CompiledModule,
+    function: &IdentStr,
+    ty_args: &[TypeTag],
+) -> Result<Vec<TypeTag>> {
+    let handle = module
+        .function_defs()
+        .iter()
+        .filter(|def| def.is_entry)
+        .map(|def| module.function_handle_at(def.function))
+        .find(|handle| module.identifier_at(handle.name) == function)
+        .with_context(|| {
+            format!(
+                "{}::{} is not an entry function",
+                module.self_id(),
+                function
+            )
+        })?;
+    ensure!(
+        handle.type_parameters.len() == ty_args.len(),
+        "{}::{} takes {} type argument(s), got {}",
+        module.self_id(),
+        function,
+        handle.type_parameters.len(),
+        ty_args.len()
+    );
+    module
+        .signature_at(handle.parameters)
+        .0
+        .iter()
+        .filter(|token| !is_signer(token))
+        .map(|token| type_tag(module, token, ty_args))
+        .collect()
+}
+
+fn is_signer(token: &SignatureToken) -> bool {
+    match token {
+        SignatureToken::Signer => true,
+        SignatureToken::Reference(inner) => **inner == SignatureToken::Signer,
+        _ => false,
+    }
+}
+
+fn type_tag(
+    module: &CompiledModule,
+    token: &SignatureToken,
+    ty_args: &[TypeTag],
+) -> Result<TypeTag> {
+    Ok(match token {
+        SignatureToken::Bool => TypeTag::Bool,
+        SignatureToken::U8 => TypeTag::U8,
+        SignatureToken::U16 => TypeTag::U16,
+        SignatureToken::U32 => TypeTag::U32,
+        SignatureToken::U64 => TypeTag::U64,
+        SignatureToken::U128 => TypeTag::U128,
+        SignatureToken::U256 => TypeTag::U256,
+        SignatureToken::Address => TypeTag::Address,
+        SignatureToken::Vector(inner) => {
+            TypeTag::Vector(Box::new(type_tag(module, inner, ty_args)?))
+        }
+        SignatureToken::TypeParameter(idx) => ty_args[*idx as usize].clone(),
+        SignatureToken::Struct(idx) => TypeTag::Struct(Box::new(struct_tag(module, *idx, vec![]))),
+        SignatureToken::StructInstantiation(idx, args) => {
+            let type_args = args
+                .iter()
+                .map(|arg| type_tag(module, arg, ty_args))
+                .collect::<Result<_>>()?;
+            TypeTag::Struct(Box::new(struct_tag(module, *idx, type_args)))
+        }
+        _ => bail!("Unsupported entry function parameter {:?}", token),
+    })
+}
+
+fn struct_tag(
+    module: &CompiledModule,
+    idx: StructHandleIndex,
+    type_args: Vec<TypeTag>,
+) -> StructTag {
+    let handle = module.struct_handle_at(idx);
+    let module_handle = module.module_handle_at(handle.module);
+    StructTag {
+        address: *module.address_identifier_at(module_handle.address),
+        module: module.identifier_at(module_handle.name).to_owned(),
+        name: module.identifier_at(handle.name).to_owned(),
+        type_args,
+    }
+}
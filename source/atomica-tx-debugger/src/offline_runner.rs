@@ -1,55 +1,150 @@
 use aptos_types::{
     account_address::AccountAddress,
     chain_id::ChainId,
-    state_store::{state_key::StateKey, state_value::StateValue, state_storage_usage::StateStorageUsage, StateViewResult},
-    transaction::{SignedTransaction, TransactionOutput, TransactionStatus, signature_verified_transaction::SignatureVerifiedTransaction, Transaction, AuxiliaryInfo, authenticator::AuthenticationKey, RawTransaction},
-    write_set::WriteSet,
+    state_store::{state_key::{inner::StateKeyInner, StateKey}, state_value::StateValue, state_storage_usage::StateStorageUsage, StateViewResult, TStateView},
+    transaction::{ExecutionStatus, SignedTransaction, TransactionOutput, TransactionPayload, TransactionStatus, signature_verified_transaction::SignatureVerifiedTransaction, Transaction, AuxiliaryInfo, authenticator::AuthenticationKey, RawTransaction},
+    write_set::{WriteOpKind, WriteSet},
+    access_path::Path as StatePath,
+    contract_event::ContractEvent,
     block_executor::{
         config::BlockExecutorConfigFromOnchain,
         transaction_slice_metadata::TransactionSliceMetadata,
     },
+    account_config::{AccountResource, CoinStoreResource, FungibleStoreResource},
+    on_chain_config::{FeatureFlag, Features},
     AptosCoinType,
 };
-use aptos_framework::ReleaseBundle;
-use aptos_vm::{aptos_vm::AptosVMBlockExecutor, VMBlockExecutor};
-use aptos_vm_genesis::{generate_test_genesis, GENESIS_KEYPAIR};
+use aptos_framework::{natives::code::PackageRegistry, ReleaseBundle};
+use aptos_gas_meter::AptosGasMeter;
+use aptos_gas_profiling::GasProfiler;
+use aptos_vm::{
+    aptos_vm::{AptosSimulationVM, AptosVMBlockExecutor},
+    data_cache::AsMoveResolver,
+    gas::ProdGasMeter,
+    AptosVM, VMBlockExecutor,
+};
+use aptos_vm_environment::environment::AptosEnvironment;
+use aptos_vm_logging::log_schema::AdapterLogSchema;
+use aptos_vm_types::module_and_script_storage::AsAptosCodeStorage;
+use aptos_vm_genesis::GENESIS_KEYPAIR;
 use aptos_block_executor::txn_provider::default::DefaultTxnProvider;
-use move_core_types::move_resource::MoveStructType;
-use std::collections::HashMap;
-use anyhow::{Context, Result};
+use aptos_resource_viewer::AptosValueAnnotator;
+use move_binary_format::CompiledModule;
+use serde::Serialize;
+use serde_json::json;
+use move_core_types::{
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS},
+    move_resource::MoveStructType,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::Path,
+};
+use anyhow::{anyhow, bail, Context, Result};
 use aptos_types::event::{EventHandle, EventKey};
 
+use crate::abort::{AbortExplanation, ErrorMaps};
+use crate::accounts::{primary_store_address, AccountKey, APT_METADATA};
+use crate::decode::{
+    describe_state_key, struct_to_json, value_to_json, DecodedEvent, DecodedWrite, WriteKind,
+};
+use crate::diff::{BalanceChange, StateChange, StateDiff};
+use crate::gas_profile::GasProfile;
+use crate::move_args;
+use crate::genesis::{load_release_bundle, GenesisOptions};
+use crate::rest_fork::RestForkStateView;
+use crate::snapshot::Snapshot;
+use crate::trace::{ExecutionTrace, TracingGasMeter};
+
+/// `object::INIT_GUID_CREATION_NUM`, the first GUID an object hands out
+const INIT_GUID_CREATION_NUM: u64 = 0x4000000000000;
+
+/// `0x1::object::ObjectCore`, including the GUID counter
+#[derive(Serialize)]
+struct ObjectCore {
+    guid_creation_num: u64,
+    owner: AccountAddress,
+    allow_ungated_transfer: bool,
+    transfer_events: EventHandle,
+}
+
 /// Minimal in-memory state view for offline transaction execution
+///
+/// With a fork, keys not written locally are read from the forked network.
 pub struct InMemoryStateView {
     state: HashMap<StateKey, StateValue>,
+    fork: Option<RestForkStateView>,
+    /// Keys deleted locally, which the fork may still hold
+    deleted: HashSet<StateKey>,
 }
 
 impl InMemoryStateView {
     fn new() -> Self {
+        Self::from_entries(vec![])
+    }
+
+    fn from_entries(entries: Vec<(StateKey, StateValue)>) -> Self {
         Self {
-            state: HashMap::new(),
+            state: entries.into_iter().collect(),
+            fork: None,
+            deleted: HashSet::new(),
+        }
+    }
+
+    fn get(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        Ok(self.get_state_value(state_key)?)
+    }
+
+    fn insert(&mut self, state_key: StateKey, state_value: StateValue) {
+        self.deleted.remove(&state_key);
+        self.state.insert(state_key, state_value);
+    }
+
+    fn remove(&mut self, state_key: &StateKey) {
+        self.state.remove(state_key);
+        if self.fork.is_some() {
+            self.deleted.insert(state_key.clone());
         }
     }
 
+    /// Every present value: local writes over whatever the fork has read
+    fn entries(&self) -> Vec<(StateKey, StateValue)> {
+        let mut entries: HashMap<_, _> = self
+            .fork
+            .iter()
+            .flat_map(|fork| fork.cached_values())
+            .filter(|(key, _)| !self.deleted.contains(key))
+            .collect();
+        entries.extend(self.state.iter().map(|(key, value)| (key.clone(), value.clone())));
+        entries.into_iter().collect()
+    }
+
     fn apply_write_set(&mut self, write_set: &WriteSet) {
         for (state_key, write_op) in write_set.as_v0().iter() {
             // WriteOp is now a wrapper around BaseStateOp
             if let Some(state_value) = write_op.as_state_value_opt() {
                 // Creation or Modification
-                self.state.insert(state_key.clone(), state_value.clone());
+                self.insert(state_key.clone(), state_value.clone());
             } else {
                 // Deletion
-                self.state.remove(state_key);
+                self.remove(state_key);
             }
         }
     }
 }
 
-impl aptos_types::state_store::TStateView for InMemoryStateView {
+impl TStateView for InMemoryStateView {
     type Key = StateKey;
 
     fn get_state_value(&self, state_key: &StateKey) -> StateViewResult<Option<StateValue>> {
-        Ok(self.state.get(state_key).cloned())
+        if let Some(value) = self.state.get(state_key) {
+            return Ok(Some(value.clone()));
+        }
+        match &self.fork {
+            Some(fork) if !self.deleted.contains(state_key) => fork.get_state_value(state_key),
+            _ => Ok(None),
+        }
     }
 
     fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
@@ -61,56 +156,313 @@ impl aptos_types::state_store::TStateView for InMemoryStateView {
 pub struct OfflineTxnRunner {
     state_view: InMemoryStateView,
     chain_id: ChainId,
+    /// Every key the last applied transaction wrote, with its value before
+    /// and after
+    last_writes: Vec<(StateKey, Option<StateValue>, Option<StateValue>)>,
 }
 
 impl OfflineTxnRunner {
-    /// Create a new executor with genesis from the given release bundle
+    /// Create a new executor with the default genesis from the given release bundle
+    ///
+    /// Panics if genesis fails; use [`Self::with_genesis`] to handle the error.
     pub fn new(release_bundle: &ReleaseBundle) -> Self {
-        let mut state_view = InMemoryStateView::new();
-        let chain_id = ChainId::test();
+        Self::with_genesis(release_bundle, &GenesisOptions::default())
+            .expect("Failed to boot from release bundle")
+    }
 
-        // Generate genesis using the provided framework
-        let genesis = generate_test_genesis(release_bundle, None);
+    /// Boot from a `.mrb` file with the given genesis options
+    pub fn from_mrb(path: &Path, options: &GenesisOptions) -> Result<Self> {
+        let release_bundle = load_release_bundle(path)?;
+        Self::with_genesis(&release_bundle, options)
+            .with_context(|| format!("Failed to boot from {:?}", path))
+    }
 
-        // Apply genesis write set
-        state_view.apply_write_set(genesis.0.write_set());
+    /// Create a new executor with genesis built from `options`
+    pub fn with_genesis(release_bundle: &ReleaseBundle, options: &GenesisOptions) -> Result<Self> {
+        let genesis = options.build(release_bundle)?;
 
-        Self {
+        let mut runner = Self {
+            state_view: InMemoryStateView::new(),
+            chain_id: options.chain_id,
+            last_writes: vec![],
+        };
+        runner.state_view.apply_write_set(genesis.write_set());
+
+        if options.initial_timestamp_usecs > 0 {
+            runner.set_timestamp_usecs(options.initial_timestamp_usecs)?;
+        }
+        for account in &options.funded_accounts {
+            runner
+                .mint(account.address, account.balance)
+                .with_context(|| format!("Failed to fund {}", account.address))?;
+        }
+        Ok(runner)
+    }
+
+    /// Restore a runner from a snapshot written by [`Self::save_snapshot`]
+    pub fn load_snapshot(path: &Path) -> Result<Self> {
+        let snapshot = Snapshot::load(path)?;
+        Ok(Self {
+            state_view: InMemoryStateView::from_entries(snapshot.state),
+            chain_id: snapshot.chain_id,
+            last_writes: vec![],
+        })
+    }
+
+    /// Execute on top of a forked network's state
+    ///
+    /// The chain id is read from `0x1::chain_id::ChainId` on the fork.
+    pub fn fork(fork: RestForkStateView) -> Result<Self> {
+        let key = StateKey::resource(&CORE_CODE_ADDRESS, &framework_struct_tag("chain_id", "ChainId"))
+            .context("Failed to create state key for chain id")?;
+        let chain_id = fork
+            .get_state_value(&key)?
+            .context("Forked state has no 0x1::chain_id::ChainId")?;
+        let chain_id: u8 = bcs::from_bytes(chain_id.bytes()).context("Failed to decode chain id")?;
+        let mut state_view = InMemoryStateView::new();
+        state_view.fork = Some(fork);
+        Ok(Self {
             state_view,
-            chain_id,
+            chain_id: ChainId::new(chain_id),
+            last_writes: vec![],
+        })
+    }
+
+    /// Write what the fork has fetched so far to its cache file, if any
+    ///
+    /// The cache is also written when the runner is dropped, where a failure
+    /// is only logged; call this to get the error.
+    pub fn save_fork_cache(&self) -> Result<()> {
+        match &self.state_view.fork {
+            Some(fork) => fork.save_cache(),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the current state and chain id to `path`
+    ///
+    /// For a forked runner this covers every value read from the fork so far,
+    /// so replaying the same transactions from the snapshot needs no network.
+    pub fn save_snapshot(&self, path: &Path) -> Result<()> {
+        Snapshot::new(self.chain_id, self.state_view.entries()).save(path)
+    }
+
+    pub fn chain_id(&self) -> ChainId {
+        self.chain_id
+    }
+
+    /// Overwrite `0x1::timestamp::CurrentTimeMicroseconds`
+    pub fn set_timestamp_usecs(&mut self, usecs: u64) -> Result<()> {
+        let tag = framework_struct_tag("timestamp", "CurrentTimeMicroseconds");
+        let key = StateKey::resource(&CORE_CODE_ADDRESS, &tag)
+            .context("Failed to create state key for timestamp")?;
+        let value = bcs::to_bytes(&usecs).context("Failed to serialize timestamp")?;
+        self.state_view
+            .insert(key, StateValue::new_legacy(value.into()));
+        Ok(())
+    }
+
+    /// Current `0x1::timestamp::CurrentTimeMicroseconds`
+    pub fn timestamp_usecs(&self) -> Result<u64> {
+        let tag = framework_struct_tag("timestamp", "CurrentTimeMicroseconds");
+        let value = self
+            .resource(CORE_CODE_ADDRESS, &tag)?
+            .context("State has no 0x1::timestamp::CurrentTimeMicroseconds")?;
+        bcs::from_bytes(&value).context("Failed to decode timestamp")
+    }
+
+    /// Move on-chain time forward by `usecs`
+    pub fn advance_time_usecs(&mut self, usecs: u64) -> Result<()> {
+        let now = self.timestamp_usecs()?;
+        let later = now
+            .checked_add(usecs)
+            .context("Advancing time overflows u64 microseconds")?;
+        self.set_timestamp_usecs(later)
+    }
+
+    /// Bytes of the resource `tag` at `address`, stored either directly or as
+    /// a member of the `0x1::object::ObjectGroup` there
+    pub fn resource(&self, address: AccountAddress, tag: &StructTag) -> Result<Option<Vec<u8>>> {
+        let key = StateKey::resource(&address, tag)
+            .with_context(|| format!("Failed to create state key for {}", tag))?;
+        if let Some(value) = self.state_view.get(&key)? {
+            return Ok(Some(value.bytes().to_vec()));
         }
+        Ok(self
+            .object_group(address)?
+            .and_then(|mut group| group.remove(tag)))
+    }
+
+    /// Balance of `owner`'s primary store for the fungible asset at `metadata`
+    pub fn fungible_balance(&self, owner: AccountAddress, metadata: AccountAddress) -> Result<u64> {
+        let Some(group) = self.object_group(primary_store_address(owner, metadata))? else {
+            return Ok(0);
+        };
+        Ok(fungible_store(&group)?.map_or(0, |(_metadata, balance)| balance))
+    }
+
+    /// APT held by `owner`, counting both its primary fungible store and any
+    /// legacy `CoinStore<AptosCoin>`
+    pub fn apt_balance(&self, owner: AccountAddress) -> Result<u64> {
+        let coin_store = match self.resource(owner, &CoinStoreResource::<AptosCoinType>::struct_tag())? {
+            Some(bytes) => bcs::from_bytes::<CoinStoreResource<AptosCoinType>>(&bytes)
+                .context("Failed to decode CoinStore")?
+                .coin(),
+            None => 0,
+        };
+        Ok(coin_store + self.fungible_balance(owner, APT_METADATA)?)
+    }
+
+    /// Decode resource bytes of type `tag` as JSON, using the modules in state
+    pub fn decode_resource(&self, tag: &StructTag, bytes: &[u8]) -> Result<serde_json::Value> {
+        let value = AptosValueAnnotator::new(&self.state_view)
+            .view_resource(tag, bytes)
+            .with_context(|| format!("Failed to decode {}", tag))?;
+        Ok(struct_to_json(&value))
+    }
+
+    /// Decode a value of type `type_tag`, e.g. event data, as JSON
+    pub fn decode_value(&self, type_tag: &TypeTag, bytes: &[u8]) -> Result<serde_json::Value> {
+        let value = AptosValueAnnotator::new(&self.state_view)
+            .view_value(type_tag, bytes)
+            .with_context(|| format!("Failed to decode {}", type_tag))?;
+        Ok(value_to_json(&value))
+    }
+
+    /// Decode a state value as JSON: resources and resource group members by
+    /// their Move layout, modules as their name and size, and anything
+    /// without a known layout (table items, raw keys) as hex
+    pub fn decode_state_value(&self, key: &StateKey, bytes: &[u8]) -> serde_json::Value {
+        let decoded = match key.inner() {
+            StateKeyInner::AccessPath(access_path) => match access_path.get_path() {
+                StatePath::Resource(tag) => self.decode_resource(&tag, bytes),
+                StatePath::ResourceGroup(_) => self.decode_resource_group(bytes),
+                StatePath::Code(module) => Ok(json!({
+                    "module": module.short_str_lossless(),
+                    "size": bytes.len(),
+                })),
+            },
+            _ => Err(anyhow!("No layout for {:?}", key)),
+        };
+        decoded.unwrap_or_else(|_| json!(format!("0x{}", hex::encode(bytes))))
+    }
+
+    fn decode_resource_group(&self, bytes: &[u8]) -> Result<serde_json::Value> {
+        let group: BTreeMap<StructTag, Vec<u8>> =
+            bcs::from_bytes(bytes).context("Failed to decode resource group")?;
+        let members = group
+            .iter()
+            .map(|(tag, bytes)| Ok((tag.to_canonical_string(), self.decode_resource(tag, bytes)?)))
+            .collect::<Result<serde_json::Map<_, _>>>()?;
+        Ok(serde_json::Value::Object(members))
+    }
+
+    /// Decode every entry of `write_set` against the current state
+    ///
+    /// Call this after the transaction is applied, so that modules it
+    /// published are available for decoding its own resources.
+    pub fn decode_write_set(&self, write_set: &WriteSet) -> Vec<DecodedWrite> {
+        write_set
+            .as_v0()
+            .iter()
+            .map(|(key, write_op)| DecodedWrite {
+                key: describe_state_key(key),
+                kind: match write_op.write_op_kind() {
+                    WriteOpKind::Creation => WriteKind::Create,
+                    WriteOpKind::Modification => WriteKind::Modify,
+                    WriteOpKind::Deletion => WriteKind::Delete,
+                },
+                value: write_op
+                    .as_state_value_opt()
+                    .map(|value| self.decode_state_value(key, value.bytes())),
+            })
+            .collect()
     }
 
-    /// Fund an account by executing framework transactions as aptos_framework (0x1)
-    /// This creates the account properly and mints coins to it
-    pub fn fund_account(&mut self, address: AccountAddress, amount: u64, _sequence_number: u64) {
+    /// Decode event payloads by their Move type, falling back to hex
+    pub fn decode_events(&self, events: &[ContractEvent]) -> Vec<DecodedEvent> {
+        events
+            .iter()
+            .map(|event| DecodedEvent {
+                type_: event.type_tag().to_canonical_string(),
+                data: self
+                    .decode_value(event.type_tag(), event.event_data())
+                    .unwrap_or_else(|_| json!(format!("0x{}", hex::encode(event.event_data())))),
+            })
+            .collect()
+    }
+
+    /// Parameter types of the entry function `module::function` as
+    /// published in state, signers excluded
+    pub fn entry_function_params(
+        &self,
+        module: &ModuleId,
+        function: &IdentStr,
+        ty_args: &[TypeTag],
+    ) -> Result<Vec<TypeTag>> {
+        let bytes = self
+            .state_view
+            .get(&StateKey::module_id(module))?
+            .with_context(|| format!("Module {} is not published", module))?;
+        let compiled = CompiledModule::deserialize(bytes.bytes())
+            .with_context(|| format!("Failed to deserialize module {}", module))?;
+        move_args::entry_function_params(&compiled, function, ty_args)
+    }
+
+    /// Explain an abort `code` raised in `module`, from the module's error
+    /// map and, if its package was published with sources, the line that
+    /// declares the error constant
+    pub fn decode_abort(&self, module: &ModuleId, code: u64) -> Result<AbortExplanation> {
+        let mut maps = ErrorMaps::new();
+        let Some(bytes) = self.state_view.get(&StateKey::module_id(module))? else {
+            return Ok(maps.decode(module, code));
+        };
+        let compiled = CompiledModule::deserialize(bytes.bytes())
+            .with_context(|| format!("Failed to deserialize module {}", module))?;
+        let registry = self
+            .resource(*module.address(), &framework_struct_tag("code", "PackageRegistry"))?
+            .map(|bytes| bcs::from_bytes::<PackageRegistry>(&bytes))
+            .transpose()
+            .context("Failed to decode package registry")?;
+        let package = registry.as_ref().and_then(|registry| {
+            registry
+                .packages
+                .iter()
+                .find(|package| package.modules.iter().any(|m| m.name == module.name().as_str()))
+        });
+        match package {
+            Some(package) => maps.add_package(package, [compiled]),
+            None => maps.add_module(&compiled),
+        }
+        Ok(maps.decode(module, code))
+    }
+
+    /// Mint APT to `address` from the core resources account, which test
+    /// genesis gives a mint capability signed for by `GENESIS_KEYPAIR`.
+    fn mint(&mut self, address: AccountAddress, amount: u64) -> Result<()> {
         use aptos_cached_packages::aptos_stdlib;
-        use move_core_types::language_storage::CORE_CODE_ADDRESS;
-
-        // Execute transactions as aptos_framework (0x1) to properly create the account
-        let framework_address = CORE_CODE_ADDRESS;
-
-        // Transaction 1: Mint coins to the address (this also creates the account if it doesn't exist)
-        let mint_payload = aptos_stdlib::aptos_coin_mint(address, amount);
-        let mint_txn = RawTransaction::new_entry_function(
-            framework_address,
-            0, // sequence number for framework account
-            mint_payload.into_entry_function(),
-            1_000_000, // max gas
-            100, // gas price - must be above min bound
+        use aptos_types::account_config::aptos_test_root_address;
+
+        let root = aptos_test_root_address();
+        let sequence_number = self
+            .account(root)?
+            .context("Genesis did not create the core resources account")?
+            .sequence_number();
+
+        let txn = RawTransaction::new_entry_function(
+            root,
+            sequence_number,
+            aptos_stdlib::aptos_coin_mint(address, amount).into_entry_function(),
+            1_000_000,
+            100,
             u64::MAX,
             self.chain_id,
         );
-
-        // For framework transactions, we can use a dummy signature since prologue is skipped
-        // Actually, let's try executing it properly
-        let signed_mint = self.create_framework_transaction(mint_txn);
-
-        // Execute the mint transaction
-        if let Ok(output) = self.execute_transaction(signed_mint) {
-            eprintln!("Mint transaction status: {:?}", output.status());
-        } else {
-            eprintln!("Failed to execute mint transaction");
+        let signed = self.create_framework_transaction(txn);
+        let output = self.execute_transaction(signed)?;
+        match output.status() {
+            TransactionStatus::Keep(ExecutionStatus::Success) => Ok(()),
+            status => bail!("Mint transaction failed: {:?}", status),
         }
     }
 
@@ -121,75 +473,165 @@ impl OfflineTxnRunner {
         raw_txn.sign(private_key, public_key.clone()).unwrap().into_inner()
     }
 
-    /// Old method - manually create account resources (keeping for reference but deprecated)
-    #[allow(dead_code)]
-    fn fund_account_manual(&mut self, address: AccountAddress, amount: u64, sequence_number: u64) {
-        use aptos_types::account_config::{AccountResource, CoinStoreResource};
-
-        // The authentication key should be 32 bytes
-        // For an Ed25519 single-signer account, the auth key is derived from pubkey
-        // But since we only have the address here, we'll set it to the address bytes
-        // This assumes the address was properly derived from AuthenticationKey
-        let mut auth_key_bytes = [0u8; AuthenticationKey::LENGTH];
-        auth_key_bytes.copy_from_slice(address.as_ref());
-        let auth_key = AuthenticationKey::new(auth_key_bytes).to_vec();
-
-        // Create event handles for the account
-        let coin_register_events = EventHandle::new(EventKey::new(0, address), 0);
-        let key_rotation_events = EventHandle::new(EventKey::new(1, address), 0);
+    /// Read the `0x1::account::Account` resource at `address`, if any
+    pub fn account(&self, address: AccountAddress) -> Result<Option<AccountResource>> {
+        let key = StateKey::resource(&address, &AccountResource::struct_tag())
+            .context("Failed to create state key for account resource")?;
+        self.state_view
+            .get(&key)?
+            .map(|value| {
+                bcs::from_bytes(value.bytes())
+                    .with_context(|| format!("Failed to decode account resource at {}", address))
+            })
+            .transpose()
+    }
 
-        // Create account resource with the specified sequence number
-        let account_resource = AccountResource::new(
+    /// Create or overwrite the `0x1::account::Account` resource at `address`
+    pub fn create_account(
+        &mut self,
+        address: AccountAddress,
+        authentication_key: AuthenticationKey,
+        sequence_number: u64,
+    ) -> Result<()> {
+        // Same GUIDs as `account::create_account` hands out
+        let account = AccountResource::new(
             sequence_number,
-            auth_key,
-            coin_register_events,
-            key_rotation_events,
+            authentication_key.to_vec(),
+            EventHandle::new(EventKey::new(0, address), 0),
+            EventHandle::new(EventKey::new(1, address), 0),
         );
+        let key = StateKey::resource(&address, &AccountResource::struct_tag())
+            .context("Failed to create state key for account resource")?;
+        let value = bcs::to_bytes(&account).context("Failed to serialize account resource")?;
+        self.state_view
+            .insert(key, StateValue::new_legacy(value.into()));
+        Ok(())
+    }
 
-        // Create state key for the account resource
-        let account_state_key = StateKey::resource(
-            &address,
-            &AccountResource::struct_tag(),
-        ).expect("Failed to create state key for account resource");
-
-        // Serialize and store the account resource
-        let account_blob = bcs::to_bytes(&account_resource)
-            .expect("Failed to serialize account resource");
-        self.state_view.state.insert(
-            account_state_key,
-            StateValue::new_legacy(account_blob.into()),
-        );
+    /// Create the account `key` authenticates at its default address
+    pub fn seed_account(&mut self, key: &AccountKey, sequence_number: u64) -> Result<AccountAddress> {
+        let address = key.address();
+        self.create_account(address, key.authentication_key(), sequence_number)?;
+        Ok(address)
+    }
 
-        // Create CoinStore for AptosCoin with the specified balance
-        let deposit_events = EventHandle::new(EventKey::new(2, address), 0);
-        let withdraw_events = EventHandle::new(EventKey::new(3, address), 0);
+    /// Set the APT balance of `owner`'s primary fungible store
+    ///
+    /// A legacy `CoinStore<AptosCoin>`, if any, is left untouched and still
+    /// counts towards `coin::balance`.
+    pub fn set_apt_balance(&mut self, owner: AccountAddress, amount: u64) -> Result<()> {
+        self.set_fungible_balance(owner, APT_METADATA, amount)
+    }
 
-        let coin_store = CoinStoreResource::<AptosCoinType>::new(
-            amount,
-            false, // not frozen
-            deposit_events,
-            withdraw_events,
-        );
+    /// Set the balance of `owner`'s primary store for the fungible asset at
+    /// `metadata`, creating the store if needed
+    ///
+    /// A new store is laid out the way
+    /// `primary_fungible_store::create_primary_store` would. The asset's
+    /// supply is not adjusted.
+    pub fn set_fungible_balance(
+        &mut self,
+        owner: AccountAddress,
+        metadata: AccountAddress,
+        amount: u64,
+    ) -> Result<()> {
+        let metadata_group = self.object_group(metadata)?.unwrap_or_default();
+        if !metadata_group.contains_key(&framework_struct_tag("fungible_asset", "Metadata")) {
+            bail!("No fungible asset metadata at {}", metadata);
+        }
 
-        // Create state key for the coin store
-        let coin_store_state_key = StateKey::resource(
-            &address,
-            &CoinStoreResource::<AptosCoinType>::struct_tag(),
-        ).expect("Failed to create state key for coin store");
-
-        // Debug: print the struct tag
-        eprintln!("Creating CoinStore with struct_tag: {:?}", CoinStoreResource::<AptosCoinType>::struct_tag());
-        eprintln!("Funding account {} with {} coins", address, amount);
-
-        // Serialize and store the coin store
-        let coin_store_blob = bcs::to_bytes(&coin_store)
-            .expect("Failed to serialize coin store");
-        self.state_view.state.insert(
-            coin_store_state_key,
-            StateValue::new_legacy(coin_store_blob.into()),
-        );
+        let store = primary_store_address(owner, metadata);
+        let mut group = match self.object_group(store)? {
+            Some(group) => group,
+            None => self.primary_store_group(owner, store, metadata, &metadata_group)?,
+        };
+        let (_, _, frozen): (AccountAddress, u64, bool) = bcs::from_bytes(
+            group
+                .get(&FungibleStoreResource::struct_tag())
+                .with_context(|| format!("No fungible store at {}", store))?,
+        )
+        .context("Failed to decode fungible store")?;
+        // With a concurrent balance the store's own balance field is unused
+        let concurrent = framework_struct_tag("fungible_asset", "ConcurrentFungibleBalance");
+        let store_balance = match group.get(&concurrent) {
+            Some(bytes) => {
+                let (_value, max_value): (u64, u64) =
+                    bcs::from_bytes(bytes).context("Failed to decode concurrent balance")?;
+                group.insert(concurrent, bcs::to_bytes(&(amount, max_value))?);
+                0
+            },
+            None => amount,
+        };
+        let balance = FungibleStoreResource::new(metadata, store_balance, frozen);
+        group.insert(FungibleStoreResource::struct_tag(), bcs::to_bytes(&balance)?);
+
+        let key = StateKey::resource_group(&store, &object_group_tag());
+        let value = bcs::to_bytes(&group).context("Failed to serialize object group")?;
+        self.state_view
+            .insert(key, StateValue::new_legacy(value.into()));
+        Ok(())
+    }
+
+    /// The empty store `primary_fungible_store::create_primary_store` creates
+    /// at `store` for `owner`
+    fn primary_store_group(
+        &self,
+        owner: AccountAddress,
+        store: AccountAddress,
+        metadata: AccountAddress,
+        metadata_group: &BTreeMap<StructTag, Vec<u8>>,
+    ) -> Result<BTreeMap<StructTag, Vec<u8>>> {
+        if !metadata_group.contains_key(&framework_struct_tag("primary_fungible_store", "DeriveRefPod")) {
+            bail!("Fungible asset {} does not support primary stores", metadata);
+        }
+        let mut group = BTreeMap::new();
+        // `object::create_object_internal` spends the first GUID on the
+        // transfer events, and primary stores disable ungated transfer
+        let core = ObjectCore {
+            guid_creation_num: INIT_GUID_CREATION_NUM + 1,
+            owner,
+            allow_ungated_transfer: false,
+            transfer_events: EventHandle::new(EventKey::new(INIT_GUID_CREATION_NUM, store), 0),
+        };
+        group.insert(framework_struct_tag("object", "ObjectCore"), bcs::to_bytes(&core)?);
+        let fungible_store = FungibleStoreResource::new(metadata, 0, false);
+        group.insert(FungibleStoreResource::struct_tag(), bcs::to_bytes(&fungible_store)?);
+        // `fungible_asset::create_store` marks stores of untransferable assets
+        if metadata_group.contains_key(&framework_struct_tag("fungible_asset", "Untransferable")) {
+            // A field-less Move struct is serialized as its `dummy_field: bool`
+            group.insert(framework_struct_tag("object", "Untransferable"), bcs::to_bytes(&false)?);
+        }
+        if self
+            .features()?
+            .is_enabled(FeatureFlag::DEFAULT_TO_CONCURRENT_FUNGIBLE_BALANCE)
+        {
+            // An unbounded `Aggregator<u64> { value, max_value }`
+            group.insert(
+                framework_struct_tag("fungible_asset", "ConcurrentFungibleBalance"),
+                bcs::to_bytes(&(0u64, u64::MAX))?,
+            );
+        }
+        Ok(group)
+    }
+
+    /// The on-chain `0x1::features::Features`
+    fn features(&self) -> Result<Features> {
+        let bytes = self
+            .resource(CORE_CODE_ADDRESS, &framework_struct_tag("features", "Features"))?
+            .context("No 0x1::features::Features in state")?;
+        bcs::from_bytes(&bytes).context("Failed to decode features")
+    }
 
-        eprintln!("Account funded successfully. State has {} entries", self.state_view.state.len());
+    /// Read the `0x1::object::ObjectGroup` at `address`
+    fn object_group(&self, address: AccountAddress) -> Result<Option<BTreeMap<StructTag, Vec<u8>>>> {
+        let key = StateKey::resource_group(&address, &object_group_tag());
+        self.state_view
+            .get(&key)?
+            .map(|value| {
+                bcs::from_bytes(value.bytes())
+                    .with_context(|| format!("Failed to decode object group at {}", address))
+            })
+            .transpose()
     }
 
     /// Execute a transaction and return the output
@@ -220,20 +662,241 @@ impl OfflineTxnRunner {
         let output = block_output.into_transaction_outputs_forced().into_iter().next()
             .context("No output from VM")?;
 
-        // Apply the write set if the transaction succeeded
-        if matches!(output.status(), TransactionStatus::Keep(_)) {
-            let write_set = output.write_set();
-            self.state_view.apply_write_set(write_set);
-        }
+        self.apply_output(&output)?;
+        Ok(output)
+    }
 
+    /// Simulate a transaction the way the `/simulate` endpoint does: the
+    /// signature is not verified, every other prologue check still runs
+    ///
+    /// The output is applied like any executed transaction's. See
+    /// [`crate::simulate::into_simulation`] for wrapping a `RawTransaction`.
+    pub fn simulate_transaction(&mut self, txn: SignedTransaction) -> Result<TransactionOutput> {
+        let (_, output) =
+            AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &self.state_view);
+        self.apply_output(&output)?;
         Ok(output)
     }
 
-    pub fn enable_tracing(&mut self, trace_path: std::path::PathBuf) {
-        std::env::set_var("TRACE", &trace_path);
-        // Note: Opcode tracing is controlled by the move-vm-runtime "debugging" feature
-        // and environment variables. The actual trace output will be handled by the VM.
+    /// Execute a transaction, recording a structured trace of every call,
+    /// instruction and return along the way
+    ///
+    /// Traced execution runs the transaction alone on the VM rather than
+    /// through the block executor, with the same outcome and gas.
+    pub fn execute_transaction_traced(
+        &mut self,
+        txn: SignedTransaction,
+    ) -> Result<(TransactionOutput, ExecutionTrace)> {
+        let state_view = &self.state_view;
+        let (output, trace) = execute_with_gas_meter(
+            state_view,
+            &txn,
+            |base| match txn.payload() {
+                TransactionPayload::EntryFunction(entry) => TracingGasMeter::for_entry_function(
+                    base,
+                    state_view,
+                    entry.module(),
+                    entry.function(),
+                    entry.ty_args(),
+                ),
+                TransactionPayload::Script(script) => {
+                    TracingGasMeter::for_script(base, state_view, script.code())
+                }
+                _ => TracingGasMeter::new(base, state_view),
+            },
+            |meter, output| meter.finish(output.status()),
+        )?;
+        self.apply_output(&output)?;
+        Ok((output, trace))
+    }
+
+    /// Execute a transaction under the VM's gas profiler
+    ///
+    /// Like [`Self::execute_transaction_traced`], the transaction runs alone
+    /// on the VM rather than through the block executor.
+    pub fn execute_transaction_profiled(
+        &mut self,
+        txn: SignedTransaction,
+    ) -> Result<(TransactionOutput, GasProfile)> {
+        let (output, log) = execute_with_gas_meter(
+            &self.state_view,
+            &txn,
+            |base| match txn.payload() {
+                TransactionPayload::EntryFunction(entry) => GasProfiler::new_function(
+                    base,
+                    entry.module().clone(),
+                    entry.function().to_owned(),
+                    entry.ty_args().to_vec(),
+                ),
+                _ => GasProfiler::new_script(base),
+            },
+            |profiler, _| profiler.finish(),
+        )?;
+        self.apply_output(&output)?;
+        Ok((output, GasProfile::new(log)))
     }
+
+    /// Apply the write set if the transaction is kept, remembering the
+    /// values it replaces for [`Self::last_diff`]
+    fn apply_output(&mut self, output: &TransactionOutput) -> Result<()> {
+        self.last_writes.clear();
+        if !matches!(output.status(), TransactionStatus::Keep(_)) {
+            return Ok(());
+        }
+        for (key, write_op) in output.write_set().as_v0().iter() {
+            let before = self.state_view.get(key)?;
+            let after = write_op.as_state_value_opt().cloned();
+            self.last_writes.push((key.clone(), before, after));
+        }
+        self.state_view.apply_write_set(output.write_set());
+        Ok(())
+    }
+
+    /// Before/after report of the last transaction executed
+    ///
+    /// Old values are decoded with the current modules, which only matters
+    /// when the transaction upgraded the module declaring them.
+    pub fn last_diff(&self) -> Result<StateDiff> {
+        let mut before_balances = BTreeMap::new();
+        let mut after_balances = BTreeMap::new();
+        let mut changes = vec![];
+        for (key, before, after) in &self.last_writes {
+            for (value, balances) in [(before, &mut before_balances), (after, &mut after_balances)] {
+                if let Some(value) = value {
+                    for (account, balance) in store_balances(key, value)? {
+                        *balances.entry(account).or_insert(0u64) += balance;
+                    }
+                }
+            }
+            changes.push(StateChange {
+                key: describe_state_key(key),
+                kind: match (before, after) {
+                    (None, _) => WriteKind::Create,
+                    (Some(_), Some(_)) => WriteKind::Modify,
+                    (Some(_), None) => WriteKind::Delete,
+                },
+                before: before
+                    .as_ref()
+                    .map(|value| self.decode_state_value(key, value.bytes())),
+                after: after
+                    .as_ref()
+                    .map(|value| self.decode_state_value(key, value.bytes())),
+            });
+        }
+
+        let accounts: BTreeSet<_> = before_balances.keys().chain(after_balances.keys()).collect();
+        let balances = accounts
+            .into_iter()
+            .map(|&(owner, asset)| {
+                let before = before_balances.get(&(owner, asset)).copied().unwrap_or(0);
+                let after = after_balances.get(&(owner, asset)).copied().unwrap_or(0);
+                BalanceChange::new(owner, asset, before, after)
+            })
+            .filter(|balance| balance.delta != 0)
+            .collect();
+        Ok(StateDiff { changes, balances })
+    }
+}
+
+/// Run a single user transaction on the VM with a custom gas meter built
+/// from the production one, and `finish` the meter once the output is known
+fn execute_with_gas_meter<G, T>(
+    state_view: &InMemoryStateView,
+    txn: &SignedTransaction,
+    make_gas_meter: impl FnOnce(ProdGasMeter) -> G,
+    finish: impl FnOnce(G, &TransactionOutput) -> T,
+) -> Result<(TransactionOutput, T)>
+where
+    G: AptosGasMeter,
+{
+    let env = AptosEnvironment::new(state_view);
+    let vm = AptosVM::new(&env, state_view);
+    let resolver = state_view.as_move_resolver();
+    let code_storage = state_view.as_aptos_code_storage(&env);
+    let log_context = AdapterLogSchema::new(state_view.id(), 0);
+
+    let (_, vm_output, gas_meter) = vm
+        .execute_user_transaction_with_modified_gas_meter(
+            &resolver,
+            &code_storage,
+            txn,
+            &log_context,
+            make_gas_meter,
+            &AuxiliaryInfo::default(),
+        )
+        .map_err(|status| anyhow!("VM execution failed: {:?}", status))?;
+    let output = vm_output
+        .try_materialize_into_transaction_output(&resolver)
+        .context("Failed to materialize transaction output")?;
+    let result = finish(gas_meter, &output);
+    Ok((output, result))
+}
+
+/// Metadata and balance of the fungible store in an object group, if any
+fn fungible_store(group: &BTreeMap<StructTag, Vec<u8>>) -> Result<Option<(AccountAddress, u64)>> {
+    let Some(bytes) = group.get(&FungibleStoreResource::struct_tag()) else {
+        return Ok(None);
+    };
+    let (metadata, balance, _frozen): (AccountAddress, u64, bool) =
+        bcs::from_bytes(bytes).context("Failed to decode fungible store")?;
+    // `ConcurrentFungibleBalance { balance: Aggregator<u64> }` leads with
+    // the aggregator's value and, when present, replaces the store's own
+    if let Some(bytes) = group.get(&framework_struct_tag("fungible_asset", "ConcurrentFungibleBalance")) {
+        let (value, _max): (u64, u64) =
+            bcs::from_bytes(bytes).context("Failed to decode concurrent balance")?;
+        return Ok(Some((metadata, value)));
+    }
+    Ok(Some((metadata, balance)))
+}
+
+/// Balances held in `value` by (owner, asset): an APT `CoinStore`, or an
+/// object group holding a fungible store
+fn store_balances(key: &StateKey, value: &StateValue) -> Result<Option<((AccountAddress, AccountAddress), u64)>> {
+    let StateKeyInner::AccessPath(access_path) = key.inner() else {
+        return Ok(None);
+    };
+    match access_path.get_path() {
+        StatePath::Resource(tag) if tag == CoinStoreResource::<AptosCoinType>::struct_tag() => {
+            let store: CoinStoreResource<AptosCoinType> =
+                bcs::from_bytes(value.bytes()).context("Failed to decode CoinStore")?;
+            Ok(Some(((access_path.address, APT_METADATA), store.coin())))
+        },
+        StatePath::ResourceGroup(tag) if tag == object_group_tag() => {
+            let group: BTreeMap<StructTag, Vec<u8>> = bcs::from_bytes(value.bytes())
+                .with_context(|| format!("Failed to decode object group at {}", access_path.address))?;
+            let Some((metadata, balance)) = fungible_store(&group)? else {
+                return Ok(None);
+            };
+            let owner = match group.get(&framework_struct_tag("object", "ObjectCore")) {
+                Some(bytes) => {
+                    let (_guid_creation_num, owner, _allow_ungated_transfer, _transfer_events): (
+                        u64,
+                        AccountAddress,
+                        bool,
+                        EventHandle,
+                    ) = bcs::from_bytes(bytes).context("Failed to decode object core")?;
+                    owner
+                },
+                // A store without an ObjectCore is not owned; attribute it to itself
+                None => access_path.address,
+            };
+            Ok(Some(((owner, metadata), balance)))
+        },
+        _ => Ok(None),
+    }
+}
+
+fn framework_struct_tag(module: &str, name: &str) -> StructTag {
+    StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new(module).expect("valid module name"),
+        name: Identifier::new(name).expect("valid struct name"),
+        type_args: vec![],
+    }
+}
+
+fn object_group_tag() -> StructTag {
+    framework_struct_tag("object", "ObjectGroup")
 }
 
 #[cfg(test)]
@@ -0,0 +1,204 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, RwLock,
+    },
+};
+
+use anyhow::{bail, Context, Result};
+use aptos_types::state_store::{
+    state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    StateViewError, StateViewResult, TStateView,
+};
+use reqwest::{blocking::Client, StatusCode};
+use serde::{Deserialize, Serialize};
+
+/// On-disk cache of a [`RestForkStateView`]: every key read so far, including
+/// keys the node reported as absent.
+#[derive(Serialize, Deserialize)]
+struct ForkCache {
+    version: u64,
+    entries: Vec<(StateKey, Option<StateValue>)>,
+}
+
+/// State of a live network at a pinned ledger version, read lazily from an
+/// Aptos REST endpoint.
+///
+/// Every value fetched is kept in memory and, if a cache path is set, written
+/// to disk by [`Self::save_cache`], so a view over a populated cache works
+/// without the endpoint. Dropping the view saves too, but only logs a failure.
+pub struct RestForkStateView {
+    /// `None` for a view that only serves its cache.
+    endpoint: Option<(Client, String)>,
+    version: u64,
+    cache_path: Option<PathBuf>,
+    cache: RwLock<HashMap<StateKey, Option<StateValue>>>,
+    /// Serialises cache file writes.
+    save_lock: Mutex<()>,
+    /// Whether values were fetched since the cache was last written.
+    dirty: AtomicBool,
+}
+
+impl RestForkStateView {
+    /// Fork `url` (e.g. `https://fullnode.mainnet.aptoslabs.com/v1`) at
+    /// `version`, reusing and extending `cache_path` if given.
+    pub fn new(url: &str, version: u64, cache_path: Option<PathBuf>) -> Result<Self> {
+        let cache = match &cache_path {
+            Some(path) if path.exists() => {
+                let cache = load_cache(path)?;
+                if cache.version != version {
+                    bail!(
+                        "Fork cache {:?} was recorded at version {}, not {}",
+                        path,
+                        cache.version,
+                        version
+                    );
+                }
+                cache.entries.into_iter().collect()
+            }
+            _ => HashMap::new(),
+        };
+        Ok(Self {
+            endpoint: Some((Client::new(), url.trim_end_matches('/').to_string())),
+            version,
+            cache_path,
+            cache: RwLock::new(cache),
+            save_lock: Mutex::new(()),
+            dirty: AtomicBool::new(false),
+        })
+    }
+
+    /// Serve only what `cache_path` recorded; reading any other key fails.
+    pub fn offline(cache_path: &Path) -> Result<Self> {
+        let cache = load_cache(cache_path)?;
+        Ok(Self {
+            endpoint: None,
+            version: cache.version,
+            cache_path: Some(cache_path.to_path_buf()),
+            cache: RwLock::new(cache.entries.into_iter().collect()),
+            save_lock: Mutex::new(()),
+            dirty: AtomicBool::new(false),
+        })
+    }
+
+    /// The ledger version the view is pinned to.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Every present value read so far.
+    pub fn cached_values(&self) -> Vec<(StateKey, StateValue)> {
+        self.cache
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|(key, value)| Some((key.clone(), value.clone()?)))
+            .collect()
+    }
+
+    /// Write the cache to its file, if it has one and fetched anything new.
+    pub fn save_cache(&self) -> Result<()> {
+        let Some(path) = &self.cache_path else {
+            return Ok(());
+        };
+        let _guard = self.save_lock.lock().unwrap();
+        if !self.dirty.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let mut entries: Vec<_> = self
+            .cache
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        entries.sort_by(|(a, _), (b, _)| a.encoded().cmp(b.encoded()));
+        let cache = ForkCache {
+            version: self.version,
+            entries,
+        };
+        let written = bcs::to_bytes(&cache)
+            .context("Failed to serialize fork cache")
+            .and_then(|bytes| {
+                fs::write(path, bytes)
+                    .with_context(|| format!("Failed to write fork cache {:?}", path))
+            });
+        if written.is_err() {
+            self.dirty.store(true, Ordering::SeqCst);
+        }
+        written
+    }
+
+    /// `POST /experimental/state_values/raw`, which answers with the BCS
+    /// encoded `StateValue` or 404 if the key is absent at `version`.
+    fn fetch(&self, state_key: &StateKey) -> Result<Option<StateValue>> {
+        let Some((client, url)) = &self.endpoint else {
+            bail!("{:?} is not in the offline fork cache", state_key);
+        };
+        let key = bcs::to_bytes(state_key).context("Failed to serialize state key")?;
+        let response = client
+            .post(format!("{}/experimental/state_values/raw", url))
+            .query(&[("ledger_version", self.version)])
+            .header("Accept", "application/x-bcs")
+            .json(&serde_json::json!({ "key": format!("0x{}", hex::encode(key)) }))
+            .send()
+            .with_context(|| format!("Failed to fetch {:?} from {}", state_key, url))?;
+        match response.status() {
+            StatusCode::OK => {
+                let bytes = response.bytes().context("Failed to read state value")?;
+                Ok(Some(
+                    bcs::from_bytes(&bytes).context("Failed to deserialize state value")?,
+                ))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            status => bail!(
+                "Fetching {:?} from {} failed with {}: {}",
+                state_key,
+                url,
+                status,
+                response.text().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+impl TStateView for RestForkStateView {
+    type Key = StateKey;
+
+    fn get_state_value(&self, state_key: &StateKey) -> StateViewResult<Option<StateValue>> {
+        if let Some(value) = self.cache.read().unwrap().get(state_key) {
+            return Ok(value.clone());
+        }
+        let value = self
+            .fetch(state_key)
+            .map_err(|err| StateViewError::Other(format!("{:#}", err)))?;
+        self.cache
+            .write()
+            .unwrap()
+            .insert(state_key.clone(), value.clone());
+        self.dirty.store(true, Ordering::SeqCst);
+        Ok(value)
+    }
+
+    fn get_usage(&self) -> StateViewResult<StateStorageUsage> {
+        Ok(StateStorageUsage::zero())
+    }
+}
+
+/// A last-chance save; callers that care whether it worked use
+/// [`RestForkStateView::save_cache`] first, which leaves nothing to write here.
+impl Drop for RestForkStateView {
+    fn drop(&mut self) {
+        if let Err(err) = self.save_cache() {
+            aptos_logger::warn!("Fork cache not saved: {:#}", err);
+        }
+    }
+}
+
+fn load_cache(path: &Path) -> Result<ForkCache> {
+    let bytes = fs::read(path).with_context(|| format!("Failed to read fork cache {:?}", path))?;
+    bcs::from_bytes(&bytes).with_context(|| format!("Failed to deserialize fork cache {:?}", path))
+}
@@ -0,0 +1,595 @@
+//! Multi-step scenarios for the offline runner.
+//!
+//! A scenario names its accounts, seeds them, then runs steps (publishing
+//! packages, calling entry functions, advancing time, topping up balances),
+//! each with optional expectations on the outcome. Scenarios are written in
+//! YAML or JSON:
+//!
+//! ```yaml
+//! accounts:
+//!   atomica: { address: "0xcafe", balance: 100000000000 }
+//!   alice: { balance: 10000000 }
+//! steps:
+//!   - name: publish fake_eth
+//!     publish: { sender: atomica, package: ../atomica-move-contracts, named_addresses: { atomica: atomica } }
+//!   - call: { sender: atomica, function: "atomica::fake_eth::initialize" }
+//!   - call: { sender: alice, function: "atomica::fake_eth::mint", args: ["u64:2000000000000"] }
+//!     expect: { status: abort, abort_code: 1 }
+//!   - advance_time: { seconds: 3600 }
+//! ```
+//!
+//! Accounts without a `private_key` get an Ed25519 key derived from their
+//! name, so addresses are stable from run to run. Addresses, types and
+//! function ids accept account names wherever an address is expected (see
+//! [`NamedAddresses`]).
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+    ValidCryptoMaterialStringExt,
+};
+use aptos_framework::{BuildOptions, BuiltPackage};
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{
+        EntryFunction, ExecutionStatus, RawTransaction, TransactionOutput, TransactionStatus,
+    },
+};
+use move_core_types::{language_storage::TypeTag, vm_status::AbortLocation};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    accounts::{local_account_key, AccountKey},
+    decode::json_contains,
+    genesis::GenesisOptions,
+    move_args::{parse_arg, NamedAddresses},
+    offline_runner::OfflineTxnRunner,
+};
+
+const DEFAULT_MAX_GAS: u64 = 2_000_000;
+const GAS_UNIT_PRICE: u64 = 100;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub genesis: GenesisSpec,
+    #[serde(default)]
+    pub accounts: BTreeMap<String, AccountSpec>,
+    pub steps: Vec<Step>,
+}
+
+/// Genesis overrides, used when the scenario boots its own chain.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenesisSpec {
+    pub chain_id: Option<u8>,
+    #[serde(default)]
+    pub enable_features: Vec<u64>,
+    #[serde(default)]
+    pub disable_features: Vec<u64>,
+    pub timestamp_usecs: Option<u64>,
+}
+
+impl GenesisSpec {
+    pub fn options(&self) -> GenesisOptions {
+        let mut options = GenesisOptions::new();
+        if let Some(chain_id) = self.chain_id {
+            options = options.chain_id(ChainId::new(chain_id));
+        }
+        for &id in &self.enable_features {
+            options = options.enable_feature_id(id);
+        }
+        for &id in &self.disable_features {
+            options = options.disable_feature_id(id);
+        }
+        if let Some(usecs) = self.timestamp_usecs {
+            options = options.initial_timestamp_usecs(usecs);
+        }
+        options
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountSpec {
+    /// Defaults to the address of the account's key.
+    pub address: Option<String>,
+    /// Hex Ed25519 private key; defaults to one derived from the name.
+    pub private_key: Option<String>,
+    #[serde(default)]
+    pub sequence_number: u64,
+    /// APT balance in octas.
+    pub balance: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Step {
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub action: Action,
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Publish(PublishStep),
+    Call(CallStep),
+    AdvanceTime(AdvanceTimeStep),
+    Fund(FundStep),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PublishStep {
+    pub sender: String,
+    /// Package directory, relative to the scenario file.
+    pub package: PathBuf,
+    /// Named address to address expression.
+    #[serde(default)]
+    pub named_addresses: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CallStep {
+    pub sender: String,
+    /// `<address>::<module>::<function>`.
+    pub function: String,
+    #[serde(default)]
+    pub type_args: Vec<String>,
+    /// Typed arguments, see [`parse_arg`].
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub max_gas: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdvanceTimeStep {
+    #[serde(default)]
+    pub seconds: u64,
+    #[serde(default)]
+    pub microseconds: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FundStep {
+    pub account: String,
+    /// New APT balance in octas.
+    pub apt: Option<u64>,
+    /// Metadata address expression to new primary store balance.
+    #[serde(default)]
+    pub fungible: BTreeMap<String, u64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpectedStatus {
+    Success,
+    /// A Move abort, optionally with `abort_code`.
+    Abort,
+    /// Kept, but not successful: an abort, out of gas or an execution error.
+    Failure,
+    Discard,
+}
+
+/// What must hold after a step. Transaction steps expect `success` unless
+/// `status` or `abort_code` say otherwise.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectations {
+    pub status: Option<ExpectedStatus>,
+    pub abort_code: Option<u64>,
+    /// Account to APT balance in octas.
+    #[serde(default)]
+    pub balances: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub fungible_balances: Vec<FungibleBalanceExpectation>,
+    #[serde(default)]
+    pub resources: Vec<ResourceExpectation>,
+    /// Each listed event must be among the step's events.
+    #[serde(default)]
+    pub events: Vec<EventExpectation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FungibleBalanceExpectation {
+    pub account: String,
+    pub metadata: String,
+    pub amount: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ResourceExpectation {
+    pub account: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// Fields the decoded resource must contain; omit to only require that
+    /// the resource exists.
+    #[serde(default)]
+    pub fields: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventExpectation {
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// Fields the decoded event data must contain.
+    #[serde(default)]
+    pub data: Value,
+}
+
+/// Outcome of one step.
+#[derive(Debug)]
+pub struct StepReport {
+    pub name: String,
+    /// The transaction status and gas, for transaction steps.
+    pub status: Option<TransactionStatus>,
+    pub gas_used: Option<u64>,
+    /// Unmet expectations; empty if the step passed.
+    pub failures: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ScenarioReport {
+    /// Steps run, up to and including the first failing one.
+    pub steps: Vec<StepReport>,
+    /// Steps not run because an earlier one failed.
+    pub skipped: usize,
+}
+
+impl ScenarioReport {
+    pub fn passed(&self) -> bool {
+        self.skipped == 0 && self.steps.iter().all(|step| step.failures.is_empty())
+    }
+}
+
+impl Scenario {
+    /// Read a scenario, as JSON if the file ends in `.json` and YAML otherwise.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scenario {:?}", path))?;
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            serde_json::from_str(&text)
+                .with_context(|| format!("Failed to parse scenario {:?}", path))
+        } else {
+            serde_yaml::from_str(&text)
+                .with_context(|| format!("Failed to parse scenario {:?}", path))
+        }
+    }
+
+    /// Seed the accounts and run the steps on `runner`, stopping at the first
+    /// step whose expectations fail. Relative package paths are resolved
+    /// against `base_dir`.
+    ///
+    /// Malformed steps (unknown accounts, bad arguments, packages that fail
+    /// to build) are errors; unmet expectations are reported.
+    pub fn run(&self, runner: &mut OfflineTxnRunner, base_dir: &Path) -> Result<ScenarioReport> {
+        let mut accounts = Accounts::default();
+        for (name, spec) in &self.accounts {
+            accounts
+                .create(runner, name, spec)
+                .with_context(|| format!("Failed to create account {}", name))?;
+        }
+
+        let mut report = ScenarioReport::default();
+        for (index, step) in self.steps.iter().enumerate() {
+            let name = step
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("step {}", index + 1));
+            let step_report = accounts
+                .run_step(runner, base_dir, step, name.clone())
+                .with_context(|| format!("Failed to run {}", name))?;
+            let failed = !step_report.failures.is_empty();
+            report.steps.push(step_report);
+            if failed {
+                report.skipped = self.steps.len() - index - 1;
+                break;
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Named accounts with their signing keys.
+#[derive(Default)]
+struct Accounts {
+    names: NamedAddresses,
+    keys: BTreeMap<String, (Ed25519PrivateKey, Ed25519PublicKey)>,
+}
+
+impl Accounts {
+    fn create(
+        &mut self,
+        runner: &mut OfflineTxnRunner,
+        name: &str,
+        spec: &AccountSpec,
+    ) -> Result<()> {
+        let private_key = match &spec.private_key {
+            Some(key) => Ed25519PrivateKey::from_encoded_string(key)
+                .map_err(|err| anyhow!("Invalid private key: {}", err))?,
+            None => local_account_key(name),
+        };
+        let public_key = Ed25519PublicKey::from(&private_key);
+        let key = AccountKey::Ed25519(public_key.clone());
+        let address = match &spec.address {
+            Some(address) => self.names.address(address)?,
+            None => key.address(),
+        };
+        runner.create_account(address, key.authentication_key(), spec.sequence_number)?;
+        if let Some(balance) = spec.balance {
+            runner.set_apt_balance(address, balance)?;
+        }
+        self.names.insert(name, address);
+        self.keys
+            .insert(name.to_string(), (private_key, public_key));
+        Ok(())
+    }
+
+    fn address(&self, name: &str) -> Result<AccountAddress> {
+        self.names.address(name)
+    }
+
+    fn run_step(
+        &self,
+        runner: &mut OfflineTxnRunner,
+        base_dir: &Path,
+        step: &Step,
+        name: String,
+    ) -> Result<StepReport> {
+        let output = match &step.action {
+            Action::Publish(publish) => {
+                let mut named_addresses = BTreeMap::new();
+                for (named, expr) in &publish.named_addresses {
+                    named_addresses.insert(named.clone(), self.address(expr)?);
+                }
+                let path = base_dir.join(&publish.package);
+                // Published with sources, as `aptos move publish` does, so
+                // aborts can point at the error constant.
+                let options = BuildOptions {
+                    named_addresses,
+                    with_srcs: true,
+                    ..BuildOptions::default()
+                };
+                let package = BuiltPackage::build(path.clone(), options)
+                    .with_context(|| format!("Failed to build {:?}", path))?;
+                let payload = aptos_stdlib::code_publish_package_txn(
+                    bcs::to_bytes(&package.extract_metadata()?)?,
+                    package.extract_code(),
+                );
+                Some(self.submit(runner, &publish.sender, payload.into_entry_function(), None)?)
+            }
+            Action::Call(call) => {
+                let (module, function) = self.names.function(&call.function)?;
+                let type_args = call
+                    .type_args
+                    .iter()
+                    .map(|ty| self.names.type_tag(ty))
+                    .collect::<Result<Vec<_>>>()?;
+                let args = call
+                    .args
+                    .iter()
+                    .map(|arg| parse_arg(arg, &self.names))
+                    .collect::<Result<Vec<_>>>()?;
+                let entry_function = EntryFunction::new(module, function, type_args, args);
+                Some(self.submit(runner, &call.sender, entry_function, call.max_gas)?)
+            }
+            Action::AdvanceTime(advance) => {
+                let usecs = advance
+                    .seconds
+                    .checked_mul(1_000_000)
+                    .and_then(|usecs| usecs.checked_add(advance.microseconds))
+                    .context("Time advance overflows u64 microseconds")?;
+                runner.advance_time_usecs(usecs)?;
+                None
+            }
+            Action::Fund(fund) => {
+                let address = self.address(&fund.account)?;
+                if let Some(apt) = fund.apt {
+                    runner.set_apt_balance(address, apt)?;
+                }
+                for (metadata, amount) in &fund.fungible {
+                    runner.set_fungible_balance(address, self.address(metadata)?, *amount)?;
+                }
+                None
+            }
+        };
+
+        let failures = self.check(runner, &step.expect, output.as_ref())?;
+        Ok(StepReport {
+            name,
+            status: output.as_ref().map(|output| output.status().clone()),
+            gas_used: output.as_ref().map(|output| output.gas_used()),
+            failures,
+        })
+    }
+
+    fn submit(
+        &self,
+        runner: &mut OfflineTxnRunner,
+        sender: &str,
+        entry_function: EntryFunction,
+        max_gas: Option<u64>,
+    ) -> Result<TransactionOutput> {
+        let (private_key, public_key) = self
+            .keys
+            .get(sender)
+            .with_context(|| format!("{} is not a scenario account", sender))?;
+        let address = self.address(sender)?;
+        let sequence_number = runner
+            .account(address)?
+            .with_context(|| format!("Account {} does not exist", sender))?
+            .sequence_number();
+        let txn = RawTransaction::new_entry_function(
+            address,
+            sequence_number,
+            entry_function,
+            max_gas.unwrap_or(DEFAULT_MAX_GAS),
+            GAS_UNIT_PRICE,
+            u64::MAX,
+            runner.chain_id(),
+        )
+        .sign(private_key, public_key.clone())
+        .map_err(|err| anyhow!("Failed to sign transaction: {:?}", err))?
+        .into_inner();
+        runner.execute_transaction(txn)
+    }
+
+    fn check(
+        &self,
+        runner: &OfflineTxnRunner,
+        expect: &Expectations,
+        output: Option<&TransactionOutput>,
+    ) -> Result<Vec<String>> {
+        let mut failures = vec![];
+        match output {
+            Some(output) => {
+                if let Some(mut failure) = check_status(expect, output.status()) {
+                    if let TransactionStatus::Keep(ExecutionStatus::MoveAbort {
+                        location: AbortLocation::Module(module),
+                        code,
+                        ..
+                    }) = output.status()
+                    {
+                        let explanation = runner.decode_abort(module, *code)?;
+                        failure.push_str(&format!("; aborted with {}", explanation));
+                    }
+                    failures.push(failure);
+                }
+            }
+            None if expect.status.is_some()
+                || expect.abort_code.is_some()
+                || !expect.events.is_empty() =>
+            {
+                bail!("Only transaction steps have a status and events");
+            }
+            None => {}
+        }
+
+        for (account, amount) in &expect.balances {
+            let balance = runner.apt_balance(self.address(account)?)?;
+            if balance != *amount {
+                failures.push(format!(
+                    "{} has {} octas, expected {}",
+                    account, balance, amount
+                ));
+            }
+        }
+        for expected in &expect.fungible_balances {
+            let balance = runner.fungible_balance(
+                self.address(&expected.account)?,
+                self.address(&expected.metadata)?,
+            )?;
+            if balance != expected.amount {
+                failures.push(format!(
+                    "{} has {} of {}, expected {}",
+                    expected.account, balance, expected.metadata, expected.amount
+                ));
+            }
+        }
+        for expected in &expect.resources {
+            let TypeTag::Struct(tag) = self.names.type_tag(&expected.type_)? else {
+                bail!("{} is not a struct type", expected.type_);
+            };
+            match runner.resource(self.address(&expected.account)?, &tag)? {
+                None => failures.push(format!("{} has no {}", expected.account, expected.type_)),
+                Some(_) if expected.fields.is_null() => {}
+                Some(bytes) => {
+                    let actual = runner.decode_resource(&tag, &bytes)?;
+                    if !json_contains(&actual, &expected.fields) {
+                        failures.push(format!(
+                            "{} of {} is {}, expected it to contain {}",
+                            expected.type_, expected.account, actual, expected.fields
+                        ));
+                    }
+                }
+            }
+        }
+        if let Some(output) = output {
+            for expected in &expect.events {
+                let type_tag = self.names.type_tag(&expected.type_)?;
+                let mut found = false;
+                for event in output
+                    .events()
+                    .iter()
+                    .filter(|event| *event.type_tag() == type_tag)
+                {
+                    if expected.data.is_null()
+                        || json_contains(
+                            &runner.decode_value(&type_tag, event.event_data())?,
+                            &expected.data,
+                        )
+                    {
+                        found = true;
+                        break;
+                    }
+                }
+                if !found {
+                    let emitted: Vec<String> = output
+                        .events()
+                        .iter()
+                        .map(|event| event.type_tag().to_canonical_string())
+                        .collect();
+                    failures.push(format!(
+                        "No {} event matching {}; emitted: [{}]",
+                        expected.type_,
+                        expected.data,
+                        emitted.join(", ")
+                    ));
+                }
+            }
+        }
+        Ok(failures)
+    }
+}
+
+fn check_status(expect: &Expectations, status: &TransactionStatus) -> Option<String> {
+    let expected = match (expect.status, expect.abort_code) {
+        (Some(status), _) => status,
+        (None, Some(_)) => ExpectedStatus::Abort,
+        (None, None) => ExpectedStatus::Success,
+    };
+    let matches = match (expected, status) {
+        (ExpectedStatus::Success, TransactionStatus::Keep(ExecutionStatus::Success)) => true,
+        (ExpectedStatus::Abort, TransactionStatus::Keep(ExecutionStatus::MoveAbort { code, .. })) => {
+            expect.abort_code.is_none_or(|expected| expected == *code)
+        },
+        (ExpectedStatus::Failure, TransactionStatus::Keep(status)) => {
+            *status != ExecutionStatus::Success
+                && expect.abort_code.is_none_or(|expected| {
+                    matches!(status, ExecutionStatus::MoveAbort { code, .. } if *code == expected)
+                })
+        },
+        (ExpectedStatus::Discard, TransactionStatus::Discard(_)) => true,
+        _ => false,
+    };
+    (!matches).then(|| match expect.abort_code {
+        Some(code) => format!(
+            "status {:?}, expected {:?} with code {}",
+            status, expected, code
+        ),
+        None => format!("status {:?}, expected {:?}", status, expected),
+    })
+}
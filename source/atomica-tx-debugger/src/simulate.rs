@@ -0,0 +1,88 @@
+//! Unsigned transactions, run the way the `/simulate` REST endpoint runs
+//! them.
+//!
+//! Simulation skips signature verification but keeps every other prologue
+//! check, including the authentication key check when the sender claims a
+//! key. [`into_simulation`] attaches a placeholder signature for that key.
+
+use anyhow::{bail, Context, Result};
+use aptos_crypto::{ed25519::Ed25519Signature, secp256k1_ecdsa};
+use aptos_types::transaction::{
+    authenticator::{
+        AccountAuthenticator, AnyPublicKey, AnySignature, MultiKeyAuthenticator,
+        TransactionAuthenticator,
+    },
+    RawTransaction, SignedTransaction,
+};
+
+use crate::accounts::AccountKey;
+
+/// Authenticator claiming `key` with placeholder signatures
+///
+/// Without a key, or for abstracted accounts whose authentication function
+/// can't be satisfied without a real signature, the sender's authentication
+/// key isn't checked at all. The prologue only allows that when the chain
+/// enables `TRANSACTION_SIMULATION_ENHANCEMENT`; otherwise it discards the
+/// transaction with `INVALID_AUTH_KEY`.
+pub fn simulation_authenticator(key: Option<&AccountKey>) -> Result<AccountAuthenticator> {
+    match key {
+        Some(AccountKey::Ed25519(public_key)) => Ok(AccountAuthenticator::ed25519(
+            public_key.clone(),
+            Ed25519Signature::dummy_signature(),
+        )),
+        Some(AccountKey::MultiKey(public_keys)) => {
+            let required = usize::from(public_keys.signatures_required());
+            let mut signatures = Vec::with_capacity(required);
+            for (idx, public_key) in public_keys.public_keys().iter().enumerate() {
+                if signatures.len() == required {
+                    break;
+                }
+                if let Some(signature) = dummy_signature(public_key)? {
+                    signatures.push((idx as u8, signature));
+                }
+            }
+            if signatures.len() < required {
+                bail!(
+                    "Multi-key account needs {} signatures but only {} of its keys can be simulated",
+                    required,
+                    signatures.len()
+                );
+            }
+            let authenticator = MultiKeyAuthenticator::new(public_keys.clone(), signatures)
+                .context("Failed to build multi-key authenticator")?;
+            Ok(AccountAuthenticator::multi_key(authenticator))
+        }
+        Some(AccountKey::Derivable { .. }) | None => {
+            Ok(AccountAuthenticator::NoAccountAuthenticator)
+        }
+    }
+}
+
+/// Placeholder signature of the scheme `public_key` verifies, if it has one
+///
+/// WebAuthn and keyless signatures carry more than the signature bytes, so
+/// their keys can't be claimed without a real signature.
+fn dummy_signature(public_key: &AnyPublicKey) -> Result<Option<AnySignature>> {
+    Ok(match public_key {
+        AnyPublicKey::Ed25519 { .. } => {
+            Some(AnySignature::ed25519(Ed25519Signature::dummy_signature()))
+        }
+        AnyPublicKey::Secp256k1Ecdsa { .. } => {
+            let signature = secp256k1_ecdsa::Signature::try_from(&[1u8; 64][..])
+                .context("Failed to build placeholder secp256k1 signature")?;
+            Some(AnySignature::secp256k1_ecdsa(signature))
+        }
+        _ => None,
+    })
+}
+
+/// Wrap an unsigned transaction for [`OfflineTxnRunner::simulate_transaction`]
+///
+/// [`OfflineTxnRunner::simulate_transaction`]: crate::offline_runner::OfflineTxnRunner::simulate_transaction
+pub fn into_simulation(raw: RawTransaction, key: Option<&AccountKey>) -> Result<SignedTransaction> {
+    let authenticator = simulation_authenticator(key)?;
+    Ok(SignedTransaction::new_signed_transaction(
+        raw,
+        TransactionAuthenticator::single_sender(authenticator),
+    ))
+}
@@ -0,0 +1,66 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+use aptos_types::{
+    chain_id::ChainId,
+    state_store::{state_key::StateKey, state_value::StateValue},
+};
+use serde::{Deserialize, Serialize};
+
+/// Leading bytes of every snapshot file.
+const MAGIC: &[u8; 8] = b"ATXSNAP\0";
+
+/// Bumped whenever the encoding of [`Snapshot`] changes.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// The full state of an offline runner, as written by
+/// `OfflineTxnRunner::save_snapshot`.
+///
+/// On disk this is `MAGIC || BCS(Snapshot)`, with entries sorted by encoded
+/// state key so the same state always produces the same file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub chain_id: ChainId,
+    pub state: Vec<(StateKey, StateValue)>,
+}
+
+impl Snapshot {
+    pub fn new(chain_id: ChainId, mut state: Vec<(StateKey, StateValue)>) -> Self {
+        state.sort_by(|(a, _), (b, _)| a.encoded().cmp(b.encoded()));
+        Self {
+            version: SNAPSHOT_VERSION,
+            chain_id,
+            state,
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(bcs::to_bytes(self).context("Failed to serialize snapshot")?);
+        fs::write(path, bytes).with_context(|| format!("Failed to write snapshot to {:?}", path))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes =
+            fs::read(path).with_context(|| format!("Failed to read snapshot {:?}", path))?;
+        let Some(body) = bytes.strip_prefix(MAGIC.as_slice()) else {
+            bail!("{:?} is not a state snapshot", path);
+        };
+        // The version leads the BCS encoding, so check it before decoding
+        // the rest with a layout that may not match.
+        let version = body
+            .get(..4)
+            .map(|version| u32::from_le_bytes(version.try_into().unwrap()))
+            .with_context(|| format!("Truncated snapshot {:?}", path))?;
+        if version != SNAPSHOT_VERSION {
+            bail!(
+                "Snapshot {:?} has format version {}, expected {}",
+                path,
+                version,
+                SNAPSHOT_VERSION
+            );
+        }
+        bcs::from_bytes(body).with_context(|| format!("Failed to deserialize snapshot {:?}", path))
+    }
+}
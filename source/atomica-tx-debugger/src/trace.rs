@@ -0,0 +1,750 @@
+//! Structured Move execution traces.
+//!
+//! The VM reports every instruction, call and return to its gas meter, so
+//! [`TracingGasMeter`] wraps the production meter and records them as
+//! [`TraceEvent`]s while passing every charge through unchanged. Program
+//! counters are followed per frame (branch targets come with the charge),
+//! and the bytecode of each called function is read from state so that
+//! `StLoc`/`CopyLoc`/`MoveLoc` can be tied to a local index. Gas is in
+//! internal gas units.
+
+use std::{collections::BTreeMap, io::Write, sync::Arc};
+
+use anyhow::Result;
+use aptos_gas_algebra::{Fee, FeePerGasUnit, NumArgs, NumBytes, NumTypeNodes};
+use aptos_gas_meter::AptosGasMeter;
+use aptos_types::{
+    account_address::AccountAddress,
+    contract_event::ContractEvent,
+    on_chain_config::Features,
+    state_store::{state_key::StateKey, StateView},
+    transaction::TransactionStatus,
+    write_set::WriteOpSize,
+};
+use aptos_vm_types::change_set::ChangeSetInterface;
+use move_binary_format::{
+    access::ModuleAccess,
+    errors::{PartialVMResult, VMResult},
+    file_format::{Bytecode, CodeOffset, LocalIndex},
+    CompiledModule, CompiledScript,
+};
+use move_core_types::{
+    gas_algebra::InternalGas,
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, TypeTag},
+    u256::U256,
+};
+use move_vm_types::{
+    delayed_values::delayed_field_id::DelayedFieldID,
+    gas::{DependencyGasMeter, DependencyKind, GasMeter, NativeGasMeter, SimpleInstruction},
+    views::{TypeView, ValueView, ValueVisitor},
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// One step of a traced execution.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TraceEvent {
+    /// A Move function was called; `args` are its first locals.
+    Enter {
+        depth: usize,
+        function: String,
+        ty_args: Vec<String>,
+        args: Vec<Value>,
+    },
+    /// A bytecode instruction was executed at `pc` of the current function.
+    Instruction {
+        depth: usize,
+        function: String,
+        pc: CodeOffset,
+        op: String,
+        gas: u64,
+    },
+    /// A native function was called.
+    Native {
+        depth: usize,
+        function: String,
+        ty_args: Vec<String>,
+        gas: u64,
+    },
+    /// A resource was loaded from storage.
+    LoadResource {
+        depth: usize,
+        address: String,
+        ty: String,
+        bytes: u64,
+        gas: u64,
+    },
+    /// A function returned; `gas` covers the whole call including callees.
+    Exit {
+        depth: usize,
+        function: String,
+        gas: u64,
+    },
+    /// Execution stopped with frames still on the stack, innermost first.
+    Abort {
+        status: String,
+        stack: Vec<FrameState>,
+    },
+}
+
+/// A frame on the stack when execution stopped.
+#[derive(Clone, Debug, Serialize)]
+pub struct FrameState {
+    pub function: String,
+    /// The instruction the frame was executing (for callers, the call)
+    pub pc: Option<CodeOffset>,
+    /// Last known value of each local, by index; a moved-out local keeps
+    /// the value it was moved with
+    pub locals: BTreeMap<LocalIndex, Value>,
+}
+
+/// Everything recorded while executing one transaction.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ExecutionTrace {
+    pub events: Vec<TraceEvent>,
+}
+
+impl ExecutionTrace {
+    /// The stack at the point execution stopped, if it did not return normally
+    pub fn abort(&self) -> Option<&[FrameState]> {
+        self.events.iter().rev().find_map(|event| match event {
+            TraceEvent::Abort { stack, .. } => Some(stack.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Write one JSON object per event, one per line.
+    pub fn write_jsonl(&self, mut out: impl Write) -> Result<()> {
+        for event in &self.events {
+            serde_json::to_writer(&mut out, event)?;
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+struct Frame {
+    function: String,
+    code: Option<Arc<Vec<Bytecode>>>,
+    pc: CodeOffset,
+    last_pc: Option<CodeOffset>,
+    locals: BTreeMap<LocalIndex, Value>,
+    start_balance: u64,
+}
+
+impl Frame {
+    fn current(&self) -> Option<&Bytecode> {
+        self.code.as_ref()?.get(self.pc as usize)
+    }
+}
+
+/// A gas meter that records a [`TraceEvent`] for everything the VM charges
+/// for, then delegates to `base`.
+pub struct TracingGasMeter<'a, G> {
+    base: G,
+    state_view: &'a dyn StateView,
+    code: BTreeMap<(ModuleId, Identifier), Option<Arc<Vec<Bytecode>>>>,
+    frames: Vec<Frame>,
+    events: Vec<TraceEvent>,
+}
+
+impl<'a, G: AptosGasMeter> TracingGasMeter<'a, G> {
+    /// Trace an execution starting from no frame; the first call opens one.
+    pub fn new(base: G, state_view: &'a dyn StateView) -> Self {
+        Self {
+            base,
+            state_view,
+            code: BTreeMap::new(),
+            frames: vec![],
+            events: vec![],
+        }
+    }
+
+    /// Trace an entry function call. The VM enters it without a call
+    /// instruction, so the frame is opened up front.
+    pub fn for_entry_function(
+        base: G,
+        state_view: &'a dyn StateView,
+        module: &ModuleId,
+        function: &IdentStr,
+        ty_args: &[TypeTag],
+    ) -> Self {
+        let mut meter = Self::new(base, state_view);
+        let code = meter.function_code(module, function);
+        meter.enter(
+            format!("{}::{}", module.short_str_lossless(), function),
+            code,
+            ty_args.iter().map(|ty| ty.to_canonical_string()).collect(),
+            vec![],
+        );
+        meter
+    }
+
+    /// Trace a script; `code` is the serialized script.
+    pub fn for_script(base: G, state_view: &'a dyn StateView, code: &[u8]) -> Self {
+        let mut meter = Self::new(base, state_view);
+        let code = CompiledScript::deserialize(code)
+            .ok()
+            .map(|script| Arc::new(script.code.code.clone()));
+        meter.enter("<script>".to_string(), code, vec![], vec![]);
+        meter
+    }
+
+    /// Finish the trace once the transaction has run. Frames still open
+    /// mean execution stopped early, and are reported as an
+    /// [`TraceEvent::Abort`].
+    pub fn finish(mut self, status: &TransactionStatus) -> ExecutionTrace {
+        if !self.frames.is_empty() {
+            let stack = self
+                .frames
+                .drain(..)
+                .rev()
+                .map(|frame| FrameState {
+                    function: frame.function,
+                    pc: frame.last_pc,
+                    locals: frame.locals,
+                })
+                .collect();
+            self.events.push(TraceEvent::Abort {
+                status: format!("{:?}", status),
+                stack,
+            });
+        }
+        ExecutionTrace {
+            events: self.events,
+        }
+    }
+
+    fn balance(&self) -> u64 {
+        self.base.balance_internal().into()
+    }
+
+    fn function_code(
+        &mut self,
+        module: &ModuleId,
+        function: &IdentStr,
+    ) -> Option<Arc<Vec<Bytecode>>> {
+        let state_view = self.state_view;
+        self.code
+            .entry((module.clone(), function.to_owned()))
+            .or_insert_with(|| {
+                let bytes = state_view
+                    .get_state_value_bytes(&StateKey::module_id(module))
+                    .ok()??;
+                let module = CompiledModule::deserialize(&bytes).ok()?;
+                let code = module.function_defs().iter().find_map(|def| {
+                    let handle = module.function_handle_at(def.function);
+                    (module.identifier_at(handle.name) == function)
+                        .then(|| def.code.as_ref())
+                        .flatten()
+                })?;
+                Some(Arc::new(code.code.clone()))
+            })
+            .clone()
+    }
+
+    fn enter(
+        &mut self,
+        function: String,
+        code: Option<Arc<Vec<Bytecode>>>,
+        ty_args: Vec<String>,
+        args: Vec<Value>,
+    ) {
+        self.events.push(TraceEvent::Enter {
+            depth: self.frames.len(),
+            function: function.clone(),
+            ty_args,
+            args: args.clone(),
+        });
+        self.frames.push(Frame {
+            function,
+            code,
+            pc: 0,
+            last_pc: None,
+            locals: args
+                .into_iter()
+                .enumerate()
+                .map(|(idx, value)| (idx as LocalIndex, value))
+                .collect(),
+            start_balance: self.balance(),
+        });
+    }
+
+    fn call(
+        &mut self,
+        module: &ModuleId,
+        function: &str,
+        ty_args: Vec<String>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) {
+        let code = Identifier::new(function)
+            .ok()
+            .and_then(|name| self.function_code(module, &name));
+        self.enter(
+            format!("{}::{}", module.short_str_lossless(), function),
+            code,
+            ty_args,
+            args.map(|arg| render(&arg)).collect(),
+        );
+    }
+
+    /// Delegate a charge and return how much gas it took.
+    fn charge<R>(&mut self, charge: impl FnOnce(&mut G) -> R) -> (u64, R) {
+        let before = self.balance();
+        let result = charge(&mut self.base);
+        (before.saturating_sub(self.balance()), result)
+    }
+
+    /// Charge for the instruction at the current pc and record it.
+    fn instruction(
+        &mut self,
+        op: &str,
+        charge: impl FnOnce(&mut G) -> PartialVMResult<()>,
+    ) -> PartialVMResult<()> {
+        let (gas, result) = self.charge(charge);
+        if self.frames.is_empty() {
+            self.enter("<unknown>".to_string(), None, vec![], vec![]);
+        }
+        let depth = self.frames.len() - 1;
+        let frame = self.frames.last_mut().expect("a frame is open");
+        let op = frame
+            .current()
+            .map(|instr| format!("{:?}", instr))
+            .unwrap_or_else(|| op.to_string());
+        self.events.push(TraceEvent::Instruction {
+            depth,
+            function: frame.function.clone(),
+            pc: frame.pc,
+            op,
+            gas,
+        });
+        frame.last_pc = Some(frame.pc);
+        frame.pc += 1;
+        result
+    }
+
+    fn jump(&mut self, target: Option<CodeOffset>) {
+        if let (Some(frame), Some(target)) = (self.frames.last_mut(), target) {
+            frame.pc = target;
+        }
+    }
+
+    fn ret(&mut self) {
+        if let Some(frame) = self.frames.pop() {
+            self.events.push(TraceEvent::Exit {
+                depth: self.frames.len(),
+                function: frame.function,
+                gas: frame.start_balance.saturating_sub(self.balance()),
+            });
+        }
+    }
+
+    /// Record the value of the local used by the `StLoc`, `CopyLoc` or
+    /// `MoveLoc` at the current pc.
+    fn touch_local(&mut self, value: Value) {
+        let Some(frame) = self.frames.last_mut() else {
+            return;
+        };
+        if let Some(Bytecode::StLoc(idx) | Bytecode::CopyLoc(idx) | Bytecode::MoveLoc(idx)) =
+            frame.current()
+        {
+            let idx = *idx;
+            frame.locals.insert(idx, value);
+        }
+    }
+}
+
+/// Instructions whose charge needs no more than recording.
+macro_rules! instructions {
+    ($($op:literal => fn $name:ident(&mut self $(, $arg:ident: $ty:ty)*);)*) => {
+        $(
+            fn $name(&mut self $(, $arg: $ty)*) -> PartialVMResult<()> {
+                self.instruction($op, |base| base.$name($($arg),*))
+            }
+        )*
+    };
+}
+
+impl<G: AptosGasMeter> DependencyGasMeter for TracingGasMeter<'_, G> {
+    fn charge_dependency(
+        &mut self,
+        kind: DependencyKind,
+        addr: &AccountAddress,
+        name: &IdentStr,
+        size: NumBytes,
+    ) -> PartialVMResult<()> {
+        self.base.charge_dependency(kind, addr, name, size)
+    }
+}
+
+impl<G: AptosGasMeter> NativeGasMeter for TracingGasMeter<'_, G> {
+    fn legacy_gas_budget_in_native_context(&self) -> InternalGas {
+        self.base.legacy_gas_budget_in_native_context()
+    }
+
+    fn charge_native_execution(&mut self, amount: InternalGas) -> PartialVMResult<()> {
+        self.base.charge_native_execution(amount)
+    }
+
+    fn use_heap_memory_in_native_context(&mut self, amount: u64) -> PartialVMResult<()> {
+        self.base.use_heap_memory_in_native_context(amount)
+    }
+}
+
+impl<G: AptosGasMeter> GasMeter for TracingGasMeter<'_, G> {
+    fn balance_internal(&self) -> InternalGas {
+        self.base.balance_internal()
+    }
+
+    instructions! {
+        "Pop" => fn charge_pop(&mut self, popped_val: impl ValueView);
+        "LdConst" => fn charge_ld_const(&mut self, size: NumBytes);
+        "Pack" => fn charge_pack(&mut self, is_generic: bool, args: impl ExactSizeIterator<Item = impl ValueView> + Clone);
+        "Unpack" => fn charge_unpack(&mut self, is_generic: bool, args: impl ExactSizeIterator<Item = impl ValueView> + Clone);
+        "PackVariant" => fn charge_pack_variant(&mut self, is_generic: bool, args: impl ExactSizeIterator<Item = impl ValueView> + Clone);
+        "UnpackVariant" => fn charge_unpack_variant(&mut self, is_generic: bool, args: impl ExactSizeIterator<Item = impl ValueView> + Clone);
+        "PackClosure" => fn charge_pack_closure(&mut self, is_generic: bool, args: impl ExactSizeIterator<Item = impl ValueView> + Clone);
+        "ReadRef" => fn charge_read_ref(&mut self, val: impl ValueView);
+        "WriteRef" => fn charge_write_ref(&mut self, new_val: impl ValueView, old_val: impl ValueView);
+        "Eq" => fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView);
+        "Neq" => fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView);
+        "BorrowGlobal" => fn charge_borrow_global(&mut self, is_mut: bool, is_generic: bool, ty: impl TypeView, is_success: bool);
+        "Exists" => fn charge_exists(&mut self, is_generic: bool, ty: impl TypeView, exists: bool);
+        "MoveFrom" => fn charge_move_from(&mut self, is_generic: bool, ty: impl TypeView, val: Option<impl ValueView>);
+        "MoveTo" => fn charge_move_to(&mut self, is_generic: bool, ty: impl TypeView, val: impl ValueView, is_success: bool);
+        "VecLen" => fn charge_vec_len(&mut self, ty: impl TypeView);
+        "VecBorrow" => fn charge_vec_borrow(&mut self, is_mut: bool, ty: impl TypeView, is_success: bool);
+        "VecPushBack" => fn charge_vec_push_back(&mut self, ty: impl TypeView, val: impl ValueView);
+        "VecPopBack" => fn charge_vec_pop_back(&mut self, ty: impl TypeView, val: Option<impl ValueView>);
+        "VecUnpack" => fn charge_vec_unpack(&mut self, ty: impl TypeView, expect_num_elements: NumArgs, elems: impl ExactSizeIterator<Item = impl ValueView>);
+        "VecSwap" => fn charge_vec_swap(&mut self, ty: impl TypeView);
+    }
+
+    fn charge_vec_pack<'b>(
+        &mut self,
+        ty: impl TypeView + 'b,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+    ) -> PartialVMResult<()> {
+        self.instruction("VecPack", |base| base.charge_vec_pack(ty, args))
+    }
+
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        let result = self.instruction(&format!("{:?}", instr), |base| {
+            base.charge_simple_instr(instr)
+        });
+        if matches!(instr, SimpleInstruction::Ret) {
+            self.ret();
+        }
+        result
+    }
+
+    fn charge_br_true(&mut self, target_offset: Option<CodeOffset>) -> PartialVMResult<()> {
+        let result = self.instruction("BrTrue", |base| base.charge_br_true(target_offset));
+        self.jump(target_offset);
+        result
+    }
+
+    fn charge_br_false(&mut self, target_offset: Option<CodeOffset>) -> PartialVMResult<()> {
+        let result = self.instruction("BrFalse", |base| base.charge_br_false(target_offset));
+        self.jump(target_offset);
+        result
+    }
+
+    fn charge_branch(&mut self, target_offset: CodeOffset) -> PartialVMResult<()> {
+        let result = self.instruction("Branch", |base| base.charge_branch(target_offset));
+        self.jump(Some(target_offset));
+        result
+    }
+
+    fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.touch_local(render(&val));
+        self.instruction("CopyLoc", |base| base.charge_copy_loc(val))
+    }
+
+    fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.touch_local(render(&val));
+        self.instruction("MoveLoc", |base| base.charge_move_loc(val))
+    }
+
+    fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.touch_local(render(&val));
+        self.instruction("StLoc", |base| base.charge_store_loc(val))
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let result = self.instruction("Call", |base| {
+            base.charge_call(module_id, func_name, args.clone(), num_locals)
+        });
+        if result.is_ok() {
+            self.call(module_id, func_name, vec![], args);
+        }
+        result
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let tys = ty_args
+            .clone()
+            .map(|ty| ty.to_type_tag().to_canonical_string())
+            .collect();
+        let result = self.instruction("CallGeneric", |base| {
+            base.charge_call_generic(module_id, func_name, ty_args, args.clone(), num_locals)
+        });
+        if result.is_ok() {
+            self.call(module_id, func_name, tys, args);
+        }
+        result
+    }
+
+    fn charge_ld_const_after_deserialization(
+        &mut self,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.base.charge_ld_const_after_deserialization(val)
+    }
+
+    fn charge_load_resource(
+        &mut self,
+        addr: AccountAddress,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+        bytes_loaded: NumBytes,
+    ) -> PartialVMResult<()> {
+        let type_tag = ty.to_type_tag();
+        let (gas, result) =
+            self.charge(|base| base.charge_load_resource(addr, ty, val, bytes_loaded));
+        self.events.push(TraceEvent::LoadResource {
+            depth: self.frames.len().saturating_sub(1),
+            address: addr.to_hex_literal(),
+            ty: type_tag.to_canonical_string(),
+            bytes: bytes_loaded.into(),
+            gas,
+        });
+        result
+    }
+
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+    ) -> PartialVMResult<()> {
+        self.base
+            .charge_native_function_before_execution(ty_args, args)
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView> + Clone>,
+    ) -> PartialVMResult<()> {
+        let (gas, result) = self.charge(|base| base.charge_native_function(amount, ret_vals));
+        // The call opened a frame before the VM knew the function was
+        // native; turn that frame into a single native event.
+        let opened_by_call = matches!(
+            (self.frames.last(), self.events.last()),
+            (Some(frame), Some(TraceEvent::Enter { function, .. }))
+                if frame.last_pc.is_none() && *function == frame.function
+        );
+        if opened_by_call {
+            let frame = self.frames.pop().expect("checked above");
+            let Some(TraceEvent::Enter { ty_args, .. }) = self.events.pop() else {
+                unreachable!("checked above")
+            };
+            self.events.push(TraceEvent::Native {
+                depth: self.frames.len(),
+                function: frame.function,
+                ty_args,
+                gas,
+            });
+        }
+        result
+    }
+
+    fn charge_drop_frame(
+        &mut self,
+        locals: impl Iterator<Item = impl ValueView> + Clone,
+    ) -> PartialVMResult<()> {
+        self.base.charge_drop_frame(locals)
+    }
+
+    fn charge_create_ty(&mut self, num_nodes: NumTypeNodes) -> PartialVMResult<()> {
+        self.base.charge_create_ty(num_nodes)
+    }
+
+    fn charge_heap_memory(&mut self, amount: u64) -> PartialVMResult<()> {
+        self.base.charge_heap_memory(amount)
+    }
+}
+
+impl<G: AptosGasMeter> AptosGasMeter for TracingGasMeter<'_, G> {
+    type Algebra = G::Algebra;
+
+    fn algebra(&self) -> &Self::Algebra {
+        self.base.algebra()
+    }
+
+    fn algebra_mut(&mut self) -> &mut Self::Algebra {
+        self.base.algebra_mut()
+    }
+
+    fn charge_storage_fee(
+        &mut self,
+        amount: Fee,
+        gas_unit_price: FeePerGasUnit,
+    ) -> PartialVMResult<()> {
+        self.base.charge_storage_fee(amount, gas_unit_price)
+    }
+
+    fn charge_io_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()> {
+        self.base.charge_io_gas_for_transaction(txn_size)
+    }
+
+    fn charge_io_gas_for_event(&mut self, event: &ContractEvent) -> VMResult<()> {
+        self.base.charge_io_gas_for_event(event)
+    }
+
+    fn charge_io_gas_for_write(&mut self, key: &StateKey, op: &WriteOpSize) -> VMResult<()> {
+        self.base.charge_io_gas_for_write(key, op)
+    }
+
+    fn process_storage_fee_for_all(
+        &mut self,
+        change_set: &mut impl ChangeSetInterface,
+        txn_size: NumBytes,
+        gas_unit_price: FeePerGasUnit,
+        features: &Features,
+    ) -> VMResult<Fee> {
+        self.base
+            .process_storage_fee_for_all(change_set, txn_size, gas_unit_price, features)
+    }
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()> {
+        self.base.charge_intrinsic_gas_for_transaction(txn_size)
+    }
+
+    fn charge_keyless(&mut self) -> VMResult<()> {
+        self.base.charge_keyless()
+    }
+}
+
+/// Render a runtime value as JSON, with the same conventions as
+/// [`crate::decode::value_to_json`]. Runtime values carry no field names,
+/// so structs render as arrays of their fields.
+fn render(value: &impl ValueView) -> Value {
+    let mut builder = JsonBuilder::default();
+    value.visit(&mut builder);
+    builder.finish()
+}
+
+/// Rebuilds a value tree from the visitor's pre-order walk: each value is
+/// visited at its container's depth plus one.
+#[derive(Default)]
+struct JsonBuilder {
+    open: Vec<(u64, Vec<Value>)>,
+    root: Option<Value>,
+}
+
+impl JsonBuilder {
+    fn close_to(&mut self, depth: u64) {
+        while self.open.last().is_some_and(|(open, _)| *open >= depth) {
+            let (_, items) = self.open.pop().expect("checked above");
+            self.attach(Value::Array(items));
+        }
+    }
+
+    fn attach(&mut self, value: Value) {
+        match self.open.last_mut() {
+            Some((_, items)) => items.push(value),
+            None => self.root = Some(value),
+        }
+    }
+
+    fn leaf(&mut self, depth: u64, value: Value) {
+        self.close_to(depth);
+        self.attach(value);
+    }
+
+    fn container(&mut self, depth: u64) -> bool {
+        self.close_to(depth);
+        self.open.push((depth, vec![]));
+        true
+    }
+
+    fn finish(mut self) -> Value {
+        self.close_to(0);
+        self.root.unwrap_or(Value::Null)
+    }
+}
+
+impl ValueVisitor for JsonBuilder {
+    fn visit_delayed(&mut self, depth: u64, id: DelayedFieldID) {
+        self.leaf(depth, json!(format!("{:?}", id)));
+    }
+
+    fn visit_u8(&mut self, depth: u64, val: u8) {
+        self.leaf(depth, json!(val));
+    }
+
+    fn visit_u16(&mut self, depth: u64, val: u16) {
+        self.leaf(depth, json!(val));
+    }
+
+    fn visit_u32(&mut self, depth: u64, val: u32) {
+        self.leaf(depth, json!(val));
+    }
+
+    fn visit_u64(&mut self, depth: u64, val: u64) {
+        self.leaf(depth, json!(val.to_string()));
+    }
+
+    fn visit_u128(&mut self, depth: u64, val: u128) {
+        self.leaf(depth, json!(val.to_string()));
+    }
+
+    fn visit_u256(&mut self, depth: u64, val: U256) {
+        self.leaf(depth, json!(val.to_string()));
+    }
+
+    fn visit_bool(&mut self, depth: u64, val: bool) {
+        self.leaf(depth, json!(val));
+    }
+
+    fn visit_address(&mut self, depth: u64, val: AccountAddress) {
+        self.leaf(depth, json!(val.to_hex_literal()));
+    }
+
+    fn visit_struct(&mut self, depth: u64, _len: usize) -> bool {
+        self.container(depth)
+    }
+
+    fn visit_closure(&mut self, depth: u64, _len: usize) -> bool {
+        self.container(depth)
+    }
+
+    fn visit_vec(&mut self, depth: u64, _len: usize) -> bool {
+        self.container(depth)
+    }
+
+    fn visit_vec_u8(&mut self, depth: u64, vals: &[u8]) {
+        self.leaf(depth, json!(format!("0x{}", hex::encode(vals))));
+    }
+
+    fn visit_ref(&mut self, _depth: u64, _is_global: bool) -> bool {
+        // Render the referenced value in place of the reference.
+        true
+    }
+}
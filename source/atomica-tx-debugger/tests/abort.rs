@@ -0,0 +1,104 @@
+use aptos_types::account_address::AccountAddress;
+use atomica_tx_debugger::{
+    abort::{ErrorMaps, SourceLocation},
+    offline_runner::OfflineTxnRunner,
+    scenario::Scenario,
+};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use std::{fs, path::PathBuf};
+
+fn module(address: &str, name: &str) -> ModuleId {
+    ModuleId::new(
+        AccountAddress::from_hex_literal(address).unwrap(),
+        Identifier::new(name).unwrap(),
+    )
+}
+
+#[test]
+fn test_release_bundle_error_map() {
+    let maps = ErrorMaps::from_release_bundle(aptos_cached_packages::head_release_bundle());
+    let coin = module("0x1", "coin");
+    let explanation = maps.decode(&coin, 0x10006);
+    assert_eq!(explanation.category, Some("invalid_argument"));
+    assert_eq!(explanation.reason, 6);
+    assert_eq!(explanation.name.as_deref(), Some("EINSUFFICIENT_BALANCE"));
+    assert!(explanation.description.is_some());
+    if let Some(source) = &explanation.source {
+        assert!(source.file.ends_with("sources/coin.move"));
+        assert!(source.line > 1);
+    }
+
+    // Unknown modules and codes still report what the code itself says.
+    let unknown = maps.decode(&module("0xbeef", "nothing"), 0x50002);
+    assert_eq!(unknown.category, Some("permission_denied"));
+    assert_eq!(unknown.reason, 2);
+    assert_eq!(unknown.name, None);
+    assert_eq!(
+        unknown.to_string(),
+        "0xbeef::nothing::2 (permission_denied)"
+    );
+}
+
+#[test]
+fn test_user_package_abort() {
+    let dir = std::env::temp_dir().join(format!("atomica_abort_{}", std::process::id()));
+    let package = dir.join("fake_eth");
+    fs::create_dir_all(package.join("sources")).unwrap();
+    fs::write(
+        package.join("Move.toml"),
+        format!(
+            "[package]\nname = \"FakeEth\"\nversion = \"0.0.0\"\n\n\
+             [addresses]\natomica = \"_\"\n\n\
+             [dependencies]\nAptosFramework = {{ local = \"{}\" }}\n",
+            aptos_framework::path_in_crate("aptos-framework").display()
+        ),
+    )
+    .unwrap();
+    let source = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("atomica-move-contracts/sources/fake_eth.move");
+    fs::copy(source, package.join("sources/fake_eth.move")).unwrap();
+    let path = dir.join("over_cap.yaml");
+    fs::write(
+        &path,
+        r#"
+accounts:
+  atomica: { address: "0xcafe", balance: 100000000000 }
+  alice: { balance: 100000000 }
+steps:
+  - publish: { sender: atomica, package: fake_eth, named_addresses: { atomica: atomica } }
+  - call: { sender: atomica, function: "atomica::fake_eth::initialize" }
+  - name: mint over the cap
+    call: { sender: alice, function: "atomica::fake_eth::mint", args: ["u64:1000000000001"] }
+"#,
+    )
+    .unwrap();
+
+    let scenario = Scenario::load(&path).unwrap();
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let report = scenario.run(&mut runner, &dir).unwrap();
+    let failure = &report.steps[2].failures[0];
+    assert!(
+        failure.contains(
+            "aborted with 0xcafe::fake_eth::E_EXCEEDS_MAX_MINT: \
+             Error code when trying to mint more than the maximum allowed"
+        ),
+        "{}",
+        failure
+    );
+
+    let explanation = runner
+        .decode_abort(&module("0xcafe", "fake_eth"), 1)
+        .unwrap();
+    assert_eq!(explanation.category, None);
+    assert_eq!(explanation.name.as_deref(), Some("E_EXCEEDS_MAX_MINT"));
+    assert_eq!(
+        explanation.source,
+        Some(SourceLocation {
+            file: "FakeEth/sources/fake_eth.move".to_string(),
+            line: 15,
+        })
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,262 @@
+mod common;
+
+use anyhow::Result;
+use aptos_cached_packages::aptos_stdlib;
+use aptos_framework::{BuildOptions, BuiltPackage};
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    function_info::FunctionInfo,
+    transaction::{
+        authenticator::AuthenticationKey, EntryFunction, ExecutionStatus, TransactionOutput,
+        TransactionStatus,
+    },
+};
+use atomica_tx_debugger::{
+    accounts::{object_address, primary_store_address, AccountKey},
+    offline_runner::OfflineTxnRunner,
+};
+use common::{transfer_apt, Sender};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS},
+};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+fn run(
+    runner: &mut OfflineTxnRunner,
+    sender: &Sender,
+    sequence_number: u64,
+    entry_function: EntryFunction,
+) -> TransactionOutput {
+    runner
+        .execute_transaction(sender.sign(sequence_number, entry_function, ChainId::test()))
+        .expect("transaction execution failed")
+}
+
+fn is_success(output: &TransactionOutput) -> bool {
+    output.status() == &TransactionStatus::Keep(ExecutionStatus::Success)
+}
+
+/// Compiles `atomica::fake_eth` against the local framework, at `address`.
+fn build_fake_eth(address: AccountAddress) -> Result<BuiltPackage> {
+    let dir = std::env::temp_dir().join(format!("seed_fake_eth_{}", address.short_str_lossless()));
+    fs::create_dir_all(dir.join("sources"))?;
+    let framework = aptos_framework::path_in_crate("aptos-framework");
+    fs::write(
+        dir.join("Move.toml"),
+        format!(
+            "[package]\nname = \"FakeEth\"\nversion = \"0.0.0\"\n\n\
+             [addresses]\natomica = \"_\"\n\n\
+             [dependencies]\nAptosFramework = {{ local = \"{}\" }}\n",
+            framework.display()
+        ),
+    )?;
+    let source = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("atomica-move-contracts/sources/fake_eth.move");
+    fs::copy(source, dir.join("sources/fake_eth.move"))?;
+
+    let options = BuildOptions {
+        named_addresses: BTreeMap::from([("atomica".to_string(), address)]),
+        ..BuildOptions::default()
+    };
+    BuiltPackage::build(dir, options)
+}
+
+#[test]
+fn test_seeded_sequence_number_and_balance() {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let sender = Sender::new(40);
+    let address = runner.seed_account(&sender.key(), 5).unwrap();
+    assert_eq!(address, sender.address);
+    runner.set_apt_balance(address, 10_000_000).unwrap();
+
+    let recipient = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    let output = run(&mut runner, &sender, 0, transfer_apt(recipient, 1_000));
+    assert!(matches!(output.status(), TransactionStatus::Discard(_)));
+
+    let output = run(&mut runner, &sender, 5, transfer_apt(recipient, 1_000));
+    assert!(is_success(&output), "{:?}", output.status());
+
+    // The balance is spendable but not more: 10_000_000 minus gas.
+    let output = run(&mut runner, &sender, 6, transfer_apt(recipient, 10_000_000));
+    assert!(!is_success(&output));
+}
+
+#[test]
+fn test_account_at_chosen_address_with_rotated_key() {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let owner = Sender::new(41);
+    let address = AccountAddress::from_hex_literal("0xbeef").unwrap();
+    runner
+        .create_account(address, AuthenticationKey::ed25519(&owner.public_key), 0)
+        .unwrap();
+    runner.set_apt_balance(address, 10_000_000).unwrap();
+
+    let rotated = Sender {
+        address,
+        ..Sender::new(41)
+    };
+    let output = run(
+        &mut runner,
+        &rotated,
+        0,
+        transfer_apt(AccountAddress::ONE, 1),
+    );
+    assert!(is_success(&output), "{:?}", output.status());
+}
+
+#[test]
+fn test_derivable_address_binds_identity() {
+    let function_info = FunctionInfo::new(
+        AccountAddress::ONE,
+        "ethereum_derivable_account".to_string(),
+        "authenticate".to_string(),
+    );
+    let alice = AccountKey::Derivable {
+        function_info: function_info.clone(),
+        identity: b"alice".to_vec(),
+    };
+    let bob = AccountKey::Derivable {
+        function_info,
+        identity: b"bob".to_vec(),
+    };
+    assert_ne!(alice.address(), bob.address());
+    assert_eq!(
+        alice.authentication_key().to_vec(),
+        alice.address().to_vec()
+    );
+}
+
+#[test]
+fn test_fungible_balance_requires_metadata() {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let err = runner
+        .set_fungible_balance(
+            AccountAddress::ONE,
+            AccountAddress::from_hex_literal("0xbeef").unwrap(),
+            1,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("No fungible asset metadata"));
+}
+
+#[test]
+fn test_seed_fake_eth_balance() {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let deployer = Sender::new(42);
+    let atomica = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    runner
+        .create_account(atomica, AuthenticationKey::ed25519(&deployer.public_key), 0)
+        .unwrap();
+    runner.set_apt_balance(atomica, 100_000_000_000).unwrap();
+    let deployer = Sender {
+        address: atomica,
+        ..deployer
+    };
+
+    let package = build_fake_eth(atomica).expect("fake_eth should compile");
+    let publish = aptos_stdlib::code_publish_package_txn(
+        bcs::to_bytes(&package.extract_metadata().unwrap()).unwrap(),
+        package.extract_code(),
+    );
+    assert!(is_success(&run(
+        &mut runner,
+        &deployer,
+        0,
+        publish.into_entry_function()
+    )));
+    let initialize = EntryFunction::new(
+        ModuleId::new(atomica, Identifier::new("fake_eth").unwrap()),
+        Identifier::new("initialize").unwrap(),
+        vec![],
+        vec![],
+    );
+    assert!(is_success(&run(&mut runner, &deployer, 1, initialize)));
+
+    let user = Sender::new(43);
+    runner.seed_account(&user.key(), 0).unwrap();
+    runner.set_apt_balance(user.address, 10_000_000).unwrap();
+    let fake_eth = object_address(atomica, b"FAKEETH");
+    runner
+        .set_fungible_balance(user.address, fake_eth, 1_000)
+        .unwrap();
+
+    let transfer = |amount: u64| {
+        EntryFunction::new(
+            ModuleId::new(
+                CORE_CODE_ADDRESS,
+                Identifier::new("primary_fungible_store").unwrap(),
+            ),
+            Identifier::new("transfer").unwrap(),
+            vec![TypeTag::Struct(Box::new(framework_struct_tag(
+                "fungible_asset",
+                "Metadata",
+            )))],
+            vec![
+                bcs::to_bytes(&fake_eth).unwrap(),
+                bcs::to_bytes(&atomica).unwrap(),
+                bcs::to_bytes(&amount).unwrap(),
+            ],
+        )
+    };
+    let output = run(&mut runner, &user, 0, transfer(600));
+    assert!(is_success(&output), "{:?}", output.status());
+    let output = run(&mut runner, &user, 1, transfer(600));
+    assert!(matches!(
+        output.status(),
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort { .. })
+    ));
+    assert_eq!(
+        runner.fungible_balance(user.address, fake_eth).unwrap(),
+        400
+    );
+    assert_eq!(runner.fungible_balance(atomica, fake_eth).unwrap(), 600);
+
+    // The seeded store is laid out like the one the deposit created.
+    let seeded = primary_store_address(user.address, fake_eth);
+    let created = primary_store_address(atomica, fake_eth);
+    for (module, name) in [
+        ("object", "ObjectCore"),
+        ("object", "Untransferable"),
+        ("fungible_asset", "FungibleStore"),
+        ("fungible_asset", "ConcurrentFungibleBalance"),
+    ] {
+        let tag = framework_struct_tag(module, name);
+        assert_eq!(
+            runner.resource(seeded, &tag).unwrap().is_some(),
+            runner.resource(created, &tag).unwrap().is_some(),
+            "{module}::{name}"
+        );
+    }
+    let object_core = |store: AccountAddress| {
+        let bytes = runner
+            .resource(store, &framework_struct_tag("object", "ObjectCore"))
+            .unwrap()
+            .unwrap();
+        // (guid_creation_num, owner, allow_ungated_transfer, transfer_events)
+        let (guid_creation_num, owner, allow_ungated_transfer, _): (
+            u64,
+            AccountAddress,
+            bool,
+            (u64, (u64, AccountAddress)),
+        ) = bcs::from_bytes(&bytes).unwrap();
+        (guid_creation_num, owner, allow_ungated_transfer)
+    };
+    assert_eq!(
+        object_core(seeded),
+        (object_core(created).0, user.address, false)
+    );
+    assert_eq!(object_core(created).1, atomica);
+}
+
+fn framework_struct_tag(module: &str, name: &str) -> StructTag {
+    StructTag {
+        address: CORE_CODE_ADDRESS,
+        module: Identifier::new(module).unwrap(),
+        name: Identifier::new(name).unwrap(),
+        type_args: vec![],
+    }
+}
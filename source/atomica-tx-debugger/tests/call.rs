@@ -0,0 +1,80 @@
+use aptos_crypto::ed25519::Ed25519PublicKey;
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{ExecutionStatus, TransactionStatus},
+};
+use atomica_tx_debugger::{
+    accounts::{local_account_key, AccountKey},
+    call::{entry_function, LocalAccounts},
+    offline_runner::OfflineTxnRunner,
+};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+};
+use std::str::FromStr;
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn test_call_transfer_between_local_accounts() {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let mut accounts = LocalAccounts::new();
+    let alice = accounts.ensure(&mut runner, "alice", 10_000_000).unwrap();
+    let bob = accounts.ensure(&mut runner, "bob", 0).unwrap();
+    // Keys come from the name, like scenario accounts.
+    let key = AccountKey::Ed25519(Ed25519PublicKey::from(&local_account_key("alice")));
+    assert_eq!(alice, key.address());
+
+    let function = "0x1::aptos_account::transfer";
+    let call = entry_function(
+        &runner,
+        accounts.names(),
+        function,
+        &[],
+        &strings(&["bob", "u64:1000"]),
+    )
+    .unwrap();
+    let txn = accounts.sign(&runner, "alice", call, 2_000_000).unwrap();
+    let output = runner.execute_transaction(txn).unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+    assert_eq!(runner.apt_balance(bob).unwrap(), 1_000);
+
+    // Existing accounts are reused, not reset.
+    accounts.ensure(&mut runner, "alice", 10_000_000).unwrap();
+    assert_eq!(runner.account(alice).unwrap().unwrap().sequence_number(), 1);
+
+    // Arguments are checked against the on-chain signature.
+    let names = accounts.names();
+    assert!(entry_function(&runner, names, function, &[], &strings(&["bob"])).is_err());
+    assert!(entry_function(
+        &runner,
+        names,
+        function,
+        &[],
+        &strings(&["bob", "address:0xcafe"])
+    )
+    .is_err());
+    assert!(entry_function(&runner, names, "0x1::coin::balance", &[], &[]).is_err());
+    assert!(entry_function(&runner, names, "0x1::nothing::here", &[], &[]).is_err());
+}
+
+#[test]
+fn test_generic_entry_function_params() {
+    let runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let coin = ModuleId::new(AccountAddress::ONE, Identifier::new("coin").unwrap());
+    let transfer = Identifier::new("transfer").unwrap();
+    let apt = TypeTag::from_str("0x1::aptos_coin::AptosCoin").unwrap();
+    assert_eq!(
+        runner
+            .entry_function_params(&coin, &transfer, &[apt])
+            .unwrap(),
+        vec![TypeTag::Address, TypeTag::U64]
+    );
+    assert!(runner.entry_function_params(&coin, &transfer, &[]).is_err());
+}
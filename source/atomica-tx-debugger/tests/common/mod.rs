@@ -0,0 +1,82 @@
+//! Accounts and transfers shared by the integration tests.
+#![allow(dead_code)]
+
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey};
+use aptos_keygen::KeyGen;
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{EntryFunction, RawTransaction, SignedTransaction},
+};
+use atomica_tx_debugger::{accounts::AccountKey, offline_runner::OfflineTxnRunner};
+
+/// APT in octas [`funded_sender`] seeds.
+pub const BALANCE: u64 = 10_000_000;
+pub const MAX_GAS: u64 = 2_000_000;
+pub const GAS_UNIT_PRICE: u64 = 100;
+
+/// An Ed25519 account with a key derived from a one-byte seed.
+pub struct Sender {
+    pub address: AccountAddress,
+    pub private_key: Ed25519PrivateKey,
+    pub public_key: Ed25519PublicKey,
+}
+
+impl Sender {
+    pub fn new(seed: u8) -> Self {
+        let (private_key, public_key) = KeyGen::from_seed([seed; 32]).generate_ed25519_keypair();
+        Self {
+            address: AccountKey::Ed25519(public_key.clone()).address(),
+            private_key,
+            public_key,
+        }
+    }
+
+    pub fn key(&self) -> AccountKey {
+        AccountKey::Ed25519(self.public_key.clone())
+    }
+
+    /// Sign a call to `entry_function` on `chain_id`
+    pub fn sign(
+        &self,
+        sequence_number: u64,
+        entry_function: EntryFunction,
+        chain_id: ChainId,
+    ) -> SignedTransaction {
+        RawTransaction::new_entry_function(
+            self.address,
+            sequence_number,
+            entry_function,
+            MAX_GAS,
+            GAS_UNIT_PRICE,
+            u64::MAX,
+            chain_id,
+        )
+        .sign(&self.private_key, self.public_key.clone())
+        .unwrap()
+        .into_inner()
+    }
+}
+
+/// Seed the account for `seed` at sequence number 0 with [`BALANCE`]
+pub fn funded_sender(runner: &mut OfflineTxnRunner, seed: u8) -> Sender {
+    let sender = Sender::new(seed);
+    runner.seed_account(&sender.key(), 0).unwrap();
+    runner.set_apt_balance(sender.address, BALANCE).unwrap();
+    sender
+}
+
+pub fn transfer_apt(to: AccountAddress, amount: u64) -> EntryFunction {
+    aptos_stdlib::aptos_account_transfer(to, amount).into_entry_function()
+}
+
+/// An APT transfer from `sender` on the test chain
+pub fn transfer(
+    sender: &Sender,
+    sequence_number: u64,
+    to: AccountAddress,
+    amount: u64,
+) -> SignedTransaction {
+    sender.sign(sequence_number, transfer_apt(to, amount), ChainId::test())
+}
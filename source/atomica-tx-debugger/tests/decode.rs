@@ -0,0 +1,45 @@
+mod common;
+
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{ExecutionStatus, TransactionStatus},
+};
+use atomica_tx_debugger::{decode::WriteKind, offline_runner::OfflineTxnRunner};
+use common::{funded_sender, transfer};
+
+#[test]
+fn test_decoded_write_set_and_events() {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let sender = funded_sender(&mut runner, 46);
+    let recipient = AccountAddress::from_hex_literal("0xfeed").unwrap();
+    let output = runner
+        .execute_transaction(transfer(&sender, 0, recipient, 1_000))
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+
+    let writes = runner.decode_write_set(output.write_set());
+    let account = writes
+        .iter()
+        .find(|w| w.key == format!("{}::0x1::account::Account", sender.address.to_hex_literal()))
+        .unwrap();
+    assert_eq!(account.kind, WriteKind::Modify);
+    assert_eq!(account.value.as_ref().unwrap()["sequence_number"], "1");
+
+    // Fungible stores live in object resource groups, decoded member by member.
+    let store = writes
+        .iter()
+        .filter_map(|w| w.value.as_ref()?.get("0x1::fungible_asset::FungibleStore"))
+        .find(|store| store["balance"] == "1000")
+        .expect("the recipient's new store holds the transfer");
+    assert!(store["metadata"].is_object());
+
+    let events = runner.decode_events(output.events());
+    let fee = events
+        .iter()
+        .find(|e| e.type_ == "0x1::transaction_fee::FeeStatement")
+        .unwrap();
+    assert!(fee.data["total_charge_gas_units"].is_string());
+}
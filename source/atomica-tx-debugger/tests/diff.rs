@@ -0,0 +1,76 @@
+mod common;
+
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{ExecutionStatus, TransactionStatus},
+};
+use atomica_tx_debugger::{
+    accounts::APT_METADATA, decode::WriteKind, offline_runner::OfflineTxnRunner,
+};
+use common::{funded_sender, transfer, Sender, BALANCE, GAS_UNIT_PRICE};
+
+#[test]
+fn test_diff_of_apt_transfer() {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let sender = funded_sender(&mut runner, 47);
+    let recipient = AccountAddress::from_hex_literal("0xfeed").unwrap();
+    let output = runner
+        .execute_transaction(transfer(&sender, 0, recipient, 1_000))
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+
+    let diff = runner.last_diff().unwrap();
+    assert_eq!(
+        diff.changes.len(),
+        output.write_set().as_v0().iter().count()
+    );
+    let account = diff
+        .changes
+        .iter()
+        .find(|c| c.key == format!("{}::0x1::account::Account", sender.address.to_hex_literal()))
+        .unwrap();
+    assert_eq!(account.kind, WriteKind::Modify);
+    assert_eq!(account.before.as_ref().unwrap()["sequence_number"], "0");
+    assert_eq!(account.after.as_ref().unwrap()["sequence_number"], "1");
+    // The recipient's primary store is a new object.
+    assert!(diff.changes.iter().any(|c| c.kind == WriteKind::Create
+        && c.before.is_none()
+        && c.after.as_ref().unwrap()["0x1::fungible_asset::FungibleStore"]["balance"] == "1000"));
+
+    let received = diff.balance_change(recipient, APT_METADATA).unwrap();
+    assert_eq!(
+        (received.before, received.after, received.delta),
+        (0, 1_000, 1_000)
+    );
+    let sent = diff.balance_change(sender.address, APT_METADATA).unwrap();
+    assert_eq!(
+        sent.delta,
+        -(1_000 + i128::from(output.gas_used() * GAS_UNIT_PRICE))
+    );
+    assert_eq!(sent.before, BALANCE);
+    assert_eq!(diff.balances.len(), 2);
+
+    let text = diff.to_string();
+    assert!(text.contains(&format!(
+        "~ {}::0x1::account::Account",
+        sender.address.to_hex_literal()
+    )));
+    assert!(text.contains("0xfeed APT: 0 -> 1000 (+1000)"));
+    let json = serde_json::to_value(&diff).unwrap();
+    assert!(json["changes"][0]["kind"].is_string());
+    assert!(json["balances"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|b| b["delta"] == 1_000));
+
+    // Discarded transactions leave nothing to diff.
+    let stranger = Sender::new(48);
+    runner
+        .execute_transaction(transfer(&stranger, 0, recipient, 1))
+        .unwrap();
+    assert!(runner.last_diff().unwrap().is_empty());
+}
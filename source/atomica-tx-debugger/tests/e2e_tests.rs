@@ -1,4 +1,4 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use aptos_cached_packages::aptos_stdlib;
 use aptos_crypto::{ed25519::{Ed25519PrivateKey, Ed25519PublicKey}};
 use aptos_framework::ReleaseBundle;
@@ -10,7 +10,10 @@ use aptos_types::{
         authenticator::AuthenticationKey, RawTransaction, SignedTransaction, TransactionStatus,
     },
 };
-use atomica_tx_debugger::offline_runner::OfflineTxnRunner;
+use atomica_tx_debugger::{
+    genesis::{load_release_bundle, release_bundle_fixtures, GenesisOptions},
+    offline_runner::OfflineTxnRunner,
+};
 use std::fs;
 use std::path::PathBuf;
 
@@ -22,22 +25,20 @@ fn fixtures_dir() -> PathBuf {
         .join("move-framework-fixtures")
 }
 
-/// Load the head.mrb fixture - use cached packages for now as loading from file has issues
+/// Load the head.mrb fixture
 fn load_head_mrb() -> Result<ReleaseBundle> {
-    // TODO: Fix loading from .mrb files directly - for now use cached packages
-    Ok(aptos_cached_packages::head_release_bundle().clone())
+    load_release_bundle(&fixtures_dir().join("head.mrb"))
 }
 
-/// Test that we can actually load from the .mrb file (currently failing)
+/// Every versioned fixture must boot, not just the latest one
 #[test]
-#[ignore] // Ignored until genesis initialization from .mrb files is fixed
 fn test_load_mrb_file_directly() {
-    let mrb_path = fixtures_dir().join("head.mrb");
-    let mrb_content = fs::read(&mrb_path).expect("Failed to read mrb file");
-    let release_bundle: ReleaseBundle =
-        bcs::from_bytes(&mrb_content).expect("Failed to deserialize ReleaseBundle");
-    // This will panic during genesis initialization
-    let _runner = OfflineTxnRunner::new(&release_bundle);
+    let fixtures = release_bundle_fixtures(&fixtures_dir()).expect("Failed to list fixtures");
+    assert!(!fixtures.is_empty(), "No head-<hash>.mrb fixtures found");
+    for mrb_path in fixtures {
+        OfflineTxnRunner::from_mrb(&mrb_path, &GenesisOptions::default())
+            .unwrap_or_else(|err| panic!("{:?}: {:#}", mrb_path, err));
+    }
 }
 
 /// Create a test transaction that transfers coins
@@ -92,7 +93,12 @@ fn test_execute_successful_transfer() {
     // Fund the sender
     let initial_balance = 100_000_000_000u64;
     let sequence_number = 0;
-    runner.fund_account(sender, initial_balance, sequence_number);
+    runner
+        .create_account(sender, AuthenticationKey::ed25519(&public_key), sequence_number)
+        .expect("Failed to create sender");
+    runner
+        .set_apt_balance(sender, initial_balance)
+        .expect("Failed to fund sender");
 
     // Create and execute transfer transaction
     let transfer_amount = 1_000u64;
@@ -153,7 +159,12 @@ fn test_execute_transaction_with_insufficient_sequence() {
     let recipient = AccountAddress::from_hex_literal("0xcafe").unwrap();
 
     // Fund the sender with sequence number 0
-    runner.fund_account(sender, 100_000_000_000u64, 0);
+    runner
+        .create_account(sender, AuthenticationKey::ed25519(&public_key), 0)
+        .expect("Failed to create sender");
+    runner
+        .set_apt_balance(sender, 100_000_000_000u64)
+        .expect("Failed to fund sender");
 
     // Try to execute transaction with wrong sequence number (10 instead of 0)
     let wrong_sequence = 10;
@@ -196,7 +207,12 @@ fn test_write_set_and_events_populated() {
     let recipient = AccountAddress::from_hex_literal("0xcafe").unwrap();
 
     // Fund the sender
-    runner.fund_account(sender, 100_000_000_000u64, 0);
+    runner
+        .create_account(sender, AuthenticationKey::ed25519(&public_key), 0)
+        .expect("Failed to create sender");
+    runner
+        .set_apt_balance(sender, 100_000_000_000u64)
+        .expect("Failed to fund sender");
 
     // Execute transfer
     let txn = create_transfer_transaction(sender, recipient, 1_000u64, 0, &private_key, public_key);
@@ -0,0 +1,69 @@
+mod common;
+
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{ExecutionStatus, TransactionStatus},
+};
+use atomica_tx_debugger::offline_runner::OfflineTxnRunner;
+use common::{funded_sender, transfer};
+use std::fs;
+
+#[test]
+fn test_profile_of_apt_transfer() {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let sender = funded_sender(&mut runner, 45);
+    // A new recipient, so the transfer creates state and pays a storage fee.
+    let recipient = AccountAddress::from_hex_literal("0xfeed").unwrap();
+    let txn = transfer(&sender, 0, recipient, 1_000);
+
+    let (output, profile) = runner.execute_transaction_profiled(txn).unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+
+    // The entry function is called once and covers every other function.
+    let entry = profile
+        .functions
+        .iter()
+        .find(|f| f.function == "0x1::aptos_account::transfer")
+        .unwrap();
+    assert_eq!(entry.calls, 1);
+    assert_eq!(profile.functions[0].function, entry.function);
+    assert!(profile
+        .functions
+        .iter()
+        .all(|f| f.inclusive >= f.execution + f.storage_reads && f.inclusive <= entry.inclusive));
+    assert!(profile.functions.iter().any(|f| f.native));
+
+    // Every written key pays IO, and the new recipient's state pays a fee.
+    assert!(!profile.resources.is_empty());
+    assert!(profile.resources.iter().all(|r| r.io > 0));
+    let created = &profile.resources[0];
+    assert!(created.storage_fee > 0);
+
+    // Folded stacks are `<frames> <count>` and add up to the profiled total.
+    let folded = profile.folded_execution();
+    let total: u64 = folded
+        .lines()
+        .map(|line| {
+            let (stack, count) = line.rsplit_once(' ').unwrap();
+            assert!(!stack.is_empty());
+            count.parse::<u64>().unwrap()
+        })
+        .sum();
+    assert_eq!(total, u64::from(profile.log.exec_io.total));
+    assert!(folded
+        .lines()
+        .any(|line| line.starts_with("0x1::aptos_account::transfer;")));
+
+    let dir = std::env::temp_dir().join(format!("atomica_gas_profile_{}", std::process::id()));
+    profile.write_to(&dir, "transfer").unwrap();
+    let report = fs::read_to_string(dir.join("report.md")).unwrap();
+    assert!(report.starts_with("# Gas profile: transfer"));
+    assert!(report.contains("| `0x1::aptos_account::transfer` | 1 |"));
+    assert!(report.contains(&format!("| `{}` |", created.resource)));
+    assert!(dir.join("execution.folded").exists());
+    assert!(dir.join("storage.folded").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
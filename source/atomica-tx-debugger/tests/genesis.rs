@@ -0,0 +1,122 @@
+mod common;
+
+use aptos_framework::ReleaseBundle;
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    on_chain_config::FeatureFlag,
+    transaction::{ExecutionStatus, TransactionStatus},
+};
+use atomica_tx_debugger::{
+    genesis::{load_release_bundle, loadable_framework, release_bundle_fixtures, GenesisOptions},
+    offline_runner::OfflineTxnRunner,
+};
+use common::{transfer_apt, Sender};
+use std::path::PathBuf;
+
+/// Every checked-in `head-<hash>.mrb` fixture, with its path.
+fn fixtures() -> Vec<(PathBuf, ReleaseBundle)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("move-framework-fixtures");
+    let fixtures = release_bundle_fixtures(&dir).expect("Failed to list fixtures");
+    assert!(!fixtures.is_empty(), "No head-<hash>.mrb fixtures found");
+    fixtures
+        .into_iter()
+        .map(|path| {
+            let bundle = load_release_bundle(&path).expect("Failed to load fixture");
+            (path, bundle)
+        })
+        .collect()
+}
+
+#[test]
+fn test_feature_overrides() {
+    let options = GenesisOptions::new()
+        .disable_feature(FeatureFlag::CODE_DEPENDENCY_CHECK)
+        .enable_feature_id(250);
+    let features = options.features();
+    assert!(!features.is_enabled(FeatureFlag::CODE_DEPENDENCY_CHECK));
+    assert_eq!(
+        features.features[250 / 8] & (1 << (250 % 8)),
+        1 << (250 % 8)
+    );
+
+    // The last override of a flag wins.
+    let options = options.enable_feature(FeatureFlag::CODE_DEPENDENCY_CHECK);
+    assert!(options
+        .features()
+        .is_enabled(FeatureFlag::CODE_DEPENDENCY_CHECK));
+}
+
+#[test]
+fn test_zero_validators_is_an_error() {
+    let options = GenesisOptions::new().validators(0, 100_000_000);
+    for (_, framework) in fixtures() {
+        assert!(OfflineTxnRunner::with_genesis(&framework, &options).is_err());
+    }
+}
+
+/// Only packages nothing else depends on may be left out of genesis.
+#[test]
+fn test_fixtures_keep_the_framework() {
+    for (path, framework) in fixtures() {
+        let loadable = loadable_framework(&framework).unwrap();
+        let names: Vec<_> = loadable
+            .packages
+            .iter()
+            .map(|package| package.package_metadata().name.as_str())
+            .collect();
+        for name in ["MoveStdlib", "AptosStdlib", "AptosFramework"] {
+            assert!(names.contains(&name), "{:?} lost {}", path, name);
+        }
+    }
+}
+
+#[test]
+fn test_rejects_missing_mrb() {
+    let err = OfflineTxnRunner::from_mrb(
+        &PathBuf::from("does-not-exist.mrb"),
+        &GenesisOptions::default(),
+    )
+    .err()
+    .expect("missing file must not boot");
+    assert!(format!("{:#}", err).contains("does-not-exist.mrb"));
+}
+
+/// A pre-funded account on a custom chain can pay for a transfer.
+#[test]
+fn test_custom_chain_with_funded_account() {
+    let sender = Sender::new(39);
+    let chain_id = ChainId::new(42);
+
+    let options = GenesisOptions::new()
+        .chain_id(chain_id)
+        .validators(2, 100_000_000)
+        .epoch_duration_secs(600)
+        .initial_timestamp_usecs(1_700_000_000_000_000)
+        .fund_account(sender.address, 100_000_000_000);
+    let transfer = |chain_id| {
+        let to = AccountAddress::from_hex_literal("0xcafe").unwrap();
+        sender.sign(0, transfer_apt(to, 1_000), chain_id)
+    };
+
+    for (path, framework) in fixtures() {
+        let mut runner = OfflineTxnRunner::with_genesis(&framework, &options)
+            .unwrap_or_else(|err| panic!("{:?}: {:#}", path, err));
+        assert_eq!(runner.chain_id(), chain_id);
+
+        // The prologue checks the chain id.
+        let output = runner
+            .execute_transaction(transfer(ChainId::test()))
+            .unwrap();
+        assert!(matches!(output.status(), TransactionStatus::Discard(_)));
+
+        let output = runner.execute_transaction(transfer(chain_id)).unwrap();
+        assert_eq!(
+            output.status(),
+            &TransactionStatus::Keep(ExecutionStatus::Success)
+        );
+    }
+}
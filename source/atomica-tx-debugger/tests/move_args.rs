@@ -0,0 +1,137 @@
+use std::str::FromStr;
+
+use aptos_types::account_address::AccountAddress;
+use atomica_tx_debugger::{
+    accounts::object_address,
+    move_args::{parse_arg, parse_arg_as, NamedAddresses},
+};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+};
+
+fn names() -> NamedAddresses {
+    let mut names = NamedAddresses::new();
+    names.insert(
+        "atomica",
+        AccountAddress::from_hex_literal("0xcafe").unwrap(),
+    );
+    names
+}
+
+#[test]
+fn test_address_expressions() {
+    let names = names();
+    let atomica = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    assert_eq!(names.address("atomica").unwrap(), atomica);
+    assert_eq!(names.address("0xcafe").unwrap(), atomica);
+    assert_eq!(
+        names.address("object:atomica:FAKEETH").unwrap(),
+        object_address(atomica, b"FAKEETH")
+    );
+    assert!(names.address("mallory").is_err());
+}
+
+#[test]
+fn test_types_and_functions_resolve_names() {
+    let names = names();
+    let atomica = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    let bid = TypeTag::Struct(Box::new(StructTag {
+        address: atomica,
+        module: Identifier::new("auction").unwrap(),
+        name: Identifier::new("Bid").unwrap(),
+        type_args: vec![],
+    }));
+    assert_eq!(
+        names.type_tag("vector<atomica::auction::Bid>").unwrap(),
+        TypeTag::Vector(Box::new(bid))
+    );
+    assert_eq!(
+        names
+            .type_tag("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>")
+            .unwrap(),
+        "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+            .parse::<TypeTag>()
+            .unwrap()
+    );
+    assert_eq!(
+        names.function("atomica::fake_eth::mint").unwrap(),
+        (
+            ModuleId::new(atomica, Identifier::new("fake_eth").unwrap()),
+            Identifier::new("mint").unwrap()
+        )
+    );
+    assert!(names.function("atomica::fake_eth").is_err());
+}
+
+#[test]
+fn test_typed_args_encode_as_bcs() {
+    let names = names();
+    assert_eq!(
+        parse_arg("u64:1000", &names).unwrap(),
+        bcs::to_bytes(&1000u64).unwrap()
+    );
+    assert_eq!(parse_arg("bool:true", &names).unwrap(), vec![1]);
+    assert_eq!(
+        parse_arg("address:atomica", &names).unwrap(),
+        bcs::to_bytes(&AccountAddress::from_hex_literal("0xcafe").unwrap()).unwrap()
+    );
+    assert_eq!(
+        parse_arg("string: spaced out", &names).unwrap(),
+        bcs::to_bytes(" spaced out").unwrap()
+    );
+    assert_eq!(
+        parse_arg("hex:0xdead", &names).unwrap(),
+        bcs::to_bytes(&vec![0xdeu8, 0xad]).unwrap()
+    );
+    assert_eq!(
+        parse_arg("vector<u64>:1,2,3", &names).unwrap(),
+        bcs::to_bytes(&vec![1u64, 2, 3]).unwrap()
+    );
+    assert_eq!(
+        parse_arg("vector<u8>:", &names).unwrap(),
+        bcs::to_bytes(&Vec::<u8>::new()).unwrap()
+    );
+    assert!(parse_arg("u8:256", &names).is_err());
+    assert!(parse_arg("1000", &names).is_err());
+    assert!(parse_arg("float:1.5", &names).is_err());
+}
+
+#[test]
+fn test_args_parse_against_parameter_types() {
+    let names = names();
+    let atomica = bcs::to_bytes(&AccountAddress::from_hex_literal("0xcafe").unwrap()).unwrap();
+    assert_eq!(
+        parse_arg_as(&TypeTag::U64, "1000", &names).unwrap(),
+        bcs::to_bytes(&1000u64).unwrap()
+    );
+    assert_eq!(
+        parse_arg_as(&TypeTag::U64, "u64:1000", &names).unwrap(),
+        bcs::to_bytes(&1000u64).unwrap()
+    );
+    assert_eq!(
+        parse_arg_as(&TypeTag::Address, "atomica", &names).unwrap(),
+        atomica
+    );
+    // Colons inside a plain value are not mistaken for a type prefix.
+    assert!(parse_arg_as(&TypeTag::Address, "object:atomica:FAKEETH", &names).is_ok());
+    let bytes = TypeTag::Vector(Box::new(TypeTag::U8));
+    assert_eq!(
+        parse_arg_as(&bytes, "0xdead", &names).unwrap(),
+        bcs::to_bytes(&vec![0xdeu8, 0xad]).unwrap()
+    );
+    assert!(parse_arg_as(&bytes, "vector<u8>:222,173", &names).is_ok());
+
+    let object = TypeTag::from_str("0x1::object::Object<0x1::fungible_asset::Metadata>").unwrap();
+    assert_eq!(parse_arg_as(&object, "atomica", &names).unwrap(), atomica);
+    let string = TypeTag::from_str("0x1::string::String").unwrap();
+    assert_eq!(
+        parse_arg_as(&string, "a:b", &names).unwrap(),
+        bcs::to_bytes("a:b").unwrap()
+    );
+
+    assert!(parse_arg_as(&TypeTag::U64, "address:0xcafe", &names).is_err());
+    assert!(parse_arg_as(&TypeTag::U8, "256", &names).is_err());
+    let option = TypeTag::from_str("0x1::option::Option<u64>").unwrap();
+    assert!(parse_arg_as(&option, "1", &names).is_err());
+}
@@ -0,0 +1,219 @@
+//! Runs Move verifiers generated for registered diem-prover-halo2 circuits
+//! in the offline VM.
+
+use anyhow::Result;
+use aptos_cached_packages::aptos_stdlib;
+use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey};
+use aptos_framework::{BuildOptions, BuiltPackage};
+use aptos_gas_schedule::{AptosGasParameters, InitialGasSchedule};
+use aptos_keygen::KeyGen;
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{
+        EntryFunction, ExecutionStatus, RawTransaction, TransactionOutput, TransactionStatus,
+    },
+};
+use atomica_tx_debugger::{accounts::AccountKey, offline_runner::OfflineTxnRunner};
+use diem_prover_halo2::{
+    halo2_base::halo2_proofs::{
+        halo2curves::{
+            bn256::{Bn256, Fr},
+            ff::PrimeField,
+        },
+        poly::kzg::commitment::ParamsKZG,
+    },
+    registry::{keys::CircuitKeys, CircuitRegistry},
+};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId, u256::U256};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{collections::BTreeMap, fs};
+
+/// `E_INVALID_PROOF` in the generated module.
+const E_INVALID_PROOF: u64 = 1;
+
+/// A verifier is only usable if a proof fits in one transaction, so
+/// verification is held to the chain's per-transaction gas limit.
+fn max_gas() -> u64 {
+    AptosGasParameters::initial()
+        .vm
+        .txn
+        .maximum_number_of_gas_units
+        .into()
+}
+
+/// A registered circuit's generated verifier, plus a proof of its example.
+struct Fixture {
+    module: String,
+    code: String,
+    instances: Vec<Fr>,
+    proof: Vec<u8>,
+}
+
+fn fixture(id: &str) -> Fixture {
+    let registry = CircuitRegistry::builtin();
+    let info = registry.get(id).expect("registered circuit");
+    let params = ParamsKZG::<Bn256>::setup(info.params.k, StdRng::seed_from_u64(29));
+    let keys = CircuitKeys::generate(info, &params);
+    let module = id.replace('-', "_");
+    let code = keys.move_verifier(&params, &format!("verifier::{module}"));
+    let (instances, proof) = keys.prove(&params, (info.example)().as_ref());
+    Fixture {
+        module,
+        code,
+        instances: instances.concat(),
+        proof,
+    }
+}
+
+/// Compiles the generated module into a package published at `address`.
+fn build_package(fixture: &Fixture, address: AccountAddress) -> Result<BuiltPackage> {
+    let dir = std::env::temp_dir().join(format!("move_verifier_{}", address.short_str_lossless()));
+    fs::create_dir_all(dir.join("sources"))?;
+    let stdlib = aptos_framework::path_in_crate("aptos-stdlib");
+    fs::write(
+        dir.join("Move.toml"),
+        format!(
+            "[package]\nname = \"GeneratedVerifier\"\nversion = \"0.0.0\"\n\n\
+             [addresses]\nverifier = \"_\"\n\n\
+             [dependencies]\nAptosStdlib = {{ local = \"{}\" }}\n",
+            stdlib.display()
+        ),
+    )?;
+    fs::write(
+        dir.join("sources").join(format!("{}.move", fixture.module)),
+        &fixture.code,
+    )?;
+
+    let options = BuildOptions {
+        named_addresses: BTreeMap::from([("verifier".to_string(), address)]),
+        ..BuildOptions::default()
+    };
+    BuiltPackage::build(dir, options)
+}
+
+struct Account {
+    address: AccountAddress,
+    private_key: Ed25519PrivateKey,
+    public_key: Ed25519PublicKey,
+    sequence_number: u64,
+}
+
+impl Account {
+    fn new(runner: &mut OfflineTxnRunner) -> Self {
+        let (private_key, public_key) = KeyGen::from_os_rng().generate_ed25519_keypair();
+        let address = runner
+            .seed_account(&AccountKey::Ed25519(public_key.clone()), 0)
+            .expect("Failed to seed account");
+        runner
+            .set_apt_balance(address, 100_000_000_000)
+            .expect("Failed to fund account");
+        Self {
+            address,
+            private_key,
+            public_key,
+            sequence_number: 0,
+        }
+    }
+
+    fn run(&mut self, runner: &mut OfflineTxnRunner, entry_function: EntryFunction) -> TransactionOutput {
+        let txn = RawTransaction::new_entry_function(
+            self.address,
+            self.sequence_number,
+            entry_function,
+            max_gas(),
+            100,      // gas unit price
+            u64::MAX, // expiration
+            ChainId::test(),
+        )
+        .sign(&self.private_key, self.public_key.clone())
+        .unwrap()
+        .into_inner();
+        self.sequence_number += 1;
+        runner.execute_transaction(txn).expect("transaction execution failed")
+    }
+}
+
+fn assert_valid(
+    verifier: AccountAddress,
+    module: &str,
+    instances: &[Fr],
+    proof: &[u8],
+) -> EntryFunction {
+    let instances: Vec<U256> = instances
+        .iter()
+        .map(|instance| U256::from_le_bytes(&instance.to_repr()))
+        .collect();
+    EntryFunction::new(
+        ModuleId::new(verifier, Identifier::new(module).unwrap()),
+        Identifier::new("assert_valid").unwrap(),
+        vec![],
+        vec![bcs::to_bytes(&instances).unwrap(), bcs::to_bytes(&proof).unwrap()],
+    )
+}
+
+fn execution_status(output: &TransactionOutput) -> &ExecutionStatus {
+    match output.status() {
+        TransactionStatus::Keep(status) => status,
+        status => panic!("Expected Keep status, got: {:?}", status),
+    }
+}
+
+fn assert_rejected(output: &TransactionOutput) {
+    match execution_status(output) {
+        ExecutionStatus::MoveAbort { code, .. } => assert_eq!(*code, E_INVALID_PROOF),
+        status => panic!("Expected E_INVALID_PROOF abort, got: {:?}", status),
+    }
+}
+
+/// Publishes the verifier for `id`, then checks that it accepts the example's
+/// proof and rejects tampered proofs and instances.
+fn check_registered_verifier(id: &str) {
+    let fixture = fixture(id);
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let mut account = Account::new(&mut runner);
+    let verifier = account.address;
+    let call = |instances: &[Fr], proof: &[u8]| assert_valid(verifier, &fixture.module, instances, proof);
+
+    let package = build_package(&fixture, verifier).expect("generated module should compile");
+    let publish = aptos_stdlib::code_publish_package_txn(
+        bcs::to_bytes(&package.extract_metadata().unwrap()).unwrap(),
+        package.extract_code(),
+    );
+    let output = account.run(&mut runner, publish.into_entry_function());
+    assert_eq!(execution_status(&output), &ExecutionStatus::Success, "publishing failed");
+
+    // A valid proof is accepted.
+    let output = account.run(&mut runner, call(&fixture.instances, &fixture.proof));
+    assert_eq!(execution_status(&output), &ExecutionStatus::Success, "{id}");
+    assert!(
+        output.gas_used() < max_gas(),
+        "{id}: verification used {} gas units",
+        output.gas_used()
+    );
+
+    // The same proof against other instances is rejected.
+    if let Some((first, rest)) = fixture.instances.split_first() {
+        let instances = [&[*first + Fr::one()], rest].concat();
+        let output = account.run(&mut runner, call(&instances, &fixture.proof));
+        assert_rejected(&output);
+    }
+
+    // Tampering with an opening evaluation (the last scalar before the two
+    // SHPLONK witness points) is rejected.
+    let mut proof = fixture.proof.clone();
+    let last_evaluation = proof.len() - 2 * 64 - 1;
+    proof[last_evaluation] ^= 1;
+    let output = account.run(&mut runner, call(&fixture.instances, &proof));
+    assert_rejected(&output);
+}
+
+#[test]
+fn test_equivalence_move_verifier() {
+    check_registered_verifier("equivalence");
+}
+
+#[test]
+fn test_dkg_aggregation_move_verifier() {
+    check_registered_verifier("dkg-aggregation");
+}
@@ -0,0 +1,210 @@
+mod common;
+
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    state_store::TStateView,
+    transaction::{ExecutionStatus, SignedTransaction, TransactionStatus},
+};
+use atomica_tx_debugger::{
+    genesis::GenesisOptions, offline_runner::OfflineTxnRunner, rest_fork::RestForkStateView,
+    snapshot::Snapshot,
+};
+use common::{funded_sender, transfer_apt};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+const VERSION: u64 = 7;
+
+/// Serves `POST /v1/experimental/state_values/raw` from recorded
+/// `StateValue`s, as a node pinned at [`VERSION`] would.
+struct MockNode {
+    url: String,
+    requests: Arc<AtomicUsize>,
+}
+
+impl MockNode {
+    fn start(recorded: HashMap<String, Vec<u8>>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1", listener.local_addr().unwrap());
+        let recorded = Arc::new(recorded);
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let (recorded, counter) = (recorded.clone(), counter.clone());
+                thread::spawn(move || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    respond(stream.unwrap(), &recorded);
+                });
+            }
+        });
+        Self { url, requests }
+    }
+
+    fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+fn respond(mut stream: TcpStream, recorded: &HashMap<String, Vec<u8>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        if header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+
+    let (status, payload) = if !request_line.starts_with("POST /v1/experimental/state_values/raw?")
+    {
+        ("404 Not Found", b"unknown route".to_vec())
+    } else if !request_line.contains(&format!("ledger_version={VERSION}")) {
+        ("500 Internal Server Error", b"version pruned".to_vec())
+    } else {
+        let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        match recorded.get(request["key"].as_str().unwrap()) {
+            Some(value) => ("200 OK", value.clone()),
+            None => (
+                "404 Not Found",
+                br#"{"error_code":"state_value_not_found"}"#.to_vec(),
+            ),
+        }
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        payload.len()
+    )
+    .unwrap();
+    stream.write_all(&payload).unwrap();
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("atomica_fork_{}_{}", std::process::id(), name))
+}
+
+/// A chain with a funded sender, recorded as the node's responses, plus a
+/// transfer from that sender.
+fn recorded_chain() -> (HashMap<String, Vec<u8>>, SignedTransaction) {
+    let chain_id = ChainId::new(42);
+    let mut runner = OfflineTxnRunner::with_genesis(
+        aptos_cached_packages::head_release_bundle(),
+        &GenesisOptions::new().chain_id(chain_id),
+    )
+    .unwrap();
+    let sender = funded_sender(&mut runner, 42);
+
+    let path = temp_path("recorded");
+    runner.save_snapshot(&path).unwrap();
+    let snapshot = Snapshot::load(&path).unwrap();
+    fs::remove_file(path).unwrap();
+    let recorded = snapshot
+        .state
+        .into_iter()
+        .map(|(key, value)| {
+            (
+                format!("0x{}", hex::encode(bcs::to_bytes(&key).unwrap())),
+                bcs::to_bytes(&value).unwrap(),
+            )
+        })
+        .collect();
+
+    let to = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    let txn = sender.sign(0, transfer_apt(to, 1), chain_id);
+    (recorded, txn)
+}
+
+#[test]
+fn test_fork_executes_and_replays_offline() {
+    let (recorded, txn) = recorded_chain();
+    let node = MockNode::start(recorded);
+    let cache = temp_path("cache");
+
+    let mut runner = OfflineTxnRunner::fork(
+        RestForkStateView::new(&node.url, VERSION, Some(cache.clone())).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(runner.chain_id(), ChainId::new(42));
+    let forked = runner.execute_transaction(txn.clone()).unwrap();
+    assert_eq!(
+        forked.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+    let requests = node.requests();
+    assert!(requests > 0);
+    // Fetched values are written out once, not per key.
+    assert!(!cache.exists());
+    runner.save_fork_cache().unwrap();
+    assert!(cache.exists());
+
+    // Reads served from the cache do not hit the node again.
+    let view = RestForkStateView::new(&node.url, VERSION, Some(cache.clone())).unwrap();
+    let mut runner = OfflineTxnRunner::fork(view).unwrap();
+    let replayed = runner.execute_transaction(txn.clone()).unwrap();
+    assert_eq!(node.requests(), requests);
+    assert_eq!(forked.write_set(), replayed.write_set());
+
+    // And the cache alone is enough to replay.
+    let mut runner = OfflineTxnRunner::fork(RestForkStateView::offline(&cache).unwrap()).unwrap();
+    let offline = runner.execute_transaction(txn).unwrap();
+    assert_eq!(forked.write_set(), offline.write_set());
+    assert_eq!(forked.gas_used(), offline.gas_used());
+
+    fs::remove_file(cache).unwrap();
+}
+
+#[test]
+fn test_cache_is_pinned_to_its_version() {
+    let (recorded, _) = recorded_chain();
+    let node = MockNode::start(recorded);
+    let cache = temp_path("pinned");
+    OfflineTxnRunner::fork(
+        RestForkStateView::new(&node.url, VERSION, Some(cache.clone())).unwrap(),
+    )
+    .unwrap();
+
+    let err = RestForkStateView::new(&node.url, VERSION + 1, Some(cache.clone()))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("recorded at version 7"));
+    fs::remove_file(cache).unwrap();
+}
+
+#[test]
+fn test_node_errors_are_reported() {
+    let (recorded, _) = recorded_chain();
+    let node = MockNode::start(recorded);
+    let view = RestForkStateView::new(&node.url, VERSION + 1, None).unwrap();
+    let err = OfflineTxnRunner::fork(view).err().unwrap();
+    assert!(format!("{:#}", err).contains("500"));
+
+    // Absent keys are cached as absent rather than failing.
+    let view = RestForkStateView::new(&node.url, VERSION, None).unwrap();
+    let missing = aptos_types::state_store::state_key::StateKey::raw(b"missing");
+    assert_eq!(view.get_state_value(&missing).unwrap(), None);
+    let requests = node.requests();
+    assert_eq!(view.get_state_value(&missing).unwrap(), None);
+    assert_eq!(node.requests(), requests);
+}
@@ -0,0 +1,151 @@
+use atomica_tx_debugger::{
+    offline_runner::OfflineTxnRunner,
+    scenario::{Scenario, ScenarioReport},
+};
+use std::{fs, path::PathBuf};
+
+/// A scratch directory holding `atomica::fake_eth` as a package built
+/// against the local framework.
+fn workspace(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("atomica_scenario_{}_{}", std::process::id(), name));
+    let package = dir.join("fake_eth");
+    fs::create_dir_all(package.join("sources")).unwrap();
+    fs::write(
+        package.join("Move.toml"),
+        format!(
+            "[package]\nname = \"FakeEth\"\nversion = \"0.0.0\"\n\n\
+             [addresses]\natomica = \"_\"\n\n\
+             [dependencies]\nAptosFramework = {{ local = \"{}\" }}\n",
+            aptos_framework::path_in_crate("aptos-framework").display()
+        ),
+    )
+    .unwrap();
+    let source = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .join("atomica-move-contracts/sources/fake_eth.move");
+    fs::copy(source, package.join("sources/fake_eth.move")).unwrap();
+    dir
+}
+
+fn run(dir: &PathBuf, file: &str, text: &str) -> ScenarioReport {
+    let path = dir.join(file);
+    fs::write(&path, text).unwrap();
+    let scenario = Scenario::load(&path).unwrap();
+    let mut runner = OfflineTxnRunner::with_genesis(
+        aptos_cached_packages::head_release_bundle(),
+        &scenario.genesis.options(),
+    )
+    .unwrap();
+    scenario.run(&mut runner, dir).unwrap()
+}
+
+const FAUCET: &str = r#"
+name: fake eth faucet
+accounts:
+  atomica: { address: "0xcafe", balance: 100000000000 }
+  alice: { balance: 100000000 }
+steps:
+  - name: publish
+    publish: { sender: atomica, package: fake_eth, named_addresses: { atomica: atomica } }
+  - name: initialize
+    call: { sender: atomica, function: "atomica::fake_eth::initialize" }
+    expect:
+      resources:
+        - { account: atomica, type: "atomica::fake_eth::ManagingRefs" }
+        - account: "object:atomica:FAKEETH"
+          type: "0x1::fungible_asset::Metadata"
+          fields: { symbol: FAKEETH, decimals: 8 }
+  - name: mint
+    call: { sender: alice, function: "atomica::fake_eth::mint", args: ["u64:1000"] }
+    expect:
+      fungible_balances:
+        - { account: alice, metadata: "object:atomica:FAKEETH", amount: 1000 }
+      events:
+        - { type: "0x1::fungible_asset::Deposit", data: { amount: 1000 } }
+  - name: mint over the cap
+    call: { sender: alice, function: "atomica::fake_eth::mint", args: ["u64:1000000000001"] }
+    expect: { abort_code: 1 }
+  - advance_time: { seconds: 60 }
+    expect:
+      resources:
+        - account: "0x1"
+          type: "0x1::timestamp::CurrentTimeMicroseconds"
+          fields: { microseconds: 60000000 }
+  - fund: { account: alice, apt: 5, fungible: { "object:atomica:FAKEETH": 7 } }
+    expect:
+      balances: { alice: 5 }
+      fungible_balances:
+        - { account: alice, metadata: "object:atomica:FAKEETH", amount: 7 }
+"#;
+
+#[test]
+fn test_fake_eth_faucet_scenario() {
+    let dir = workspace("faucet");
+    let report = run(&dir, "faucet.yaml", FAUCET);
+    for step in &report.steps {
+        assert!(
+            step.failures.is_empty(),
+            "{}: {:?}",
+            step.name,
+            step.failures
+        );
+    }
+    assert!(report.passed());
+    assert_eq!(report.steps.len(), 6);
+    assert!(report.steps[0].gas_used.unwrap() > 0);
+    assert!(report.steps[4].status.is_none());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_failed_expectation_stops_the_scenario() {
+    let dir = workspace("failing");
+    let report = run(
+        &dir,
+        "failing.json",
+        r#"{
+            "genesis": { "chain_id": 9 },
+            "accounts": { "alice": { "balance": 10000000 }, "bob": {} },
+            "steps": [
+                {
+                    "name": "transfer",
+                    "call": {
+                        "sender": "alice",
+                        "function": "0x1::aptos_account::transfer",
+                        "args": ["address:bob", "u64:1000"]
+                    },
+                    "expect": { "balances": { "bob": 999 } }
+                },
+                { "advance_time": { "seconds": 1 } }
+            ]
+        }"#,
+    );
+    assert!(!report.passed());
+    assert_eq!(report.skipped, 1);
+    assert_eq!(
+        report.steps[0].failures,
+        ["bob has 1000 octas, expected 999"]
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_malformed_steps_are_errors() {
+    let dir = workspace("malformed");
+    let path = dir.join("malformed.yaml");
+    fs::write(
+        &path,
+        "accounts: { alice: {} }\nsteps:\n  - call: { sender: mallory, function: \"0x1::aptos_account::transfer\" }\n",
+    )
+    .unwrap();
+    let scenario = Scenario::load(&path).unwrap();
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let err = scenario.run(&mut runner, &dir).unwrap_err();
+    assert!(format!("{:#}", err).contains("mallory is not a scenario account"));
+
+    fs::write(&path, "steps:\n  - teleport: {}\n").unwrap();
+    assert!(Scenario::load(&path).is_err());
+    fs::remove_dir_all(dir).unwrap();
+}
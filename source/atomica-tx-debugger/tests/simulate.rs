@@ -0,0 +1,126 @@
+mod common;
+
+use aptos_crypto::{secp256k1_ecdsa, PrivateKey};
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    on_chain_config::FeatureFlag,
+    transaction::{
+        authenticator::{AnyPublicKey, MultiKey},
+        ExecutionStatus, RawTransaction, TransactionStatus,
+    },
+    vm_status::StatusCode,
+};
+use atomica_tx_debugger::{
+    accounts::AccountKey, genesis::GenesisOptions, offline_runner::OfflineTxnRunner,
+    simulate::into_simulation,
+};
+use common::{funded_sender, transfer_apt, Sender, BALANCE};
+
+fn transfer_from(address: AccountAddress, sequence_number: u64) -> RawTransaction {
+    RawTransaction::new_entry_function(
+        address,
+        sequence_number,
+        transfer_apt(AccountAddress::from_hex_literal("0xfeed").unwrap(), 1_000),
+        common::MAX_GAS,
+        common::GAS_UNIT_PRICE,
+        u64::MAX,
+        ChainId::test(),
+    )
+}
+
+#[test]
+fn test_simulate_unsigned_transfer() {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let sender = funded_sender(&mut runner, 49);
+    let key = sender.key();
+    let transfer = |sequence_number| transfer_from(sender.address, sequence_number);
+
+    // No signature, but the sender's key matches its authentication key.
+    let output = runner
+        .simulate_transaction(into_simulation(transfer(0), Some(&key)).unwrap())
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+    assert_eq!(
+        runner
+            .account(sender.address)
+            .unwrap()
+            .unwrap()
+            .sequence_number(),
+        1
+    );
+
+    // Other prologue checks still apply.
+    let wrong_key = Sender::new(50).key();
+    let output = runner
+        .simulate_transaction(into_simulation(transfer(1), Some(&wrong_key)).unwrap())
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Discard(StatusCode::INVALID_AUTH_KEY)
+    );
+    let output = runner
+        .simulate_transaction(into_simulation(transfer(0), Some(&key)).unwrap())
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Discard(StatusCode::SEQUENCE_NUMBER_TOO_OLD)
+    );
+}
+
+/// Without a claimed key the authentication key check is skipped, but only
+/// on chains with the simulation enhancement enabled.
+#[test]
+fn test_simulate_without_public_key() {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let sender = funded_sender(&mut runner, 51);
+    let output = runner
+        .simulate_transaction(into_simulation(transfer_from(sender.address, 0), None).unwrap())
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+
+    let options =
+        GenesisOptions::new().disable_feature(FeatureFlag::TRANSACTION_SIMULATION_ENHANCEMENT);
+    let mut runner =
+        OfflineTxnRunner::with_genesis(aptos_cached_packages::head_release_bundle(), &options)
+            .unwrap();
+    let sender = funded_sender(&mut runner, 51);
+    let output = runner
+        .simulate_transaction(into_simulation(transfer_from(sender.address, 0), None).unwrap())
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Discard(StatusCode::INVALID_AUTH_KEY)
+    );
+}
+
+/// Placeholder signatures match the scheme of each claimed key.
+#[test]
+fn test_simulate_mixed_multi_key() {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let secp256k1 = secp256k1_ecdsa::PrivateKey::try_from(&[52u8; 32][..]).unwrap();
+    let public_keys = MultiKey::new(
+        vec![
+            AnyPublicKey::secp256k1_ecdsa(secp256k1.public_key()),
+            AnyPublicKey::ed25519(Sender::new(53).public_key),
+        ],
+        2,
+    )
+    .unwrap();
+    let key = AccountKey::MultiKey(public_keys);
+    let sender = runner.seed_account(&key, 0).unwrap();
+    runner.set_apt_balance(sender, BALANCE).unwrap();
+
+    let txn = into_simulation(transfer_from(sender, 0), Some(&key)).unwrap();
+    let output = runner.simulate_transaction(txn).unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    transaction::{ExecutionStatus, TransactionStatus},
+};
+use atomica_tx_debugger::{genesis::GenesisOptions, offline_runner::OfflineTxnRunner};
+use common::{transfer_apt, Sender, BALANCE};
+use std::{fs, path::PathBuf};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("atomica_snapshot_{}_{}", std::process::id(), name))
+}
+
+#[test]
+fn test_snapshot_round_trip_replays_identically() {
+    let chain_id = ChainId::new(41);
+    let options = GenesisOptions::new().chain_id(chain_id);
+    let mut runner =
+        OfflineTxnRunner::with_genesis(aptos_cached_packages::head_release_bundle(), &options)
+            .unwrap();
+
+    let sender = Sender::new(41);
+    runner.seed_account(&sender.key(), 3).unwrap();
+    runner.set_apt_balance(sender.address, BALANCE).unwrap();
+
+    let path = temp_path("round_trip");
+    runner.save_snapshot(&path).unwrap();
+    let mut restored = OfflineTxnRunner::load_snapshot(&path).unwrap();
+    assert_eq!(restored.chain_id(), chain_id);
+    assert_eq!(
+        restored
+            .account(sender.address)
+            .unwrap()
+            .unwrap()
+            .sequence_number(),
+        3
+    );
+
+    // Saving the same state again produces the same bytes.
+    let again = temp_path("round_trip_again");
+    restored.save_snapshot(&again).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), fs::read(&again).unwrap());
+
+    let to = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    let txn = sender.sign(3, transfer_apt(to, 1), chain_id);
+    let original = runner.execute_transaction(txn.clone()).unwrap();
+    let replayed = restored.execute_transaction(txn).unwrap();
+    assert_eq!(
+        replayed.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+    assert_eq!(original.gas_used(), replayed.gas_used());
+    assert_eq!(original.write_set(), replayed.write_set());
+
+    fs::remove_file(path).unwrap();
+    fs::remove_file(again).unwrap();
+}
+
+#[test]
+fn test_load_rejects_foreign_files() {
+    let path = temp_path("foreign");
+    fs::write(&path, b"not a snapshot").unwrap();
+    let err = OfflineTxnRunner::load_snapshot(&path).err().unwrap();
+    assert!(err.to_string().contains("is not a state snapshot"));
+
+    let mut future = b"ATXSNAP\0".to_vec();
+    future.extend(99u32.to_le_bytes());
+    fs::write(&path, future).unwrap();
+    let err = OfflineTxnRunner::load_snapshot(&path).err().unwrap();
+    assert!(err.to_string().contains("format version 99"));
+
+    fs::remove_file(path).unwrap();
+}
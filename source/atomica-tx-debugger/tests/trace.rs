@@ -0,0 +1,146 @@
+mod common;
+
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{ExecutionStatus, SignedTransaction, TransactionStatus},
+};
+use atomica_tx_debugger::{
+    offline_runner::OfflineTxnRunner,
+    trace::{ExecutionTrace, TraceEvent},
+};
+use common::{funded_sender, transfer};
+
+const TRANSFER: &str = "0x1::aptos_account::transfer";
+
+fn runner_with_sender() -> (OfflineTxnRunner, impl Fn(u64, u64) -> SignedTransaction) {
+    let mut runner = OfflineTxnRunner::new(aptos_cached_packages::head_release_bundle());
+    let sender = funded_sender(&mut runner, 44);
+    let to = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    let transfer = move |sequence_number, amount| transfer(&sender, sequence_number, to, amount);
+    (runner, transfer)
+}
+
+fn instructions(trace: &ExecutionTrace, function: &str) -> Vec<(u16, String)> {
+    trace
+        .events
+        .iter()
+        .filter_map(|event| match event {
+            TraceEvent::Instruction {
+                function: f,
+                pc,
+                op,
+                ..
+            } if f == function => Some((*pc, op.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_trace_of_successful_transfer() {
+    let (mut runner, transfer) = runner_with_sender();
+    let (output, trace) = runner
+        .execute_transaction_traced(transfer(0, 1_000))
+        .unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+
+    assert!(matches!(
+        trace.events.first(),
+        Some(TraceEvent::Enter { depth: 0, function, .. }) if function == TRANSFER
+    ));
+    let Some(TraceEvent::Exit {
+        depth: 0,
+        function,
+        gas,
+    }) = trace.events.last()
+    else {
+        panic!("trace should end by returning from the entry function");
+    };
+    assert_eq!(function, TRANSFER);
+    assert!(*gas > 0);
+
+    // The entry function starts at pc 0 and ends with a return.
+    let body = instructions(&trace, TRANSFER);
+    assert_eq!(body.first().unwrap().0, 0);
+    assert_eq!(body.last().unwrap().1, "Ret");
+    assert!(trace.abort().is_none());
+
+    // Nested calls are balanced and natives are reported on their own.
+    let enters = trace
+        .events
+        .iter()
+        .filter(|e| matches!(e, TraceEvent::Enter { .. }))
+        .count();
+    let exits = trace
+        .events
+        .iter()
+        .filter(|e| matches!(e, TraceEvent::Exit { .. }))
+        .count();
+    assert_eq!(enters, exits);
+    assert!(enters > 1);
+    assert!(trace
+        .events
+        .iter()
+        .any(|e| matches!(e, TraceEvent::Native { .. })));
+    assert!(trace
+        .events
+        .iter()
+        .any(|e| matches!(e, TraceEvent::LoadResource { .. })));
+
+    // Traced execution commits like the untraced path.
+    let output = runner.execute_transaction(transfer(1, 1_000)).unwrap();
+    assert_eq!(
+        output.status(),
+        &TransactionStatus::Keep(ExecutionStatus::Success)
+    );
+}
+
+#[test]
+fn test_trace_reports_stack_and_locals_on_abort() {
+    let (mut runner, transfer) = runner_with_sender();
+    let (output, trace) = runner
+        .execute_transaction_traced(transfer(0, 20_000_000))
+        .unwrap();
+    assert!(matches!(
+        output.status(),
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort { .. })
+    ));
+
+    let stack = trace
+        .abort()
+        .expect("an aborted transaction leaves frames open");
+    assert!(stack.len() > 1);
+    assert_eq!(stack.last().unwrap().function, TRANSFER);
+    assert_ne!(stack.first().unwrap().function, TRANSFER);
+    assert!(stack.iter().all(|frame| frame.pc.is_some()));
+    // The amount was read from a local somewhere on the way down.
+    assert!(stack
+        .iter()
+        .flat_map(|frame| frame.locals.values())
+        .any(|value| value == "20000000"));
+}
+
+#[test]
+fn test_trace_as_json_lines() {
+    let (mut runner, transfer) = runner_with_sender();
+    let (_, trace) = runner
+        .execute_transaction_traced(transfer(0, 1_000))
+        .unwrap();
+    let mut out = vec![];
+    trace.write_jsonl(&mut out).unwrap();
+
+    let lines: Vec<serde_json::Value> = String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), trace.events.len());
+    assert_eq!(lines[0]["event"], "enter");
+    assert_eq!(lines[0]["function"], TRANSFER);
+    assert!(lines
+        .iter()
+        .any(|line| line["event"] == "instruction" && line["pc"].is_u64() && line["gas"].is_u64()));
+}
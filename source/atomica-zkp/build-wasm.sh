@@ -0,0 +1,28 @@
+#!/bin/bash
+set -e
+
+# Builds the browser verifier (src/wasm.rs) into pkg/ as an ES module.
+#
+# The crate is an rlib everywhere else, so the cdylib is requested for this
+# build only. Needs the wasm32-unknown-unknown target and a wasm-bindgen CLI
+# matching the wasm-bindgen crate version:
+#
+#   rustup target add wasm32-unknown-unknown
+#   cargo install wasm-bindgen-cli
+
+SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+cd "$SCRIPT_DIR"
+
+TARGET=wasm32-unknown-unknown
+OUT_DIR="${OUT_DIR:-$SCRIPT_DIR/pkg}"
+
+echo "Building diem-prover-halo2 for $TARGET..."
+cargo rustc --lib --release --target "$TARGET" \
+  --no-default-features --features wasm \
+  --crate-type cdylib
+
+TARGET_DIR="$(cargo metadata --format-version 1 --no-deps | sed -n 's/.*"target_directory":"\([^"]*\)".*/\1/p')"
+WASM="$TARGET_DIR/$TARGET/release/diem_prover_halo2.wasm"
+
+echo "Generating JavaScript bindings in $OUT_DIR..."
+wasm-bindgen --target web --out-dir "$OUT_DIR" "$WASM"
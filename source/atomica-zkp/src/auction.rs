@@ -0,0 +1,312 @@
+//! Sealed-bid auction clearing proof.
+//!
+//! Replaces the on-chain loop in `auction::reveal_bids`: given the committed
+//! list of encrypted bids and the round signature, [`AuctionClearingCircuit`]
+//! decrypts every bid and proves the winner, the winning price and which bids
+//! were valid. Settlement then verifies one proof instead of running `n`
+//! pairings on chain.
+//!
+//! The clearing rule is exactly the Move one: walking bids in submission order,
+//! a bid becomes the new best if its amount is strictly greater than the
+//! current best and its locked payment covers it. Ties therefore go to the
+//! earliest bid, and an auction with no funded non-zero bid has no winner.
+//!
+//! Public instances, in order:
+//!
+//! 1. `keccak256(record_0 || ... || record_{n-1})` as two 16-byte words, where
+//!    a record is `bidder (32) || U (96, canonical x || y) || payment (8, LE)
+//!    || ciphertext (8)`,
+//! 2. the round signature `Sig` (12 words, as in [`crate::ibe`]),
+//! 3. `has_winner` (0 or 1),
+//! 4. the winning bid's index (0 when there is no winner),
+//! 5. the winner's address (2 words, zero when there is no winner),
+//! 6. the winning price,
+//! 7. a bitmask with bit `i` set when bid `i` was funded.
+
+use ark_bls12_381::{G1Affine, G2Affine};
+use halo2_proofs_axiom::halo2curves::bn256::Fr;
+use sha3::{Digest, Keccak256};
+
+use crate::circuit::{BuilderCircuit, CircuitParams};
+use crate::gadgets::{
+    amount::{AmountChip, LimbBits},
+    bytes::{load_bytes, pack_words, pack_words_native},
+    keccak::KeccakChip,
+};
+use crate::halo2_base::{
+    gates::{
+        circuit::builder::BaseCircuitBuilder, GateChip, GateInstructions, RangeChip,
+        RangeInstructions,
+    },
+    AssignedValue, Context,
+    QuantumCell::{Constant, Existing},
+};
+use crate::halo2_ecc::bls12_381::FpChip;
+use crate::ibe::{
+    chip::{ark_g1_bytes, ark_g2_bytes, IbeChip},
+    native, LIMB_BITS, NUM_LIMBS,
+};
+
+/// A sealed bid is an encrypted little-endian `u64`.
+pub const BID_BYTES: usize = 8;
+
+/// Bytes per committed bid record.
+pub const RECORD_BYTES: usize = 32 + 96 + 8 + BID_BYTES;
+
+/// Largest auction whose validity bitmask fits in one instance word.
+pub const MAX_BIDS: usize = 248;
+
+/// Default sizing for a handful of bids; each bid costs one pairing.
+pub const AUCTION_CIRCUIT_PARAMS: CircuitParams = CircuitParams {
+    k: 22,
+    lookup_bits: 21,
+    minimum_rows: 20,
+};
+
+/// One entry of the auction's on-chain bid list.
+#[derive(Clone, Debug)]
+pub struct SealedBid {
+    pub bidder: [u8; 32],
+    pub u: G1Affine,
+    /// FAKEUSD locked alongside the bid.
+    pub payment: u64,
+    pub ciphertext: [u8; BID_BYTES],
+}
+
+impl SealedBid {
+    /// The record hashed into the bid-list commitment.
+    pub fn record(&self) -> Vec<u8> {
+        let mut record = Vec::with_capacity(RECORD_BYTES);
+        record.extend_from_slice(&self.bidder);
+        record.extend(ark_g1_bytes(&self.u));
+        record.extend_from_slice(&self.payment.to_le_bytes());
+        record.extend_from_slice(&self.ciphertext);
+        record
+    }
+}
+
+/// Result of clearing an auction, as settlement would apply it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClearingOutcome {
+    pub winner: Option<usize>,
+    pub price: u64,
+    /// `valid[i]` is true when bid `i`'s payment covers its amount.
+    pub valid: Vec<bool>,
+}
+
+/// Native clearing rule, mirroring `auction::reveal_bids`.
+pub fn clear_native(amounts: &[u64], payments: &[u64]) -> ClearingOutcome {
+    assert_eq!(amounts.len(), payments.len());
+    let mut winner = None;
+    let mut price = 0;
+    let mut valid = Vec::with_capacity(amounts.len());
+    for (i, (&amount, &payment)) in amounts.iter().zip(payments).enumerate() {
+        let funded = payment >= amount;
+        if funded && amount > price {
+            winner = Some(i);
+            price = amount;
+        }
+        valid.push(funded);
+    }
+    ClearingOutcome { winner, price, valid }
+}
+
+/// Assigned result of [`clear_bids`].
+#[derive(Clone, Debug)]
+pub struct AssignedClearing {
+    pub has_winner: AssignedValue<Fr>,
+    pub winner_index: AssignedValue<Fr>,
+    pub price: AssignedValue<Fr>,
+    /// One-hot selector of the winning bid, all zero without a winner.
+    pub winner_flags: Vec<AssignedValue<Fr>>,
+    pub valid: Vec<AssignedValue<Fr>>,
+}
+
+impl AssignedClearing {
+    /// Packs the validity flags into a single little-endian bitmask.
+    pub fn valid_mask(&self, ctx: &mut Context<Fr>, gate: &GateChip<Fr>) -> AssignedValue<Fr> {
+        if self.valid.is_empty() {
+            return ctx.load_zero();
+        }
+        let pow2 = gate.pow_of_two()[..self.valid.len()].iter().map(|c| Constant(*c));
+        gate.inner_product(ctx, self.valid.iter().copied(), pow2)
+    }
+
+    /// Picks the winner's entry out of `values`, or zero without a winner.
+    pub fn select_winner(
+        &self,
+        ctx: &mut Context<Fr>,
+        gate: &GateChip<Fr>,
+        values: &[AssignedValue<Fr>],
+    ) -> AssignedValue<Fr> {
+        if values.is_empty() {
+            return ctx.load_zero();
+        }
+        let flags = self.winner_flags.iter().map(|flag| Existing(*flag));
+        gate.inner_product(ctx, values.iter().copied(), flags)
+    }
+}
+
+/// Applies the clearing rule to decrypted `amounts` and locked `payments`.
+///
+/// Both must already be constrained to 64 bits.
+pub fn clear_bids(
+    ctx: &mut Context<Fr>,
+    amount_chip: &AmountChip<Fr>,
+    amounts: &[AssignedValue<Fr>],
+    payments: &[AssignedValue<Fr>],
+) -> AssignedClearing {
+    assert_eq!(amounts.len(), payments.len());
+    assert!(amounts.len() <= MAX_BIDS, "at most {MAX_BIDS} bids per auction");
+    let gate = amount_chip.range().gate();
+
+    let mut price = ctx.load_zero();
+    let mut winner_index = ctx.load_zero();
+    let mut has_winner = ctx.load_zero();
+    let mut valid = Vec::with_capacity(amounts.len());
+    for (i, (amount, payment)) in amounts.iter().zip(payments).enumerate() {
+        let underfunded = amount_chip.is_less_than(ctx, *payment, *amount, 64);
+        let funded = gate.not(ctx, underfunded);
+        let outbids = amount_chip.is_less_than(ctx, price, *amount, 64);
+        let take = gate.and(ctx, funded, outbids);
+
+        price = gate.select(ctx, *amount, price, take);
+        winner_index = gate.select(ctx, Constant(Fr::from(i as u64)), winner_index, take);
+        has_winner = gate.or(ctx, has_winner, take);
+        valid.push(funded);
+    }
+
+    let winner_flags = (0..amounts.len())
+        .map(|i| {
+            let at_index = gate.is_equal(ctx, winner_index, Constant(Fr::from(i as u64)));
+            gate.and(ctx, at_index, has_winner)
+        })
+        .collect();
+
+    AssignedClearing {
+        has_winner,
+        winner_index,
+        price,
+        winner_flags,
+        valid,
+    }
+}
+
+/// Proves the outcome of a sealed-bid auction from its encrypted bid list.
+#[derive(Clone, Debug)]
+pub struct AuctionClearingCircuit {
+    pub bids: Vec<SealedBid>,
+    pub signature: G2Affine,
+}
+
+impl AuctionClearingCircuit {
+    pub fn new(bids: Vec<SealedBid>, signature: G2Affine) -> Self {
+        assert!(bids.len() <= MAX_BIDS, "at most {MAX_BIDS} bids per auction");
+        assert!(
+            bids.iter().all(|bid| !bid.u.infinity) && !signature.infinity,
+            "points at infinity are not valid IBE inputs"
+        );
+        Self { bids, signature }
+    }
+
+    /// Decrypted bid amounts.
+    pub fn amounts(&self) -> Vec<u64> {
+        self.bids
+            .iter()
+            .map(|bid| {
+                let plaintext = native::decrypt(&bid.u, &self.signature, &bid.ciphertext);
+                u64::from_le_bytes(plaintext.try_into().expect("bid plaintext is 8 bytes"))
+            })
+            .collect()
+    }
+
+    pub fn outcome(&self) -> ClearingOutcome {
+        let payments: Vec<_> = self.bids.iter().map(|bid| bid.payment).collect();
+        clear_native(&self.amounts(), &payments)
+    }
+
+    /// Keccak-256 of the concatenated bid records.
+    pub fn commitment(&self) -> [u8; 32] {
+        let records: Vec<u8> = self.bids.iter().flat_map(SealedBid::record).collect();
+        Keccak256::digest(records).into()
+    }
+
+    /// Expected public instances in the layout described in the module docs.
+    pub fn instances(&self) -> Vec<Fr> {
+        let outcome = self.outcome();
+        let winner_address = outcome
+            .winner
+            .map(|i| self.bids[i].bidder)
+            .unwrap_or_default();
+        let valid_mask = outcome
+            .valid
+            .iter()
+            .rev()
+            .fold(Fr::zero(), |acc, valid| acc + acc + Fr::from(*valid as u64));
+
+        let mut instances = pack_words_native(&self.commitment());
+        instances.extend(pack_words_native::<Fr>(&ark_g2_bytes(&self.signature)));
+        instances.push(Fr::from(outcome.winner.is_some() as u64));
+        instances.push(Fr::from(outcome.winner.unwrap_or(0) as u64));
+        instances.extend(pack_words_native::<Fr>(&winner_address));
+        instances.push(Fr::from(outcome.price));
+        instances.push(valid_mask);
+        instances
+    }
+}
+
+impl BuilderCircuit for AuctionClearingCircuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let gate = range.gate();
+        let fp_chip = FpChip::new(range, LIMB_BITS, NUM_LIMBS);
+        let ibe = IbeChip::new(&fp_chip);
+
+        let signature = ibe.load_g2(ctx, &self.signature);
+
+        let mut records = Vec::with_capacity(self.bids.len() * RECORD_BYTES);
+        let mut bidders = Vec::with_capacity(self.bids.len());
+        let mut amounts = Vec::with_capacity(self.bids.len());
+        let mut payments = Vec::with_capacity(self.bids.len());
+        for bid in &self.bids {
+            let bidder = load_bytes(ctx, range, &bid.bidder);
+            let u = ibe.load_g1(ctx, &bid.u);
+            let payment_bytes = load_bytes(ctx, range, &bid.payment.to_le_bytes());
+            let ciphertext = load_bytes(ctx, range, &bid.ciphertext);
+
+            records.extend(bidder.iter().copied());
+            records.extend(ibe.g1_to_bytes(ctx, &u));
+            records.extend(payment_bytes.iter().copied());
+            records.extend(ciphertext.iter().copied());
+
+            let plaintext = ibe.decrypt(ctx, &u, &signature, &ciphertext);
+            // Eight little-endian bytes fit in a single word.
+            amounts.push(pack_words(ctx, gate, &plaintext)[0]);
+            payments.push(pack_words(ctx, gate, &payment_bytes)[0]);
+            bidders.push(pack_words(ctx, gate, &bidder));
+        }
+
+        let commitment = KeccakChip::new(gate).keccak256(ctx, &records);
+        let amount_chip = AmountChip::new(range, LimbBits::Sixteen);
+        let clearing = clear_bids(ctx, &amount_chip, &amounts, &payments);
+        let winner_address: Vec<_> = (0..2)
+            .map(|w| {
+                let words: Vec<_> = bidders.iter().map(|words| words[w]).collect();
+                clearing.select_winner(ctx, gate, &words)
+            })
+            .collect();
+
+        let mut instances = pack_words(ctx, gate, &commitment);
+        instances.extend(ibe.g2_words(ctx, &signature));
+        instances.push(clearing.has_winner);
+        instances.push(clearing.winner_index);
+        instances.extend(winner_address);
+        instances.push(clearing.price);
+        instances.push(clearing.valid_mask(ctx, gate));
+        instances
+    }
+}
@@ -0,0 +1,150 @@
+//! Works with any circuit in the registry by id.
+//!
+//! Usage:
+//!
+//! ```text
+//! circuits list
+//! circuits check <id> <witness.json>
+//! circuits keys <id> <params> <key-dir>
+//! circuits solidity <id> <params> <key-dir> <output.sol>
+//! circuits gas <id> <params> <key-dir> <witness.json>
+//! ```
+//!
+//! Keys are generated from the registered example on first use and cached in
+//! `<key-dir>` (see [`KeyCache`]). `gas` needs the `revm` feature and `solc`.
+
+use std::{env, fs, io, process};
+
+use diem_prover_halo2::{
+    halo2_base::gates::circuit::CircuitBuilderStage,
+    registry::{
+        keys::{CircuitKeys, KeyCache},
+        CircuitInfo, CircuitRegistry,
+    },
+    verifier::read_params,
+};
+use halo2_proofs_axiom::{
+    dev::MockProver,
+    halo2curves::bn256::Bn256,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use snark_verifier_sdk::CircuitExt;
+
+const USAGE: &str = "usage: circuits list
+       circuits check <id> <witness.json>
+       circuits keys <id> <params> <key-dir>
+       circuits solidity <id> <params> <key-dir> <output.sol>
+       circuits gas <id> <params> <key-dir> <witness.json>";
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(2);
+}
+
+fn lookup<'a>(registry: &'a CircuitRegistry, id: &str) -> &'a CircuitInfo {
+    registry.get(id).unwrap_or_else(|| {
+        eprintln!("unknown circuit {id}; see `circuits list`");
+        process::exit(2);
+    })
+}
+
+fn list(registry: &CircuitRegistry) {
+    for info in registry.iter() {
+        println!("{}  (k = {})  {}", info.id, info.params.k, info.description);
+        for field in info.instances {
+            match field.words {
+                Some(words) => println!("    {:<24} {words} words", field.name),
+                None => println!("    {:<24} variable", field.name),
+            }
+        }
+    }
+}
+
+/// Runs `MockProver` on a witness and prints the public instances.
+fn check(info: &CircuitInfo, witness_path: &str) -> io::Result<()> {
+    let circuit = (info.load_witness)(&fs::read_to_string(witness_path)?)?;
+    let builder = circuit.build(CircuitBuilderStage::Mock, info.params);
+    let instances = builder.instances();
+    let prover = MockProver::run(info.params.k, &builder, instances.clone())
+        .map_err(|err| io::Error::other(format!("{err:?}")))?;
+    if let Err(failures) = prover.verify() {
+        eprintln!(
+            "{} constraint failures, first: {:?}",
+            failures.len(),
+            failures[0]
+        );
+        process::exit(1);
+    }
+    for (i, instance) in instances[0].iter().enumerate() {
+        println!("{i:>4}  {instance:?}");
+    }
+    Ok(())
+}
+
+/// Reads `params_path`, downsized to the circuit's `k`, and its cached keys.
+fn keys(
+    info: &CircuitInfo,
+    params_path: &str,
+    key_dir: &str,
+) -> io::Result<(ParamsKZG<Bn256>, CircuitKeys)> {
+    let mut params = read_params(&fs::read(params_path)?)?;
+    if params.k() < info.params.k {
+        eprintln!(
+            "{} needs 2^{} params, got 2^{}",
+            info.id,
+            info.params.k,
+            params.k()
+        );
+        process::exit(1);
+    }
+    params.downsize(info.params.k);
+    let keys = KeyCache::new(key_dir).keys(info, &params)?;
+    Ok((params, keys))
+}
+
+/// Exports a Solidity verifier for the cached keys.
+fn solidity(info: &CircuitInfo, params_path: &str, key_dir: &str, output: &str) -> io::Result<()> {
+    let (params, keys) = keys(info, params_path, key_dir)?;
+    fs::write(output, keys.solidity_verifier(&params))?;
+    println!("Wrote {output}");
+    Ok(())
+}
+
+/// Proves a witness and reports what the Solidity verifier spends on it.
+#[cfg(feature = "revm")]
+fn gas(info: &CircuitInfo, params_path: &str, key_dir: &str, witness_path: &str) -> io::Result<()> {
+    let circuit = (info.load_witness)(&fs::read_to_string(witness_path)?)?;
+    let (params, keys) = keys(info, params_path, key_dir)?;
+    let (instances, proof) = keys.prove(&params, circuit.as_ref());
+    let gas = keys.verification_gas(&params, &instances, &proof)?;
+    println!("{}  {} proof bytes  {gas} gas", info.id, proof.len());
+    Ok(())
+}
+
+#[cfg(not(feature = "revm"))]
+fn gas(_: &CircuitInfo, _: &str, _: &str, _: &str) -> io::Result<()> {
+    eprintln!("gas reporting needs the `revm` feature");
+    process::exit(2);
+}
+
+fn main() -> io::Result<()> {
+    let registry = CircuitRegistry::builtin();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["list"] => list(&registry),
+        ["check", id, witness] => check(lookup(&registry, id), witness)?,
+        ["keys", id, params, key_dir] => {
+            keys(lookup(&registry, id), params, key_dir)?;
+            println!("Keys for {id} are in {key_dir}");
+        }
+        ["solidity", id, params, key_dir, output] => {
+            solidity(lookup(&registry, id), params, key_dir, output)?
+        }
+        ["gas", id, params, key_dir, witness] => {
+            gas(lookup(&registry, id), params, key_dir, witness)?
+        }
+        _ => usage(),
+    }
+    Ok(())
+}
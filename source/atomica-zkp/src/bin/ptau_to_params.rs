@@ -0,0 +1,40 @@
+//! Converts a snarkjs `.ptau` file into halo2 `ParamsKZG<Bn256>` raw bytes.
+//!
+//! Usage: `ptau_to_params <input.ptau> <k> <output>`
+
+use std::{
+    env,
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    process,
+};
+
+use diem_prover_halo2::ptau::PowersOfTau;
+use rand::rngs::OsRng;
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+    let [_, input, k, output] = args.as_slice() else {
+        eprintln!("usage: ptau_to_params <input.ptau> <k> <output>");
+        process::exit(2);
+    };
+    let k: u32 = k.parse().unwrap_or_else(|_| {
+        eprintln!("k must be a positive integer, got {k}");
+        process::exit(2);
+    });
+
+    let mut reader = BufReader::new(File::open(input)?);
+    let powers = PowersOfTau::read(&mut reader, k)?;
+    println!(
+        "Read 2^{k} powers from a 2^{} ceremony (originally 2^{})",
+        powers.power, powers.ceremony_power
+    );
+    powers.verify(OsRng)?;
+    println!("Pairing checks passed");
+
+    let mut writer = BufWriter::new(File::create(output)?);
+    powers.write_params(&mut writer)?;
+    writer.flush()?;
+    println!("Wrote {output}");
+    Ok(())
+}
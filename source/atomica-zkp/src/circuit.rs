@@ -0,0 +1,61 @@
+use crate::halo2_base::{
+    gates::{
+        circuit::{builder::BaseCircuitBuilder, BaseCircuitParams, CircuitBuilderStage},
+        flex_gate::MultiPhaseThreadBreakPoints,
+        RangeChip,
+    },
+    AssignedValue,
+};
+use halo2_proofs_axiom::halo2curves::bn256::Fr;
+
+/// Sizing for a circuit built on halo2-base's `BaseCircuitBuilder`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitParams {
+    /// The circuit has 2^k rows.
+    pub k: u32,
+    /// Bit width of the range chip's lookup table.
+    pub lookup_bits: usize,
+    /// Rows reserved at the bottom of every column for blinding.
+    pub minimum_rows: usize,
+}
+
+/// A circuit whose constraints are written against a `BaseCircuitBuilder`.
+///
+/// The builder itself implements `Circuit` and `CircuitExt`, so the returned
+/// value can be handed straight to `MockProver`, `keygen_vk` or the
+/// snark-verifier-sdk proving helpers.
+pub trait BuilderCircuit {
+    /// Assigns the witness and returns the cells exposed as public instances.
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>>;
+
+    /// Builds the circuit for `MockProver` or key generation, sizing the
+    /// advice columns from the assigned witness.
+    fn build(&self, stage: CircuitBuilderStage, params: CircuitParams) -> BaseCircuitBuilder<Fr> {
+        let mut builder = BaseCircuitBuilder::from_stage(stage)
+            .use_k(params.k as usize)
+            .use_lookup_bits(params.lookup_bits)
+            .use_instance_columns(1);
+        let range = builder.range_chip();
+        let instances = self.synthesize(&mut builder, &range);
+        builder.assigned_instances[0].extend(instances);
+        builder.calculate_params(Some(params.minimum_rows));
+        builder
+    }
+
+    /// Rebuilds the circuit for proving with the layout fixed at key generation.
+    fn build_prover(
+        &self,
+        config: BaseCircuitParams,
+        break_points: MultiPhaseThreadBreakPoints,
+    ) -> BaseCircuitBuilder<Fr> {
+        let mut builder = BaseCircuitBuilder::prover(config, break_points);
+        let range = builder.range_chip();
+        let instances = self.synthesize(&mut builder, &range);
+        builder.assigned_instances[0].extend(instances);
+        builder
+    }
+}
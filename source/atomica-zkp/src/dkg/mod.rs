@@ -0,0 +1,160 @@
+//! Proof that a DKG group public key aggregates the dealers' contributions.
+//!
+//! Timelock encryption targets the validators' G1 group key. Aptos records a
+//! finished DKG session in `0x1::dkg::DKGState.last_completed`, whose
+//! `transcript` is the BCS-encoded aggregate of the dealers' PVSS
+//! transcripts and whose `metadata.dealer_epoch` is the epoch.
+//! [`DkgAggregationCircuit`] proves that a key is `sum_i c_i` over the
+//! contributions `c_i = a_i0 G` in that transcript, so an EVM-side auction
+//! can accept the key without trusting whoever relayed it.
+//!
+//! The transcript opens with its `soks` section: a one-byte dealer count and
+//! one fixed-size entry per dealer (see [`native::SOK_BYTES`]). The circuit
+//! hashes exactly those bytes and reads each `c_i` from its entry, checking
+//! the count and every signature length prefix so the fixed offsets match
+//! the BCS layout. A contract that holds the on-chain transcript therefore
+//! recomputes the digest as `keccak256(transcript[..1 + n * SOK_BYTES])`.
+//!
+//! The PVSS transcripts themselves are not verified here; validators do that
+//! before the session completes. Proofs are only as good as the transcript
+//! the verifier hashed, which must come from `last_completed` for `epoch`.
+//! Public instances are little-endian 16-byte words (see
+//! [`crate::gadgets::bytes::pack_words`]), in order:
+//!
+//! 1. the epoch as 8 little-endian bytes (1 word),
+//! 2. the `soks` digest (2 words),
+//! 3. the group key as canonical `x || y` (6 words).
+
+pub mod native;
+
+use std::io;
+
+use ark_bls12_381::G1Affine;
+use halo2_proofs_axiom::halo2curves::{bls12_381, bn256::Fr};
+
+use crate::circuit::{BuilderCircuit, CircuitParams};
+use crate::gadgets::{
+    bytes::{load_bytes, pack_words, pack_words_native},
+    keccak::KeccakChip,
+};
+use crate::halo2_base::{
+    gates::{circuit::builder::BaseCircuitBuilder, RangeChip, RangeInstructions},
+    AssignedValue,
+};
+use crate::halo2_ecc::{bls12_381::FpChip, ecc::EccChip};
+use crate::ibe::{
+    chip::{ark_g1_bytes, IbeChip},
+    LIMB_BITS, NUM_LIMBS,
+};
+
+/// Default sizing: a handful of G1 additions plus Keccak over a few dealers'
+/// entries.
+pub const DKG_CIRCUIT_PARAMS: CircuitParams = CircuitParams {
+    k: 19,
+    lookup_bits: 18,
+    minimum_rows: 20,
+};
+
+/// Proves that the contributions in a transcript's `soks` sum to the group
+/// key for `epoch`.
+#[derive(Clone, Debug)]
+pub struct DkgAggregationCircuit {
+    pub epoch: u64,
+    soks: Vec<u8>,
+    contributions: Vec<G1Affine>,
+}
+
+impl DkgAggregationCircuit {
+    /// Panics if `soks` is not a well-formed section; see
+    /// [`native::soks_section`].
+    pub fn new(epoch: u64, soks: Vec<u8>) -> Self {
+        let section = native::soks_section(&soks).expect("malformed soks section");
+        assert_eq!(section.len(), soks.len(), "trailing bytes after soks");
+        let contributions = native::contributions(&soks).expect("malformed contribution");
+        Self {
+            epoch,
+            soks,
+            contributions,
+        }
+    }
+
+    /// Builds the circuit from a whole on-chain `DKGSessionState.transcript`.
+    pub fn from_transcript(epoch: u64, transcript: &[u8]) -> io::Result<Self> {
+        let soks = native::soks_section(transcript)?;
+        native::contributions(soks)?;
+        Ok(Self::new(epoch, soks.to_vec()))
+    }
+
+    /// The `soks` section of the session's on-chain transcript.
+    pub fn soks(&self) -> &[u8] {
+        &self.soks
+    }
+
+    /// Each dealer's contribution, in transcript order.
+    pub fn contributions(&self) -> &[G1Affine] {
+        &self.contributions
+    }
+
+    /// The group key the circuit will expose, computed natively.
+    pub fn group_key(&self) -> G1Affine {
+        native::aggregate(&self.contributions)
+    }
+
+    pub fn soks_digest(&self) -> [u8; 32] {
+        native::soks_digest(&self.soks)
+    }
+
+    /// Expected public instances in the layout described in the module docs.
+    pub fn instances(&self) -> Vec<Fr> {
+        let mut instances = pack_words_native(&self.epoch.to_le_bytes());
+        instances.extend(pack_words_native::<Fr>(&self.soks_digest()));
+        instances.extend(pack_words_native::<Fr>(&ark_g1_bytes(&self.group_key())));
+        instances
+    }
+}
+
+impl BuilderCircuit for DkgAggregationCircuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let gate = range.gate();
+        let fp_chip = FpChip::new(range, LIMB_BITS, NUM_LIMBS);
+        let chip = IbeChip::new(&fp_chip);
+
+        let epoch = load_bytes(ctx, range, &self.epoch.to_le_bytes());
+        let soks = load_bytes(ctx, range, &self.soks);
+        // Pin the layout the fixed offsets below assume.
+        let dealers = self.contributions.len();
+        gate.assert_is_const(ctx, &soks[0], &Fr::from(dealers as u64));
+        let contributions: Vec<_> = self
+            .contributions
+            .iter()
+            .enumerate()
+            .map(|(i, contribution)| {
+                let entry = &soks[1 + i * native::SOK_BYTES..][..native::SOK_BYTES];
+                gate.assert_is_const(
+                    ctx,
+                    &entry[native::SIGNATURE_LENGTH_OFFSET],
+                    &Fr::from(native::SIGNATURE_LENGTH as u64),
+                );
+                let point = chip.load_g1(ctx, contribution);
+                let encoded = chip.g1_to_compressed_bytes(ctx, &point);
+                for (byte, expected) in entry[native::CONTRIBUTION_OFFSET..].iter().zip(&encoded) {
+                    ctx.constrain_equal(byte, expected);
+                }
+                point
+            })
+            .collect();
+
+        let digest = KeccakChip::new(gate).keccak256(ctx, &soks);
+        let group_key = EccChip::new(&fp_chip).sum::<bls12_381::G1Affine>(ctx, contributions);
+
+        let mut instances = pack_words(ctx, gate, &epoch);
+        instances.extend(pack_words(ctx, gate, &digest));
+        instances.extend(chip.g1_words(ctx, &group_key));
+        instances
+    }
+}
@@ -0,0 +1,111 @@
+//! Out-of-circuit reference for the DKG group key relation.
+
+use std::io;
+
+use ark_bls12_381::{G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use sha3::{Digest, Keccak256};
+
+use crate::envelope::invalid_data;
+
+/// Bytes of a compressed G1 point.
+pub const G1_COMPRESSED_BYTES: usize = 48;
+/// BCS length prefix of a `bls12381::Signature`.
+pub const SIGNATURE_LENGTH: u8 = 96;
+/// Bytes of one `soks` entry: `(Player, G1, bls12381::Signature, (G1, Scalar))`
+/// with a `u64` player id, a length-prefixed signature and a Schnorr proof.
+pub const SOK_BYTES: usize =
+    8 + G1_COMPRESSED_BYTES + 1 + SIGNATURE_LENGTH as usize + G1_COMPRESSED_BYTES + 32;
+/// Offset of the dealer's contribution `c_i` within its entry.
+pub const CONTRIBUTION_OFFSET: usize = 8;
+/// Offset of the signature's length prefix within an entry.
+pub const SIGNATURE_LENGTH_OFFSET: usize = CONTRIBUTION_OFFSET + G1_COMPRESSED_BYTES;
+/// Most dealers whose count BCS encodes as a single ULEB128 byte.
+pub const MAX_DEALERS: usize = 0x7f;
+
+/// `sum_i c_i`, the group key the dealers' contributions aggregate to.
+pub fn aggregate(contributions: &[G1Affine]) -> G1Affine {
+    contributions
+        .iter()
+        .fold(G1Projective::default(), |acc, contribution| {
+            acc + contribution
+        })
+        .into_affine()
+}
+
+/// The `soks` section of a BCS-encoded `Transcripts`, as stored in
+/// `DKGSessionState.transcript`: the dealer count followed by one
+/// [`SOK_BYTES`] entry per dealer, at the very start of the transcript.
+pub fn soks_section(transcript: &[u8]) -> io::Result<&[u8]> {
+    let dealers = *transcript
+        .first()
+        .ok_or_else(|| invalid_data("empty transcript"))? as usize;
+    if dealers == 0 || dealers > MAX_DEALERS {
+        return Err(invalid_data(format!(
+            "transcript has {dealers} dealers, expected 1..={MAX_DEALERS}"
+        )));
+    }
+    let soks = transcript
+        .get(..1 + dealers * SOK_BYTES)
+        .ok_or_else(|| invalid_data(format!("transcript too short for {dealers} dealers")))?;
+    for entry in soks[1..].chunks(SOK_BYTES) {
+        if entry[SIGNATURE_LENGTH_OFFSET] != SIGNATURE_LENGTH {
+            return Err(invalid_data("unexpected signature length in transcript"));
+        }
+    }
+    Ok(soks)
+}
+
+/// The contributions `c_i` recorded in a `soks` section, in transcript order.
+pub fn contributions(soks: &[u8]) -> io::Result<Vec<G1Affine>> {
+    soks.get(1..)
+        .unwrap_or_default()
+        .chunks(SOK_BYTES)
+        .map(|entry| {
+            let bytes = &entry[CONTRIBUTION_OFFSET..SIGNATURE_LENGTH_OFFSET];
+            let point = G1Affine::deserialize_compressed(bytes)
+                .map_err(|err| invalid_data(format!("bad contribution: {err}")))?;
+            if point.is_zero() {
+                return Err(invalid_data("contribution at infinity"));
+            }
+            Ok(point)
+        })
+        .collect()
+}
+
+/// `Keccak256(soks)`, which a contract recomputes over the prefix of the
+/// on-chain transcript.
+pub fn soks_digest(soks: &[u8]) -> [u8; 32] {
+    Keccak256::digest(soks).into()
+}
+
+/// A `soks` section carrying `contributions`, with zeroed player ids,
+/// signatures and proofs of knowledge. The circuit only reads `c_i`, so this
+/// stands in for a real transcript in examples and tests.
+pub fn encode_soks(contributions: &[G1Affine]) -> Vec<u8> {
+    assert!(
+        (1..=MAX_DEALERS).contains(&contributions.len()),
+        "unsupported dealer count"
+    );
+    let mut soks = vec![contributions.len() as u8];
+    for contribution in contributions {
+        let mut entry = vec![0; SOK_BYTES];
+        contribution
+            .serialize_compressed(&mut entry[CONTRIBUTION_OFFSET..SIGNATURE_LENGTH_OFFSET])
+            .expect("a G1 point fits in 48 bytes");
+        entry[SIGNATURE_LENGTH_OFFSET] = SIGNATURE_LENGTH;
+        soks.extend(entry);
+    }
+    soks
+}
+
+/// Parses a compressed (48-byte), `0x`-prefixed G1 group key as returned by
+/// the REST API.
+pub fn parse_group_key(value: &str) -> io::Result<G1Affine> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    let bytes =
+        hex::decode(digits).map_err(|err| invalid_data(format!("bad hex {value}: {err}")))?;
+    G1Affine::deserialize_compressed(bytes.as_slice())
+        .map_err(|err| invalid_data(format!("bad G1 point {value}: {err}")))
+}
@@ -0,0 +1,344 @@
+use ff::Field;
+use halo2_proofs_axiom::halo2curves::bn256::Fr;
+use num_bigint::BigUint;
+
+use super::field::{Fq, Scalar};
+use super::native::{d, EdwardsAffine};
+use crate::gadgets::{
+    bytes::{bits_to_num, bytes_to_bits, limbs_to_bytes_le},
+    sha512::Sha512Chip,
+};
+use crate::halo2_base::{gates::GateInstructions, utils::ScalarField, AssignedValue, Context};
+use crate::halo2_ecc::{
+    bigint::ProperCrtUint,
+    fields::{fp::FpChip, FieldChip, Selectable},
+};
+
+/// Non-native arithmetic over the Curve25519 base field.
+pub type FqChip<'range> = FpChip<'range, Fr, Fq>;
+
+/// Non-native arithmetic modulo the Ed25519 group order `L`.
+pub type ScalarChip<'range> = FpChip<'range, Fr, Scalar>;
+
+/// Bits in a canonical scalar; `L < 2^253`.
+const SCALAR_BITS: usize = 253;
+
+/// An affine Edwards25519 point with non-native coordinates.
+#[derive(Clone, Debug)]
+pub struct EdwardsPoint {
+    pub x: ProperCrtUint<Fr>,
+    pub y: ProperCrtUint<Fr>,
+}
+
+/// In-circuit Ed25519 verification.
+///
+/// Points use the complete twisted Edwards addition law, so additions need
+/// no case analysis and divisions never hit zero for points on the curve.
+#[derive(Clone, Copy, Debug)]
+pub struct Ed25519Chip<'chip> {
+    fq_chip: &'chip FqChip<'chip>,
+    scalar_chip: &'chip ScalarChip<'chip>,
+}
+
+impl<'chip> Ed25519Chip<'chip> {
+    pub fn new(fq_chip: &'chip FqChip<'chip>, scalar_chip: &'chip ScalarChip<'chip>) -> Self {
+        Self {
+            fq_chip,
+            scalar_chip,
+        }
+    }
+
+    pub fn fq_chip(&self) -> &FqChip<'chip> {
+        self.fq_chip
+    }
+
+    pub fn load_constant(&self, ctx: &mut Context<Fr>, point: &EdwardsAffine) -> EdwardsPoint {
+        EdwardsPoint {
+            x: self.fq_chip.load_constant(ctx, point.x),
+            y: self.fq_chip.load_constant(ctx, point.y),
+        }
+    }
+
+    /// Constrains `-x^2 + y^2 = 1 + d x^2 y^2`.
+    pub fn assert_on_curve(&self, ctx: &mut Context<Fr>, point: &EdwardsPoint) {
+        let chip = self.fq_chip;
+        let x2 = chip.mul(ctx, point.x.clone(), point.x.clone());
+        let y2 = chip.mul(ctx, point.y.clone(), point.y.clone());
+        let x2y2 = chip.mul(ctx, x2.clone(), y2.clone());
+        let d = chip.load_constant(ctx, d());
+        let dx2y2 = chip.mul_no_carry(ctx, x2y2, d);
+        let one = chip.load_constant(ctx, Fq::ONE);
+
+        let lhs = chip.sub_no_carry(ctx, y2, x2);
+        let rhs = chip.add_no_carry(ctx, one, dx2y2);
+        let diff = chip.sub_no_carry(ctx, lhs, rhs);
+        chip.check_carry_mod_to_zero(ctx, diff);
+    }
+
+    /// `(x1 y2 + y1 x2) / (1 + t), (y1 y2 + x1 x2) / (1 - t)` with
+    /// `t = d x1 x2 y1 y2`.
+    pub fn add(&self, ctx: &mut Context<Fr>, p: &EdwardsPoint, q: &EdwardsPoint) -> EdwardsPoint {
+        let chip = self.fq_chip;
+        let x1y2 = chip.mul_no_carry(ctx, p.x.clone(), q.y.clone());
+        let y1x2 = chip.mul_no_carry(ctx, p.y.clone(), q.x.clone());
+        let x_num = chip.add_no_carry(ctx, x1y2, y1x2);
+        let x_num = chip.carry_mod(ctx, x_num);
+
+        let x1x2 = chip.mul(ctx, p.x.clone(), q.x.clone());
+        let y1y2 = chip.mul(ctx, p.y.clone(), q.y.clone());
+        let y_num = chip.add_no_carry(ctx, y1y2.clone(), x1x2.clone());
+        let y_num = chip.carry_mod(ctx, y_num);
+
+        let product = chip.mul(ctx, x1x2, y1y2);
+        let d = chip.load_constant(ctx, d());
+        let t = chip.mul(ctx, product, d);
+        let one = chip.load_constant(ctx, Fq::ONE);
+        let x_den = chip.add_no_carry(ctx, one.clone(), t.clone());
+        let x_den = chip.carry_mod(ctx, x_den);
+        let y_den = chip.sub_no_carry(ctx, one, t);
+        let y_den = chip.carry_mod(ctx, y_den);
+
+        EdwardsPoint {
+            x: chip.divide_unsafe(ctx, x_num, x_den),
+            y: chip.divide_unsafe(ctx, y_num, y_den),
+        }
+    }
+
+    pub fn double(&self, ctx: &mut Context<Fr>, p: &EdwardsPoint) -> EdwardsPoint {
+        self.add(ctx, p, p)
+    }
+
+    /// `if bit { a } else { b }`.
+    pub fn select(
+        &self,
+        ctx: &mut Context<Fr>,
+        a: EdwardsPoint,
+        b: EdwardsPoint,
+        bit: AssignedValue<Fr>,
+    ) -> EdwardsPoint {
+        EdwardsPoint {
+            x: self.fq_chip.select(ctx, a.x, b.x, bit),
+            y: self.fq_chip.select(ctx, a.y, b.y, bit),
+        }
+    }
+
+    pub fn assert_equal(&self, ctx: &mut Context<Fr>, p: &EdwardsPoint, q: &EdwardsPoint) {
+        self.fq_chip.assert_equal(ctx, p.x.clone(), q.x.clone());
+        self.fq_chip.assert_equal(ctx, p.y.clone(), q.y.clone());
+    }
+
+    /// `[k] P` for `k` given as little-endian bits, by double-and-add.
+    pub fn scalar_mul(
+        &self,
+        ctx: &mut Context<Fr>,
+        point: &EdwardsPoint,
+        bits: &[AssignedValue<Fr>],
+    ) -> EdwardsPoint {
+        let mut acc = self.load_constant(ctx, &EdwardsAffine::identity());
+        for bit in bits.iter().rev() {
+            acc = self.double(ctx, &acc);
+            let sum = self.add(ctx, &acc, point);
+            acc = self.select(ctx, sum, acc, *bit);
+        }
+        acc
+    }
+
+    /// `[k] B` against a constant table of `2^i B`.
+    pub fn basepoint_mul(&self, ctx: &mut Context<Fr>, bits: &[AssignedValue<Fr>]) -> EdwardsPoint {
+        let mut acc = self.load_constant(ctx, &EdwardsAffine::identity());
+        let mut power = EdwardsAffine::basepoint();
+        for bit in bits {
+            let term = self.load_constant(ctx, &power);
+            let sum = self.add(ctx, &acc, &term);
+            acc = self.select(ctx, sum, acc, *bit);
+            power = power.double();
+        }
+        acc
+    }
+
+    /// Constrains `[8] P` to not be the identity, i.e. `P` is not of small
+    /// order. `[8] P` lies in the prime-order subgroup, where only the
+    /// identity has `x = 0`.
+    pub fn assert_not_small_order(&self, ctx: &mut Context<Fr>, point: &EdwardsPoint) {
+        let mut multiple = point.clone();
+        for _ in 0..3 {
+            multiple = self.double(ctx, &multiple);
+        }
+        let is_zero = self.fq_chip.is_zero(ctx, multiple.x);
+        self.fq_chip
+            .gate()
+            .assert_is_const(ctx, &is_zero, &Fr::zero());
+    }
+
+    /// Canonical little-endian bytes of a base field element.
+    pub fn fq_to_bytes(
+        &self,
+        ctx: &mut Context<Fr>,
+        x: &ProperCrtUint<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let reduced = self.fq_chip.enforce_less_than(ctx, x.clone());
+        limbs_to_bytes_le(
+            ctx,
+            self.fq_chip.gate(),
+            reduced.inner().limbs(),
+            self.fq_chip.limb_bits,
+            32,
+        )
+    }
+
+    /// Canonical little-endian bytes of a scalar, which is thereby `< L`.
+    pub fn scalar_to_bytes(
+        &self,
+        ctx: &mut Context<Fr>,
+        s: &ProperCrtUint<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let reduced = self.scalar_chip.enforce_less_than(ctx, s.clone());
+        limbs_to_bytes_le(
+            ctx,
+            self.scalar_chip.gate(),
+            reduced.inner().limbs(),
+            self.scalar_chip.limb_bits,
+            32,
+        )
+    }
+
+    /// RFC 8032 point decoding of 32 byte cells.
+    ///
+    /// Rejects non-canonical `y` and requires the sign bit to match the
+    /// parity of `x`, so `x = 0` with the sign bit set is rejected too; such
+    /// points have small order and fail verification regardless.
+    pub fn decompress(&self, ctx: &mut Context<Fr>, bytes: &[AssignedValue<Fr>]) -> EdwardsPoint {
+        assert_eq!(bytes.len(), 32, "compressed points are 32 bytes");
+        let gate = self.fq_chip.gate();
+        let last_bits = gate.num_to_bits(ctx, bytes[31], 8);
+        let sign = last_bits[7];
+        let mut y_bytes = bytes[..31].to_vec();
+        y_bytes.push(bits_to_num(ctx, gate, &last_bits[..7]));
+
+        // An undecodable input gets x = 0, which fails the curve equation.
+        let encoded: Vec<u8> = bytes
+            .iter()
+            .map(|byte| byte.value().get_lower_64() as u8)
+            .collect();
+        let mut y_encoded = encoded.clone();
+        y_encoded[31] &= 0x7f;
+        let witness =
+            EdwardsAffine::decompress(&encoded.try_into().unwrap()).unwrap_or(EdwardsAffine {
+                x: Fq::ZERO,
+                y: Fq::from_bytes_le_wide(&y_encoded),
+            });
+
+        let y = self.fq_chip.load_private(ctx, witness.y);
+        for (canonical, byte) in self.fq_to_bytes(ctx, &y).iter().zip(&y_bytes) {
+            ctx.constrain_equal(canonical, byte);
+        }
+        let x = self.fq_chip.load_private(ctx, witness.x);
+        let x_bytes = self.fq_to_bytes(ctx, &x);
+        let x_parity = gate.num_to_bits(ctx, x_bytes[0], 8)[0];
+        ctx.constrain_equal(&x_parity, &sign);
+
+        let point = EdwardsPoint { x, y };
+        self.assert_on_curve(ctx, &point);
+        point
+    }
+
+    /// Loads the scalar encoded by 32 byte cells, constraining it to be
+    /// canonical, and returns its [`SCALAR_BITS`] little-endian bits.
+    pub fn load_scalar(
+        &self,
+        ctx: &mut Context<Fr>,
+        bytes: &[AssignedValue<Fr>],
+    ) -> Vec<AssignedValue<Fr>> {
+        assert_eq!(bytes.len(), 32, "scalars are 32 bytes");
+        let encoded: Vec<u8> = bytes
+            .iter()
+            .map(|byte| byte.value().get_lower_64() as u8)
+            .collect();
+        let s = self
+            .scalar_chip
+            .load_private(ctx, Scalar::from_bytes_le_wide(&encoded));
+        for (canonical, byte) in self.scalar_to_bytes(ctx, &s).iter().zip(bytes) {
+            ctx.constrain_equal(canonical, byte);
+        }
+        let mut bits = bytes_to_bits(ctx, self.fq_chip.gate(), bytes);
+        bits.truncate(SCALAR_BITS);
+        bits
+    }
+
+    /// Reduces a 64-byte little-endian digest modulo `L` and returns the
+    /// [`SCALAR_BITS`] little-endian bits of the result.
+    pub fn reduce_digest(
+        &self,
+        ctx: &mut Context<Fr>,
+        digest: &[AssignedValue<Fr>],
+    ) -> Vec<AssignedValue<Fr>> {
+        assert_eq!(digest.len(), 64, "SHA-512 digests are 64 bytes");
+        let chip = self.scalar_chip;
+        let gate = chip.gate();
+
+        // Each 128-bit chunk is below L, so it loads as an exact scalar.
+        let mut acc = None;
+        for (i, chunk) in digest.chunks(16).enumerate() {
+            let encoded: Vec<u8> = chunk
+                .iter()
+                .map(|byte| byte.value().get_lower_64() as u8)
+                .collect();
+            let limb = chip.load_private(ctx, Scalar::from_bytes_le_wide(&encoded));
+            let limb_bytes = self.scalar_to_bytes(ctx, &limb);
+            for (canonical, byte) in limb_bytes.iter().zip(chunk) {
+                ctx.constrain_equal(canonical, byte);
+            }
+            for high in &limb_bytes[16..] {
+                gate.assert_is_const(ctx, high, &Fr::zero());
+            }
+
+            let shift = chip.load_constant(
+                ctx,
+                Scalar::from_biguint(&(BigUint::from(1u32) << (128 * i))),
+            );
+            let term = chip.mul_no_carry(ctx, limb, shift);
+            acc = Some(match acc {
+                None => term,
+                Some(acc) => chip.add_no_carry(ctx, acc, term),
+            });
+        }
+        let k = chip.carry_mod(ctx, acc.unwrap());
+        let k_bytes = self.scalar_to_bytes(ctx, &k);
+        let mut bits = bytes_to_bits(ctx, gate, &k_bytes);
+        bits.truncate(SCALAR_BITS);
+        bits
+    }
+
+    /// Constrains `signature` to be a valid Ed25519 signature of `message`
+    /// under `public_key`, with the checks of `verify_strict`: canonical `S`,
+    /// canonical and not small-order `A` and `R`, and the cofactorless
+    /// equation `[S] B = R + [k] A` with `k = SHA-512(R || A || M) mod L`.
+    ///
+    /// All inputs must already be constrained to bytes.
+    pub fn verify(
+        &self,
+        ctx: &mut Context<Fr>,
+        public_key: &[AssignedValue<Fr>],
+        signature: &[AssignedValue<Fr>],
+        message: &[AssignedValue<Fr>],
+    ) {
+        assert_eq!(signature.len(), 64, "signatures are 64 bytes");
+        let (r_bytes, s_bytes) = signature.split_at(32);
+        let a = self.decompress(ctx, public_key);
+        let r = self.decompress(ctx, r_bytes);
+        self.assert_not_small_order(ctx, &a);
+        self.assert_not_small_order(ctx, &r);
+        let s_bits = self.load_scalar(ctx, s_bytes);
+
+        let mut hash_input = r_bytes.to_vec();
+        hash_input.extend_from_slice(public_key);
+        hash_input.extend_from_slice(message);
+        let digest = Sha512Chip::new(self.fq_chip.gate()).sha512(ctx, &hash_input);
+        let k_bits = self.reduce_digest(ctx, &digest);
+
+        let lhs = self.basepoint_mul(ctx, &s_bits);
+        let ka = self.scalar_mul(ctx, &a, &k_bits);
+        let rhs = self.add(ctx, &r, &ka);
+        self.assert_equal(ctx, &lhs, &rhs);
+    }
+}
@@ -0,0 +1,96 @@
+//! The Curve25519 base field and the Ed25519 scalar field.
+//!
+//! halo2curves has no Ed25519 fields that halo2-base knows about, so both are
+//! derived here with `ff` and given the `ScalarField` impls `FpChip` needs to
+//! use them as non-native fields.
+
+use std::hash::{Hash, Hasher};
+
+use ff::{FromUniformBytes, PrimeField};
+use num_bigint::BigUint;
+
+use crate::halo2_base::utils::ScalarField;
+
+/// `GF(2^255 - 19)`.
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "57896044618658097711785492504343953926634992332820282019728792003956564819949"]
+#[PrimeFieldGenerator = "2"]
+#[PrimeFieldReprEndianness = "little"]
+pub struct Fq([u64; 4]);
+
+/// `GF(L)` with `L = 2^252 + 27742317777372353535851937790883648493`, the
+/// order of the Ed25519 base point.
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "7237005577332262213973186563042994240857116359379907606001950938285454250989"]
+#[PrimeFieldGenerator = "2"]
+#[PrimeFieldReprEndianness = "little"]
+pub struct Scalar([u64; 4]);
+
+macro_rules! impl_scalar_field {
+    ($field:ident) => {
+        impl $field {
+            /// Reduces an arbitrary integer into the field.
+            pub fn from_biguint(value: &BigUint) -> Self {
+                let modulus = BigUint::from_bytes_le((-Self::ONE).to_repr().as_ref()) + 1u32;
+                let bytes = (value % modulus).to_bytes_le();
+                let mut repr = <Self as PrimeField>::Repr::default();
+                repr.as_mut()[..bytes.len()].copy_from_slice(&bytes);
+                Self::from_repr(repr).expect("reduced value is canonical")
+            }
+
+            /// Reduces little-endian bytes of any length into the field.
+            pub fn from_bytes_le_wide(bytes: &[u8]) -> Self {
+                Self::from_biguint(&BigUint::from_bytes_le(bytes))
+            }
+        }
+
+        impl Hash for $field {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.to_repr().as_ref().hash(state);
+            }
+        }
+
+        impl From<bool> for $field {
+            fn from(bit: bool) -> Self {
+                Self::from(bit as u64)
+            }
+        }
+
+        /// Little-endian `u64` digits, as halo2-base's `BigPrimeField` expects.
+        impl From<[u64; 4]> for $field {
+            fn from(digits: [u64; 4]) -> Self {
+                let bytes: Vec<u8> = digits
+                    .iter()
+                    .flat_map(|digit| digit.to_le_bytes())
+                    .collect();
+                Self::from_bytes_le_wide(&bytes)
+            }
+        }
+
+        impl FromUniformBytes<64> for $field {
+            fn from_uniform_bytes(bytes: &[u8; 64]) -> Self {
+                Self::from_bytes_le_wide(bytes)
+            }
+        }
+
+        impl ScalarField for $field {
+            fn to_u64_limbs(self, num_limbs: usize, bit_len: usize) -> Vec<u64> {
+                let value = BigUint::from_bytes_le(self.to_repr().as_ref());
+                let mask = (BigUint::from(1u32) << bit_len) - 1u32;
+                (0..num_limbs)
+                    .map(|i| {
+                        let limb = (&value >> (i * bit_len)) & &mask;
+                        limb.to_u64_digits().first().copied().unwrap_or(0)
+                    })
+                    .collect()
+            }
+
+            fn to_bytes_le(&self) -> Vec<u8> {
+                self.to_repr().as_ref().to_vec()
+            }
+        }
+    };
+}
+
+impl_scalar_field!(Fq);
+impl_scalar_field!(Scalar);
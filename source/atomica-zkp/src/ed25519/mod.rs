@@ -0,0 +1,111 @@
+//! Proof that an Aptos account signed a `RawTransaction`.
+//!
+//! Aptos user transactions are authorized by an Ed25519 signature over the
+//! signing message `SHA3-256("APTOS::RawTransaction") || BCS(raw_txn)`.
+//! [`Ed25519SignatureCircuit`] verifies such a signature in-circuit, with the
+//! same acceptance rules as aptos-crypto (`verify_strict`), so a bridge can
+//! act on the transaction without trusting the relayer that delivered it.
+//!
+//! Public instances are little-endian 16-byte words (see
+//! [`crate::gadgets::bytes::pack_words`]), in order:
+//!
+//! 1. the 32-byte Ed25519 public key (2 words),
+//! 2. the BCS-encoded `RawTransaction`.
+//!
+//! The signature itself stays private.
+
+pub mod chip;
+pub mod field;
+pub mod native;
+
+use halo2_proofs_axiom::halo2curves::bn256::Fr;
+use sha3::{Digest, Sha3_256};
+
+use crate::circuit::{BuilderCircuit, CircuitParams};
+use crate::gadgets::bytes::{load_bytes, pack_words, pack_words_native};
+use crate::halo2_base::{
+    gates::{circuit::builder::BaseCircuitBuilder, RangeChip, RangeInstructions},
+    AssignedValue,
+};
+use crate::halo2_ecc::fields::fp::FpChip;
+use chip::Ed25519Chip;
+
+/// Domain separator Aptos hashes to salt `RawTransaction` signing messages.
+pub const RAW_TRANSACTION_SALT: &[u8] = b"APTOS::RawTransaction";
+
+/// Limb width for non-native Ed25519 field arithmetic.
+pub const LIMB_BITS: usize = 88;
+
+/// Number of limbs for non-native Ed25519 field arithmetic.
+pub const NUM_LIMBS: usize = 3;
+
+/// Default sizing: two scalar multiplications plus a few SHA-512 blocks.
+pub const ED25519_CIRCUIT_PARAMS: CircuitParams = CircuitParams {
+    k: 20,
+    lookup_bits: 19,
+    minimum_rows: 20,
+};
+
+/// `SHA3-256(RAW_TRANSACTION_SALT)`, the prefix of every signing message.
+pub fn raw_transaction_prefix() -> [u8; 32] {
+    Sha3_256::digest(RAW_TRANSACTION_SALT).into()
+}
+
+/// Proves `signature` is a valid Ed25519 signature by `public_key` over the
+/// Aptos signing message of `raw_transaction`.
+#[derive(Clone, Debug)]
+pub struct Ed25519SignatureCircuit {
+    pub public_key: [u8; 32],
+    pub signature: [u8; 64],
+    /// BCS encoding of the `RawTransaction`.
+    pub raw_transaction: Vec<u8>,
+}
+
+impl Ed25519SignatureCircuit {
+    pub fn new(public_key: [u8; 32], signature: [u8; 64], raw_transaction: Vec<u8>) -> Self {
+        Self {
+            public_key,
+            signature,
+            raw_transaction,
+        }
+    }
+
+    /// The message the signature covers.
+    pub fn signing_message(&self) -> Vec<u8> {
+        [raw_transaction_prefix().as_slice(), &self.raw_transaction].concat()
+    }
+
+    /// Expected public instances in the layout described in the module docs.
+    pub fn instances(&self) -> Vec<Fr> {
+        let mut instances = pack_words_native(&self.public_key);
+        instances.extend(pack_words_native::<Fr>(&self.raw_transaction));
+        instances
+    }
+}
+
+impl BuilderCircuit for Ed25519SignatureCircuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let fq_chip = FpChip::new(range, LIMB_BITS, NUM_LIMBS);
+        let scalar_chip = FpChip::new(range, LIMB_BITS, NUM_LIMBS);
+        let chip = Ed25519Chip::new(&fq_chip, &scalar_chip);
+
+        let public_key = load_bytes(ctx, range, &self.public_key);
+        let signature = load_bytes(ctx, range, &self.signature);
+        let raw_transaction = load_bytes(ctx, range, &self.raw_transaction);
+        let mut message: Vec<_> = raw_transaction_prefix()
+            .iter()
+            .map(|byte| ctx.load_constant(Fr::from(*byte as u64)))
+            .collect();
+        message.extend_from_slice(&raw_transaction);
+        chip.verify(ctx, &public_key, &signature, &message);
+
+        let mut instances = pack_words(ctx, range.gate(), &public_key);
+        instances.extend(pack_words(ctx, range.gate(), &raw_transaction));
+        instances
+    }
+}
@@ -0,0 +1,85 @@
+//! Native Edwards25519 arithmetic used for witness generation.
+//!
+//! Only what the circuit needs: affine addition, point decompression and the
+//! fixed-base table. Signature checking itself is left to aptos-crypto, which
+//! the circuit is tested against.
+
+use ff::{Field, PrimeField};
+
+use super::field::Fq;
+
+/// `d = -121665 / 121666`.
+pub fn d() -> Fq {
+    -Fq::from(121665) * Fq::from(121666).invert().unwrap()
+}
+
+/// A point on `-x^2 + y^2 = 1 + d x^2 y^2`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EdwardsAffine {
+    pub x: Fq,
+    pub y: Fq,
+}
+
+impl EdwardsAffine {
+    pub fn identity() -> Self {
+        Self {
+            x: Fq::ZERO,
+            y: Fq::ONE,
+        }
+    }
+
+    /// The RFC 8032 base point `B`, with `y = 4/5` and even `x`.
+    pub fn basepoint() -> Self {
+        let y = Fq::from(4) * Fq::from(5).invert().unwrap();
+        Self::from_y(y, false).expect("4/5 is the y-coordinate of B")
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        let (x2, y2) = (self.x.square(), self.y.square());
+        y2 - x2 == Fq::ONE + d() * x2 * y2
+    }
+
+    /// The complete twisted Edwards addition law.
+    pub fn add(&self, other: &Self) -> Self {
+        let t = d() * self.x * other.x * self.y * other.y;
+        let x = (self.x * other.y + self.y * other.x) * (Fq::ONE + t).invert().unwrap();
+        let y = (self.y * other.y + self.x * other.x) * (Fq::ONE - t).invert().unwrap();
+        Self { x, y }
+    }
+
+    pub fn double(&self) -> Self {
+        self.add(self)
+    }
+
+    /// Recovers `x` from `y` and its parity, if `y` is on the curve.
+    pub fn from_y(y: Fq, x_is_odd: bool) -> Option<Self> {
+        let y2 = y.square();
+        let x2 = (y2 - Fq::ONE) * (d() * y2 + Fq::ONE).invert().unwrap();
+        let x = Option::<Fq>::from(x2.sqrt())?;
+        let x = if is_odd(&x) == x_is_odd { x } else { -x };
+        Some(Self { x, y })
+    }
+
+    /// RFC 8032 decoding. Rejects non-canonical `y` but, like
+    /// curve25519-dalek, accepts `x = 0` with the sign bit set.
+    pub fn decompress(bytes: &[u8; 32]) -> Option<Self> {
+        let mut y_bytes = *bytes;
+        let sign = y_bytes[31] >> 7 == 1;
+        y_bytes[31] &= 0x7f;
+        let mut repr = <Fq as PrimeField>::Repr::default();
+        repr.as_mut().copy_from_slice(&y_bytes);
+        let y = Option::<Fq>::from(Fq::from_repr(repr))?;
+        Self::from_y(y, sign)
+    }
+
+    pub fn compress(&self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        bytes.copy_from_slice(self.y.to_repr().as_ref());
+        bytes[31] |= (is_odd(&self.x) as u8) << 7;
+        bytes
+    }
+}
+
+fn is_odd(x: &Fq) -> bool {
+    x.to_repr().as_ref()[0] & 1 == 1
+}
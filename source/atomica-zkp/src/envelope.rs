@@ -0,0 +1,99 @@
+//! Transport encoding of a proof and its public instances.
+//!
+//! A [`ProofEnvelope`] is JSON so that browser and relayer code can build and
+//! inspect it without linking the prover:
+//!
+//! ```json
+//! { "version": 1, "instances": [["0x…", "0x…"]], "proof": "0x…" }
+//! ```
+//!
+//! Instances are 32-byte big-endian hex, one array per instance column, and
+//! the proof is the byte string returned by `gen_evm_proof_shplonk`.
+
+use std::io;
+
+use halo2_proofs_axiom::halo2curves::{bn256::Fr, ff::PrimeField};
+use serde::{Deserialize, Serialize};
+
+/// The only envelope version understood by this crate.
+pub const ENVELOPE_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofEnvelope {
+    pub instances: Vec<Vec<Fr>>,
+    pub proof: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawEnvelope {
+    version: u32,
+    instances: Vec<Vec<String>>,
+    proof: String,
+}
+
+pub(crate) fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+pub(crate) fn decode_hex(value: &str) -> io::Result<Vec<u8>> {
+    let digits = value
+        .strip_prefix("0x")
+        .ok_or_else(|| invalid_data(format!("missing 0x prefix: {value}")))?;
+    hex::decode(digits).map_err(|err| invalid_data(format!("bad hex {value}: {err}")))
+}
+
+fn encode_fr(value: &Fr) -> String {
+    let mut bytes = value.to_repr();
+    bytes.reverse();
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn decode_fr(value: &str) -> io::Result<Fr> {
+    let mut bytes: [u8; 32] = decode_hex(value)?
+        .try_into()
+        .map_err(|_| invalid_data(format!("instance is not 32 bytes: {value}")))?;
+    bytes.reverse();
+    Option::<Fr>::from(Fr::from_repr(bytes))
+        .ok_or_else(|| invalid_data(format!("instance is not reduced: {value}")))
+}
+
+impl ProofEnvelope {
+    pub fn new(instances: Vec<Vec<Fr>>, proof: Vec<u8>) -> Self {
+        Self { instances, proof }
+    }
+
+    pub fn to_json(&self) -> String {
+        let raw = RawEnvelope {
+            version: ENVELOPE_VERSION,
+            instances: self
+                .instances
+                .iter()
+                .map(|column| column.iter().map(encode_fr).collect())
+                .collect(),
+            proof: format!("0x{}", hex::encode(&self.proof)),
+        };
+        serde_json::to_string(&raw).expect("envelope serialization cannot fail")
+    }
+
+    /// Parses an envelope, rejecting unknown versions and non-canonical
+    /// instances.
+    pub fn from_json(json: &str) -> io::Result<Self> {
+        let raw: RawEnvelope =
+            serde_json::from_str(json).map_err(|err| invalid_data(err.to_string()))?;
+        if raw.version != ENVELOPE_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported envelope version {}",
+                raw.version
+            )));
+        }
+        let instances = raw
+            .instances
+            .iter()
+            .map(|column| column.iter().map(|value| decode_fr(value)).collect())
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            instances,
+            proof: decode_hex(&raw.proof)?,
+        })
+    }
+}
@@ -0,0 +1,614 @@
+//! Incremental proofs over a sequence of epoch changes.
+//!
+//! [`EpochChainCircuit`] is an IVC step in the style of snark-verifier's
+//! recursion example. Round `i` verifies the [`EpochChangeCircuit`] proof of
+//! change `i` and the chain proof of round `i - 1`, and folds both KZG
+//! accumulators into the one it exposes. The previous proof has this
+//! circuit's own verifying key, so its preprocessed commitments are loaded
+//! as witnesses and bound to the `vk_digest` instance; in round 0 a dummy
+//! proof stands in for it and its accumulator is replaced by a trivially
+//! valid one.
+//!
+//! Public instances are, in order:
+//!
+//! 1. the folded accumulator as 12 limbs (see [`ACCUMULATOR_LIMBS`]),
+//! 2. the digest of this circuit's verifying key ([`vk_digest`]),
+//! 3. the state the chain started from,
+//! 4. the state after the latest change,
+//! 5. the round, i.e. the number of changes minus one.
+//!
+//! A verifier of the final proof must run the accumulator's pairing check
+//! (the generated Solidity verifier does), check the vk digest against the
+//! deployed key and check the initial state against the state it trusts.
+//!
+//! [`EpochChangeCircuit`]: super::EpochChangeCircuit
+
+use std::{mem, rc::Rc};
+
+use halo2_proofs_axiom::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{ProvingKey, VerifyingKey},
+    poly::kzg::commitment::ParamsKZG,
+};
+use rand::rngs::OsRng;
+use snark_verifier_sdk::{
+    gen_pk,
+    halo2::{
+        aggregation::{BaseFieldEccChip, Halo2Loader, Svk},
+        gen_dummy_snark, PoseidonTranscript, POSEIDON_SPEC,
+    },
+    snark_verifier::{
+        loader::{native::NativeLoader, Loader, ScalarLoader},
+        pcs::{kzg::KzgAccumulator, AccumulationScheme, AccumulationSchemeProver},
+        system::halo2::{compile, Config},
+        util::{
+            arithmetic::{fe_to_fe, fe_to_limbs},
+            hash::Poseidon,
+        },
+        verifier::{
+            plonk::{PlonkProtocol, PlonkSuccinctVerifier},
+            SnarkVerifier,
+        },
+    },
+    Snark, BITS, LIMBS, SHPLONK,
+};
+
+use super::{EpochChange, EpochChangeCircuit};
+use crate::circuit::{BuilderCircuit, CircuitParams};
+use crate::halo2_base::gates::{
+    circuit::{builder::BaseCircuitBuilder, BaseCircuitParams, CircuitBuilderStage},
+    flex_gate::MultiPhaseThreadBreakPoints,
+    GateInstructions,
+};
+use crate::halo2_base::AssignedValue;
+use crate::halo2_ecc::{bn254::FpChip, ecc::EcPoint};
+use crate::prover::{poseidon_snark, prove, TranscriptKind};
+
+/// Instance words taken by the folded accumulator.
+pub const ACCUMULATOR_LIMBS: usize = 4 * LIMBS;
+const VK_DIGEST_ROW: usize = ACCUMULATOR_LIMBS;
+const INITIAL_STATE_ROW: usize = ACCUMULATOR_LIMBS + 1;
+const STATE_ROW: usize = ACCUMULATOR_LIMBS + 2;
+const ROUND_ROW: usize = ACCUMULATOR_LIMBS + 3;
+/// Public instances of [`EpochChainCircuit`].
+pub const NUM_INSTANCE: usize = ACCUMULATOR_LIMBS + 4;
+
+/// Default sizing: two in-circuit SHPLONK verifications.
+pub const CHAIN_CIRCUIT_PARAMS: CircuitParams = CircuitParams {
+    k: 22,
+    lookup_bits: 21,
+    minimum_rows: 20,
+};
+
+type SuccinctVerifier = PlonkSuccinctVerifier<SHPLONK>;
+type LoadedAccumulator<'a> = KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>;
+
+fn poseidon<L: Loader<G1Affine>>(loader: &L, inputs: &[L::LoadedScalar]) -> L::LoadedScalar {
+    let mut hasher = Poseidon::from_spec(loader, POSEIDON_SPEC.clone());
+    hasher.update(inputs);
+    hasher.squeeze()
+}
+
+/// The compiled protocol of a chain proof.
+fn chain_protocol(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+) -> PlonkProtocol<G1Affine> {
+    compile(
+        params,
+        vk,
+        Config::kzg()
+            .with_num_instance(vec![NUM_INSTANCE])
+            .with_accumulator_indices(Some(EpochChainCircuit::accumulator_indices())),
+    )
+}
+
+/// Poseidon digest of a chain verifying key's preprocessed commitments and
+/// transcript initial state, as exposed by every chain proof.
+pub fn vk_digest(params: &ParamsKZG<Bn256>, vk: &VerifyingKey<G1Affine>) -> Fr {
+    let protocol = chain_protocol(params, vk);
+    let inputs: Vec<Fr> = protocol
+        .preprocessed
+        .iter()
+        .flat_map(|preprocessed| [preprocessed.x, preprocessed.y])
+        .map(fe_to_fe)
+        .chain(protocol.transcript_initial_state)
+        .collect();
+    poseidon(&NativeLoader, &inputs)
+}
+
+/// Native succinct verification of a Poseidon-transcript snark.
+fn native_accumulators(svk: &Svk, snark: &Snark) -> Vec<KzgAccumulator<G1Affine, NativeLoader>> {
+    let mut transcript =
+        PoseidonTranscript::<NativeLoader, &[u8]>::from_spec(snark.proof(), POSEIDON_SPEC.clone());
+    let proof =
+        SuccinctVerifier::read_proof(svk, &snark.protocol, &snark.instances, &mut transcript)
+            .expect("snark has a well-formed proof");
+    SuccinctVerifier::verify(svk, &snark.protocol, &snark.instances, &proof)
+        .expect("snark passes succinct verification")
+}
+
+/// In-circuit succinct verification. With `vk_digest`, the snark's
+/// preprocessed commitments are witnesses constrained to hash to it.
+fn succinct_verify<'a>(
+    svk: &Svk,
+    loader: &Rc<Halo2Loader<'a>>,
+    snark: &Snark,
+    vk_digest: Option<AssignedValue<Fr>>,
+) -> (Vec<AssignedValue<Fr>>, Vec<LoadedAccumulator<'a>>) {
+    let protocol = match vk_digest {
+        Some(vk_digest) => {
+            let vk_digest = loader.scalar_from_assigned(vk_digest);
+            let protocol = snark.protocol.loaded_preprocessed_as_witness(loader, false);
+            let inputs: Vec<_> = protocol
+                .preprocessed
+                .iter()
+                .flat_map(|preprocessed| {
+                    let assigned = preprocessed.assigned();
+                    [assigned.x(), assigned.y()]
+                        .map(|coordinate| loader.scalar_from_assigned(*coordinate.native()))
+                })
+                .chain(protocol.transcript_initial_state.clone())
+                .collect();
+            loader.assert_eq("vk digest", &poseidon(loader, &inputs), &vk_digest);
+            protocol
+        }
+        None => snark.protocol.loaded(loader),
+    };
+
+    let instances: Vec<Vec<_>> = snark
+        .instances
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|value| loader.assign_scalar(*value))
+                .collect()
+        })
+        .collect();
+    let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, &[u8]>::from_spec(
+        loader,
+        snark.proof(),
+        POSEIDON_SPEC.clone(),
+    );
+    let proof = SuccinctVerifier::read_proof(svk, &protocol, &instances, &mut transcript)
+        .expect("snark has a well-formed proof");
+    let accumulators = SuccinctVerifier::verify(svk, &protocol, &instances, &proof)
+        .expect("succinct verification only records constraints");
+
+    let instances = instances
+        .into_iter()
+        .next()
+        .expect("one instance column")
+        .into_iter()
+        .map(|value| value.into_assigned())
+        .collect();
+    (instances, accumulators)
+}
+
+/// One round of the epoch chain.
+#[derive(Clone, Debug)]
+pub struct EpochChainCircuit {
+    svk: Svk,
+    default_accumulator: KzgAccumulator<G1Affine, NativeLoader>,
+    step: Snark,
+    previous: Snark,
+    round: usize,
+    instances: Vec<Fr>,
+    as_proof: Vec<u8>,
+    builder: BaseCircuitBuilder<Fr>,
+}
+
+impl EpochChainCircuit {
+    /// Round `round` of a chain starting at `initial_state`, extending
+    /// `previous` by the change proven in `step`.
+    ///
+    /// `params` are the chain circuit's KZG parameters, and `previous` must
+    /// come from [`Self::initial_snark`] in round 0.
+    pub fn new(
+        stage: CircuitBuilderStage,
+        config: BaseCircuitParams,
+        params: &ParamsKZG<Bn256>,
+        step: Snark,
+        previous: Snark,
+        initial_state: Fr,
+        round: usize,
+    ) -> Self {
+        let svk: Svk = params.get_g()[0].into();
+        let default_accumulator = KzgAccumulator::new(params.get_g()[1], params.get_g()[0]);
+
+        let accumulators: Vec<_> = native_accumulators(&svk, &step)
+            .into_iter()
+            .chain(if round > 0 {
+                native_accumulators(&svk, &previous)
+            } else {
+                vec![default_accumulator.clone(); 1 + previous.protocol.accumulator_indices.len()]
+            })
+            .collect();
+        let (accumulator, as_proof) = {
+            let mut transcript = PoseidonTranscript::<NativeLoader, Vec<u8>>::from_spec(
+                vec![],
+                POSEIDON_SPEC.clone(),
+            );
+            let accumulator =
+                SHPLONK::create_proof(&Default::default(), &accumulators, &mut transcript, OsRng)
+                    .expect("accumulators fold");
+            (accumulator, transcript.finalize())
+        };
+
+        let vk_digest = {
+            let inputs: Vec<Fr> = previous
+                .protocol
+                .preprocessed
+                .iter()
+                .flat_map(|preprocessed| [preprocessed.x, preprocessed.y])
+                .map(fe_to_fe)
+                .chain(previous.protocol.transcript_initial_state)
+                .collect();
+            poseidon(&NativeLoader, &inputs)
+        };
+        let state = step.instances[0][1];
+        let instances = [
+            accumulator.lhs.x,
+            accumulator.lhs.y,
+            accumulator.rhs.x,
+            accumulator.rhs.y,
+        ]
+        .into_iter()
+        .flat_map(fe_to_limbs::<_, _, LIMBS, BITS>)
+        .chain([vk_digest, initial_state, state, Fr::from(round as u64)])
+        .collect();
+
+        let mut circuit = Self {
+            svk,
+            default_accumulator,
+            step,
+            previous,
+            round,
+            instances,
+            as_proof,
+            builder: BaseCircuitBuilder::from_stage(stage).use_params(config),
+        };
+        circuit.synthesize_builder();
+        circuit
+    }
+
+    /// Fixes the layout found at key generation, for proving.
+    pub fn use_break_points(mut self, break_points: MultiPhaseThreadBreakPoints) -> Self {
+        self.builder.set_break_points(break_points);
+        self
+    }
+
+    pub fn break_points(&self) -> MultiPhaseThreadBreakPoints {
+        self.builder.break_points()
+    }
+
+    /// Sizes the advice columns from the assigned witness.
+    pub fn calculate_params(&mut self, minimum_rows: Option<usize>) -> BaseCircuitParams {
+        self.builder.calculate_params(minimum_rows)
+    }
+
+    /// The state after this round's change.
+    pub fn state(&self) -> Fr {
+        self.instances[STATE_ROW]
+    }
+
+    /// Public instances in the layout described in the module docs.
+    pub fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![self.instances.clone()]
+    }
+
+    /// Where the folded accumulator sits among the instances.
+    pub fn accumulator_indices() -> Vec<(usize, usize)> {
+        (0..ACCUMULATOR_LIMBS).map(|i| (0, i)).collect()
+    }
+
+    /// The assigned round, for key generation or proving.
+    ///
+    /// The round is not a `Circuit` itself: a builder has no witness-free
+    /// form or parameterless configuration to offer, so the builder is what
+    /// gets handed to the prover.
+    pub fn into_builder(self) -> BaseCircuitBuilder<Fr> {
+        self.builder
+    }
+
+    /// Stand-in for the previous proof in round 0: a proof-shaped snark
+    /// with this circuit's verifying key and zeroed chain instances.
+    ///
+    /// Pass `vk: None` only during key generation, before the key exists.
+    pub fn initial_snark(
+        params: &ParamsKZG<Bn256>,
+        vk: Option<&VerifyingKey<G1Affine>>,
+        config: BaseCircuitParams,
+    ) -> Snark {
+        let mut snark = gen_dummy_snark::<BaseCircuitBuilder<Fr>, SHPLONK>(
+            params,
+            vk,
+            vec![NUM_INSTANCE],
+            config,
+        );
+        // The builder does not know where the accumulator is.
+        snark.protocol.accumulator_indices = vec![Self::accumulator_indices()];
+        let g = params.get_g();
+        snark.instances = vec![[g[1].x, g[1].y, g[0].x, g[0].y]
+            .into_iter()
+            .flat_map(fe_to_limbs::<_, _, LIMBS, BITS>)
+            .chain([Fr::zero(); 4])
+            .collect()];
+        snark
+    }
+
+    fn load_default_accumulator<'a>(&self, loader: &Rc<Halo2Loader<'a>>) -> LoadedAccumulator<'a> {
+        let [lhs, rhs] =
+            [self.default_accumulator.lhs, self.default_accumulator.rhs].map(|point| {
+                let assigned = loader
+                    .ecc_chip()
+                    .assign_constant_point(loader.ctx_mut().main(), point);
+                loader.ec_point_from_assigned(assigned)
+            });
+        KzgAccumulator::new(lhs, rhs)
+    }
+
+    fn synthesize_builder(&mut self) {
+        let range = self.builder.range_chip();
+        let gate = range.gate();
+        let pool = self.builder.pool(0);
+        let [vk_digest, initial_state, state, round] = [
+            self.instances[VK_DIGEST_ROW],
+            self.instances[INITIAL_STATE_ROW],
+            self.instances[STATE_ROW],
+            Fr::from(self.round as u64),
+        ]
+        .map(|value| pool.main().load_witness(value));
+        let first_round = gate.is_zero(pool.main(), round);
+        let not_first_round = gate.not(pool.main(), first_round);
+
+        let fp_chip = FpChip::<Fr>::new(&range, BITS, LIMBS);
+        let ecc_chip = BaseFieldEccChip::new(&fp_chip);
+        let loader = Halo2Loader::new(ecc_chip, mem::take(self.builder.pool(0)));
+
+        let (step_instances, step_accumulators) =
+            succinct_verify(&self.svk, &loader, &self.step, None);
+        let (previous_instances, previous_accumulators) =
+            succinct_verify(&self.svk, &loader, &self.previous, Some(vk_digest));
+
+        // Round 0 has no previous proof to vouch for; swap its accumulators
+        // for the default one, which always passes the pairing check.
+        let default_accumulator = self.load_default_accumulator(&loader);
+        let previous_accumulators: Vec<_> = previous_accumulators
+            .iter()
+            .map(|previous| {
+                let [lhs, rhs] = [
+                    (&default_accumulator.lhs, &previous.lhs),
+                    (&default_accumulator.rhs, &previous.rhs),
+                ]
+                .map(|(default, previous)| {
+                    let selected = loader.ecc_chip().select(
+                        loader.ctx_mut().main(),
+                        EcPoint::clone(&default.assigned()),
+                        EcPoint::clone(&previous.assigned()),
+                        first_round,
+                    );
+                    loader.ec_point_from_assigned(selected)
+                });
+                KzgAccumulator::new(lhs, rhs)
+            })
+            .collect();
+
+        let accumulators = [step_accumulators, previous_accumulators].concat();
+        let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, &[u8]>::from_spec(
+            &loader,
+            &self.as_proof,
+            POSEIDON_SPEC.clone(),
+        );
+        let as_proof = SHPLONK::read_proof(&Default::default(), &accumulators, &mut transcript)
+            .expect("accumulation proof is well-formed");
+        let KzgAccumulator { lhs, rhs } =
+            SHPLONK::verify(&Default::default(), &accumulators, &as_proof)
+                .expect("accumulation only records constraints");
+        let (lhs, rhs) = (lhs.into_assigned(), rhs.into_assigned());
+
+        let mut pool = loader.take_ctx();
+        let ctx = pool.main();
+        let links = [
+            // Every round verifies a proof with the same key.
+            (
+                gate.mul(ctx, vk_digest, not_first_round),
+                previous_instances[VK_DIGEST_ROW],
+            ),
+            // The initial state is carried forward from round 0, where it
+            // is where the first change starts.
+            (
+                gate.mul(ctx, initial_state, not_first_round),
+                previous_instances[INITIAL_STATE_ROW],
+            ),
+            (
+                gate.mul(ctx, initial_state, first_round),
+                gate.mul(ctx, step_instances[0], first_round),
+            ),
+            // Each change starts where the previous round ended.
+            (
+                gate.mul(ctx, step_instances[0], not_first_round),
+                previous_instances[STATE_ROW],
+            ),
+            (state, step_instances[1]),
+            (
+                round,
+                gate.add(ctx, previous_instances[ROUND_ROW], not_first_round),
+            ),
+        ];
+        for (lhs, rhs) in links {
+            ctx.constrain_equal(&lhs, &rhs);
+        }
+        *self.builder.pool(0) = pool;
+
+        self.builder.assigned_instances[0].extend(
+            [lhs.x(), lhs.y(), rhs.x(), rhs.y()]
+                .into_iter()
+                .flat_map(|coordinate| coordinate.limbs().to_vec())
+                .chain([vk_digest, initial_state, state, round]),
+        );
+    }
+}
+
+/// Keys for proving chains of epoch changes.
+#[derive(Debug)]
+pub struct EpochChainProver {
+    step_params: ParamsKZG<Bn256>,
+    step_pk: ProvingKey<G1Affine>,
+    step_config: BaseCircuitParams,
+    step_break_points: MultiPhaseThreadBreakPoints,
+    capacity: usize,
+    chain_params: ParamsKZG<Bn256>,
+    chain_pk: ProvingKey<G1Affine>,
+    chain_config: BaseCircuitParams,
+    chain_break_points: MultiPhaseThreadBreakPoints,
+}
+
+impl EpochChainProver {
+    /// Generates the step and chain keys.
+    ///
+    /// `example` fixes the step layout and need not be a valid change. The
+    /// chain layout is found by rebuilding until the dummy previous proof it
+    /// verifies has the shape of the circuit itself.
+    pub fn setup(
+        step_params: ParamsKZG<Bn256>,
+        step_sizing: CircuitParams,
+        example: EpochChangeCircuit,
+        chain_params: ParamsKZG<Bn256>,
+        chain_sizing: CircuitParams,
+    ) -> Self {
+        let keygen = example.build(CircuitBuilderStage::Keygen, step_sizing);
+        let step_pk = gen_pk(&step_params, &keygen, None);
+        let step_config = keygen.config_params.clone();
+        let step_break_points = keygen.break_points();
+        // Only the shape of the step proof matters for the chain layout.
+        let step = gen_dummy_snark::<BaseCircuitBuilder<Fr>, SHPLONK>(
+            &step_params,
+            Some(step_pk.get_vk()),
+            vec![2],
+            step_config.clone(),
+        );
+
+        let mut chain_config = BaseCircuitParams {
+            k: chain_sizing.k as usize,
+            num_advice_per_phase: vec![1],
+            num_fixed: 1,
+            num_lookup_advice_per_phase: vec![1],
+            lookup_bits: Some(chain_sizing.lookup_bits),
+            num_instance_columns: 1,
+        };
+        let keygen = loop {
+            let previous =
+                EpochChainCircuit::initial_snark(&chain_params, None, chain_config.clone());
+            let mut keygen = EpochChainCircuit::new(
+                CircuitBuilderStage::Keygen,
+                chain_config.clone(),
+                &chain_params,
+                step.clone(),
+                previous,
+                Fr::zero(),
+                0,
+            );
+            let config = keygen.calculate_params(Some(chain_sizing.minimum_rows));
+            if config.num_advice_per_phase == chain_config.num_advice_per_phase
+                && config.num_lookup_advice_per_phase == chain_config.num_lookup_advice_per_phase
+                && config.num_fixed == chain_config.num_fixed
+            {
+                break keygen;
+            }
+            chain_config = config;
+        };
+        let keygen = keygen.into_builder();
+        let chain_pk = gen_pk(&chain_params, &keygen, None);
+        let chain_break_points = keygen.break_points();
+
+        Self {
+            step_params,
+            step_pk,
+            step_config,
+            step_break_points,
+            capacity: example.capacity,
+            chain_params,
+            chain_pk,
+            chain_config,
+            chain_break_points,
+        }
+    }
+
+    pub fn chain_vk(&self) -> &VerifyingKey<G1Affine> {
+        self.chain_pk.get_vk()
+    }
+
+    /// The digest a verifier pins the chain key to.
+    pub fn vk_digest(&self) -> Fr {
+        vk_digest(&self.chain_params, self.chain_vk())
+    }
+
+    /// Proves `changes` in order, returning the final round's instances and
+    /// proof. Intermediate rounds use the Poseidon transcript; the last one
+    /// uses `transcript`.
+    pub fn prove(&self, changes: &[EpochChange], transcript: TranscriptKind) -> (Vec<Fr>, Vec<u8>) {
+        assert!(!changes.is_empty(), "at least one epoch change is required");
+        let initial_state = EpochChangeCircuit::new(changes[0].clone(), self.capacity).state_in();
+        let mut previous = EpochChainCircuit::initial_snark(
+            &self.chain_params,
+            Some(self.chain_vk()),
+            self.chain_config.clone(),
+        );
+        for (round, change) in changes.iter().enumerate() {
+            let step = EpochChangeCircuit::new(change.clone(), self.capacity);
+            let step_instances = vec![step.instances()];
+            let step_proof = prove(
+                &self.step_params,
+                &self.step_pk,
+                step.build_prover(self.step_config.clone(), self.step_break_points.clone()),
+                step_instances.clone(),
+                TranscriptKind::Poseidon,
+            );
+            let step_snark = poseidon_snark(
+                &self.step_params,
+                self.step_pk.get_vk(),
+                step_instances,
+                step_proof,
+            );
+
+            let circuit = EpochChainCircuit::new(
+                CircuitBuilderStage::Prover,
+                self.chain_config.clone(),
+                &self.chain_params,
+                step_snark,
+                previous,
+                initial_state,
+                round,
+            )
+            .use_break_points(self.chain_break_points.clone());
+            let instances = circuit.instances();
+            let last = round + 1 == changes.len();
+            let kind = if last {
+                transcript
+            } else {
+                TranscriptKind::Poseidon
+            };
+            let proof = prove(
+                &self.chain_params,
+                &self.chain_pk,
+                circuit.into_builder(),
+                instances.clone(),
+                kind,
+            );
+            if last {
+                return (
+                    instances.into_iter().next().expect("one instance column"),
+                    proof,
+                );
+            }
+            previous = Snark::new(
+                chain_protocol(&self.chain_params, self.chain_vk()),
+                instances,
+                proof,
+            );
+        }
+        unreachable!("the last round returns")
+    }
+}
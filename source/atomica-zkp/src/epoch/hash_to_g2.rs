@@ -0,0 +1,439 @@
+//! In-circuit hashing to BLS12-381 G2, the `BLS12381G2_XMD:SHA-256_SSWU_RO_`
+//! suite of RFC 9380 that Aptos consensus signatures use.
+//!
+//! [`HashToG2Chip`] follows the RFC step by step: `expand_message_xmd` with
+//! SHA-256, two `Fq2` elements from 64-byte chunks, the simplified SWU map
+//! onto the 3-isogenous curve `E2'`, the isogeny back to `E2` and cofactor
+//! clearing with the `ψ` endomorphism.
+//!
+//! Square roots are witnessed. Of `g(x1)` and `g(x2) = Z^3 u^6 g(x1)`
+//! exactly one is square because `Z` is not, so constraining `y^2` to the
+//! selected one leaves the prover no choice but the sign, which is fixed by
+//! `sgn0(y) = sgn0(u)`.
+
+use ark_bls12_381::Fq2 as ArkFq2;
+use ark_ff::{BigInteger, Field, MontFp, PrimeField, Zero};
+use halo2_proofs_axiom::halo2curves::{bls12_381::Fq, bn256::Fr};
+use num_bigint::BigUint;
+
+use super::native::CONSENSUS_DST;
+use crate::gadgets::{
+    bytes::{load_constant_bytes, xor_bytes},
+    sha256::{self, Sha256Chip},
+};
+use crate::halo2_base::{gates::GateInstructions, utils::biguint_to_fe, AssignedValue, Context};
+use crate::halo2_ecc::{
+    bigint::ProperCrtUint,
+    bls12_381::{Fp2Chip, FpChip},
+    ecc::{EcPoint, EccChip},
+    fields::{FieldChip, FieldVector, Selectable},
+};
+use crate::ibe::chip::{fq2_from_ark, fq_to_ark, G2Point, IbeChip};
+
+/// Bytes `expand_message_xmd` produces: two `Fq2` elements of two 64-byte
+/// coordinates each.
+const UNIFORM_BYTES: usize = 256;
+
+/// Bytes reduced into each base field coordinate.
+const CHUNK_BYTES: usize = 64;
+
+/// `|x|` for the BLS parameter `x = -0xd201000000010000`.
+const BLS_X_ABS: u64 = 0xd201_0000_0001_0000;
+
+/// `A'` of the isogenous curve `E2': y^2 = x^3 + A' x + B'`.
+const ISO_A: ArkFq2 = ArkFq2::new(MontFp!("0"), MontFp!("240"));
+
+/// `B'` of the isogenous curve.
+const ISO_B: ArkFq2 = ArkFq2::new(MontFp!("1012"), MontFp!("1012"));
+
+/// The SWU non-square `Z = -(2 + I)`.
+const SWU_Z: ArkFq2 = ArkFq2::new(MontFp!("-2"), MontFp!("-1"));
+
+/// `1 / (1 + I)^((p - 1) / 3)`, the `x` factor of `ψ`.
+const PSI_X: ArkFq2 = ArkFq2::new(
+    MontFp!("0"),
+    MontFp!("4002409555221667392624310435006688643935503118305586438271171395842971157480381377015405980053539358417135540939437"),
+);
+
+/// `1 / (1 + I)^((p - 1) / 2)`, the `y` factor of `ψ`.
+const PSI_Y: ArkFq2 = ArkFq2::new(
+    MontFp!("2973677408986561043442465346520108879172042883009249989176415018091420807192182638567116318576472649347015917690530"),
+    MontFp!("1028732146235106349975324479215795277384839936929757896155643118032610843298655225875571310552543014690878354869257"),
+);
+
+/// Isogeny `x` numerator coefficients, constant term first.
+const ISO_X_NUMERATOR: [ArkFq2; 4] = [
+    ArkFq2::new(
+        MontFp!("889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235542"),
+        MontFp!("889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235542"),
+    ),
+    ArkFq2::new(
+        MontFp!("0"),
+        MontFp!("2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706522"),
+    ),
+    ArkFq2::new(
+        MontFp!("2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706526"),
+        MontFp!("1334136518407222464472596608578634718852294273313002628444019378708010550163612621480895876376338554679298090853261"),
+    ),
+    ArkFq2::new(
+        MontFp!("3557697382419259905260257622876359250272784728834673675850718343221361467102966990615722337003569479144794908942033"),
+        MontFp!("0"),
+    ),
+];
+
+/// Isogeny `x` denominator coefficients, constant term first.
+const ISO_X_DENOMINATOR: [ArkFq2; 3] = [
+    ArkFq2::new(
+        MontFp!("0"),
+        MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559715"),
+    ),
+    ArkFq2::new(
+        MontFp!("12"),
+        MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559775"),
+    ),
+    ArkFq2::new(
+        MontFp!("1"),
+        MontFp!("0"),
+    ),
+];
+
+/// Isogeny `y` numerator coefficients, constant term first.
+const ISO_Y_NUMERATOR: [ArkFq2; 4] = [
+    ArkFq2::new(
+        MontFp!("3261222600550988246488569487636662646083386001431784202863158481286248011511053074731078808919938689216061999863558"),
+        MontFp!("3261222600550988246488569487636662646083386001431784202863158481286248011511053074731078808919938689216061999863558"),
+    ),
+    ArkFq2::new(
+        MontFp!("0"),
+        MontFp!("889424345604814976315064405719089812568196182208668418962679585805340366775741747653930584250892369786198727235518"),
+    ),
+    ArkFq2::new(
+        MontFp!("2668273036814444928945193217157269437704588546626005256888038757416021100327225242961791752752677109358596181706524"),
+        MontFp!("1334136518407222464472596608578634718852294273313002628444019378708010550163612621480895876376338554679298090853263"),
+    ),
+    ArkFq2::new(
+        MontFp!("2816510427748580758331037284777117739799287910327449993381818688383577828123182200904113516794492504322962636245776"),
+        MontFp!("0"),
+    ),
+];
+
+/// Isogeny `y` denominator coefficients, constant term first.
+const ISO_Y_DENOMINATOR: [ArkFq2; 4] = [
+    ArkFq2::new(
+        MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559355"),
+        MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559355"),
+    ),
+    ArkFq2::new(
+        MontFp!("0"),
+        MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559571"),
+    ),
+    ArkFq2::new(
+        MontFp!("18"),
+        MontFp!("4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559769"),
+    ),
+    ArkFq2::new(
+        MontFp!("1"),
+        MontFp!("0"),
+    ),
+];
+
+/// An `Fq2` element with non-native coordinates.
+type Fq2Point = FieldVector<ProperCrtUint<Fr>>;
+
+/// Hashes byte strings to G2 with the Aptos consensus DST.
+#[derive(Clone, Copy, Debug)]
+pub struct HashToG2Chip<'chip> {
+    fp_chip: &'chip FpChip<'chip, Fr>,
+}
+
+impl<'chip> HashToG2Chip<'chip> {
+    pub fn new(fp_chip: &'chip FpChip<'chip, Fr>) -> Self {
+        Self { fp_chip }
+    }
+
+    /// `hash_to_curve` of whichever of `messages` the one-hot `indicator`
+    /// selects (see [`Sha256Chip::sha256_any`]).
+    ///
+    /// Message cells must already be constrained to bytes.
+    pub fn hash_to_g2(
+        &self,
+        ctx: &mut Context<Fr>,
+        messages: &[Vec<AssignedValue<Fr>>],
+        indicator: &[AssignedValue<Fr>],
+    ) -> G2Point {
+        let uniform = self.expand_message_xmd(ctx, messages, indicator);
+        let [u0, u1] = self.hash_to_field(ctx, &uniform);
+        let q0 = self.map_to_curve(ctx, &u0);
+        let q1 = self.map_to_curve(ctx, &u1);
+        let fp2_chip = Fp2Chip::new(self.fp_chip);
+        let g2_chip = EccChip::new(&fp2_chip);
+        let sum = g2_chip.add_unequal(ctx, q0, q1, true);
+        self.clear_cofactor(ctx, &sum)
+    }
+
+    /// `expand_message_xmd` to [`UNIFORM_BYTES`] bytes.
+    pub fn expand_message_xmd(
+        &self,
+        ctx: &mut Context<Fr>,
+        messages: &[Vec<AssignedValue<Fr>>],
+        indicator: &[AssignedValue<Fr>],
+    ) -> Vec<AssignedValue<Fr>> {
+        let gate = self.fp_chip.gate();
+        let sha = Sha256Chip::new(gate);
+        let dst_prime =
+            load_constant_bytes(ctx, &[CONSENSUS_DST, &[CONSENSUS_DST.len() as u8]].concat());
+
+        // b_0 = H(Z_pad || msg || I2OSP(len_in_bytes, 2) || I2OSP(0, 1) || DST_prime)
+        let z_pad = load_constant_bytes(ctx, &[0; sha256::BLOCK_BYTES]);
+        let length =
+            load_constant_bytes(ctx, &[(UNIFORM_BYTES >> 8) as u8, UNIFORM_BYTES as u8, 0]);
+        let inputs: Vec<_> = messages
+            .iter()
+            .map(|message| [z_pad.as_slice(), message, &length, &dst_prime].concat())
+            .collect();
+        let b0 = sha.sha256_any(ctx, &inputs, indicator);
+
+        // b_i = H(strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime), b_1 = H(b_0 || ..)
+        let mut uniform = Vec::with_capacity(UNIFORM_BYTES);
+        let mut previous: Option<Vec<AssignedValue<Fr>>> = None;
+        for i in 1..=UNIFORM_BYTES / sha256::DIGEST_BYTES {
+            let mut input = match &previous {
+                None => b0.clone(),
+                Some(b) => xor_bytes(ctx, gate, &b0, b),
+            };
+            input.extend(load_constant_bytes(ctx, &[i as u8]));
+            input.extend_from_slice(&dst_prime);
+            let b = sha.sha256(ctx, &input);
+            uniform.extend_from_slice(&b);
+            previous = Some(b);
+        }
+        uniform
+    }
+
+    /// `hash_to_field` with `count = 2`: each coordinate is a 64-byte
+    /// big-endian chunk reduced modulo `p`.
+    fn hash_to_field(&self, ctx: &mut Context<Fr>, uniform: &[AssignedValue<Fr>]) -> [Fq2Point; 2] {
+        let coordinates: Vec<_> = uniform
+            .chunks(CHUNK_BYTES)
+            .map(|chunk| self.reduce_chunk(ctx, chunk))
+            .collect();
+        [
+            FieldVector(coordinates[..2].to_vec()),
+            FieldVector(coordinates[2..].to_vec()),
+        ]
+    }
+
+    /// `hi * 2^256 + lo mod p` for a chunk `hi || lo`.
+    fn reduce_chunk(
+        &self,
+        ctx: &mut Context<Fr>,
+        chunk: &[AssignedValue<Fr>],
+    ) -> ProperCrtUint<Fr> {
+        let chip = self.fp_chip;
+        let (hi, lo) = chunk.split_at(CHUNK_BYTES / 2);
+        let hi = self.load_be(ctx, hi);
+        let lo = self.load_be(ctx, lo);
+        let shift = chip.load_constant(ctx, biguint_to_fe(&(BigUint::from(1u8) << 256)));
+        let high = chip.mul_no_carry(ctx, hi, shift);
+        let sum = chip.add_no_carry(ctx, high, lo);
+        chip.carry_mod(ctx, sum)
+    }
+
+    /// Loads the big-endian integer in `bytes` as a field element. At most
+    /// 32 bytes, so it is below `p` and loads exactly.
+    fn load_be(&self, ctx: &mut Context<Fr>, bytes: &[AssignedValue<Fr>]) -> ProperCrtUint<Fr> {
+        let encoded: Vec<u8> = bytes
+            .iter()
+            .map(|byte| byte.value().get_lower_64() as u8)
+            .collect();
+        let x = self
+            .fp_chip
+            .load_private(ctx, biguint_to_fe::<Fq>(&BigUint::from_bytes_be(&encoded)));
+        let canonical = IbeChip::new(self.fp_chip).fq_to_bytes(ctx, &x);
+        for (canonical, byte) in canonical.iter().zip(bytes.iter().rev()) {
+            ctx.constrain_equal(canonical, byte);
+        }
+        for high in &canonical[bytes.len()..] {
+            self.fp_chip.gate().assert_is_const(ctx, high, &Fr::from(0));
+        }
+        x
+    }
+
+    /// Simplified SWU onto `E2'` followed by the isogeny to `E2`.
+    fn map_to_curve(&self, ctx: &mut Context<Fr>, u: &Fq2Point) -> G2Point {
+        let fp2_chip = Fp2Chip::new(self.fp_chip);
+        let gate = self.fp_chip.gate();
+
+        // x1 = (-B' / A') (1 + 1 / (Z^2 u^4 + Z u^2)); the denominator is
+        // zero only for u in {0, ±sqrt(-1 / Z)}, which no honest hash hits.
+        let u2 = fp2_chip.mul(ctx, u.clone(), u.clone());
+        let z = self.constant(ctx, &SWU_Z);
+        let zu2 = fp2_chip.mul(ctx, z, u2);
+        let zu2_squared = fp2_chip.mul_no_carry(ctx, zu2.clone(), zu2.clone());
+        let den = fp2_chip.add_no_carry(ctx, zu2_squared, zu2.clone());
+        let den = fp2_chip.carry_mod(ctx, den);
+        let one = self.constant(ctx, &ArkFq2::ONE);
+        let num = fp2_chip.add_no_carry(ctx, den.clone(), one);
+        let ratio = fp2_chip.divide_unsafe(ctx, num, den);
+        let c = self.constant(ctx, &(-ISO_B / ISO_A));
+        let x1 = fp2_chip.mul(ctx, ratio, c);
+        let gx1 = self.iso_curve_rhs(ctx, &x1);
+        let x2 = fp2_chip.mul(ctx, zu2, x1.clone());
+        let gx2 = self.iso_curve_rhs(ctx, &x2);
+
+        let is_square = self.value(&gx1).legendre().is_qr();
+        let is_square = ctx.load_witness(Fr::from(is_square as u64));
+        gate.assert_bit(ctx, is_square);
+        let x = self.select(ctx, x1, x2, is_square);
+        let gx = self.select(ctx, gx1, gx2, is_square);
+
+        // An invalid witness gets y = 0, which fails y^2 = g(x).
+        let mut y = self.value(&gx).sqrt().unwrap_or_default();
+        if sgn0(&y) != sgn0(&self.value(u)) {
+            y = -y;
+        }
+        let y = fp2_chip.load_private(ctx, fq2_from_ark(&y));
+        let y_squared = fp2_chip.mul(ctx, y.clone(), y.clone());
+        fp2_chip.assert_equal(ctx, y_squared, gx);
+        let u_sign = self.sgn0(ctx, u);
+        let y_sign = self.sgn0(ctx, &y);
+        ctx.constrain_equal(&u_sign, &y_sign);
+
+        self.iso_map(ctx, &x, &y)
+    }
+
+    /// `x^3 + A' x + B'`.
+    fn iso_curve_rhs(&self, ctx: &mut Context<Fr>, x: &Fq2Point) -> Fq2Point {
+        let fp2_chip = Fp2Chip::new(self.fp_chip);
+        let x2 = fp2_chip.mul(ctx, x.clone(), x.clone());
+        let x3 = fp2_chip.mul_no_carry(ctx, x2, x.clone());
+        let a = self.constant(ctx, &ISO_A);
+        let ax = fp2_chip.mul_no_carry(ctx, a, x.clone());
+        let b = self.constant(ctx, &ISO_B);
+        let sum = fp2_chip.add_no_carry(ctx, x3, ax);
+        let sum = fp2_chip.add_no_carry(ctx, sum, b);
+        fp2_chip.carry_mod(ctx, sum)
+    }
+
+    /// The 3-isogeny `E2' -> E2`.
+    fn iso_map(&self, ctx: &mut Context<Fr>, x: &Fq2Point, y: &Fq2Point) -> G2Point {
+        let fp2_chip = Fp2Chip::new(self.fp_chip);
+        let x_num = self.polynomial(ctx, x, &ISO_X_NUMERATOR);
+        let x_den = self.polynomial(ctx, x, &ISO_X_DENOMINATOR);
+        let y_num = self.polynomial(ctx, x, &ISO_Y_NUMERATOR);
+        let y_den = self.polynomial(ctx, x, &ISO_Y_DENOMINATOR);
+        let x = fp2_chip.divide_unsafe(ctx, x_num, x_den);
+        let y_num = fp2_chip.mul(ctx, y.clone(), y_num);
+        let y = fp2_chip.divide_unsafe(ctx, y_num, y_den);
+        EcPoint::new(x, y)
+    }
+
+    /// `sum_i coefficients[i] x^i` by Horner's rule.
+    fn polynomial(&self, ctx: &mut Context<Fr>, x: &Fq2Point, coefficients: &[ArkFq2]) -> Fq2Point {
+        let fp2_chip = Fp2Chip::new(self.fp_chip);
+        let (leading, rest) = coefficients.split_last().expect("non-empty polynomial");
+        let mut acc = self.constant(ctx, leading);
+        for coefficient in rest.iter().rev() {
+            let product = fp2_chip.mul_no_carry(ctx, acc, x.clone());
+            let coefficient = self.constant(ctx, coefficient);
+            let sum = fp2_chip.add_no_carry(ctx, product, coefficient);
+            acc = fp2_chip.carry_mod(ctx, sum);
+        }
+        acc
+    }
+
+    /// `h_eff P`, computed with `ψ` as in RFC 9380 appendix G.3.
+    fn clear_cofactor(&self, ctx: &mut Context<Fr>, p: &G2Point) -> G2Point {
+        let fp2_chip = Fp2Chip::new(self.fp_chip);
+        let g2_chip = EccChip::new(&fp2_chip);
+        let t1 = self.mul_by_x(ctx, p);
+        let t2 = self.psi(ctx, p);
+        let doubled = g2_chip.double(ctx, p.clone());
+        let t3 = self.psi(ctx, &doubled);
+        let t3 = self.psi(ctx, &t3);
+        let t3 = g2_chip.sub_unequal(ctx, t3, t2.clone(), true);
+        let t2 = g2_chip.add_unequal(ctx, t1.clone(), t2, true);
+        let t2 = self.mul_by_x(ctx, &t2);
+        let t3 = g2_chip.add_unequal(ctx, t3, t2, true);
+        let t3 = g2_chip.sub_unequal(ctx, t3, t1, true);
+        g2_chip.sub_unequal(ctx, t3, p.clone(), true)
+    }
+
+    /// `[x] P` for the negative BLS parameter `x`: double-and-add on `|x|`,
+    /// then a negation.
+    fn mul_by_x(&self, ctx: &mut Context<Fr>, p: &G2Point) -> G2Point {
+        let fp2_chip = Fp2Chip::new(self.fp_chip);
+        let g2_chip = EccChip::new(&fp2_chip);
+        let mut acc = p.clone();
+        for i in (0..BLS_X_ABS.ilog2()).rev() {
+            acc = g2_chip.double(ctx, acc);
+            if (BLS_X_ABS >> i) & 1 == 1 {
+                acc = g2_chip.add_unequal(ctx, acc, p.clone(), true);
+            }
+        }
+        g2_chip.negate(ctx, acc)
+    }
+
+    /// `ψ(x, y) = (PSI_X conj(x), PSI_Y conj(y))`.
+    fn psi(&self, ctx: &mut Context<Fr>, p: &G2Point) -> G2Point {
+        let x = self.conjugate_mul(ctx, p.x(), &PSI_X);
+        let y = self.conjugate_mul(ctx, p.y(), &PSI_Y);
+        EcPoint::new(x, y)
+    }
+
+    /// `conj(a) c`; the Frobenius map on `Fq2` is conjugation.
+    fn conjugate_mul(&self, ctx: &mut Context<Fr>, a: &Fq2Point, c: &ArkFq2) -> Fq2Point {
+        let fp2_chip = Fp2Chip::new(self.fp_chip);
+        let conjugate = FieldVector(vec![
+            a.0[0].clone(),
+            self.fp_chip.negate(ctx, a.0[1].clone()),
+        ]);
+        let c = self.constant(ctx, c);
+        fp2_chip.mul(ctx, conjugate, c)
+    }
+
+    /// `sgn0` of RFC 9380: the parity of the first non-zero coordinate.
+    fn sgn0(&self, ctx: &mut Context<Fr>, x: &Fq2Point) -> AssignedValue<Fr> {
+        let gate = self.fp_chip.gate();
+        let [parity0, parity1] = [0, 1].map(|i| {
+            let reduced = self.fp_chip.enforce_less_than(ctx, x.0[i].clone());
+            gate.num_to_bits(ctx, reduced.inner().limbs()[0], self.fp_chip.limb_bits)[0]
+        });
+        let c0_is_zero = self.fp_chip.is_zero(ctx, x.0[0].clone());
+        let c1_decides = gate.and(ctx, c0_is_zero, parity1);
+        gate.or(ctx, parity0, c1_decides)
+    }
+
+    /// `a` if `condition` is set, `b` otherwise.
+    fn select(
+        &self,
+        ctx: &mut Context<Fr>,
+        a: Fq2Point,
+        b: Fq2Point,
+        condition: AssignedValue<Fr>,
+    ) -> Fq2Point {
+        FieldVector(
+            a.0.into_iter()
+                .zip(b.0)
+                .map(|(a, b)| self.fp_chip.select(ctx, a, b, condition))
+                .collect(),
+        )
+    }
+
+    fn constant(&self, ctx: &mut Context<Fr>, c: &ArkFq2) -> Fq2Point {
+        Fp2Chip::new(self.fp_chip).load_constant(ctx, fq2_from_ark(c))
+    }
+
+    /// The value assigned to `x`, for computing witnesses.
+    fn value(&self, x: &Fq2Point) -> ArkFq2 {
+        let [c0, c1] =
+            [0, 1].map(|i| fq_to_ark(&self.fp_chip.get_assigned_value(&x.0[i].clone().into())));
+        ArkFq2::new(c0, c1)
+    }
+}
+
+/// Native `sgn0`.
+fn sgn0(x: &ArkFq2) -> bool {
+    let odd = |c: &ark_bls12_381::Fq| c.into_bigint().is_odd();
+    odd(&x.c0) || (x.c0.is_zero() && odd(&x.c1))
+}
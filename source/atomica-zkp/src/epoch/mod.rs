@@ -0,0 +1,411 @@
+//! Proofs of consecutive Aptos epoch changes.
+//!
+//! A relayer that was offline for many epochs has to replay every
+//! `EpochChangeProof` before it can trust a ledger info again. The circuits
+//! here let it do that off-chain and hand the EVM a single proof of
+//! "trusted state A leads to trusted state B":
+//!
+//! - [`EpochChangeCircuit`] proves one step: a quorum of epoch `e`'s
+//!   validators signed the epoch-ending ledger info, and epoch `e + 1` starts
+//!   with the validator set that ledger info names.
+//! - [`chain::EpochChainCircuit`] verifies one step proof together with its
+//!   own previous proof, so the final proof has a fixed verifier no matter
+//!   how many epochs it covers.
+//!
+//! A trusted state is the [`native::state`] commitment to an epoch number
+//! and its validator set, padded to the circuit's validator capacity.
+//! Public instances of [`EpochChangeCircuit`] are, in order:
+//!
+//! 1. the state the change starts from,
+//! 2. the state it ends in.
+//!
+//! # Message binding
+//!
+//! The circuit rebuilds the signing message
+//! `SHA3-256("APTOS::LedgerInfo") || BCS(LedgerInfo)` around the same epoch,
+//! key and voting power cells that go into the two states, and hashes it to
+//! G2 with [`hash_to_g2::HashToG2Chip`]. Fields the states do not commit to
+//! (round, block ids, version, timestamp, validator addresses and the
+//! consensus data hash) are free witness bytes, pinned by the signature.
+//!
+//! The size of the next validator set is a witness as well. The message is
+//! laid out for every size up to the capacity and a one-hot indicator picks
+//! the hashed one, so the layout does not depend on the set; slots past the
+//! size must hold the zero-power padding.
+//!
+//! The registry lists it as `epoch-change` at [`DEFAULT_VALIDATOR_CAPACITY`].
+
+pub mod chain;
+pub mod hash_to_g2;
+pub mod native;
+
+use std::iter;
+
+use ark_bls12_381::{G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use halo2_proofs_axiom::halo2curves::{bls12_381, bn256::Fr, group::Group};
+
+use crate::circuit::{BuilderCircuit, CircuitParams};
+use crate::gadgets::{
+    bytes::{load_bytes, load_constant_bytes, pack_words},
+    keccak::KeccakChip,
+};
+use crate::halo2_base::{
+    gates::{circuit::builder::BaseCircuitBuilder, GateInstructions, RangeChip, RangeInstructions},
+    AssignedValue, Context,
+    QuantumCell::Constant,
+};
+use crate::halo2_ecc::{
+    bls12_381::{pairing::PairingChip, Fp12Chip, FpChip},
+    ecc::EccChip,
+    fields::FieldChip,
+};
+use crate::ibe::{
+    chip::{G1Point, G2Point, IbeChip},
+    LIMB_BITS, NUM_LIMBS,
+};
+use hash_to_g2::HashToG2Chip;
+use native::{LedgerInfo, ValidatorSet};
+
+/// Default sizing: two pairings, hashing the ledger info to G2 and hashing
+/// two small validator sets.
+pub const EPOCH_CIRCUIT_PARAMS: CircuitParams = CircuitParams {
+    k: 21,
+    lookup_bits: 20,
+    minimum_rows: 20,
+};
+
+/// Validator slots of the default layout.
+pub const DEFAULT_VALIDATOR_CAPACITY: usize = 4;
+
+/// One epoch change, as extracted from an `EpochChangeProof`.
+#[derive(Clone, Debug)]
+pub struct EpochChange {
+    /// The validators of `ledger_info.epoch`.
+    pub validators: ValidatorSet,
+    /// Which of `validators` contributed to `signature`.
+    pub signers: Vec<bool>,
+    /// The aggregate BLS signature on `ledger_info`.
+    pub signature: G2Affine,
+    /// The epoch-ending ledger info, naming the next validator set.
+    pub ledger_info: LedgerInfo,
+}
+
+impl EpochChange {
+    /// The epoch whose validators signed the change.
+    pub fn epoch(&self) -> u64 {
+        self.ledger_info.epoch
+    }
+
+    /// The validator set of epoch `epoch() + 1`.
+    pub fn next_validators(&self) -> &ValidatorSet {
+        &self.ledger_info.next_validators
+    }
+
+    /// Checks the change natively: a quorum signed the ledger info.
+    pub fn verify(&self) -> bool {
+        native::has_quorum(&self.validators, &self.signers)
+            && native::verify_aggregate(
+                &native::aggregate_public_key(&self.validators, &self.signers),
+                &self.ledger_info.message_point(),
+                &self.signature,
+            )
+    }
+}
+
+/// Proves one [`EpochChange`] over validator sets of a fixed capacity.
+#[derive(Clone, Debug)]
+pub struct EpochChangeCircuit {
+    pub change: EpochChange,
+    /// Number of validator slots; sets are padded with zero-power entries.
+    pub capacity: usize,
+}
+
+impl EpochChangeCircuit {
+    pub fn new(change: EpochChange, capacity: usize) -> Self {
+        assert_eq!(
+            change.signers.len(),
+            change.validators.len(),
+            "one signer flag per validator"
+        );
+        assert!(
+            capacity < 0x80,
+            "{capacity} slots do not fit a one-byte BCS length"
+        );
+        assert_eq!(
+            change.ledger_info.next_addresses.len(),
+            change.next_validators().len(),
+            "one address per next validator"
+        );
+        for set in [&change.validators, change.next_validators()] {
+            assert!(
+                set.len() <= capacity,
+                "{} validators do not fit in {capacity} slots",
+                set.len()
+            );
+            assert!(
+                set.public_keys.iter().all(|key| !key.infinity),
+                "points at infinity are not valid validator keys"
+            );
+        }
+        assert!(
+            change.epoch() < u64::MAX,
+            "epoch {} has no successor",
+            change.epoch()
+        );
+        Self { change, capacity }
+    }
+
+    /// The state the change starts from.
+    pub fn state_in(&self) -> Fr {
+        native::state(
+            self.change.epoch(),
+            &pad(&self.change.validators, self.capacity),
+        )
+    }
+
+    /// The state the change ends in.
+    pub fn state_out(&self) -> Fr {
+        native::state(
+            self.change.epoch() + 1,
+            &pad(self.change.next_validators(), self.capacity),
+        )
+    }
+
+    /// Expected public instances in the layout described in the module docs.
+    pub fn instances(&self) -> Vec<Fr> {
+        vec![self.state_in(), self.state_out()]
+    }
+
+    /// Witnesses the next validator set and the ledger info handing over to
+    /// it, returning the state the change ends in and the ledger info's
+    /// message point `H(m)`.
+    ///
+    /// `epoch` holds the little-endian bytes of the signing epoch.
+    pub fn load_next_state(
+        &self,
+        ctx: &mut Context<Fr>,
+        range: &RangeChip<Fr>,
+        fp_chip: &FpChip<Fr>,
+        epoch: &[AssignedValue<Fr>],
+    ) -> (AssignedValue<Fr>, G2Point) {
+        let gate = range.gate();
+        let chip = IbeChip::new(fp_chip);
+        let g1_chip = EccChip::new(fp_chip);
+
+        let next_epoch = load_bytes(ctx, range, &(self.change.epoch() + 1).to_le_bytes());
+        let epoch_word = pack_words(ctx, gate, epoch)[0];
+        let next_epoch_word = pack_words(ctx, gate, &next_epoch)[0];
+        let successor = gate.add(ctx, epoch_word, Constant(Fr::one()));
+        ctx.constrain_equal(&successor, &next_epoch_word);
+        let next = self.load_state(
+            ctx,
+            range,
+            &chip,
+            &next_epoch,
+            self.change.next_validators(),
+        );
+
+        // The set holds `size` validators followed by padding.
+        let size = ctx.load_witness(Fr::from(self.change.next_validators().len() as u64));
+        let indicator = gate.idx_to_indicator(ctx, size, self.capacity + 1);
+        let in_range = gate.sum(ctx, indicator.clone());
+        gate.assert_is_const(ctx, &in_range, &Fr::one());
+        let generator = g1_chip.assign_constant_point(ctx, bls12_381::G1Affine::generator());
+        for (i, (key, power)) in next.keys.iter().zip(&next.powers).enumerate() {
+            let active = gate.sum(ctx, indicator[i + 1..].iter().copied());
+            let padded = g1_chip.select(ctx, key.clone(), generator.clone(), active);
+            g1_chip.assert_equal(ctx, padded, key.clone());
+            let padded = gate.mul(ctx, *power, active);
+            ctx.constrain_equal(&padded, power);
+        }
+
+        let messages = self.signing_messages(ctx, range, &chip, epoch, &next_epoch, &next);
+        let message = HashToG2Chip::new(fp_chip).hash_to_g2(ctx, &messages, &indicator);
+        (next.state, message)
+    }
+
+    /// Witnesses `epoch` and a padded validator set.
+    fn load_state(
+        &self,
+        ctx: &mut Context<Fr>,
+        range: &RangeChip<Fr>,
+        chip: &IbeChip,
+        epoch: &[AssignedValue<Fr>],
+        validators: &ValidatorSet,
+    ) -> LoadedValidators {
+        let gate = range.gate();
+        let validators = pad(validators, self.capacity);
+        let mut preimage = epoch.to_vec();
+        let mut keys = Vec::with_capacity(validators.len());
+        let mut powers = Vec::with_capacity(validators.len());
+        let mut power_bytes = Vec::with_capacity(validators.len());
+        for (key, power) in validators.public_keys.iter().zip(&validators.voting_powers) {
+            let key = chip.load_g1(ctx, key);
+            let bytes = load_bytes(ctx, range, &power.to_le_bytes());
+            preimage.extend(chip.g1_to_bytes(ctx, &key));
+            preimage.extend(bytes.iter().copied());
+            keys.push(key);
+            powers.push(pack_words(ctx, gate, &bytes)[0]);
+            power_bytes.push(bytes);
+        }
+        let digest = KeccakChip::new(gate).keccak256(ctx, &preimage);
+        let words = pack_words(ctx, gate, &digest);
+        let state = gate.mul_add(ctx, words[1], Constant(native::word_shift()), words[0]);
+        LoadedValidators {
+            state,
+            keys,
+            powers,
+            power_bytes,
+        }
+    }
+
+    /// The ledger info's signing message for every size `0..=capacity` of
+    /// the next validator set, around the epochs and the `next` set already
+    /// committed to.
+    fn signing_messages(
+        &self,
+        ctx: &mut Context<Fr>,
+        range: &RangeChip<Fr>,
+        chip: &IbeChip,
+        epoch: &[AssignedValue<Fr>],
+        next_epoch: &[AssignedValue<Fr>],
+        next: &LoadedValidators,
+    ) -> Vec<Vec<AssignedValue<Fr>>> {
+        let ledger_info = &self.change.ledger_info;
+        // BCS lengths of the 32-byte hashes and addresses and 48-byte keys.
+        let hash_length = load_constant_bytes(ctx, &[0x20]);
+        let key_length = load_constant_bytes(ctx, &[0x30]);
+
+        let mut header = load_constant_bytes(ctx, &native::ledger_info_prefix());
+        header.extend_from_slice(epoch);
+        header.extend(load_bytes(ctx, range, &ledger_info.round.to_le_bytes()));
+        header.extend_from_slice(&hash_length);
+        header.extend(load_bytes(ctx, range, &ledger_info.id));
+        header.extend_from_slice(&hash_length);
+        header.extend(load_bytes(ctx, range, &ledger_info.executed_state_id));
+        header.extend(load_bytes(ctx, range, &ledger_info.version.to_le_bytes()));
+        header.extend(load_bytes(
+            ctx,
+            range,
+            &ledger_info.timestamp_usecs.to_le_bytes(),
+        ));
+        // `next_epoch_state` is `Some`.
+        header.extend(load_constant_bytes(ctx, &[1]));
+        header.extend_from_slice(next_epoch);
+
+        let addresses = ledger_info
+            .next_addresses
+            .iter()
+            .chain(iter::repeat(&[0; 32]))
+            .take(self.capacity);
+        let mut entries = Vec::with_capacity(self.capacity);
+        for ((address, key), power) in addresses.zip(&next.keys).zip(&next.power_bytes) {
+            let mut entry = load_bytes(ctx, range, address);
+            entry.extend_from_slice(&key_length);
+            entry.extend(chip.g1_to_compressed_bytes(ctx, key));
+            entry.extend_from_slice(power);
+            entries.push(entry);
+        }
+
+        let mut footer = hash_length;
+        footer.extend(load_bytes(ctx, range, &ledger_info.consensus_data_hash));
+
+        let mut messages = Vec::with_capacity(self.capacity + 1);
+        for size in 0..=self.capacity {
+            let mut message = header.clone();
+            message.extend(load_constant_bytes(ctx, &[size as u8]));
+            message.extend(entries[..size].concat());
+            message.extend_from_slice(&footer);
+            messages.push(message);
+        }
+        messages
+    }
+}
+
+/// A padded validator set as witnessed by [`EpochChangeCircuit::load_state`].
+struct LoadedValidators {
+    /// The [`native::state`] commitment.
+    state: AssignedValue<Fr>,
+    keys: Vec<G1Point>,
+    powers: Vec<AssignedValue<Fr>>,
+    /// The little-endian bytes of each power.
+    power_bytes: Vec<Vec<AssignedValue<Fr>>>,
+}
+
+/// `validators` padded to `capacity` slots with zero-power copies of the
+/// generator.
+fn pad(validators: &ValidatorSet, capacity: usize) -> ValidatorSet {
+    let mut padded = validators.clone();
+    padded.public_keys.resize(capacity, G1Affine::generator());
+    padded.voting_powers.resize(capacity, 0);
+    padded
+}
+
+impl BuilderCircuit for EpochChangeCircuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let gate = range.gate();
+        let fp_chip = FpChip::new(range, LIMB_BITS, NUM_LIMBS);
+        let chip = IbeChip::new(&fp_chip);
+        let g1_chip = EccChip::new(&fp_chip);
+        let change = &self.change;
+
+        let epoch = load_bytes(ctx, range, &change.epoch().to_le_bytes());
+        let current = self.load_state(ctx, range, &chip, &epoch, &change.validators);
+        let (state_out, message) = self.load_next_state(ctx, range, &fp_chip, &epoch);
+
+        let signers: Vec<_> = (0..self.capacity)
+            .map(|i| {
+                let signed =
+                    ctx.load_witness(Fr::from(u64::from(change.signers.get(i) == Some(&true))));
+                gate.assert_bit(ctx, signed);
+                signed
+            })
+            .collect();
+
+        // Quorum: 3 * signed > 2 * total, all well below the field size.
+        let signed_power = gate.inner_product(ctx, signers.clone(), current.powers.clone());
+        let total_power = gate.sum(ctx, current.powers);
+        let twice_total = gate.mul(ctx, total_power, Constant(Fr::from(2)));
+        let thrice_signed = gate.mul(ctx, signed_power, Constant(Fr::from(3)));
+        let num_bits = 66 + usize::BITS as usize - self.capacity.leading_zeros() as usize;
+        range.check_less_than(ctx, twice_total, thrice_signed, num_bits);
+
+        // Aggregate key: start from a fixed offset so every addition has
+        // distinct inputs, then remove it. A quorum is never empty, so the
+        // final subtraction never sees equal points.
+        let offset = offset_point();
+        let mut acc = g1_chip.assign_constant_point(ctx, offset);
+        for (key, signed) in current.keys.iter().zip(&signers) {
+            let sum = g1_chip.add_unequal(ctx, acc.clone(), key.clone(), true);
+            acc = g1_chip.select(ctx, sum, acc, *signed);
+        }
+        let offset = g1_chip.assign_constant_point(ctx, offset);
+        let aggregate_key = g1_chip.sub_unequal(ctx, acc, offset, true);
+
+        // e(apk, H(m)) == e(G, sig), with m the signing message of the
+        // ledger info naming the next set.
+        let signature = chip.load_g2(ctx, &change.signature);
+        let generator = g1_chip.assign_constant_point(ctx, bls12_381::G1Affine::generator());
+        let pairing_chip = PairingChip::new(&fp_chip);
+        let lhs = pairing_chip.pairing(ctx, &message, &aggregate_key);
+        let rhs = pairing_chip.pairing(ctx, &signature, &generator);
+        Fp12Chip::new(&fp_chip).assert_equal(ctx, lhs, rhs);
+
+        vec![current.state, state_out]
+    }
+}
+
+/// `2^64 G`, a point no honest partial sum of validator keys hits.
+fn offset_point() -> bls12_381::G1Affine {
+    let mut point = bls12_381::G1::from(bls12_381::G1Affine::generator());
+    for _ in 0..64 {
+        point = point.double();
+    }
+    point.into()
+}
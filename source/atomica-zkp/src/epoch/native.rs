@@ -0,0 +1,219 @@
+//! Out-of-circuit reference for the epoch change relation.
+
+use ark_bls12_381::{g2, Bls12_381, G1Affine, G1Projective, G2Affine};
+use ark_ec::{
+    hashing::{curve_maps::wb::WBMap, map_to_curve_hasher::MapToCurveBasedHasher, HashToCurve},
+    pairing::Pairing,
+    short_weierstrass::Projective,
+    AffineRepr, CurveGroup,
+};
+use ark_ff::field_hashers::DefaultFieldHasher;
+use ark_serialize::CanonicalSerialize;
+use halo2_proofs_axiom::halo2curves::bn256::Fr;
+use num_bigint::BigUint;
+use sha2::Sha256;
+use sha3::{Digest, Keccak256, Sha3_256};
+
+use crate::gadgets::bytes::pack_words_native;
+use crate::halo2_base::utils::biguint_to_fe;
+use crate::ibe::chip::ark_g1_bytes;
+
+/// Domain separation tag of Aptos consensus signatures (BLS with proofs of
+/// possession).
+pub const CONSENSUS_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Domain separator Aptos hashes to salt `LedgerInfo` signing messages.
+pub const LEDGER_INFO_SALT: &[u8] = b"APTOS::LedgerInfo";
+
+/// `SHA3-256(LEDGER_INFO_SALT)`, the prefix of every signing message.
+pub fn ledger_info_prefix() -> [u8; 32] {
+    Sha3_256::digest(LEDGER_INFO_SALT).into()
+}
+
+/// A validator set in `ValidatorVerifier` order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorSet {
+    pub public_keys: Vec<G1Affine>,
+    pub voting_powers: Vec<u64>,
+}
+
+impl ValidatorSet {
+    pub fn new(public_keys: Vec<G1Affine>, voting_powers: Vec<u64>) -> Self {
+        assert_eq!(
+            public_keys.len(),
+            voting_powers.len(),
+            "one voting power per validator"
+        );
+        Self {
+            public_keys,
+            voting_powers,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.public_keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.public_keys.is_empty()
+    }
+
+    pub fn total_power(&self) -> u128 {
+        self.voting_powers.iter().map(|&power| power as u128).sum()
+    }
+
+    /// `PK_1 || power_1 || .. || PK_n || power_n`, each key as canonical
+    /// little-endian `x || y` and each power as 8 little-endian bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.public_keys
+            .iter()
+            .zip(&self.voting_powers)
+            .flat_map(|(key, power)| [ark_g1_bytes(key), power.to_le_bytes().to_vec()].concat())
+            .collect()
+    }
+}
+
+/// An epoch-ending `LedgerInfo`: the block that closes `epoch` and the
+/// `next_epoch_state` it hands over to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LedgerInfo {
+    pub epoch: u64,
+    pub round: u64,
+    pub id: [u8; 32],
+    pub executed_state_id: [u8; 32],
+    pub version: u64,
+    pub timestamp_usecs: u64,
+    /// Account addresses of `next_validators`, in the same order.
+    pub next_addresses: Vec<[u8; 32]>,
+    /// The validator set of epoch `epoch + 1`.
+    pub next_validators: ValidatorSet,
+    pub consensus_data_hash: [u8; 32],
+}
+
+impl LedgerInfo {
+    /// BCS encoding, laid out like `aptos_types::ledger_info::LedgerInfo`.
+    pub fn to_bcs(&self) -> Vec<u8> {
+        assert_eq!(
+            self.next_addresses.len(),
+            self.next_validators.len(),
+            "one address per validator"
+        );
+        let next_epoch = self
+            .epoch
+            .checked_add(1)
+            .expect("the last epoch has no successor");
+        let mut bytes = Vec::new();
+        bytes.extend(self.epoch.to_le_bytes());
+        bytes.extend(self.round.to_le_bytes());
+        bytes.extend(bcs_bytes(&self.id));
+        bytes.extend(bcs_bytes(&self.executed_state_id));
+        bytes.extend(self.version.to_le_bytes());
+        bytes.extend(self.timestamp_usecs.to_le_bytes());
+        // `next_epoch_state` is `Some(EpochState { epoch, verifier })`.
+        bytes.push(1);
+        bytes.extend(next_epoch.to_le_bytes());
+        bytes.extend(uleb128(self.next_validators.len()));
+        for ((address, key), power) in self
+            .next_addresses
+            .iter()
+            .zip(&self.next_validators.public_keys)
+            .zip(&self.next_validators.voting_powers)
+        {
+            bytes.extend(address);
+            bytes.extend(bcs_bytes(&compressed_g1(key)));
+            bytes.extend(power.to_le_bytes());
+        }
+        bytes.extend(bcs_bytes(&self.consensus_data_hash));
+        bytes
+    }
+
+    /// The message consensus signatures over this ledger info sign.
+    pub fn signing_message(&self) -> Vec<u8> {
+        [ledger_info_prefix().as_slice(), &self.to_bcs()].concat()
+    }
+
+    /// `H(m)` of the signing message.
+    pub fn message_point(&self) -> G2Affine {
+        message_point(&self.signing_message())
+    }
+}
+
+/// A BCS byte vector: ULEB128 length, then the bytes.
+fn bcs_bytes(bytes: &[u8]) -> Vec<u8> {
+    [uleb128(bytes.len()), bytes.to_vec()].concat()
+}
+
+fn uleb128(mut value: usize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    while value >= 0x80 {
+        bytes.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+    bytes
+}
+
+/// The 48-byte compressed encoding Aptos uses for BLS public keys.
+pub fn compressed_g1(point: &G1Affine) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(48);
+    point
+        .serialize_compressed(&mut bytes)
+        .expect("writing to a vector cannot fail");
+    bytes
+}
+
+/// The trusted state after `epoch` began with `validators`:
+/// `Keccak256(epoch_le || validators)` read as two 16-byte words
+/// `lo + hi * 2^128`, reduced into the BN254 scalar field.
+pub fn state(epoch: u64, validators: &ValidatorSet) -> Fr {
+    let mut hasher = Keccak256::new();
+    hasher.update(epoch.to_le_bytes());
+    hasher.update(validators.to_bytes());
+    let words = pack_words_native::<Fr>(&hasher.finalize());
+    words[0] + words[1] * word_shift()
+}
+
+/// `2^128`, the weight of the high word of a packed digest.
+pub(crate) fn word_shift() -> Fr {
+    biguint_to_fe(&(BigUint::from(1u8) << 128))
+}
+
+/// `H(m)`, the G2 point consensus signatures over `signing_message` sign.
+pub fn message_point(signing_message: &[u8]) -> G2Affine {
+    MapToCurveBasedHasher::<
+        Projective<g2::Config>,
+        DefaultFieldHasher<Sha256, 128>,
+        WBMap<g2::Config>,
+    >::new(CONSENSUS_DST)
+    .expect("valid hash-to-curve parameters")
+    .hash(signing_message)
+    .expect("hash to G2 failed")
+}
+
+/// Sum of the public keys of the validators flagged in `signers`.
+pub fn aggregate_public_key(validators: &ValidatorSet, signers: &[bool]) -> G1Affine {
+    validators
+        .public_keys
+        .iter()
+        .zip(signers)
+        .filter(|(_, &signed)| signed)
+        .fold(G1Projective::default(), |acc, (key, _)| acc + key)
+        .into_affine()
+}
+
+/// Whether `signers` hold strictly more than two thirds of the voting power.
+pub fn has_quorum(validators: &ValidatorSet, signers: &[bool]) -> bool {
+    let signed: u128 = validators
+        .voting_powers
+        .iter()
+        .zip(signers)
+        .filter(|(_, &signed)| signed)
+        .map(|(&power, _)| power as u128)
+        .sum();
+    3 * signed > 2 * validators.total_power()
+}
+
+/// `e(apk, H) == e(G, sig)`.
+pub fn verify_aggregate(public_key: &G1Affine, message: &G2Affine, signature: &G2Affine) -> bool {
+    Bls12_381::pairing(public_key, message) == Bls12_381::pairing(G1Affine::generator(), signature)
+}
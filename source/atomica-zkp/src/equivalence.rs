@@ -0,0 +1,45 @@
+//! The smallest circuit in the registry: a private input equal to 42.
+//!
+//! It has no public instances and fits the `hermez-raw-9` trusted setup, so
+//! it is what the Solidity and key caching pipelines are tested end to end
+//! with. `tests/common` keeps a hand-written halo2 version of the same
+//! relation for the raw prover tests.
+
+use halo2_proofs_axiom::halo2curves::bn256::Fr;
+
+use crate::circuit::{BuilderCircuit, CircuitParams};
+use crate::halo2_base::{
+    gates::{circuit::builder::BaseCircuitBuilder, GateInstructions, RangeChip, RangeInstructions},
+    AssignedValue,
+};
+
+/// The value the private input must equal.
+pub const EXPECTED_INPUT: u64 = 42;
+
+/// Sized for the `hermez-raw-9` parameters.
+pub const EQUIVALENCE_CIRCUIT_PARAMS: CircuitParams = CircuitParams {
+    k: 9,
+    lookup_bits: 8,
+    minimum_rows: 20,
+};
+
+/// Proves knowledge of a private input equal to [`EXPECTED_INPUT`].
+#[derive(Clone, Copy, Debug)]
+pub struct EquivalenceCircuit {
+    pub private_input: u64,
+}
+
+impl BuilderCircuit for EquivalenceCircuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let input = ctx.load_witness(Fr::from(self.private_input));
+        range
+            .gate()
+            .assert_is_const(ctx, &input, &Fr::from(EXPECTED_INPUT));
+        vec![]
+    }
+}
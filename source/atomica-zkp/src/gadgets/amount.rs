@@ -0,0 +1,189 @@
+use num_bigint::BigUint;
+
+use crate::halo2_base::{
+    gates::{GateInstructions, RangeChip, RangeInstructions},
+    utils::{biguint_to_fe, fe_to_biguint, ScalarField},
+    AssignedValue, Context,
+    QuantumCell::Constant,
+};
+
+/// Width of the limbs an amount is decomposed into.
+///
+/// Every limb costs one lookup into the range chip's table, so 16-bit limbs
+/// halve the lookups of 8-bit ones at the price of a 2^16-row table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LimbBits {
+    Eight = 8,
+    Sixteen = 16,
+}
+
+/// Result of [`AmountChip::compare`]; exactly one flag is set.
+#[derive(Clone, Copy, Debug)]
+pub struct AssignedOrdering<F: ScalarField> {
+    pub lt: AssignedValue<F>,
+    pub eq: AssignedValue<F>,
+    pub gt: AssignedValue<F>,
+}
+
+/// Range checks and comparisons for `u64`/`u128` amounts such as bids,
+/// balances and voting power.
+///
+/// Values are split into explicit limbs, each looked up in the range chip's
+/// table and recomposed with an equality constraint, so overflowing witnesses
+/// fail the recomposition rather than silently wrapping.
+#[derive(Clone, Copy, Debug)]
+pub struct AmountChip<'range, F: ScalarField> {
+    range: &'range RangeChip<F>,
+    limb_bits: usize,
+}
+
+impl<'range, F: ScalarField> AmountChip<'range, F> {
+    pub fn new(range: &'range RangeChip<F>, limb_bits: LimbBits) -> Self {
+        let limb_bits = limb_bits as usize;
+        assert!(
+            range.lookup_bits() >= limb_bits,
+            "{limb_bits}-bit limbs need a lookup table of at least {limb_bits} bits"
+        );
+        Self { range, limb_bits }
+    }
+
+    pub fn range(&self) -> &RangeChip<F> {
+        self.range
+    }
+
+    /// Decomposes `a` into little-endian limbs covering `num_bits` bits.
+    ///
+    /// Constrains `a < 2^num_bits`; `num_bits` must be a multiple of the limb
+    /// width.
+    pub fn decompose(
+        &self,
+        ctx: &mut Context<F>,
+        a: AssignedValue<F>,
+        num_bits: usize,
+    ) -> Vec<AssignedValue<F>> {
+        assert_eq!(num_bits % self.limb_bits, 0, "{num_bits} bits is not a whole number of limbs");
+        assert!(num_bits < F::CAPACITY as usize, "{num_bits} bits would wrap the field");
+        let num_limbs = num_bits / self.limb_bits;
+
+        let limbs: Vec<_> = a
+            .value()
+            .to_u64_limbs(num_limbs, self.limb_bits)
+            .into_iter()
+            .map(|limb| {
+                let cell = ctx.load_witness(F::from(limb));
+                self.range.range_check(ctx, cell, self.limb_bits);
+                cell
+            })
+            .collect();
+
+        let gate = self.range.gate();
+        let shifts = (0..num_limbs).map(|i| Constant(gate.pow_of_two()[i * self.limb_bits]));
+        let recomposed = gate.inner_product(ctx, limbs.iter().copied(), shifts);
+        ctx.constrain_equal(&recomposed, &a);
+        limbs
+    }
+
+    /// Constrains `a` to `[0, 2^64)`.
+    pub fn assert_u64(&self, ctx: &mut Context<F>, a: AssignedValue<F>) {
+        self.decompose(ctx, a, 64);
+    }
+
+    /// Constrains `a` to `[0, 2^128)`.
+    pub fn assert_u128(&self, ctx: &mut Context<F>, a: AssignedValue<F>) {
+        self.decompose(ctx, a, 128);
+    }
+
+    /// Witnesses a `u64` and range-checks it.
+    pub fn load_u64(&self, ctx: &mut Context<F>, value: u64) -> AssignedValue<F> {
+        let cell = ctx.load_witness(F::from(value));
+        self.assert_u64(ctx, cell);
+        cell
+    }
+
+    /// Witnesses a `u128` and range-checks it.
+    pub fn load_u128(&self, ctx: &mut Context<F>, value: u128) -> AssignedValue<F> {
+        let cell = ctx.load_witness(F::from_u128(value));
+        self.assert_u128(ctx, cell);
+        cell
+    }
+
+    /// Returns `a < b` as a boolean cell.
+    ///
+    /// Both inputs must already be constrained to `num_bits`. Works by
+    /// splitting `a - b + 2^num_bits` into its top bit and a `num_bits` remainder:
+    /// the top bit is clear exactly when `a < b`.
+    pub fn is_less_than(
+        &self,
+        ctx: &mut Context<F>,
+        a: AssignedValue<F>,
+        b: AssignedValue<F>,
+        num_bits: usize,
+    ) -> AssignedValue<F> {
+        let gate = self.range.gate();
+        let offset = gate.pow_of_two()[num_bits];
+        let shifted = gate.add(ctx, a, Constant(offset));
+        let shifted = gate.sub(ctx, shifted, b);
+
+        let value = fe_to_biguint(shifted.value());
+        let high = ctx.load_witness(biguint_to_fe(&(&value >> num_bits)));
+        gate.assert_bit(ctx, high);
+        let low = ctx.load_witness(biguint_to_fe(&(value % (BigUint::from(1u32) << num_bits))));
+        self.decompose(ctx, low, num_bits);
+
+        let recomposed = gate.mul_add(ctx, high, Constant(offset), low);
+        ctx.constrain_equal(&recomposed, &shifted);
+        gate.not(ctx, high)
+    }
+
+    /// Returns `a <= b` as a boolean cell; inputs as for [`Self::is_less_than`].
+    pub fn is_less_than_or_equal(
+        &self,
+        ctx: &mut Context<F>,
+        a: AssignedValue<F>,
+        b: AssignedValue<F>,
+        num_bits: usize,
+    ) -> AssignedValue<F> {
+        let gt = self.is_less_than(ctx, b, a, num_bits);
+        self.range.gate().not(ctx, gt)
+    }
+
+    /// Constrains `a < b`; inputs as for [`Self::is_less_than`].
+    pub fn assert_less_than(
+        &self,
+        ctx: &mut Context<F>,
+        a: AssignedValue<F>,
+        b: AssignedValue<F>,
+        num_bits: usize,
+    ) {
+        let lt = self.is_less_than(ctx, a, b, num_bits);
+        self.range.gate().assert_is_const(ctx, &lt, &F::ONE);
+    }
+
+    /// Three-way comparison; inputs as for [`Self::is_less_than`].
+    pub fn compare(
+        &self,
+        ctx: &mut Context<F>,
+        a: AssignedValue<F>,
+        b: AssignedValue<F>,
+        num_bits: usize,
+    ) -> AssignedOrdering<F> {
+        let gate = self.range.gate();
+        let lt = self.is_less_than(ctx, a, b, num_bits);
+        let eq = gate.is_equal(ctx, a, b);
+        let lt_or_eq = gate.add(ctx, lt, eq);
+        let gt = gate.not(ctx, lt_or_eq);
+        AssignedOrdering { lt, eq, gt }
+    }
+
+    /// The larger of `a` and `b`; inputs as for [`Self::is_less_than`].
+    pub fn max(
+        &self,
+        ctx: &mut Context<F>,
+        a: AssignedValue<F>,
+        b: AssignedValue<F>,
+        num_bits: usize,
+    ) -> AssignedValue<F> {
+        let lt = self.is_less_than(ctx, a, b, num_bits);
+        self.range.gate().select(ctx, b, a, lt)
+    }
+}
@@ -0,0 +1,153 @@
+use crate::halo2_base::{
+    gates::{GateChip, GateInstructions, RangeChip, RangeInstructions},
+    utils::ScalarField,
+    AssignedValue, Context,
+    QuantumCell::Constant,
+};
+
+/// Bytes packed into each public-instance word.
+///
+/// 16 bytes keeps every word below the BN254 scalar modulus and lines up with
+/// `uint128` on the EVM side.
+pub const WORD_BYTES: usize = 16;
+
+/// Witnesses `bytes`, range-checking every cell to 8 bits.
+pub fn load_bytes<F: ScalarField>(
+    ctx: &mut Context<F>,
+    range: &RangeChip<F>,
+    bytes: &[u8],
+) -> Vec<AssignedValue<F>> {
+    bytes
+        .iter()
+        .map(|byte| {
+            let cell = ctx.load_witness(F::from(*byte as u64));
+            range.range_check(ctx, cell, 8);
+            cell
+        })
+        .collect()
+}
+
+/// Assigns `bytes` as constants.
+pub fn load_constant_bytes<F: ScalarField>(
+    ctx: &mut Context<F>,
+    bytes: &[u8],
+) -> Vec<AssignedValue<F>> {
+    bytes
+        .iter()
+        .map(|byte| ctx.load_constant(F::from(*byte as u64)))
+        .collect()
+}
+
+/// Splits every byte into eight little-endian bits.
+pub fn bytes_to_bits<F: ScalarField>(
+    ctx: &mut Context<F>,
+    gate: &GateChip<F>,
+    bytes: &[AssignedValue<F>],
+) -> Vec<AssignedValue<F>> {
+    let mut bits = Vec::with_capacity(bytes.len() * 8);
+    for byte in bytes {
+        bits.extend(gate.num_to_bits(ctx, *byte, 8));
+    }
+    bits
+}
+
+/// Recomposes little-endian bits into a single value.
+pub fn bits_to_num<F: ScalarField>(
+    ctx: &mut Context<F>,
+    gate: &GateChip<F>,
+    bits: &[AssignedValue<F>],
+) -> AssignedValue<F> {
+    let pow2 = gate.pow_of_two()[..bits.len()].iter().map(|c| Constant(*c));
+    gate.inner_product(ctx, bits.iter().copied(), pow2)
+}
+
+/// Recomposes little-endian bits into bytes.
+pub fn bits_to_bytes<F: ScalarField>(
+    ctx: &mut Context<F>,
+    gate: &GateChip<F>,
+    bits: &[AssignedValue<F>],
+) -> Vec<AssignedValue<F>> {
+    assert_eq!(bits.len() % 8, 0, "bit string is not byte aligned");
+    bits.chunks(8).map(|chunk| bits_to_num(ctx, gate, chunk)).collect()
+}
+
+/// XOR of two boolean cells, computed as `a + b * (1 - 2a)`.
+pub fn xor_bit<F: ScalarField>(
+    ctx: &mut Context<F>,
+    gate: &GateChip<F>,
+    a: AssignedValue<F>,
+    b: AssignedValue<F>,
+) -> AssignedValue<F> {
+    let one_minus_two_a = gate.mul_add(ctx, a, Constant(-F::from(2)), Constant(F::ONE));
+    gate.mul_add(ctx, b, one_minus_two_a, a)
+}
+
+/// Bytewise XOR, truncated to the shorter input like the Move `xor` helper.
+pub fn xor_bytes<F: ScalarField>(
+    ctx: &mut Context<F>,
+    gate: &GateChip<F>,
+    a: &[AssignedValue<F>],
+    b: &[AssignedValue<F>],
+) -> Vec<AssignedValue<F>> {
+    let len = a.len().min(b.len());
+    let a_bits = bytes_to_bits(ctx, gate, &a[..len]);
+    let b_bits = bytes_to_bits(ctx, gate, &b[..len]);
+    let bits: Vec<_> = a_bits
+        .into_iter()
+        .zip(b_bits)
+        .map(|(x, y)| xor_bit(ctx, gate, x, y))
+        .collect();
+    bits_to_bytes(ctx, gate, &bits)
+}
+
+/// Serialises a big integer held in `limb_bits`-wide limbs into `num_bytes`
+/// little-endian bytes, constraining every bit above `8 * num_bytes` to zero.
+///
+/// The caller must pass limbs of a value already reduced below the modulus,
+/// otherwise the encoding is not canonical.
+pub fn limbs_to_bytes_le<F: ScalarField>(
+    ctx: &mut Context<F>,
+    gate: &GateChip<F>,
+    limbs: &[AssignedValue<F>],
+    limb_bits: usize,
+    num_bytes: usize,
+) -> Vec<AssignedValue<F>> {
+    let mut bits = Vec::with_capacity(limbs.len() * limb_bits);
+    for limb in limbs {
+        bits.extend(gate.num_to_bits(ctx, *limb, limb_bits));
+    }
+    assert!(bits.len() >= num_bytes * 8, "limbs too narrow for {num_bytes} bytes");
+    let (value_bits, high_bits) = bits.split_at(num_bytes * 8);
+    for bit in high_bits {
+        gate.assert_is_const(ctx, bit, &F::ZERO);
+    }
+    bits_to_bytes(ctx, gate, value_bits)
+}
+
+/// Packs bytes little-endian into [`WORD_BYTES`]-byte public-instance words.
+pub fn pack_words<F: ScalarField>(
+    ctx: &mut Context<F>,
+    gate: &GateChip<F>,
+    bytes: &[AssignedValue<F>],
+) -> Vec<AssignedValue<F>> {
+    bytes
+        .chunks(WORD_BYTES)
+        .map(|chunk| {
+            let shifts = (0..chunk.len()).map(|i| Constant(gate.pow_of_two()[8 * i]));
+            gate.inner_product(ctx, chunk.iter().copied(), shifts)
+        })
+        .collect()
+}
+
+/// Native counterpart of [`pack_words`], used to compute expected instances.
+pub fn pack_words_native<F: ScalarField>(bytes: &[u8]) -> Vec<F> {
+    bytes
+        .chunks(WORD_BYTES)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .rev()
+                .fold(F::ZERO, |acc, byte| acc * F::from(256) + F::from(*byte as u64))
+        })
+        .collect()
+}
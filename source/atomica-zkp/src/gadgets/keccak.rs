@@ -0,0 +1,193 @@
+use super::bytes::{bits_to_bytes, bytes_to_bits, xor_bit};
+use crate::halo2_base::{
+    gates::{GateChip, GateInstructions},
+    utils::ScalarField,
+    AssignedValue, Context,
+};
+
+/// Sponge rate of Keccak-256 / SHA3-256 in bytes.
+pub const RATE_BYTES: usize = 136;
+
+/// Digest length of Keccak-256 / SHA3-256 in bytes.
+pub const DIGEST_BYTES: usize = 32;
+
+/// Domain separation byte of Ethereum's (pre-FIPS) Keccak-256.
+const KECCAK_DOMAIN: u8 = 0x01;
+
+/// Domain separation byte of FIPS-202 SHA3-256, used for Aptos crypto hashes.
+const SHA3_DOMAIN: u8 = 0x06;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000_0000_0000_0001,
+    0x0000_0000_0000_8082,
+    0x8000_0000_0000_808A,
+    0x8000_0000_8000_8000,
+    0x0000_0000_0000_808B,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8009,
+    0x0000_0000_0000_008A,
+    0x0000_0000_0000_0088,
+    0x0000_0000_8000_8009,
+    0x0000_0000_8000_000A,
+    0x0000_0000_8000_808B,
+    0x8000_0000_0000_008B,
+    0x8000_0000_0000_8089,
+    0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002,
+    0x8000_0000_0000_0080,
+    0x0000_0000_0000_800A,
+    0x8000_0000_8000_000A,
+    0x8000_0000_8000_8081,
+    0x8000_0000_0000_8080,
+    0x0000_0000_8000_0001,
+    0x8000_0000_8000_8008,
+];
+
+/// ρ rotation offsets, indexed `[x][y]`.
+const ROTATIONS: [[usize; 5]; 5] = [
+    [0, 36, 3, 41, 18],
+    [1, 44, 10, 45, 2],
+    [62, 6, 43, 15, 61],
+    [28, 55, 25, 21, 56],
+    [27, 20, 39, 8, 14],
+];
+
+/// A 64-bit lane as little-endian boolean cells.
+type Lane<F> = Vec<AssignedValue<F>>;
+
+/// Keccak-f[1600] over bit-decomposed lanes.
+///
+/// Every state bit is its own advice cell, so XOR and AND-NOT cost a couple of
+/// gates each and rotations are free rewiring. That is roughly 1.2M cells per
+/// permutation, which is acceptable next to the non-native pairings that feed
+/// it and keeps the gadget on plain halo2-base gates.
+#[derive(Clone, Copy, Debug)]
+pub struct KeccakChip<'a, F: ScalarField> {
+    gate: &'a GateChip<F>,
+}
+
+impl<'a, F: ScalarField> KeccakChip<'a, F> {
+    pub fn new(gate: &'a GateChip<F>) -> Self {
+        Self { gate }
+    }
+
+    /// Ethereum's Keccak-256 of a fixed-length byte string.
+    ///
+    /// Input cells must already be constrained to bytes.
+    pub fn keccak256(
+        &self,
+        ctx: &mut Context<F>,
+        bytes: &[AssignedValue<F>],
+    ) -> Vec<AssignedValue<F>> {
+        self.sponge(ctx, bytes, KECCAK_DOMAIN)
+    }
+
+    /// FIPS-202 SHA3-256 of a fixed-length byte string.
+    ///
+    /// Input cells must already be constrained to bytes.
+    pub fn sha3_256(
+        &self,
+        ctx: &mut Context<F>,
+        bytes: &[AssignedValue<F>],
+    ) -> Vec<AssignedValue<F>> {
+        self.sponge(ctx, bytes, SHA3_DOMAIN)
+    }
+
+    fn sponge(
+        &self,
+        ctx: &mut Context<F>,
+        bytes: &[AssignedValue<F>],
+        domain: u8,
+    ) -> Vec<AssignedValue<F>> {
+        // The input length is fixed when the circuit is built, so the
+        // pad10*1 suffix is made of constants.
+        let mut padding = vec![0u8; RATE_BYTES - bytes.len() % RATE_BYTES];
+        padding[0] ^= domain;
+        *padding.last_mut().unwrap() ^= 0x80;
+
+        let mut bits = bytes_to_bits(ctx, self.gate, bytes);
+        for byte in padding {
+            for j in 0..8 {
+                bits.push(ctx.load_constant(F::from(((byte >> j) & 1) as u64)));
+            }
+        }
+
+        let zero = ctx.load_zero();
+        let mut state: Vec<Lane<F>> = vec![vec![zero; 64]; 25];
+        for (block_idx, block) in bits.chunks(RATE_BYTES * 8).enumerate() {
+            for (i, lane) in block.chunks(64).enumerate() {
+                for (z, bit) in lane.iter().enumerate() {
+                    // Nothing to mix into the all-zero initial state.
+                    state[i][z] = if block_idx == 0 {
+                        *bit
+                    } else {
+                        xor_bit(ctx, self.gate, state[i][z], *bit)
+                    };
+                }
+            }
+            self.permute(ctx, &mut state);
+        }
+
+        let digest: Vec<_> = state[..DIGEST_BYTES / 8].iter().flatten().copied().collect();
+        bits_to_bytes(ctx, self.gate, &digest)
+    }
+
+    /// Keccak-f[1600]; lane `(x, y)` lives at `state[x + 5 * y]`.
+    fn permute(&self, ctx: &mut Context<F>, state: &mut [Lane<F>]) {
+        for rc in ROUND_CONSTANTS {
+            // θ
+            let mut c = Vec::with_capacity(5);
+            for x in 0..5 {
+                let mut column = state[x].clone();
+                for y in 1..5 {
+                    for z in 0..64 {
+                        column[z] = xor_bit(ctx, self.gate, column[z], state[x + 5 * y][z]);
+                    }
+                }
+                c.push(column);
+            }
+            for x in 0..5 {
+                let mut d = Vec::with_capacity(64);
+                for z in 0..64 {
+                    d.push(xor_bit(ctx, self.gate, c[(x + 4) % 5][z], c[(x + 1) % 5][(z + 63) % 64]));
+                }
+                for y in 0..5 {
+                    for z in 0..64 {
+                        state[x + 5 * y][z] = xor_bit(ctx, self.gate, state[x + 5 * y][z], d[z]);
+                    }
+                }
+            }
+
+            // ρ and π are pure rewiring.
+            let mut b = state.to_vec();
+            for x in 0..5 {
+                for y in 0..5 {
+                    let r = ROTATIONS[x][y];
+                    b[y + 5 * ((2 * x + 3 * y) % 5)] =
+                        (0..64).map(|z| state[x + 5 * y][(z + 64 - r) % 64]).collect();
+                }
+            }
+
+            // χ
+            for x in 0..5 {
+                for y in 0..5 {
+                    for z in 0..64 {
+                        let next = b[(x + 1) % 5 + 5 * y][z];
+                        let after = b[(x + 2) % 5 + 5 * y][z];
+                        // (!next & after) = after - next * after
+                        let and_not = self.gate.sub_mul(ctx, after, next, after);
+                        state[x + 5 * y][z] = xor_bit(ctx, self.gate, b[x + 5 * y][z], and_not);
+                    }
+                }
+            }
+
+            // ι: XOR with a constant is either a no-op or a NOT.
+            for z in 0..64 {
+                if (rc >> z) & 1 == 1 {
+                    state[0][z] = self.gate.not(ctx, state[0][z]);
+                }
+            }
+        }
+    }
+}
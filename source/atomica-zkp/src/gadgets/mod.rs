@@ -0,0 +1,5 @@
+pub mod amount;
+pub mod bytes;
+pub mod keccak;
+pub mod sha256;
+pub mod sha512;
@@ -0,0 +1,359 @@
+use super::bytes::{bits_to_bytes, bits_to_num, bytes_to_bits, xor_bit};
+use crate::halo2_base::{
+    gates::{GateChip, GateInstructions},
+    utils::ScalarField,
+    AssignedValue, Context,
+    QuantumCell::{Constant, Existing},
+};
+
+/// Block size of SHA-256 in bytes.
+pub const BLOCK_BYTES: usize = 64;
+
+/// Digest length of SHA-256 in bytes.
+pub const DIGEST_BYTES: usize = 32;
+
+const INITIAL_STATE: [u32; 8] = [
+    0x6a09_e667,
+    0xbb67_ae85,
+    0x3c6e_f372,
+    0xa54f_f53a,
+    0x510e_527f,
+    0x9b05_688c,
+    0x1f83_d9ab,
+    0x5be0_cd19,
+];
+
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a_2f98,
+    0x7137_4491,
+    0xb5c0_fbcf,
+    0xe9b5_dba5,
+    0x3956_c25b,
+    0x59f1_11f1,
+    0x923f_82a4,
+    0xab1c_5ed5,
+    0xd807_aa98,
+    0x1283_5b01,
+    0x2431_85be,
+    0x550c_7dc3,
+    0x72be_5d74,
+    0x80de_b1fe,
+    0x9bdc_06a7,
+    0xc19b_f174,
+    0xe49b_69c1,
+    0xefbe_4786,
+    0x0fc1_9dc6,
+    0x240c_a1cc,
+    0x2de9_2c6f,
+    0x4a74_84aa,
+    0x5cb0_a9dc,
+    0x76f9_88da,
+    0x983e_5152,
+    0xa831_c66d,
+    0xb003_27c8,
+    0xbf59_7fc7,
+    0xc6e0_0bf3,
+    0xd5a7_9147,
+    0x06ca_6351,
+    0x1429_2967,
+    0x27b7_0a85,
+    0x2e1b_2138,
+    0x4d2c_6dfc,
+    0x5338_0d13,
+    0x650a_7354,
+    0x766a_0abb,
+    0x81c2_c92e,
+    0x9272_2c85,
+    0xa2bf_e8a1,
+    0xa81a_664b,
+    0xc24b_8b70,
+    0xc76c_51a3,
+    0xd192_e819,
+    0xd699_0624,
+    0xf40e_3585,
+    0x106a_a070,
+    0x19a4_c116,
+    0x1e37_6c08,
+    0x2748_774c,
+    0x34b0_bcb5,
+    0x391c_0cb3,
+    0x4ed8_aa4a,
+    0x5b9c_ca4f,
+    0x682e_6ff3,
+    0x748f_82ee,
+    0x78a5_636f,
+    0x84c8_7814,
+    0x8cc7_0208,
+    0x90be_fffa,
+    0xa450_6ceb,
+    0xbef9_a3f7,
+    0xc671_78f2,
+];
+
+/// A 32-bit word as little-endian boolean cells.
+type Word<F> = Vec<AssignedValue<F>>;
+
+/// SHA-256 over bit-decomposed words, built like [`super::sha512::Sha512Chip`].
+#[derive(Clone, Copy, Debug)]
+pub struct Sha256Chip<'a, F: ScalarField> {
+    gate: &'a GateChip<F>,
+}
+
+impl<'a, F: ScalarField> Sha256Chip<'a, F> {
+    pub fn new(gate: &'a GateChip<F>) -> Self {
+        Self { gate }
+    }
+
+    /// SHA-256 of a fixed-length byte string.
+    ///
+    /// Input cells must already be constrained to bytes.
+    pub fn sha256(
+        &self,
+        ctx: &mut Context<F>,
+        bytes: &[AssignedValue<F>],
+    ) -> Vec<AssignedValue<F>> {
+        let message = self.pad(ctx, bytes);
+        let mut state = self.initial_state(ctx);
+        for block in message.chunks(BLOCK_BYTES) {
+            self.compress(ctx, &mut state, block);
+        }
+        self.digest(ctx, &state)
+    }
+
+    /// SHA-256 of whichever of `messages` the one-hot `indicator` selects.
+    ///
+    /// This is how a message whose length is only known at proving time is
+    /// hashed: the caller lists every layout the message can have. All
+    /// candidates are padded, the padded inputs are merged byte by byte and
+    /// hashed as far as the longest one, and the digest is read off after
+    /// the last block of the selected candidate.
+    pub fn sha256_any(
+        &self,
+        ctx: &mut Context<F>,
+        messages: &[Vec<AssignedValue<F>>],
+        indicator: &[AssignedValue<F>],
+    ) -> Vec<AssignedValue<F>> {
+        assert_eq!(
+            messages.len(),
+            indicator.len(),
+            "one indicator per candidate message"
+        );
+        let padded: Vec<_> = messages
+            .iter()
+            .map(|message| self.pad(ctx, message))
+            .collect();
+        let num_blocks: Vec<_> = padded
+            .iter()
+            .map(|message| message.len() / BLOCK_BYTES)
+            .collect();
+        let max_len = padded
+            .iter()
+            .map(Vec::len)
+            .max()
+            .expect("at least one candidate message");
+
+        let zero = ctx.load_zero();
+        let merged: Vec<_> = (0..max_len)
+            .map(|i| {
+                let candidates = padded
+                    .iter()
+                    .map(|message| Existing(*message.get(i).unwrap_or(&zero)));
+                self.gate
+                    .select_by_indicator(ctx, candidates, indicator.iter().copied())
+            })
+            .collect();
+
+        let mut state = self.initial_state(ctx);
+        let mut digests = Vec::with_capacity(max_len / BLOCK_BYTES);
+        for (i, block) in merged.chunks(BLOCK_BYTES).enumerate() {
+            self.compress(ctx, &mut state, block);
+            // Only digests some candidate can end with are read.
+            digests.push(
+                num_blocks
+                    .contains(&(i + 1))
+                    .then(|| self.digest(ctx, &state)),
+            );
+        }
+
+        (0..DIGEST_BYTES)
+            .map(|i| {
+                let candidates = num_blocks.iter().map(|&blocks| {
+                    Existing(digests[blocks - 1].as_ref().expect("final block digest")[i])
+                });
+                self.gate
+                    .select_by_indicator(ctx, candidates, indicator.iter().copied())
+            })
+            .collect()
+    }
+
+    /// `bytes` followed by the padding and 64-bit length suffix, which are
+    /// constants because the length is fixed when the circuit is built.
+    fn pad(&self, ctx: &mut Context<F>, bytes: &[AssignedValue<F>]) -> Vec<AssignedValue<F>> {
+        let padding_len = 9 + (BLOCK_BYTES - (bytes.len() + 9) % BLOCK_BYTES) % BLOCK_BYTES;
+        let mut padding = vec![0u8; padding_len];
+        padding[0] = 0x80;
+        let bit_len = (bytes.len() as u64) * 8;
+        padding[padding_len - 8..].copy_from_slice(&bit_len.to_be_bytes());
+
+        let mut message = bytes.to_vec();
+        message.extend(
+            padding
+                .iter()
+                .map(|byte| ctx.load_constant(F::from(*byte as u64))),
+        );
+        message
+    }
+
+    fn initial_state(&self, ctx: &mut Context<F>) -> Vec<Word<F>> {
+        INITIAL_STATE
+            .iter()
+            .map(|h| self.constant_word(ctx, *h))
+            .collect()
+    }
+
+    /// Words are big-endian: emit each word's bytes most significant first.
+    fn digest(&self, ctx: &mut Context<F>, state: &[Word<F>]) -> Vec<AssignedValue<F>> {
+        state
+            .iter()
+            .flat_map(|word| {
+                let mut word_bytes = bits_to_bytes(ctx, self.gate, word);
+                word_bytes.reverse();
+                word_bytes
+            })
+            .collect()
+    }
+
+    fn compress(&self, ctx: &mut Context<F>, state: &mut [Word<F>], block: &[AssignedValue<F>]) {
+        let mut schedule: Vec<Word<F>> = block
+            .chunks(4)
+            .map(|word_bytes| {
+                let be: Vec<_> = word_bytes.iter().rev().copied().collect();
+                bytes_to_bits(ctx, self.gate, &be)
+            })
+            .collect();
+        for t in 16..64 {
+            let s0 = self.small_sigma(ctx, &schedule[t - 15], 7, 18, 3);
+            let s1 = self.small_sigma(ctx, &schedule[t - 2], 17, 19, 10);
+            let word = self.add(ctx, &[&s1, &schedule[t - 7], &s0, &schedule[t - 16]], 0);
+            schedule.push(word);
+        }
+
+        let mut v = state.to_vec();
+        for (t, k) in ROUND_CONSTANTS.iter().enumerate() {
+            let big_s1 = self.big_sigma(ctx, &v[4], 6, 11, 25);
+            let ch = self.ch(ctx, &v[4], &v[5], &v[6]);
+            let t1 = self.sum(ctx, &[&v[7], &big_s1, &ch, &schedule[t]], *k);
+            let big_s0 = self.big_sigma(ctx, &v[0], 2, 13, 22);
+            let maj = self.maj(ctx, &v[0], &v[1], &v[2]);
+            let t2 = self.sum(ctx, &[&big_s0, &maj], 0);
+
+            let d = bits_to_num(ctx, self.gate, &v[3]);
+            let e = self.gate.add(ctx, d, t1);
+            let a = self.gate.add(ctx, t1, t2);
+            v.rotate_right(1);
+            v[4] = self.low_word(ctx, e);
+            v[0] = self.low_word(ctx, a);
+        }
+
+        for (h, x) in state.iter_mut().zip(v) {
+            *h = self.add(ctx, &[&*h, &x], 0);
+        }
+    }
+
+    fn constant_word(&self, ctx: &mut Context<F>, value: u32) -> Word<F> {
+        (0..32)
+            .map(|z| ctx.load_constant(F::from(u64::from((value >> z) & 1))))
+            .collect()
+    }
+
+    /// `sum(words) + constant` as a native value, before reduction.
+    fn sum(&self, ctx: &mut Context<F>, words: &[&Word<F>], constant: u32) -> AssignedValue<F> {
+        let mut terms: Vec<_> = words
+            .iter()
+            .map(|word| Existing(bits_to_num(ctx, self.gate, word)))
+            .collect();
+        terms.push(Constant(F::from(u64::from(constant))));
+        self.gate.sum(ctx, terms)
+    }
+
+    /// The low 32 bits of a sum of at most eight words.
+    fn low_word(&self, ctx: &mut Context<F>, sum: AssignedValue<F>) -> Word<F> {
+        let mut bits = self.gate.num_to_bits(ctx, sum, 32 + 3);
+        bits.truncate(32);
+        bits
+    }
+
+    fn add(&self, ctx: &mut Context<F>, words: &[&Word<F>], constant: u32) -> Word<F> {
+        let sum = self.sum(ctx, words, constant);
+        self.low_word(ctx, sum)
+    }
+
+    fn xor3(
+        &self,
+        ctx: &mut Context<F>,
+        a: &[AssignedValue<F>],
+        b: &[AssignedValue<F>],
+        c: &[AssignedValue<F>],
+    ) -> Word<F> {
+        (0..32)
+            .map(|z| {
+                let ab = xor_bit(ctx, self.gate, a[z], b[z]);
+                xor_bit(ctx, self.gate, ab, c[z])
+            })
+            .collect()
+    }
+
+    fn rotr(word: &Word<F>, n: usize) -> Word<F> {
+        (0..32).map(|z| word[(z + n) % 32]).collect()
+    }
+
+    /// `Σ(x) = rotr(x, r0) ^ rotr(x, r1) ^ rotr(x, r2)`.
+    fn big_sigma(
+        &self,
+        ctx: &mut Context<F>,
+        x: &Word<F>,
+        r0: usize,
+        r1: usize,
+        r2: usize,
+    ) -> Word<F> {
+        self.xor3(
+            ctx,
+            &Self::rotr(x, r0),
+            &Self::rotr(x, r1),
+            &Self::rotr(x, r2),
+        )
+    }
+
+    /// `σ(x) = rotr(x, r0) ^ rotr(x, r1) ^ (x >> shift)`.
+    fn small_sigma(
+        &self,
+        ctx: &mut Context<F>,
+        x: &Word<F>,
+        r0: usize,
+        r1: usize,
+        shift: usize,
+    ) -> Word<F> {
+        let zero = ctx.load_zero();
+        let shifted: Word<F> = (0..32)
+            .map(|z| if z + shift < 32 { x[z + shift] } else { zero })
+            .collect();
+        self.xor3(ctx, &Self::rotr(x, r0), &Self::rotr(x, r1), &shifted)
+    }
+
+    /// `Ch(e, f, g) = e ? f : g`, bitwise.
+    fn ch(&self, ctx: &mut Context<F>, e: &Word<F>, f: &Word<F>, g: &Word<F>) -> Word<F> {
+        (0..32)
+            .map(|z| self.gate.select(ctx, f[z], g[z], e[z]))
+            .collect()
+    }
+
+    /// `Maj(a, b, c) = (a ^ b) ? c : a`, bitwise.
+    fn maj(&self, ctx: &mut Context<F>, a: &Word<F>, b: &Word<F>, c: &Word<F>) -> Word<F> {
+        (0..32)
+            .map(|z| {
+                let differ = xor_bit(ctx, self.gate, a[z], b[z]);
+                self.gate.select(ctx, c[z], a[z], differ)
+            })
+            .collect()
+    }
+}
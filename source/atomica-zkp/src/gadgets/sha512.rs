@@ -0,0 +1,302 @@
+use super::bytes::{bits_to_bytes, bits_to_num, bytes_to_bits, xor_bit};
+use crate::halo2_base::{
+    gates::{GateChip, GateInstructions},
+    utils::ScalarField,
+    AssignedValue, Context,
+    QuantumCell::{Constant, Existing},
+};
+
+/// Block size of SHA-512 in bytes.
+pub const BLOCK_BYTES: usize = 128;
+
+/// Digest length of SHA-512 in bytes.
+pub const DIGEST_BYTES: usize = 64;
+
+const INITIAL_STATE: [u64; 8] = [
+    0x6a09_e667_f3bc_c908,
+    0xbb67_ae85_84ca_a73b,
+    0x3c6e_f372_fe94_f82b,
+    0xa54f_f53a_5f1d_36f1,
+    0x510e_527f_ade6_82d1,
+    0x9b05_688c_2b3e_6c1f,
+    0x1f83_d9ab_fb41_bd6b,
+    0x5be0_cd19_137e_2179,
+];
+
+const ROUND_CONSTANTS: [u64; 80] = [
+    0x428a_2f98_d728_ae22,
+    0x7137_4491_23ef_65cd,
+    0xb5c0_fbcf_ec4d_3b2f,
+    0xe9b5_dba5_8189_dbbc,
+    0x3956_c25b_f348_b538,
+    0x59f1_11f1_b605_d019,
+    0x923f_82a4_af19_4f9b,
+    0xab1c_5ed5_da6d_8118,
+    0xd807_aa98_a303_0242,
+    0x1283_5b01_4570_6fbe,
+    0x2431_85be_4ee4_b28c,
+    0x550c_7dc3_d5ff_b4e2,
+    0x72be_5d74_f27b_896f,
+    0x80de_b1fe_3b16_96b1,
+    0x9bdc_06a7_25c7_1235,
+    0xc19b_f174_cf69_2694,
+    0xe49b_69c1_9ef1_4ad2,
+    0xefbe_4786_384f_25e3,
+    0x0fc1_9dc6_8b8c_d5b5,
+    0x240c_a1cc_77ac_9c65,
+    0x2de9_2c6f_592b_0275,
+    0x4a74_84aa_6ea6_e483,
+    0x5cb0_a9dc_bd41_fbd4,
+    0x76f9_88da_8311_53b5,
+    0x983e_5152_ee66_dfab,
+    0xa831_c66d_2db4_3210,
+    0xb003_27c8_98fb_213f,
+    0xbf59_7fc7_beef_0ee4,
+    0xc6e0_0bf3_3da8_8fc2,
+    0xd5a7_9147_930a_a725,
+    0x06ca_6351_e003_826f,
+    0x1429_2967_0a0e_6e70,
+    0x27b7_0a85_46d2_2ffc,
+    0x2e1b_2138_5c26_c926,
+    0x4d2c_6dfc_5ac4_2aed,
+    0x5338_0d13_9d95_b3df,
+    0x650a_7354_8baf_63de,
+    0x766a_0abb_3c77_b2a8,
+    0x81c2_c92e_47ed_aee6,
+    0x9272_2c85_1482_353b,
+    0xa2bf_e8a1_4cf1_0364,
+    0xa81a_664b_bc42_3001,
+    0xc24b_8b70_d0f8_9791,
+    0xc76c_51a3_0654_be30,
+    0xd192_e819_d6ef_5218,
+    0xd699_0624_5565_a910,
+    0xf40e_3585_5771_202a,
+    0x106a_a070_32bb_d1b8,
+    0x19a4_c116_b8d2_d0c8,
+    0x1e37_6c08_5141_ab53,
+    0x2748_774c_df8e_eb99,
+    0x34b0_bcb5_e19b_48a8,
+    0x391c_0cb3_c5c9_5a63,
+    0x4ed8_aa4a_e341_8acb,
+    0x5b9c_ca4f_7763_e373,
+    0x682e_6ff3_d6b2_b8a3,
+    0x748f_82ee_5def_b2fc,
+    0x78a5_636f_4317_2f60,
+    0x84c8_7814_a1f0_ab72,
+    0x8cc7_0208_1a64_39ec,
+    0x90be_fffa_2363_1e28,
+    0xa450_6ceb_de82_bde9,
+    0xbef9_a3f7_b2c6_7915,
+    0xc671_78f2_e372_532b,
+    0xca27_3ece_ea26_619c,
+    0xd186_b8c7_21c0_c207,
+    0xeada_7dd6_cde0_eb1e,
+    0xf57d_4f7f_ee6e_d178,
+    0x06f0_67aa_7217_6fba,
+    0x0a63_7dc5_a2c8_98a6,
+    0x113f_9804_bef9_0dae,
+    0x1b71_0b35_131c_471b,
+    0x28db_77f5_2304_7d84,
+    0x32ca_ab7b_40c7_2493,
+    0x3c9e_be0a_15c9_bebc,
+    0x431d_67c4_9c10_0d4c,
+    0x4cc5_d4be_cb3e_42b6,
+    0x597f_299c_fc65_7e2a,
+    0x5fcb_6fab_3ad6_faec,
+    0x6c44_198c_4a47_5817,
+];
+
+/// A 64-bit word as little-endian boolean cells.
+type Word<F> = Vec<AssignedValue<F>>;
+
+/// SHA-512 over bit-decomposed words.
+///
+/// Like [`super::keccak::KeccakChip`], every word bit is its own cell so that
+/// rotations are free. Modular additions recompose the operands, add them
+/// natively and decompose the sum again, keeping only the low 64 bits.
+#[derive(Clone, Copy, Debug)]
+pub struct Sha512Chip<'a, F: ScalarField> {
+    gate: &'a GateChip<F>,
+}
+
+impl<'a, F: ScalarField> Sha512Chip<'a, F> {
+    pub fn new(gate: &'a GateChip<F>) -> Self {
+        Self { gate }
+    }
+
+    /// SHA-512 of a fixed-length byte string.
+    ///
+    /// Input cells must already be constrained to bytes.
+    pub fn sha512(
+        &self,
+        ctx: &mut Context<F>,
+        bytes: &[AssignedValue<F>],
+    ) -> Vec<AssignedValue<F>> {
+        // The input length is fixed when the circuit is built, so the
+        // padding and the 128-bit length suffix are constants.
+        let padding_len = 17 + (BLOCK_BYTES - (bytes.len() + 17) % BLOCK_BYTES) % BLOCK_BYTES;
+        let mut padding = vec![0u8; padding_len];
+        padding[0] = 0x80;
+        let bit_len = (bytes.len() as u128) * 8;
+        padding[padding_len - 16..].copy_from_slice(&bit_len.to_be_bytes());
+
+        let mut message = bytes.to_vec();
+        message.extend(
+            padding
+                .iter()
+                .map(|byte| ctx.load_constant(F::from(*byte as u64))),
+        );
+
+        let mut state: Vec<Word<F>> = INITIAL_STATE
+            .iter()
+            .map(|h| self.constant_word(ctx, *h))
+            .collect();
+        for block in message.chunks(BLOCK_BYTES) {
+            self.compress(ctx, &mut state, block);
+        }
+
+        // Words are big-endian: emit each word's bytes most significant first.
+        state
+            .iter()
+            .flat_map(|word| {
+                let mut word_bytes = bits_to_bytes(ctx, self.gate, word);
+                word_bytes.reverse();
+                word_bytes
+            })
+            .collect()
+    }
+
+    fn compress(&self, ctx: &mut Context<F>, state: &mut [Word<F>], block: &[AssignedValue<F>]) {
+        let mut schedule: Vec<Word<F>> = block
+            .chunks(8)
+            .map(|word_bytes| {
+                let be: Vec<_> = word_bytes.iter().rev().copied().collect();
+                bytes_to_bits(ctx, self.gate, &be)
+            })
+            .collect();
+        for t in 16..80 {
+            let s0 = self.small_sigma(ctx, &schedule[t - 15], 1, 8, 7);
+            let s1 = self.small_sigma(ctx, &schedule[t - 2], 19, 61, 6);
+            let word = self.add(ctx, &[&s1, &schedule[t - 7], &s0, &schedule[t - 16]], 0);
+            schedule.push(word);
+        }
+
+        let mut v = state.to_vec();
+        for (t, k) in ROUND_CONSTANTS.iter().enumerate() {
+            let big_s1 = self.big_sigma(ctx, &v[4], 14, 18, 41);
+            let ch = self.ch(ctx, &v[4], &v[5], &v[6]);
+            let t1 = self.sum(ctx, &[&v[7], &big_s1, &ch, &schedule[t]], *k);
+            let big_s0 = self.big_sigma(ctx, &v[0], 28, 34, 39);
+            let maj = self.maj(ctx, &v[0], &v[1], &v[2]);
+            let t2 = self.sum(ctx, &[&big_s0, &maj], 0);
+
+            let d = bits_to_num(ctx, self.gate, &v[3]);
+            let e = self.gate.add(ctx, d, t1);
+            let a = self.gate.add(ctx, t1, t2);
+            v.rotate_right(1);
+            v[4] = self.low_word(ctx, e);
+            v[0] = self.low_word(ctx, a);
+        }
+
+        for (h, x) in state.iter_mut().zip(v) {
+            *h = self.add(ctx, &[&*h, &x], 0);
+        }
+    }
+
+    fn constant_word(&self, ctx: &mut Context<F>, value: u64) -> Word<F> {
+        (0..64)
+            .map(|z| ctx.load_constant(F::from((value >> z) & 1)))
+            .collect()
+    }
+
+    /// `sum(words) + constant` as a native value, before reduction.
+    fn sum(&self, ctx: &mut Context<F>, words: &[&Word<F>], constant: u64) -> AssignedValue<F> {
+        let mut terms: Vec<_> = words
+            .iter()
+            .map(|word| Existing(bits_to_num(ctx, self.gate, word)))
+            .collect();
+        terms.push(Constant(F::from(constant)));
+        self.gate.sum(ctx, terms)
+    }
+
+    /// The low 64 bits of a sum of at most eight words.
+    fn low_word(&self, ctx: &mut Context<F>, sum: AssignedValue<F>) -> Word<F> {
+        let mut bits = self.gate.num_to_bits(ctx, sum, 64 + 3);
+        bits.truncate(64);
+        bits
+    }
+
+    fn add(&self, ctx: &mut Context<F>, words: &[&Word<F>], constant: u64) -> Word<F> {
+        let sum = self.sum(ctx, words, constant);
+        self.low_word(ctx, sum)
+    }
+
+    fn xor3(
+        &self,
+        ctx: &mut Context<F>,
+        a: &[AssignedValue<F>],
+        b: &[AssignedValue<F>],
+        c: &[AssignedValue<F>],
+    ) -> Word<F> {
+        (0..64)
+            .map(|z| {
+                let ab = xor_bit(ctx, self.gate, a[z], b[z]);
+                xor_bit(ctx, self.gate, ab, c[z])
+            })
+            .collect()
+    }
+
+    fn rotr(word: &Word<F>, n: usize) -> Word<F> {
+        (0..64).map(|z| word[(z + n) % 64]).collect()
+    }
+
+    /// `Σ(x) = rotr(x, r0) ^ rotr(x, r1) ^ rotr(x, r2)`.
+    fn big_sigma(
+        &self,
+        ctx: &mut Context<F>,
+        x: &Word<F>,
+        r0: usize,
+        r1: usize,
+        r2: usize,
+    ) -> Word<F> {
+        self.xor3(
+            ctx,
+            &Self::rotr(x, r0),
+            &Self::rotr(x, r1),
+            &Self::rotr(x, r2),
+        )
+    }
+
+    /// `σ(x) = rotr(x, r0) ^ rotr(x, r1) ^ (x >> shift)`.
+    fn small_sigma(
+        &self,
+        ctx: &mut Context<F>,
+        x: &Word<F>,
+        r0: usize,
+        r1: usize,
+        shift: usize,
+    ) -> Word<F> {
+        let zero = ctx.load_zero();
+        let shifted: Word<F> = (0..64)
+            .map(|z| if z + shift < 64 { x[z + shift] } else { zero })
+            .collect();
+        self.xor3(ctx, &Self::rotr(x, r0), &Self::rotr(x, r1), &shifted)
+    }
+
+    /// `Ch(e, f, g) = e ? f : g`, bitwise.
+    fn ch(&self, ctx: &mut Context<F>, e: &Word<F>, f: &Word<F>, g: &Word<F>) -> Word<F> {
+        (0..64)
+            .map(|z| self.gate.select(ctx, f[z], g[z], e[z]))
+            .collect()
+    }
+
+    /// `Maj(a, b, c) = (a ^ b) ? c : a`, bitwise.
+    fn maj(&self, ctx: &mut Context<F>, a: &Word<F>, b: &Word<F>, c: &Word<F>) -> Word<F> {
+        (0..64)
+            .map(|z| {
+                let differ = xor_bit(ctx, self.gate, a[z], b[z]);
+                self.gate.select(ctx, c[z], a[z], differ)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,242 @@
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, PrimeField};
+use halo2_proofs_axiom::halo2curves::{
+    bls12_381::{Fq, Fq2, G1Affine, G2Affine},
+    bn256::Fr,
+};
+use num_bigint::BigUint;
+
+use super::{FQ_BYTES, GT_BYTES};
+use crate::gadgets::{
+    bytes::{limbs_to_bytes_le, pack_words, xor_bytes},
+    keccak::KeccakChip,
+};
+use crate::halo2_base::{
+    gates::{GateInstructions, RangeInstructions},
+    utils::{biguint_to_fe, fe_to_biguint},
+    AssignedValue, Context,
+    QuantumCell::Constant,
+};
+use crate::halo2_ecc::{
+    bigint::ProperCrtUint,
+    bls12_381::{pairing::PairingChip, Fp2Chip, FpChip},
+    ecc::{EcPoint, EccChip},
+    fields::{FieldChip, FieldVector},
+};
+
+/// A BLS12-381 G1 point with non-native coordinates.
+pub type G1Point = EcPoint<Fr, ProperCrtUint<Fr>>;
+
+/// A BLS12-381 G2 point with non-native `Fq2` coordinates.
+pub type G2Point = EcPoint<Fr, FieldVector<ProperCrtUint<Fr>>>;
+
+/// A pairing output in halo2-ecc's `Fq12` layout.
+pub type GtPoint = FieldVector<ProperCrtUint<Fr>>;
+
+/// In-circuit hashed-IBE decryption over BLS12-381.
+#[derive(Clone, Copy, Debug)]
+pub struct IbeChip<'chip> {
+    fp_chip: &'chip FpChip<'chip, Fr>,
+}
+
+impl<'chip> IbeChip<'chip> {
+    pub fn new(fp_chip: &'chip FpChip<'chip, Fr>) -> Self {
+        Self { fp_chip }
+    }
+
+    pub fn fp_chip(&self) -> &FpChip<'chip, Fr> {
+        self.fp_chip
+    }
+
+    /// Witnesses an arkworks G1 point, constraining it to the curve.
+    ///
+    /// Subgroup membership is not enforced; the circuit proves the decryption
+    /// relation for whatever point it is given.
+    pub fn load_g1(&self, ctx: &mut Context<Fr>, point: &ark_bls12_381::G1Affine) -> G1Point {
+        let g1_chip = EccChip::new(self.fp_chip);
+        g1_chip.load_private::<G1Affine>(ctx, (fq_from_ark(&point.x), fq_from_ark(&point.y)))
+    }
+
+    /// Witnesses an arkworks G2 point, constraining it to the curve.
+    pub fn load_g2(&self, ctx: &mut Context<Fr>, point: &ark_bls12_381::G2Affine) -> G2Point {
+        let fp2_chip = Fp2Chip::new(self.fp_chip);
+        let g2_chip = EccChip::new(&fp2_chip);
+        g2_chip.load_private::<G2Affine>(ctx, (fq2_from_ark(&point.x), fq2_from_ark(&point.y)))
+    }
+
+    /// Witnesses an arkworks pairing output, one reduced coefficient at a time.
+    ///
+    /// Nothing ties the result to a pairing; this is for exercising the
+    /// serialisation and unmasking without paying for one.
+    pub fn load_gt(
+        &self,
+        ctx: &mut Context<Fr>,
+        gt: &<ark_bls12_381::Bls12_381 as Pairing>::TargetField,
+    ) -> GtPoint {
+        let mut coeffs = vec![None; 12];
+        for (i, c6) in [gt.c0, gt.c1].iter().enumerate() {
+            for (j, c2) in [c6.c0, c6.c1, c6.c2].iter().enumerate() {
+                for (k, c) in [c2.c0, c2.c1].iter().enumerate() {
+                    coeffs[i + 2 * j + 6 * k] = Some(self.fp_chip.load_private(ctx, fq_from_ark(c)));
+                }
+            }
+        }
+        FieldVector(coeffs.into_iter().map(Option::unwrap).collect())
+    }
+
+    /// Canonical little-endian bytes of a base field element.
+    pub fn fq_to_bytes(
+        &self,
+        ctx: &mut Context<Fr>,
+        x: &ProperCrtUint<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let reduced = self.fp_chip.enforce_less_than(ctx, x.clone());
+        limbs_to_bytes_le(
+            ctx,
+            self.fp_chip.gate(),
+            reduced.inner().limbs(),
+            self.fp_chip.limb_bits,
+            FQ_BYTES,
+        )
+    }
+
+    /// `x || y` as canonical little-endian bytes.
+    pub fn g1_to_bytes(&self, ctx: &mut Context<Fr>, point: &G1Point) -> Vec<AssignedValue<Fr>> {
+        let mut bytes = self.fq_to_bytes(ctx, point.x());
+        bytes.extend(self.fq_to_bytes(ctx, point.y()));
+        bytes
+    }
+
+    /// The 48-byte compressed encoding of a non-identity G1 point: `x`
+    /// big-endian with the compression flag and the sign of `y` in the top
+    /// bits, which are free because `p < 2^381`.
+    pub fn g1_to_compressed_bytes(
+        &self,
+        ctx: &mut Context<Fr>,
+        point: &G1Point,
+    ) -> Vec<AssignedValue<Fr>> {
+        let gate = self.fp_chip.gate();
+        let range = self.fp_chip.range();
+        let half = ark_bls12_381::Fq::MODULUS_MINUS_ONE_DIV_TWO.to_bytes_le();
+        // y > (p - 1) / 2, comparing little-endian bytes from the bottom up.
+        let y = self.fq_to_bytes(ctx, point.y());
+        let mut greater = ctx.load_zero();
+        for (byte, bound) in y.iter().zip(half) {
+            let bound = Constant(Fr::from(bound as u64));
+            let above = range.is_less_than(ctx, bound, *byte, 8);
+            let equal = gate.is_equal(ctx, *byte, bound);
+            greater = gate.mul_add(ctx, equal, greater, above);
+        }
+        let mut bytes = self.fq_to_bytes(ctx, point.x());
+        bytes.reverse();
+        let flags = gate.mul_add(ctx, greater, Constant(Fr::from(0x20)), Constant(Fr::from(0x80)));
+        bytes[0] = gate.add(ctx, bytes[0], flags);
+        bytes
+    }
+
+    /// `x.c0 || x.c1 || y.c0 || y.c1` as canonical little-endian bytes.
+    pub fn g2_to_bytes(&self, ctx: &mut Context<Fr>, point: &G2Point) -> Vec<AssignedValue<Fr>> {
+        let mut bytes = Vec::with_capacity(4 * FQ_BYTES);
+        for coordinate in [point.x(), point.y()] {
+            for c in &coordinate.0 {
+                bytes.extend(self.fq_to_bytes(ctx, c));
+            }
+        }
+        bytes
+    }
+
+    /// Serialises a pairing output exactly like Aptos' `FormatGt`.
+    ///
+    /// arkworks writes `Fq12 = c0 + c1 w` with `Fq6 = c0 + c1 v + c2 v^2` and
+    /// `Fq2 = c0 + c1 u`, depth first. halo2-ecc stores the same element as
+    /// `sum_i (a_i + b_i u) w^i` in `[a_0..a_5, b_0..b_5]`, and `v = w^2`, so
+    /// arkworks' `c{i}.c{j}.c{k}` is halo2-ecc coefficient `i + 2j + 6k`.
+    pub fn gt_to_bytes(&self, ctx: &mut Context<Fr>, gt: &GtPoint) -> Vec<AssignedValue<Fr>> {
+        let mut bytes = Vec::with_capacity(GT_BYTES);
+        for i in 0..2 {
+            for j in 0..3 {
+                for k in 0..2 {
+                    bytes.extend(self.fq_to_bytes(ctx, &gt.0[i + 2 * j + 6 * k]));
+                }
+            }
+        }
+        bytes
+    }
+
+    /// `M = C xor Keccak256(FormatGt(e(U, Sig)))`.
+    ///
+    /// `ciphertext` must already be constrained to bytes and be at most 32
+    /// bytes long, the length of the mask.
+    pub fn decrypt(
+        &self,
+        ctx: &mut Context<Fr>,
+        u: &G1Point,
+        signature: &G2Point,
+        ciphertext: &[AssignedValue<Fr>],
+    ) -> Vec<AssignedValue<Fr>> {
+        let pairing_chip = PairingChip::new(self.fp_chip);
+        let shared = pairing_chip.pairing(ctx, signature, u);
+        self.unmask(ctx, &shared, ciphertext)
+    }
+
+    /// `M = C xor Keccak256(FormatGt(K))` for an already computed `K`.
+    pub fn unmask(
+        &self,
+        ctx: &mut Context<Fr>,
+        shared: &GtPoint,
+        ciphertext: &[AssignedValue<Fr>],
+    ) -> Vec<AssignedValue<Fr>> {
+        let shared_bytes = self.gt_to_bytes(ctx, shared);
+        let gate = self.fp_chip.gate();
+        let mask = KeccakChip::new(gate).keccak256(ctx, &shared_bytes);
+        xor_bytes(ctx, gate, ciphertext, &mask[..ciphertext.len()])
+    }
+
+    /// Packs a G1 point into public-instance words.
+    pub fn g1_words(&self, ctx: &mut Context<Fr>, point: &G1Point) -> Vec<AssignedValue<Fr>> {
+        let bytes = self.g1_to_bytes(ctx, point);
+        pack_words(ctx, self.fp_chip.gate(), &bytes)
+    }
+
+    /// Packs a G2 point into public-instance words.
+    pub fn g2_words(&self, ctx: &mut Context<Fr>, point: &G2Point) -> Vec<AssignedValue<Fr>> {
+        let bytes = self.g2_to_bytes(ctx, point);
+        pack_words(ctx, self.fp_chip.gate(), &bytes)
+    }
+}
+
+/// Canonical little-endian bytes of an arkworks base field element.
+pub fn ark_fq_bytes(x: &ark_bls12_381::Fq) -> Vec<u8> {
+    x.into_bigint().to_bytes_le()
+}
+
+/// Native counterpart of [`IbeChip::g1_to_bytes`].
+pub fn ark_g1_bytes(point: &ark_bls12_381::G1Affine) -> Vec<u8> {
+    [ark_fq_bytes(&point.x), ark_fq_bytes(&point.y)].concat()
+}
+
+/// Native counterpart of [`IbeChip::g2_to_bytes`].
+pub fn ark_g2_bytes(point: &ark_bls12_381::G2Affine) -> Vec<u8> {
+    [
+        ark_fq_bytes(&point.x.c0),
+        ark_fq_bytes(&point.x.c1),
+        ark_fq_bytes(&point.y.c0),
+        ark_fq_bytes(&point.y.c1),
+    ]
+    .concat()
+}
+
+pub(crate) fn fq_from_ark(x: &ark_bls12_381::Fq) -> Fq {
+    biguint_to_fe(&BigUint::from_bytes_le(&ark_fq_bytes(x)))
+}
+
+pub(crate) fn fq2_from_ark(x: &ark_bls12_381::Fq2) -> Fq2 {
+    Fq2 {
+        c0: fq_from_ark(&x.c0),
+        c1: fq_from_ark(&x.c1),
+    }
+}
+
+pub(crate) fn fq_to_ark(x: &Fq) -> ark_bls12_381::Fq {
+    fe_to_biguint(x).into()
+}
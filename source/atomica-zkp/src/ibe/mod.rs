@@ -0,0 +1,108 @@
+//! Proof of correct timelock (hashed-IBE) decryption of a sealed bid.
+//!
+//! `atomica::timelock_encryption::decrypt` reveals a bid on chain by computing
+//! `K = e(U, Sig)`, `mask = Keccak256(FormatGt(K))` and `M = C xor mask`.
+//! [`IbeDecryptionCircuit`] proves the same relation so the reveal can be
+//! checked by a single SNARK verification, including on an EVM chain.
+//!
+//! Public instances are little-endian 16-byte words (see
+//! [`crate::gadgets::bytes::pack_words`]), in order:
+//!
+//! 1. `U` as canonical `x || y` (6 words),
+//! 2. `Sig` as canonical `x.c0 || x.c1 || y.c0 || y.c1` (12 words),
+//! 3. the ciphertext `C`,
+//! 4. the plaintext `M`.
+
+pub mod chip;
+pub mod native;
+
+use ark_bls12_381::{G1Affine, G2Affine};
+use halo2_proofs_axiom::halo2curves::bn256::Fr;
+
+use crate::circuit::{BuilderCircuit, CircuitParams};
+use crate::gadgets::bytes::{load_bytes, pack_words, pack_words_native};
+use crate::halo2_base::{
+    gates::{circuit::builder::BaseCircuitBuilder, RangeChip, RangeInstructions},
+    AssignedValue,
+};
+use crate::halo2_ecc::bls12_381::FpChip;
+use chip::{ark_g1_bytes, ark_g2_bytes, IbeChip};
+
+/// Bytes in a canonical BLS12-381 base field element.
+pub const FQ_BYTES: usize = 48;
+
+/// Bytes in a `FormatGt` serialisation (twelve base field elements).
+pub const GT_BYTES: usize = 12 * FQ_BYTES;
+
+/// The mask is a single Keccak-256 digest, so messages are capped at 32 bytes.
+pub const MAX_MESSAGE_BYTES: usize = 32;
+
+/// Limb width for non-native BLS12-381 base field arithmetic.
+pub const LIMB_BITS: usize = 112;
+
+/// Number of limbs for non-native BLS12-381 base field arithmetic.
+pub const NUM_LIMBS: usize = 4;
+
+/// Default sizing: one pairing plus five Keccak permutations.
+pub const IBE_CIRCUIT_PARAMS: CircuitParams = CircuitParams {
+    k: 20,
+    lookup_bits: 19,
+    minimum_rows: 20,
+};
+
+/// Proves that `plaintext` is the hashed-IBE decryption of `(u, ciphertext)`
+/// under the published round signature `signature`.
+#[derive(Clone, Debug)]
+pub struct IbeDecryptionCircuit {
+    pub u: G1Affine,
+    pub signature: G2Affine,
+    pub ciphertext: Vec<u8>,
+}
+
+impl IbeDecryptionCircuit {
+    pub fn new(u: G1Affine, signature: G2Affine, ciphertext: Vec<u8>) -> Self {
+        assert!(!u.infinity && !signature.infinity, "points at infinity are not valid IBE inputs");
+        assert!(
+            ciphertext.len() <= MAX_MESSAGE_BYTES,
+            "ciphertext longer than {MAX_MESSAGE_BYTES} bytes"
+        );
+        Self { u, signature, ciphertext }
+    }
+
+    /// The plaintext the circuit will expose, computed natively.
+    pub fn plaintext(&self) -> Vec<u8> {
+        native::decrypt(&self.u, &self.signature, &self.ciphertext)
+    }
+
+    /// Expected public instances in the layout described in the module docs.
+    pub fn instances(&self) -> Vec<Fr> {
+        let mut instances = pack_words_native(&ark_g1_bytes(&self.u));
+        instances.extend(pack_words_native::<Fr>(&ark_g2_bytes(&self.signature)));
+        instances.extend(pack_words_native::<Fr>(&self.ciphertext));
+        instances.extend(pack_words_native::<Fr>(&self.plaintext()));
+        instances
+    }
+}
+
+impl BuilderCircuit for IbeDecryptionCircuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let fp_chip = FpChip::new(range, LIMB_BITS, NUM_LIMBS);
+        let chip = IbeChip::new(&fp_chip);
+
+        let u = chip.load_g1(ctx, &self.u);
+        let signature = chip.load_g2(ctx, &self.signature);
+        let ciphertext = load_bytes(ctx, range, &self.ciphertext);
+        let plaintext = chip.decrypt(ctx, &u, &signature, &ciphertext);
+
+        let mut instances = chip.g1_words(ctx, &u);
+        instances.extend(chip.g2_words(ctx, &signature));
+        instances.extend(pack_words(ctx, range.gate(), &ciphertext));
+        instances.extend(pack_words(ctx, range.gate(), &plaintext));
+        instances
+    }
+}
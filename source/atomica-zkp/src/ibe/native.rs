@@ -0,0 +1,77 @@
+//! Out-of-circuit reference for the hashed-IBE timelock scheme.
+//!
+//! Uses arkworks, the same backend as the Aptos `crypto_algebra` natives, so
+//! the Gt serialisation here is byte-for-byte what `ibe::decrypt` hashes.
+
+use ark_bls12_381::{g2, Bls12_381, Fr, G1Affine, G2Affine};
+use ark_ec::{
+    hashing::{curve_maps::wb::WBMap, map_to_curve_hasher::MapToCurveBasedHasher, HashToCurve},
+    pairing::Pairing,
+    short_weierstrass::Projective,
+    AffineRepr, CurveGroup,
+};
+use ark_ff::{field_hashers::DefaultFieldHasher, Field, PrimeField};
+use ark_serialize::CanonicalSerialize;
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+
+use super::{GT_BYTES, MAX_MESSAGE_BYTES};
+
+/// Domain separation tag used by `timelock_encryption::identity_point`.
+pub const IDENTITY_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// Derives `Q_id = HashToG2(id)`, matching `timelock_encryption::identity_point`.
+pub fn identity_point(id: &[u8]) -> G2Affine {
+    MapToCurveBasedHasher::<
+        Projective<g2::Config>,
+        DefaultFieldHasher<Sha256, 128>,
+        WBMap<g2::Config>,
+    >::new(IDENTITY_DST)
+    .expect("valid hash-to-curve parameters")
+    .hash(id)
+    .expect("hash to G2 failed")
+}
+
+/// Serialises a pairing output the way Aptos' `FormatGt` does.
+pub fn gt_bytes(gt: &<Bls12_381 as Pairing>::TargetField) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(GT_BYTES);
+    gt.serialize_uncompressed(&mut bytes)
+        .expect("serialising into a Vec cannot fail");
+    bytes
+}
+
+/// Keccak-256 mask derived from the shared secret `K`.
+fn mask(gt: &<Bls12_381 as Pairing>::TargetField) -> [u8; 32] {
+    Keccak256::digest(gt_bytes(gt)).into()
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(x, y)| x ^ y).collect()
+}
+
+/// Encrypts `message` to `identity` under the master public key `mpk`.
+///
+/// Returns `(U, C)` with `U = rG` and `C = M xor Keccak256(e(mpk, Q_id)^r)`.
+pub fn encrypt(
+    mpk: &G1Affine,
+    identity: &G2Affine,
+    r: Fr,
+    message: &[u8],
+) -> (G1Affine, Vec<u8>) {
+    assert!(message.len() <= MAX_MESSAGE_BYTES, "message longer than the mask");
+    let u = (G1Affine::generator() * r).into_affine();
+    let shared = Bls12_381::pairing(mpk, identity).0.pow(r.into_bigint());
+    (u, xor(message, &mask(&shared)))
+}
+
+/// Decrypts `(U, C)` with the round signature `Sig = s * Q_id`.
+///
+/// Mirrors `timelock_encryption::decrypt`: `M = C xor Keccak256(e(U, Sig))`.
+pub fn decrypt(u: &G1Affine, signature: &G2Affine, ciphertext: &[u8]) -> Vec<u8> {
+    xor(ciphertext, &mask(&shared_secret(u, signature)))
+}
+
+/// The shared secret `K = e(U, Sig)` that [`decrypt`] hashes into the mask.
+pub fn shared_secret(u: &G1Affine, signature: &G2Affine) -> <Bls12_381 as Pairing>::TargetField {
+    Bls12_381::pairing(u, signature).0
+}
@@ -1,2 +1,26 @@
 // Library will contain runtime prover code
 // Test circuits are in tests/common/
+
+pub mod auction;
+pub mod circuit;
+pub mod dkg;
+pub mod ed25519;
+pub mod envelope;
+pub mod epoch;
+pub mod equivalence;
+pub mod gadgets;
+pub mod ibe;
+pub mod move_verifier;
+pub mod prover;
+pub mod ptau;
+pub mod registry;
+#[cfg(feature = "revm")]
+pub mod soundness;
+pub mod verifier;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+// halo2-lib is pulled in through snark-verifier so that our chips and the
+// aggregation loader always agree on the same halo2-base version.
+pub use snark_verifier_sdk::halo2_base;
+pub use snark_verifier_sdk::snark_verifier::loader::halo2::halo2_ecc;
@@ -0,0 +1,486 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Debug},
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    rc::Rc,
+};
+
+use halo2_proofs_axiom::halo2curves::{
+    bn256::{Fq, Fr, G1Affine, G2Affine},
+    ff::{Field, PrimeField},
+    Coordinates, CurveAffine,
+};
+use snark_verifier_sdk::snark_verifier::{
+    loader::{EcPointLoader, LoadedEcPoint, LoadedScalar, Loader, ScalarLoader},
+    util::arithmetic::FieldOps,
+};
+
+#[derive(Default)]
+struct State {
+    statements: Vec<String>,
+    num_scalars: usize,
+    num_ec_points: usize,
+    scalar_constants: HashMap<Vec<u8>, usize>,
+    ec_point_constants: HashMap<Vec<u8>, usize>,
+    num_instances: usize,
+    proof_len: usize,
+    result: Option<String>,
+}
+
+/// Loader that records the verifier as straight-line Move code.
+///
+/// Scalars and points are pushed onto the `s` and `p` vectors of the generated
+/// `verify` function and referred to by index. Each value is computed into the
+/// `fr_tmp` or `g1_tmp` local first, since its expression may borrow the
+/// vector it is pushed onto. Arithmetic on constants is folded at generation
+/// time, so only proof-dependent work reaches the chain.
+#[derive(Clone, Default)]
+pub struct MoveLoader {
+    state: Rc<RefCell<State>>,
+}
+
+impl Debug for MoveLoader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MoveLoader").finish_non_exhaustive()
+    }
+}
+
+impl MoveLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of instances read so far.
+    pub fn num_instances(&self) -> usize {
+        self.state.borrow().num_instances
+    }
+
+    /// Number of proof bytes read so far.
+    pub fn proof_len(&self) -> usize {
+        self.state.borrow().proof_len
+    }
+
+    /// Body of the generated `verify` function, ending in its return value.
+    pub fn body(&self) -> String {
+        let state = self.state.borrow();
+        let result = state.result.as_ref().expect("pairing check has not been recorded");
+        state
+            .statements
+            .iter()
+            .chain(Some(result))
+            .map(|line| format!("        {line}\n"))
+            .collect()
+    }
+
+    fn statement(&self, code: String) {
+        self.state.borrow_mut().statements.push(code);
+    }
+
+    fn scalar(&self, value: Value) -> Scalar {
+        Scalar {
+            loader: self.clone(),
+            value,
+        }
+    }
+
+    fn push_scalar(&self, expr: String) -> Scalar {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            state.statements.push(format!("fr_tmp = {expr};"));
+            state.statements.push("vector::push_back(&mut s, fr_tmp);".to_string());
+            state.num_scalars += 1;
+            state.num_scalars - 1
+        };
+        self.scalar(Value::Var(index))
+    }
+
+    fn push_ec_point(&self, expr: String) -> EcPoint {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            state.statements.push(format!("g1_tmp = {expr};"));
+            state.statements.push("vector::push_back(&mut p, g1_tmp);".to_string());
+            state.num_ec_points += 1;
+            state.num_ec_points - 1
+        };
+        EcPoint {
+            loader: self.clone(),
+            index,
+        }
+    }
+
+    /// A `&Element<Fr>` expression for `scalar`, materialising constants once.
+    fn scalar_ref(&self, scalar: &Scalar) -> String {
+        let index = match scalar.value {
+            Value::Var(index) => index,
+            Value::Constant(value) => {
+                let key = value.to_repr().as_ref().to_vec();
+                let cached = self.state.borrow().scalar_constants.get(&key).copied();
+                match cached {
+                    Some(index) => index,
+                    None => {
+                        let Value::Var(index) = self.push_scalar(fr_literal(&value)).value else {
+                            unreachable!()
+                        };
+                        self.state.borrow_mut().scalar_constants.insert(key, index);
+                        index
+                    }
+                }
+            }
+        };
+        format!("vector::borrow(&s, {index})")
+    }
+
+    fn ec_point_ref(&self, ec_point: &EcPoint) -> String {
+        format!("vector::borrow(&p, {})", ec_point.index)
+    }
+
+    fn binary(&self, op: &str, lhs: &Scalar, rhs: &Scalar) -> Scalar {
+        let (lhs, rhs) = (self.scalar_ref(lhs), self.scalar_ref(rhs));
+        self.push_scalar(format!("crypto_algebra::{op}({lhs}, {rhs})"))
+    }
+
+    fn scalar_add(&self, lhs: &Scalar, rhs: &Scalar) -> Scalar {
+        match (lhs.value, rhs.value) {
+            (Value::Constant(a), Value::Constant(b)) => self.scalar(Value::Constant(a + b)),
+            (Value::Constant(a), _) if a == Fr::zero() => rhs.clone(),
+            (_, Value::Constant(b)) if b == Fr::zero() => lhs.clone(),
+            _ => self.binary("add", lhs, rhs),
+        }
+    }
+
+    fn scalar_sub(&self, lhs: &Scalar, rhs: &Scalar) -> Scalar {
+        match (lhs.value, rhs.value) {
+            (Value::Constant(a), Value::Constant(b)) => self.scalar(Value::Constant(a - b)),
+            (_, Value::Constant(b)) if b == Fr::zero() => lhs.clone(),
+            _ => self.binary("sub", lhs, rhs),
+        }
+    }
+
+    fn scalar_mul(&self, lhs: &Scalar, rhs: &Scalar) -> Scalar {
+        match (lhs.value, rhs.value) {
+            (Value::Constant(a), Value::Constant(b)) => self.scalar(Value::Constant(a * b)),
+            (Value::Constant(zero), _) | (_, Value::Constant(zero)) if zero == Fr::zero() => {
+                self.scalar(Value::Constant(Fr::zero()))
+            }
+            (Value::Constant(a), _) if a == Fr::one() => rhs.clone(),
+            (_, Value::Constant(b)) if b == Fr::one() => lhs.clone(),
+            _ => self.binary("mul", lhs, rhs),
+        }
+    }
+
+    fn scalar_neg(&self, scalar: &Scalar) -> Scalar {
+        match scalar.value {
+            Value::Constant(a) => self.scalar(Value::Constant(-a)),
+            Value::Var(_) => {
+                let scalar = self.scalar_ref(scalar);
+                self.push_scalar(format!("crypto_algebra::neg({scalar})"))
+            }
+        }
+    }
+
+    fn scalar_invert(&self, scalar: &Scalar) -> Option<Scalar> {
+        match scalar.value {
+            Value::Constant(a) => Option::<Fr>::from(a.invert()).map(|a| self.scalar(Value::Constant(a))),
+            Value::Var(_) => {
+                let scalar = self.scalar_ref(scalar);
+                Some(self.push_scalar(format!("inverse({scalar})")))
+            }
+        }
+    }
+
+    fn scalar_pow(&self, base: &Scalar, exp: &Scalar, max_bits: usize) -> Scalar {
+        match (base.value, exp.value) {
+            (Value::Constant(base), Value::Constant(exp)) => {
+                let limbs: Vec<u64> = exp
+                    .to_repr()
+                    .as_ref()
+                    .chunks(8)
+                    .map(|limb| u64::from_le_bytes(limb.try_into().unwrap()))
+                    .collect();
+                self.scalar(Value::Constant(base.pow_vartime(limbs)))
+            }
+            _ => {
+                let (base, exp) = (self.scalar_ref(base), self.scalar_ref(exp));
+                self.push_scalar(format!("pow({base}, {exp}, {max_bits})"))
+            }
+        }
+    }
+
+    /// Loads the next entry of the `instances` argument.
+    pub(crate) fn instance(&self) -> Scalar {
+        let index = {
+            let mut state = self.state.borrow_mut();
+            state.num_instances += 1;
+            state.num_instances - 1
+        };
+        self.push_scalar(format!("fr_from_u256(*vector::borrow(&instances, {index}))"))
+    }
+
+    /// Reads and absorbs the next scalar of the proof.
+    pub(crate) fn read_scalar(&self) -> Scalar {
+        let offset = self.advance_proof(0x20);
+        self.push_scalar(format!("read_fr(&mut t, &proof, {offset})"))
+    }
+
+    /// Reads and absorbs the next point of the proof.
+    pub(crate) fn read_ec_point(&self) -> EcPoint {
+        let offset = self.advance_proof(0x40);
+        self.push_ec_point(format!("read_g1(&mut t, &proof, {offset})"))
+    }
+
+    fn advance_proof(&self, len: usize) -> usize {
+        let mut state = self.state.borrow_mut();
+        state.proof_len += len;
+        state.proof_len - len
+    }
+
+    pub(crate) fn absorb_scalar(&self, scalar: &Scalar) {
+        match scalar.value {
+            Value::Constant(value) => {
+                let mut bytes = value.to_repr().as_ref().to_vec();
+                bytes.reverse();
+                self.statement(format!("vector::append(&mut t, x\"{}\");", hex::encode(bytes)));
+            }
+            Value::Var(_) => {
+                let scalar = self.scalar_ref(scalar);
+                self.statement(format!("absorb_fr(&mut t, {scalar});"));
+            }
+        }
+    }
+
+    pub(crate) fn absorb_ec_point(&self, ec_point: &EcPoint) {
+        let ec_point = self.ec_point_ref(ec_point);
+        self.statement(format!("absorb_g1(&mut t, {ec_point});"));
+    }
+
+    pub(crate) fn squeeze_challenge(&self) -> Scalar {
+        self.push_scalar("squeeze(&mut t)".to_string())
+    }
+
+    /// Records `e(lhs, g2) * e(rhs, minus_s_g2) == 1` as the verifier's result.
+    pub(crate) fn pairing_check(
+        &self,
+        lhs: &EcPoint,
+        g2: &G2Affine,
+        rhs: &EcPoint,
+        minus_s_g2: &G2Affine,
+    ) {
+        let (lhs, rhs) = (self.ec_point_ref(lhs), self.ec_point_ref(rhs));
+        let [g2, minus_s_g2] = [g2, minus_s_g2].map(|point| hex::encode(g2_uncompressed(point)));
+        self.state.borrow_mut().result = Some(format!(
+            "crypto_algebra::eq(&crypto_algebra::multi_pairing<G1, G2, Gt>(&vector[*{lhs}, *{rhs}], \
+             &vector[g2(x\"{g2}\"), g2(x\"{minus_s_g2}\")]), &crypto_algebra::zero<Gt>())"
+        ));
+    }
+}
+
+/// Move expression for a constant scalar.
+fn fr_literal(value: &Fr) -> String {
+    let repr = value.to_repr();
+    let bytes = repr.as_ref();
+    if bytes[8..].iter().all(|byte| *byte == 0) {
+        let value = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+        format!("crypto_algebra::from_u64<Fr>({value})")
+    } else {
+        format!("fr(x\"{}\")", hex::encode(bytes))
+    }
+}
+
+fn fq_bytes(value: &Fq) -> Vec<u8> {
+    value.to_repr().as_ref().to_vec()
+}
+
+/// `FormatG1Uncompr` bytes, or `None` for the identity.
+fn g1_uncompressed(point: &G1Affine) -> Option<Vec<u8>> {
+    Option::<Coordinates<G1Affine>>::from(point.coordinates())
+        .map(|coordinates| [fq_bytes(coordinates.x()), fq_bytes(coordinates.y())].concat())
+}
+
+/// `FormatG2Uncompr` bytes of a non-identity point.
+fn g2_uncompressed(point: &G2Affine) -> Vec<u8> {
+    let coordinates = Option::<Coordinates<G2Affine>>::from(point.coordinates()).expect("G2 point at infinity");
+    let (x, y) = (coordinates.x(), coordinates.y());
+    [fq_bytes(&x.c0), fq_bytes(&x.c1), fq_bytes(&y.c0), fq_bytes(&y.c1)].concat()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Value {
+    Constant(Fr),
+    Var(usize),
+}
+
+/// A scalar of the generated verifier, either a folded constant or an entry
+/// of `s`.
+#[derive(Clone, Debug)]
+pub struct Scalar {
+    loader: MoveLoader,
+    value: Value,
+}
+
+impl PartialEq for Scalar {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.loader.state, &other.loader.state) && self.value == other.value
+    }
+}
+
+impl LoadedScalar<Fr> for Scalar {
+    type Loader = MoveLoader;
+
+    fn loader(&self) -> &MoveLoader {
+        &self.loader
+    }
+
+    fn pow_var(&self, exp: &Self, exp_max_bits: usize) -> Self {
+        self.loader.scalar_pow(self, exp, exp_max_bits)
+    }
+}
+
+impl FieldOps for Scalar {
+    fn invert(&self) -> Option<Self> {
+        self.loader.scalar_invert(self)
+    }
+}
+
+macro_rules! impl_scalar_op {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:ident) => {
+        impl $trait<Scalar> for Scalar {
+            type Output = Scalar;
+
+            fn $method(self, rhs: Scalar) -> Scalar {
+                self.loader.$op(&self, &rhs)
+            }
+        }
+
+        impl<'a> $trait<&'a Scalar> for Scalar {
+            type Output = Scalar;
+
+            fn $method(self, rhs: &'a Scalar) -> Scalar {
+                self.loader.$op(&self, rhs)
+            }
+        }
+
+        impl $assign_trait<Scalar> for Scalar {
+            fn $assign_method(&mut self, rhs: Scalar) {
+                *self = self.loader.$op(self, &rhs);
+            }
+        }
+
+        impl<'a> $assign_trait<&'a Scalar> for Scalar {
+            fn $assign_method(&mut self, rhs: &'a Scalar) {
+                *self = self.loader.$op(self, rhs);
+            }
+        }
+    };
+}
+
+impl_scalar_op!(Add, add, AddAssign, add_assign, scalar_add);
+impl_scalar_op!(Sub, sub, SubAssign, sub_assign, scalar_sub);
+impl_scalar_op!(Mul, mul, MulAssign, mul_assign, scalar_mul);
+
+impl Neg for Scalar {
+    type Output = Scalar;
+
+    fn neg(self) -> Scalar {
+        self.loader.scalar_neg(&self)
+    }
+}
+
+/// A point of the generated verifier, an entry of `p`.
+#[derive(Clone, Debug)]
+pub struct EcPoint {
+    loader: MoveLoader,
+    index: usize,
+}
+
+impl PartialEq for EcPoint {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.loader.state, &other.loader.state) && self.index == other.index
+    }
+}
+
+impl LoadedEcPoint<G1Affine> for EcPoint {
+    type Loader = MoveLoader;
+
+    fn loader(&self) -> &MoveLoader {
+        &self.loader
+    }
+}
+
+impl ScalarLoader<Fr> for MoveLoader {
+    type LoadedScalar = Scalar;
+
+    fn load_const(&self, value: &Fr) -> Scalar {
+        self.scalar(Value::Constant(*value))
+    }
+
+    fn assert_eq(&self, annotation: &str, lhs: &Scalar, rhs: &Scalar) {
+        if let (Value::Constant(a), Value::Constant(b)) = (lhs.value, rhs.value) {
+            assert_eq!(a, b, "{annotation}");
+            return;
+        }
+        let (lhs, rhs) = (self.scalar_ref(lhs), self.scalar_ref(rhs));
+        self.statement(format!("if (!crypto_algebra::eq({lhs}, {rhs})) return false;"));
+    }
+}
+
+impl EcPointLoader<G1Affine> for MoveLoader {
+    type LoadedEcPoint = EcPoint;
+
+    fn ec_point_load_const(&self, value: &G1Affine) -> EcPoint {
+        let bytes = g1_uncompressed(value);
+        let key = bytes.clone().unwrap_or_default();
+        let cached = self.state.borrow().ec_point_constants.get(&key).copied();
+        if let Some(index) = cached {
+            return EcPoint {
+                loader: self.clone(),
+                index,
+            };
+        }
+        let ec_point = match bytes {
+            Some(bytes) => self.push_ec_point(format!("g1(x\"{}\")", hex::encode(bytes))),
+            None => self.push_ec_point("crypto_algebra::zero<G1>()".to_string()),
+        };
+        self.state.borrow_mut().ec_point_constants.insert(key, ec_point.index);
+        ec_point
+    }
+
+    fn ec_point_assert_eq(&self, _: &str, lhs: &EcPoint, rhs: &EcPoint) {
+        let (lhs, rhs) = (self.ec_point_ref(lhs), self.ec_point_ref(rhs));
+        self.statement(format!("if (!crypto_algebra::eq({lhs}, {rhs})) return false;"));
+    }
+
+    fn multi_scalar_multiplication(pairs: &[(&Scalar, &EcPoint)]) -> EcPoint {
+        let loader = pairs.first().expect("pairs should not be empty").1.loader.clone();
+        let pairs: Vec<_> = pairs
+            .iter()
+            .filter(|(scalar, _)| scalar.value != Value::Constant(Fr::zero()))
+            .collect();
+        match pairs[..] {
+            [] => loader.ec_point_load_zero(),
+            [(scalar, ec_point)] if scalar.value == Value::Constant(Fr::one()) => (*ec_point).clone(),
+            [(scalar, ec_point)] => {
+                let (ec_point, scalar) = (loader.ec_point_ref(ec_point), loader.scalar_ref(scalar));
+                loader.push_ec_point(format!("crypto_algebra::scalar_mul({ec_point}, {scalar})"))
+            }
+            _ => {
+                let (ec_points, scalars): (Vec<_>, Vec<_>) = pairs
+                    .iter()
+                    .map(|(scalar, ec_point)| {
+                        (
+                            format!("*{}", loader.ec_point_ref(ec_point)),
+                            format!("*{}", loader.scalar_ref(scalar)),
+                        )
+                    })
+                    .unzip();
+                loader.push_ec_point(format!(
+                    "crypto_algebra::multi_scalar_mul(&vector[{}], &vector[{}])",
+                    ec_points.join(", "),
+                    scalars.join(", ")
+                ))
+            }
+        }
+    }
+}
+
+impl Loader<G1Affine> for MoveLoader {}
@@ -0,0 +1,77 @@
+//! Move verifier generator for Halo2 KZG proofs.
+//!
+//! [`gen_move_verifier_code`] is the Aptos counterpart of snark-verifier-sdk's
+//! `gen_evm_verifier_sol_code`: it runs the SHPLONK verifier against a
+//! [`MoveLoader`], which records every step as Move code calling the BN254
+//! `crypto_algebra` natives, and wraps the result in a module exposing
+//!
+//! - `verify(instances: vector<u256>, proof: vector<u8>): bool`, and
+//! - `entry assert_valid(instances, proof)`, which aborts with
+//!   `E_INVALID_PROOF` (1) on rejection.
+//!
+//! Proofs are the bytes returned by `gen_evm_proof_shplonk`, and instances
+//! are flattened across columns. [`crate::registry::keys::CircuitKeys`]
+//! generates one for any registered circuit.
+//!
+//! Only circuits whose verification ends in a single KZG pairing check are
+//! supported, which covers every circuit in the registry. Aggregation
+//! circuits are not: their instances carry an accumulator that has to be
+//! folded into that check, and the generated module would treat it as plain
+//! public input.
+
+mod loader;
+mod transcript;
+
+pub use loader::{EcPoint, MoveLoader, Scalar};
+pub use transcript::MoveTranscript;
+
+use halo2_proofs_axiom::{
+    halo2curves::bn256::{Bn256, G1Affine},
+    plonk::VerifyingKey,
+    poly::kzg::commitment::ParamsKZG,
+};
+use snark_verifier_sdk::{
+    snark_verifier::{
+        pcs::kzg::{KzgAccumulator, KzgSuccinctVerifyingKey},
+        system::halo2::{compile, Config},
+        verifier::{plonk::PlonkSuccinctVerifier, SnarkVerifier},
+    },
+    SHPLONK,
+};
+
+const TEMPLATE: &str = include_str!("verifier.move");
+
+/// Generates a Move module `module` (e.g. `"atomica::equivalence_verifier"`)
+/// verifying SHPLONK proofs for `vk`.
+///
+/// Circuits that carry an accumulator in their instances (aggregation
+/// circuits) are not supported yet.
+pub fn gen_move_verifier_code(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+    module: &str,
+) -> String {
+    let protocol = compile(params, vk, Config::kzg().with_num_instance(num_instance.clone()));
+    let svk: KzgSuccinctVerifyingKey<G1Affine> = params.get_g()[0].into();
+
+    let loader = MoveLoader::new();
+    let protocol = protocol.loaded(&loader);
+    let mut transcript = MoveTranscript::new(&loader);
+
+    let instances = transcript.load_instances(num_instance);
+    let proof = PlonkSuccinctVerifier::<SHPLONK>::read_proof(&svk, &protocol, &instances, &mut transcript)
+        .expect("reading a symbolic proof cannot fail");
+    let accumulators = PlonkSuccinctVerifier::<SHPLONK>::verify(&svk, &protocol, &instances, &proof)
+        .expect("verifying a symbolic proof cannot fail");
+    let [KzgAccumulator { lhs, rhs }]: [_; 1] = accumulators
+        .try_into()
+        .expect("a circuit without accumulator instances yields a single accumulator");
+    loader.pairing_check(&lhs, &params.g2(), &rhs, &-params.s_g2());
+
+    TEMPLATE
+        .replace("__MODULE__", module)
+        .replace("__NUM_INSTANCES__", &loader.num_instances().to_string())
+        .replace("__PROOF_LEN__", &loader.proof_len().to_string())
+        .replace("__BODY__\n", &loader.body())
+}
@@ -0,0 +1,64 @@
+use halo2_proofs_axiom::halo2curves::bn256::G1Affine;
+use snark_verifier_sdk::snark_verifier::{
+    util::transcript::{Transcript, TranscriptRead},
+    Error,
+};
+
+use super::loader::{EcPoint, MoveLoader, Scalar};
+
+/// Keccak-256 transcript of the generated verifier.
+///
+/// Mirrors snark-verifier's `EvmTranscript`: scalars are absorbed as 32-byte
+/// big-endian words, points as `x || y`, and the transcript state is replaced
+/// by each challenge's unreduced digest. Proofs produced for the Solidity
+/// verifier therefore verify unchanged.
+#[derive(Debug)]
+pub struct MoveTranscript {
+    loader: MoveLoader,
+}
+
+impl MoveTranscript {
+    pub fn new(loader: &MoveLoader) -> Self {
+        Self {
+            loader: loader.clone(),
+        }
+    }
+
+    /// Loads the `instances` argument, split into columns of `num_instance`.
+    pub fn load_instances(&mut self, num_instance: Vec<usize>) -> Vec<Vec<Scalar>> {
+        num_instance
+            .into_iter()
+            .map(|n| (0..n).map(|_| self.loader.instance()).collect())
+            .collect()
+    }
+}
+
+impl Transcript<G1Affine, MoveLoader> for MoveTranscript {
+    fn loader(&self) -> &MoveLoader {
+        &self.loader
+    }
+
+    fn squeeze_challenge(&mut self) -> Scalar {
+        self.loader.squeeze_challenge()
+    }
+
+    fn common_ec_point(&mut self, ec_point: &EcPoint) -> Result<(), Error> {
+        self.loader.absorb_ec_point(ec_point);
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: &Scalar) -> Result<(), Error> {
+        self.loader.absorb_scalar(scalar);
+        Ok(())
+    }
+}
+
+impl TranscriptRead<G1Affine, MoveLoader> for MoveTranscript {
+    fn read_scalar(&mut self) -> Result<Scalar, Error> {
+        Ok(self.loader.read_scalar())
+    }
+
+    fn read_ec_point(&mut self) -> Result<EcPoint, Error> {
+        Ok(self.loader.read_ec_point())
+    }
+}
@@ -0,0 +1,158 @@
+/// Halo2 KZG (SHPLONK) proof verifier, generated by diem-prover-halo2.
+///
+/// Proofs and instances are encoded exactly as for the generated Solidity
+/// verifier: 32-byte big-endian scalars, `x || y` points and a Keccak-256
+/// transcript. Instances are passed as `u256`s below the BN254 scalar modulus.
+module __MODULE__ {
+    use std::bcs;
+    use std::option;
+    use std::vector;
+    use aptos_std::aptos_hash;
+    use aptos_std::crypto_algebra::{Self, Element};
+    use aptos_std::bn254_algebra::{
+        Fr, G1, G2, Gt,
+        FormatFrLsb, FormatG1Uncompr, FormatG2Uncompr
+    };
+
+    /// Error codes
+    const E_INVALID_PROOF: u64 = 1;
+    const E_MALFORMED_PROOF: u64 = 2;
+    const E_INVALID_INSTANCES: u64 = 3;
+
+    const NUM_INSTANCES: u64 = __NUM_INSTANCES__;
+    const PROOF_LEN: u64 = __PROOF_LEN__;
+
+    /// BN254 scalar field modulus.
+    const R: u256 = 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001;
+
+    /// Aborts with `E_INVALID_PROOF` unless `proof` verifies for `instances`.
+    public entry fun assert_valid(instances: vector<u256>, proof: vector<u8>) {
+        assert!(verify(instances, proof), E_INVALID_PROOF);
+    }
+
+    /// Returns whether `proof` verifies for `instances`.
+    ///
+    /// Aborts on malformed input: a wrong number of instances, an instance
+    /// outside the scalar field, a non-canonical proof element, or a variable
+    /// exponent wider than the protocol allows.
+    public fun verify(instances: vector<u256>, proof: vector<u8>): bool {
+        assert!(vector::length(&instances) == NUM_INSTANCES, E_INVALID_INSTANCES);
+        assert!(vector::length(&proof) == PROOF_LEN, E_MALFORMED_PROOF);
+        // Scalars, points and the transcript state. Values live in vectors
+        // rather than locals because the verifier needs far more than 255.
+        let s = vector::empty<Element<Fr>>();
+        let p = vector::empty<Element<G1>>();
+        let t = vector::empty<u8>();
+        // Each value is computed here before it is pushed, as its expression
+        // may borrow the vector it is pushed onto.
+        let fr_tmp: Element<Fr>;
+        let g1_tmp: Element<G1>;
+__BODY__
+    }
+
+    fun fr(bytes: vector<u8>): Element<Fr> {
+        option::destroy_some(crypto_algebra::deserialize<Fr, FormatFrLsb>(&bytes))
+    }
+
+    fun g1(bytes: vector<u8>): Element<G1> {
+        option::destroy_some(crypto_algebra::deserialize<G1, FormatG1Uncompr>(&bytes))
+    }
+
+    fun g2(bytes: vector<u8>): Element<G2> {
+        option::destroy_some(crypto_algebra::deserialize<G2, FormatG2Uncompr>(&bytes))
+    }
+
+    fun inverse(x: &Element<Fr>): Element<Fr> {
+        option::destroy_some(crypto_algebra::inv(x))
+    }
+
+    /// `x^exp` by square-and-multiply; `exp` must fit in `max_bits` bits.
+    fun pow(x: &Element<Fr>, exp: &Element<Fr>, max_bits: u64): Element<Fr> {
+        let bits = crypto_algebra::serialize<Fr, FormatFrLsb>(exp);
+        let i = max_bits;
+        while (i < 256) {
+            assert!(((*vector::borrow(&bits, i / 8) >> ((i % 8) as u8)) & 1) == 0, E_INVALID_INSTANCES);
+            i = i + 1;
+        };
+        let res = crypto_algebra::one<Fr>();
+        let i = max_bits;
+        while (i > 0) {
+            i = i - 1;
+            res = crypto_algebra::sqr(&res);
+            if (((*vector::borrow(&bits, i / 8) >> ((i % 8) as u8)) & 1) == 1) {
+                res = crypto_algebra::mul(&res, x);
+            };
+        };
+        res
+    }
+
+    fun fr_from_u256(x: u256): Element<Fr> {
+        assert!(x < R, E_INVALID_INSTANCES);
+        fr(bcs::to_bytes(&x))
+    }
+
+    /// `bytes[start..start + len]` in reverse order.
+    fun reversed(bytes: &vector<u8>, start: u64, len: u64): vector<u8> {
+        let res = vector::empty();
+        let i = start + len;
+        while (i > start) {
+            i = i - 1;
+            vector::push_back(&mut res, *vector::borrow(bytes, i));
+        };
+        res
+    }
+
+    /// Appends `bytes[start..start + len]` to the transcript.
+    fun absorb(t: &mut vector<u8>, bytes: &vector<u8>, start: u64, len: u64) {
+        let i = start;
+        while (i < start + len) {
+            vector::push_back(t, *vector::borrow(bytes, i));
+            i = i + 1;
+        };
+    }
+
+    fun absorb_fr(t: &mut vector<u8>, x: &Element<Fr>) {
+        let bytes = crypto_algebra::serialize<Fr, FormatFrLsb>(x);
+        vector::append(t, reversed(&bytes, 0, 32));
+    }
+
+    fun absorb_g1(t: &mut vector<u8>, point: &Element<G1>) {
+        let bytes = crypto_algebra::serialize<G1, FormatG1Uncompr>(point);
+        vector::append(t, reversed(&bytes, 0, 32));
+        vector::append(t, reversed(&bytes, 32, 32));
+    }
+
+    /// Reads a big-endian scalar from the proof and absorbs it.
+    fun read_fr(t: &mut vector<u8>, proof: &vector<u8>, offset: u64): Element<Fr> {
+        absorb(t, proof, offset, 32);
+        let x = crypto_algebra::deserialize<Fr, FormatFrLsb>(&reversed(proof, offset, 32));
+        assert!(option::is_some(&x), E_MALFORMED_PROOF);
+        option::destroy_some(x)
+    }
+
+    /// Reads a big-endian `x || y` point from the proof and absorbs it.
+    fun read_g1(t: &mut vector<u8>, proof: &vector<u8>, offset: u64): Element<G1> {
+        absorb(t, proof, offset, 64);
+        let bytes = reversed(proof, offset, 32);
+        vector::append(&mut bytes, reversed(proof, offset + 32, 32));
+        let point = crypto_algebra::deserialize<G1, FormatG1Uncompr>(&bytes);
+        assert!(option::is_some(&point), E_MALFORMED_PROOF);
+        option::destroy_some(point)
+    }
+
+    /// Keccak-256 challenge reduced modulo `R`. The unreduced digest becomes
+    /// the transcript state, and a lone digest is extended with a `1` byte so
+    /// consecutive challenges differ.
+    fun squeeze(t: &mut vector<u8>): Element<Fr> {
+        if (vector::length(t) == 32) vector::push_back(t, 1);
+        let hash = aptos_hash::keccak256(*t);
+        let h = 0u256;
+        let i = 0;
+        while (i < 32) {
+            h = (h << 8) | (*vector::borrow(&hash, i) as u256);
+            i = i + 1;
+        };
+        *t = hash;
+        fr(bcs::to_bytes(&(h % R)))
+    }
+}
@@ -0,0 +1,56 @@
+//! SHPLONK proving with the Fiat–Shamir transcript chosen per call.
+//!
+//! halo2's default Blake2b transcript is cheap natively but very expensive
+//! to re-hash inside a circuit. Proofs that will be aggregated are written
+//! with a Poseidon transcript over BN254, which snark-verifier's halo2
+//! loader can verify in-circuit; proofs checked by the Solidity or Move
+//! verifiers, including the outer aggregation proof, use the Keccak
+//! transcript.
+
+use halo2_proofs_axiom::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{Circuit, ProvingKey, VerifyingKey},
+    poly::kzg::commitment::ParamsKZG,
+};
+use snark_verifier_sdk::{
+    evm::gen_evm_proof_shplonk,
+    halo2::gen_proof_shplonk,
+    snark_verifier::system::halo2::{compile, Config},
+    Snark,
+};
+
+/// The transcript a proof is written with. A verifier must use the same one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptKind {
+    /// Poseidon over BN254, for inner proofs verified by another circuit.
+    Poseidon,
+    /// Keccak-256, for proofs verified on-chain.
+    Evm,
+}
+
+/// Proves `circuit` with the given transcript.
+pub fn prove<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+    transcript: TranscriptKind,
+) -> Vec<u8> {
+    match transcript {
+        TranscriptKind::Poseidon => gen_proof_shplonk(params, pk, circuit, instances, None),
+        TranscriptKind::Evm => gen_evm_proof_shplonk(params, pk, circuit, instances),
+    }
+}
+
+/// Packages a [`TranscriptKind::Poseidon`] proof as an input to
+/// snark-verifier-sdk's `AggregationCircuit`.
+pub fn poseidon_snark(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: Vec<Vec<Fr>>,
+    proof: Vec<u8>,
+) -> Snark {
+    let num_instance = instances.iter().map(Vec::len).collect();
+    let protocol = compile(params, vk, Config::kzg().with_num_instance(num_instance));
+    Snark::new(protocol, instances, proof)
+}
@@ -0,0 +1,246 @@
+//! Import of snarkjs `.ptau` (perpetual powers-of-tau) files.
+//!
+//! A `.ptau` file is `"ptau" || version: u32 || num_sections: u32` followed by
+//! sections `id: u32 || size: u64 || data`, all little-endian. We read
+//!
+//! 1. the header: `n8: u32 || q (n8 bytes) || power: u32 || ceremony_power: u32`,
+//! 2. `tau^i * G1` for `i < 2^(power + 1) - 1`,
+//! 3. `tau^i * G2` for `i < 2^power`,
+//!
+//! with field elements in 32-byte little-endian Montgomery form, which is also
+//! halo2curves' raw encoding. [`PowersOfTau::verify`] checks that the G1 powers
+//! really are successive powers of the tau committed to in G2, so the resulting
+//! [`ParamsKZG`] can be traced back to the ceremony transcript instead of being
+//! trusted as an opaque blob.
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use halo2_proofs_axiom::{
+    arithmetic::{best_multiexp, g_to_lagrange},
+    halo2curves::{
+        bn256::{Bn256, Fq, Fq2, Fr, G1Affine, G2Affine, G2},
+        ff::{Field, PrimeField},
+        group::{prime::PrimeCurveAffine, Curve, Group},
+        pairing::Engine,
+        serde::SerdeObject,
+        CurveAffine,
+    },
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::RngCore;
+
+const MAGIC: &[u8; 4] = b"ptau";
+const SECTION_HEADER: u32 = 1;
+const SECTION_TAU_G1: u32 = 2;
+const SECTION_TAU_G2: u32 = 3;
+
+/// Bytes per base field element; snarkjs calls this `n8`.
+const FQ_BYTES: usize = 32;
+
+/// The prefix of a ceremony needed for `2^k`-row circuits.
+#[derive(Clone, Debug)]
+pub struct PowersOfTau {
+    /// Powers the ceremony was run for; `2^power` is the largest usable size.
+    pub power: u32,
+    /// Powers the original ceremony was run for, before any truncation.
+    pub ceremony_power: u32,
+    /// `tau^i * G1` for `i < 2^k`.
+    pub g: Vec<G1Affine>,
+    pub g2: G2Affine,
+    /// `tau * G2`.
+    pub s_g2: G2Affine,
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_fq<R: Read>(reader: &mut R) -> io::Result<Fq> {
+    let mut bytes = [0; FQ_BYTES];
+    reader.read_exact(&mut bytes)?;
+    Fq::from_raw_bytes(&bytes).ok_or_else(|| invalid_data("non-canonical base field element"))
+}
+
+fn read_g1<R: Read>(reader: &mut R) -> io::Result<G1Affine> {
+    let (x, y) = (read_fq(reader)?, read_fq(reader)?);
+    Option::from(G1Affine::from_xy(x, y)).ok_or_else(|| invalid_data("G1 point not on the curve"))
+}
+
+fn read_g2<R: Read>(reader: &mut R) -> io::Result<G2Affine> {
+    let x = Fq2 {
+        c0: read_fq(reader)?,
+        c1: read_fq(reader)?,
+    };
+    let y = Fq2 {
+        c0: read_fq(reader)?,
+        c1: read_fq(reader)?,
+    };
+    let point: G2Affine = Option::from(G2Affine::from_xy(x, y))
+        .ok_or_else(|| invalid_data("G2 point not on the curve"))?;
+    if !is_torsion_free(&point) {
+        return Err(invalid_data("G2 point not in the prime-order subgroup"));
+    }
+    Ok(point)
+}
+
+/// Whether `point` lies in the order-`r` subgroup, i.e. `[r - 1] P == -P`.
+///
+/// Unlike G1, the G2 twist has a large cofactor. The multiple is taken bit by
+/// bit because a scalar multiplication may use an endomorphism that is only
+/// valid inside the subgroup.
+fn is_torsion_free(point: &G2Affine) -> bool {
+    let scalar = (-Fr::ONE).to_repr();
+    let mut acc = G2::identity();
+    for byte in scalar.as_ref().iter().rev() {
+        for i in (0..8).rev() {
+            acc = acc.double();
+            if (byte >> i) & 1 == 1 {
+                acc += point;
+            }
+        }
+    }
+    acc == -point.to_curve()
+}
+
+/// Offsets of the data of each section, indexed by section id.
+fn read_sections<R: Read + Seek>(reader: &mut R) -> io::Result<Vec<Option<(u64, u64)>>> {
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid_data("not a .ptau file"));
+    }
+    let _version = read_u32(reader)?;
+    let num_sections = read_u32(reader)?;
+
+    let mut sections = vec![None; SECTION_TAU_G2 as usize + 1];
+    for _ in 0..num_sections {
+        let id = read_u32(reader)?;
+        let size = read_u64(reader)?;
+        let offset = reader.stream_position()?;
+        if let Some(section) = sections.get_mut(id as usize) {
+            if section.is_some() {
+                return Err(invalid_data(format!("duplicate section {id}")));
+            }
+            *section = Some((offset, size));
+        }
+        reader.seek(SeekFrom::Current(size as i64))?;
+    }
+    Ok(sections)
+}
+
+impl PowersOfTau {
+    /// Reads the first `2^k` G1 powers and the first two G2 powers.
+    ///
+    /// Only checks that every element is a canonical point on the curve, and
+    /// that the G2 points are in the prime-order subgroup; call
+    /// [`Self::verify`] before using the result.
+    pub fn read<R: Read + Seek>(reader: &mut R, k: u32) -> io::Result<Self> {
+        let sections = read_sections(reader)?;
+        let section = |id: u32| {
+            sections[id as usize].ok_or_else(|| invalid_data(format!("missing section {id}")))
+        };
+
+        let (offset, _) = section(SECTION_HEADER)?;
+        reader.seek(SeekFrom::Start(offset))?;
+        let n8 = read_u32(reader)? as usize;
+        if n8 != FQ_BYTES {
+            return Err(invalid_data(format!(
+                "{n8}-byte field elements; expected BN254"
+            )));
+        }
+        let mut q = [0; FQ_BYTES];
+        reader.read_exact(&mut q)?;
+        q.reverse();
+        if !format!("0x{}", hex::encode(q)).eq_ignore_ascii_case(Fq::MODULUS) {
+            return Err(invalid_data("base field is not BN254"));
+        }
+        let power = read_u32(reader)?;
+        let ceremony_power = read_u32(reader)?;
+        if k == 0 || k > power {
+            return Err(invalid_data(format!("k = {k} is outside 1..={power}")));
+        }
+
+        let n = 1usize << k;
+        let (offset, size) = section(SECTION_TAU_G1)?;
+        if size < (n * 2 * FQ_BYTES) as u64 {
+            return Err(invalid_data("tau G1 section is too short"));
+        }
+        reader.seek(SeekFrom::Start(offset))?;
+        let g = (0..n)
+            .map(|_| read_g1(reader))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let (offset, size) = section(SECTION_TAU_G2)?;
+        if size < (2 * 4 * FQ_BYTES) as u64 {
+            return Err(invalid_data("tau G2 section is too short"));
+        }
+        reader.seek(SeekFrom::Start(offset))?;
+        let g2 = read_g2(reader)?;
+        let s_g2 = read_g2(reader)?;
+
+        Ok(Self {
+            power,
+            ceremony_power,
+            g,
+            g2,
+            s_g2,
+        })
+    }
+
+    pub fn k(&self) -> u32 {
+        self.g.len().trailing_zeros()
+    }
+
+    /// Checks the powers start at the generators and are consistent with
+    /// `tau * G2`: with random `r_i`,
+    /// `e(sum r_i * g[i + 1], G2) == e(sum r_i * g[i], tau * G2)`.
+    pub fn verify(&self, mut rng: impl RngCore) -> io::Result<()> {
+        if self.g[0] != G1Affine::generator() || self.g2 != G2Affine::generator() {
+            return Err(invalid_data("powers do not start at the generators"));
+        }
+        if bool::from(self.s_g2.is_identity()) {
+            return Err(invalid_data("tau is zero"));
+        }
+
+        let coeffs: Vec<_> = (1..self.g.len()).map(|_| Fr::random(&mut rng)).collect();
+        let shifted = best_multiexp(&coeffs, &self.g[1..]).to_affine();
+        let unshifted = best_multiexp(&coeffs, &self.g[..self.g.len() - 1]).to_affine();
+        if Bn256::pairing(&shifted, &self.g2) != Bn256::pairing(&unshifted, &self.s_g2) {
+            return Err(invalid_data("G1 powers are inconsistent with tau * G2"));
+        }
+        Ok(())
+    }
+
+    /// Writes the `ParamsKZG` raw-bytes encoding, the format of `hermez-raw-*`.
+    pub fn write_params<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let k = self.k();
+        let g_projective = self.g.iter().map(|g| g.to_curve()).collect();
+        let g_lagrange: Vec<G1Affine> = g_to_lagrange(g_projective, k);
+
+        writer.write_all(&k.to_le_bytes())?;
+        for point in self.g.iter().chain(&g_lagrange) {
+            point.write_raw(writer)?;
+        }
+        self.g2.write_raw(writer)?;
+        self.s_g2.write_raw(writer)
+    }
+
+    pub fn params(&self) -> ParamsKZG<Bn256> {
+        let mut bytes = vec![];
+        self.write_params(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        ParamsKZG::read(&mut bytes.as_slice()).expect("params were just encoded")
+    }
+}
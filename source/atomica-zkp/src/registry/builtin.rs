@@ -0,0 +1,401 @@
+//! Registry entries and JSON witness formats for this crate's circuits.
+//!
+//! Byte strings are `0x`-prefixed hex. Curve points use the compressed
+//! arkworks encoding, as Aptos' `crypto_algebra` serialises them.
+
+use std::io;
+
+use ark_bls12_381::{Fr as BlsScalar, G1Affine, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::CanonicalDeserialize;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use super::{CircuitInfo, InstanceField};
+use crate::auction::{AuctionClearingCircuit, SealedBid, AUCTION_CIRCUIT_PARAMS};
+use crate::circuit::BuilderCircuit;
+use crate::dkg::{self, DkgAggregationCircuit, DKG_CIRCUIT_PARAMS};
+use crate::ed25519::{native::EdwardsAffine, Ed25519SignatureCircuit, ED25519_CIRCUIT_PARAMS};
+use crate::envelope::{decode_hex, invalid_data};
+use crate::epoch::{
+    native::{LedgerInfo, ValidatorSet},
+    EpochChange, EpochChangeCircuit, DEFAULT_VALIDATOR_CAPACITY, EPOCH_CIRCUIT_PARAMS,
+};
+use crate::equivalence::{EquivalenceCircuit, EQUIVALENCE_CIRCUIT_PARAMS, EXPECTED_INPUT};
+use crate::ibe::{native, IbeDecryptionCircuit, IBE_CIRCUIT_PARAMS};
+
+pub(super) fn circuits() -> Vec<CircuitInfo> {
+    vec![
+        CircuitInfo {
+            id: "equivalence",
+            description: "Knowledge of a private input equal to 42",
+            params: EQUIVALENCE_CIRCUIT_PARAMS,
+            instances: &[],
+            load_witness: load_equivalence,
+            example: example_equivalence,
+        },
+        CircuitInfo {
+            id: "ibe-decryption",
+            description: "Hashed-IBE timelock decryption of a sealed bid",
+            params: IBE_CIRCUIT_PARAMS,
+            instances: &[
+                InstanceField::fixed("u", 6),
+                InstanceField::fixed("signature", 12),
+                InstanceField::variable("ciphertext"),
+                InstanceField::variable("plaintext"),
+            ],
+            load_witness: load_ibe,
+            example: example_ibe,
+        },
+        CircuitInfo {
+            id: "auction-clearing",
+            description: "Winner and price of a sealed-bid auction",
+            params: AUCTION_CIRCUIT_PARAMS,
+            instances: &[
+                InstanceField::fixed("bids_commitment", 2),
+                InstanceField::fixed("signature", 12),
+                InstanceField::fixed("has_winner", 1),
+                InstanceField::fixed("winner_index", 1),
+                InstanceField::fixed("winner_address", 2),
+                InstanceField::fixed("price", 1),
+                InstanceField::fixed("valid_mask", 1),
+            ],
+            load_witness: load_auction,
+            example: example_auction,
+        },
+        CircuitInfo {
+            id: "ed25519-raw-transaction",
+            description: "Ed25519 signature over an Aptos RawTransaction",
+            params: ED25519_CIRCUIT_PARAMS,
+            instances: &[
+                InstanceField::fixed("public_key", 2),
+                InstanceField::variable("raw_transaction"),
+            ],
+            load_witness: load_ed25519,
+            example: example_ed25519,
+        },
+        CircuitInfo {
+            id: "dkg-aggregation",
+            description: "DKG group key as the sum of the contributions in an on-chain transcript",
+            params: DKG_CIRCUIT_PARAMS,
+            instances: &[
+                InstanceField::fixed("epoch", 1),
+                InstanceField::fixed("soks_digest", 2),
+                InstanceField::fixed("group_key", 6),
+            ],
+            load_witness: load_dkg,
+            example: example_dkg,
+        },
+        CircuitInfo {
+            id: "epoch-change",
+            description: "Quorum-signed ledger info handing over to the next epoch's validator set",
+            params: EPOCH_CIRCUIT_PARAMS,
+            instances: &[
+                InstanceField::fixed("state_in", 1),
+                InstanceField::fixed("state_out", 1),
+            ],
+            load_witness: load_epoch_change,
+            example: example_epoch_change,
+        },
+    ]
+}
+
+fn from_json<T: DeserializeOwned>(json: &str) -> io::Result<T> {
+    serde_json::from_str(json).map_err(|err| invalid_data(format!("bad witness: {err}")))
+}
+
+fn fixed_bytes<const N: usize>(value: &str) -> io::Result<[u8; N]> {
+    decode_hex(value)?
+        .try_into()
+        .map_err(|_| invalid_data(format!("expected {N} bytes: {value}")))
+}
+
+fn g1(value: &str) -> io::Result<G1Affine> {
+    G1Affine::deserialize_compressed(decode_hex(value)?.as_slice())
+        .map_err(|err| invalid_data(format!("bad G1 point {value}: {err}")))
+}
+
+fn g2(value: &str) -> io::Result<G2Affine> {
+    G2Affine::deserialize_compressed(decode_hex(value)?.as_slice())
+        .map_err(|err| invalid_data(format!("bad G2 point {value}: {err}")))
+}
+
+/// Rejects what the circuit constructors would panic on.
+fn finite<P: AffineRepr>(point: P) -> io::Result<P> {
+    if point.is_zero() {
+        return Err(invalid_data("point at infinity"));
+    }
+    Ok(point)
+}
+
+#[derive(Deserialize)]
+struct EquivalenceWitness {
+    private_input: u64,
+}
+
+fn load_equivalence(json: &str) -> io::Result<Box<dyn BuilderCircuit>> {
+    let witness: EquivalenceWitness = from_json(json)?;
+    Ok(Box::new(EquivalenceCircuit {
+        private_input: witness.private_input,
+    }))
+}
+
+fn example_equivalence() -> Box<dyn BuilderCircuit> {
+    Box::new(EquivalenceCircuit {
+        private_input: EXPECTED_INPUT,
+    })
+}
+
+/// The `test_ibe_flow` scenario, shared by the IBE and auction examples.
+fn example_bid(message: &[u8]) -> (G1Affine, G2Affine, Vec<u8>) {
+    let s = BlsScalar::from(123456789u64);
+    let mpk = (G1Affine::generator() * s).into_affine();
+    let q_id = native::identity_point(b"epoch_100");
+    let signature = (q_id * s).into_affine();
+    let (u, ciphertext) = native::encrypt(&mpk, &q_id, BlsScalar::from(987654321u64), message);
+    (u, signature, ciphertext)
+}
+
+#[derive(Deserialize)]
+struct IbeWitness {
+    u: String,
+    signature: String,
+    ciphertext: String,
+}
+
+fn load_ibe(json: &str) -> io::Result<Box<dyn BuilderCircuit>> {
+    let witness: IbeWitness = from_json(json)?;
+    let ciphertext = decode_hex(&witness.ciphertext)?;
+    if ciphertext.len() > crate::ibe::MAX_MESSAGE_BYTES {
+        return Err(invalid_data("ciphertext longer than one Keccak mask"));
+    }
+    Ok(Box::new(IbeDecryptionCircuit::new(
+        finite(g1(&witness.u)?)?,
+        finite(g2(&witness.signature)?)?,
+        ciphertext,
+    )))
+}
+
+fn example_ibe() -> Box<dyn BuilderCircuit> {
+    let (u, signature, ciphertext) = example_bid(&1_000u64.to_le_bytes());
+    Box::new(IbeDecryptionCircuit::new(u, signature, ciphertext))
+}
+
+#[derive(Deserialize)]
+struct AuctionWitness {
+    signature: String,
+    bids: Vec<BidWitness>,
+}
+
+#[derive(Deserialize)]
+struct BidWitness {
+    bidder: String,
+    u: String,
+    payment: u64,
+    ciphertext: String,
+}
+
+fn load_auction(json: &str) -> io::Result<Box<dyn BuilderCircuit>> {
+    let witness: AuctionWitness = from_json(json)?;
+    if witness.bids.len() > crate::auction::MAX_BIDS {
+        return Err(invalid_data("too many bids"));
+    }
+    let bids = witness
+        .bids
+        .iter()
+        .map(|bid| {
+            Ok(SealedBid {
+                bidder: fixed_bytes(&bid.bidder)?,
+                u: finite(g1(&bid.u)?)?,
+                payment: bid.payment,
+                ciphertext: fixed_bytes(&bid.ciphertext)?,
+            })
+        })
+        .collect::<io::Result<_>>()?;
+    Ok(Box::new(AuctionClearingCircuit::new(
+        bids,
+        finite(g2(&witness.signature)?)?,
+    )))
+}
+
+fn example_auction() -> Box<dyn BuilderCircuit> {
+    let (u, signature, ciphertext) = example_bid(&1_000u64.to_le_bytes());
+    let bid = SealedBid {
+        bidder: [1; 32],
+        u,
+        payment: 1_000,
+        ciphertext: ciphertext.try_into().expect("bids are 8 bytes"),
+    };
+    Box::new(AuctionClearingCircuit::new(vec![bid], signature))
+}
+
+#[derive(Deserialize)]
+struct Ed25519Witness {
+    public_key: String,
+    signature: String,
+    raw_transaction: String,
+}
+
+fn load_ed25519(json: &str) -> io::Result<Box<dyn BuilderCircuit>> {
+    let witness: Ed25519Witness = from_json(json)?;
+    Ok(Box::new(Ed25519SignatureCircuit::new(
+        fixed_bytes(&witness.public_key)?,
+        fixed_bytes(&witness.signature)?,
+        decode_hex(&witness.raw_transaction)?,
+    )))
+}
+
+/// BCS length of a typical coin transfer; the layout depends on it.
+const EXAMPLE_RAW_TRANSACTION_BYTES: usize = 200;
+
+fn example_ed25519() -> Box<dyn BuilderCircuit> {
+    Box::new(Ed25519SignatureCircuit::new(
+        EdwardsAffine::basepoint().compress(),
+        [0; 64],
+        vec![0; EXAMPLE_RAW_TRANSACTION_BYTES],
+    ))
+}
+
+#[derive(Deserialize)]
+struct DkgWitness {
+    epoch: u64,
+    /// `DKGSessionState.transcript` as hex.
+    transcript: String,
+}
+
+fn load_dkg(json: &str) -> io::Result<Box<dyn BuilderCircuit>> {
+    let witness: DkgWitness = from_json(json)?;
+    let transcript = decode_hex(&witness.transcript)?;
+    Ok(Box::new(DkgAggregationCircuit::from_transcript(
+        witness.epoch,
+        &transcript,
+    )?))
+}
+
+fn example_dkg() -> Box<dyn BuilderCircuit> {
+    let contributions: Vec<_> = (1..=4u64)
+        .map(|secret| (G1Affine::generator() * BlsScalar::from(secret)).into_affine())
+        .collect();
+    Box::new(DkgAggregationCircuit::new(
+        1,
+        dkg::native::encode_soks(&contributions),
+    ))
+}
+
+#[derive(Deserialize)]
+struct EpochChangeWitness {
+    validators: Vec<ValidatorWitness>,
+    signers: Vec<bool>,
+    signature: String,
+    ledger_info: LedgerInfoWitness,
+}
+
+#[derive(Deserialize)]
+struct ValidatorWitness {
+    public_key: String,
+    voting_power: u64,
+}
+
+/// The epoch-ending `LedgerInfo`, field by field.
+#[derive(Deserialize)]
+struct LedgerInfoWitness {
+    epoch: u64,
+    round: u64,
+    id: String,
+    executed_state_id: String,
+    version: u64,
+    timestamp_usecs: u64,
+    next_validators: Vec<NextValidatorWitness>,
+    consensus_data_hash: String,
+}
+
+#[derive(Deserialize)]
+struct NextValidatorWitness {
+    address: String,
+    public_key: String,
+    voting_power: u64,
+}
+
+fn validator_set<'a>(
+    validators: impl ExactSizeIterator<Item = (&'a String, u64)>,
+) -> io::Result<ValidatorSet> {
+    if validators.len() > DEFAULT_VALIDATOR_CAPACITY {
+        return Err(invalid_data(format!(
+            "more than {DEFAULT_VALIDATOR_CAPACITY} validators"
+        )));
+    }
+    let (public_keys, voting_powers) = validators
+        .map(|(public_key, voting_power)| Ok((finite(g1(public_key)?)?, voting_power)))
+        .collect::<io::Result<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+    Ok(ValidatorSet::new(public_keys, voting_powers))
+}
+
+fn load_epoch_change(json: &str) -> io::Result<Box<dyn BuilderCircuit>> {
+    let witness: EpochChangeWitness = from_json(json)?;
+    if witness.signers.len() != witness.validators.len() {
+        return Err(invalid_data("one signer flag per validator"));
+    }
+    let info = witness.ledger_info;
+    if info.epoch == u64::MAX {
+        return Err(invalid_data("the last epoch has no successor"));
+    }
+    let validators = witness
+        .validators
+        .iter()
+        .map(|validator| (&validator.public_key, validator.voting_power));
+    let next_validators = info
+        .next_validators
+        .iter()
+        .map(|validator| (&validator.public_key, validator.voting_power));
+    let change = EpochChange {
+        validators: validator_set(validators)?,
+        signers: witness.signers,
+        signature: finite(g2(&witness.signature)?)?,
+        ledger_info: LedgerInfo {
+            epoch: info.epoch,
+            round: info.round,
+            id: fixed_bytes(&info.id)?,
+            executed_state_id: fixed_bytes(&info.executed_state_id)?,
+            version: info.version,
+            timestamp_usecs: info.timestamp_usecs,
+            next_addresses: info
+                .next_validators
+                .iter()
+                .map(|validator| fixed_bytes(&validator.address))
+                .collect::<io::Result<_>>()?,
+            next_validators: validator_set(next_validators)?,
+            consensus_data_hash: fixed_bytes(&info.consensus_data_hash)?,
+        },
+    };
+    Ok(Box::new(EpochChangeCircuit::new(
+        change,
+        DEFAULT_VALIDATOR_CAPACITY,
+    )))
+}
+
+fn example_epoch_change() -> Box<dyn BuilderCircuit> {
+    let validators = ValidatorSet::new(
+        (1..=DEFAULT_VALIDATOR_CAPACITY as u64)
+            .map(|secret| (G1Affine::generator() * BlsScalar::from(secret)).into_affine())
+            .collect(),
+        vec![1; DEFAULT_VALIDATOR_CAPACITY],
+    );
+    let change = EpochChange {
+        signers: vec![true; DEFAULT_VALIDATOR_CAPACITY],
+        signature: G2Affine::generator(),
+        ledger_info: LedgerInfo {
+            epoch: 1,
+            round: 0,
+            id: [0; 32],
+            executed_state_id: [0; 32],
+            version: 0,
+            timestamp_usecs: 0,
+            next_addresses: vec![[0; 32]; DEFAULT_VALIDATOR_CAPACITY],
+            next_validators: validators.clone(),
+            consensus_data_hash: [0; 32],
+        },
+        validators,
+    };
+    Box::new(EpochChangeCircuit::new(change, DEFAULT_VALIDATOR_CAPACITY))
+}
@@ -0,0 +1,199 @@
+//! Proving keys of registered circuits, cached on disk by circuit id.
+//!
+//! Key generation dominates the cost of every tool that proves, exports a
+//! verifier or measures gas. [`KeyCache`] runs it once per circuit from the
+//! registered example and stores the proving key next to the layout needed
+//! to rebuild the circuit for proving.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter},
+    path::PathBuf,
+};
+
+use halo2_proofs_axiom::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{ProvingKey, VerifyingKey},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "revm")]
+use snark_verifier_sdk::{
+    evm::gen_evm_verifier_shplonk,
+    snark_verifier::loader::evm::{deploy_and_call, encode_calldata},
+};
+use snark_verifier_sdk::{evm::gen_evm_verifier_sol_code, gen_pk, CircuitExt, SHPLONK};
+
+use super::CircuitInfo;
+use crate::circuit::BuilderCircuit;
+use crate::halo2_base::gates::{
+    circuit::{builder::BaseCircuitBuilder, BaseCircuitParams, CircuitBuilderStage},
+    flex_gate::MultiPhaseThreadBreakPoints,
+};
+use crate::move_verifier::gen_move_verifier_code;
+use crate::prover::{prove, TranscriptKind};
+
+/// Everything besides the proving key that proving needs, fixed at key
+/// generation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CircuitLayout {
+    pub config: BaseCircuitParams,
+    pub break_points: MultiPhaseThreadBreakPoints,
+    /// Instance words of the example; generated verifiers expect this many.
+    pub num_instance: Vec<usize>,
+}
+
+/// A circuit's proving key and layout.
+#[derive(Debug)]
+pub struct CircuitKeys {
+    pub pk: ProvingKey<G1Affine>,
+    pub layout: CircuitLayout,
+}
+
+impl CircuitKeys {
+    /// Runs key generation on `info`'s example.
+    pub fn generate(info: &CircuitInfo, params: &ParamsKZG<Bn256>) -> Self {
+        let keygen = (info.example)().build(CircuitBuilderStage::Keygen, info.params);
+        let pk = gen_pk(params, &keygen, None);
+        let layout = CircuitLayout {
+            config: keygen.config_params.clone(),
+            break_points: keygen.break_points(),
+            num_instance: keygen.num_instance(),
+        };
+        Self { pk, layout }
+    }
+
+    pub fn vk(&self) -> &VerifyingKey<G1Affine> {
+        self.pk.get_vk()
+    }
+
+    /// Proves `circuit` with the Keccak transcript the Solidity verifier
+    /// uses, returning its instances and the proof.
+    pub fn prove(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit: &dyn BuilderCircuit,
+    ) -> (Vec<Vec<Fr>>, Vec<u8>) {
+        let builder =
+            circuit.build_prover(self.layout.config.clone(), self.layout.break_points.clone());
+        let instances = builder.instances();
+        let proof = prove(
+            params,
+            &self.pk,
+            builder,
+            instances.clone(),
+            TranscriptKind::Evm,
+        );
+        (instances, proof)
+    }
+
+    /// Solidity source of a verifier for this key.
+    pub fn solidity_verifier(&self, params: &ParamsKZG<Bn256>) -> String {
+        gen_evm_verifier_sol_code::<BaseCircuitBuilder<Fr>, SHPLONK>(
+            params,
+            self.vk(),
+            self.layout.num_instance.clone(),
+        )
+    }
+
+    /// Source of a Move module `module` verifying proofs for this key; see
+    /// [`crate::move_verifier`].
+    pub fn move_verifier(&self, params: &ParamsKZG<Bn256>, module: &str) -> String {
+        gen_move_verifier_code(params, self.vk(), self.layout.num_instance.clone(), module)
+    }
+
+    /// Gas the compiled Solidity verifier spends accepting `proof`. Needs
+    /// `solc` on the `PATH`.
+    ///
+    /// Fails with the EVM's revert reason if the verifier rejects the proof.
+    #[cfg(feature = "revm")]
+    pub fn verification_gas(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        instances: &[Vec<Fr>],
+        proof: &[u8],
+    ) -> io::Result<u64> {
+        let deployment_code = gen_evm_verifier_shplonk::<BaseCircuitBuilder<Fr>>(
+            params,
+            self.vk(),
+            self.layout.num_instance.clone(),
+            None,
+        );
+        deploy_and_call(deployment_code, encode_calldata(instances, proof))
+            .map_err(|reason| io::Error::other(format!("verifier rejected the proof: {reason}")))
+    }
+}
+
+/// A directory of proving keys, one pair of files per circuit id.
+///
+/// Entries are keyed by id and `k` only, so a directory must not be shared
+/// between different trusted setups.
+#[derive(Clone, Debug)]
+pub struct KeyCache {
+    dir: PathBuf,
+}
+
+impl KeyCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, info: &CircuitInfo, extension: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}-k{}.{extension}", info.id, info.params.k))
+    }
+
+    /// The keys of `info`, generated and stored on first use.
+    ///
+    /// `params` must already be downsized to the circuit's `k`.
+    pub fn keys(&self, info: &CircuitInfo, params: &ParamsKZG<Bn256>) -> io::Result<CircuitKeys> {
+        if params.k() != info.params.k {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} needs 2^{} params, got 2^{}",
+                    info.id,
+                    info.params.k,
+                    params.k()
+                ),
+            ));
+        }
+        if let Some(keys) = self.read(info)? {
+            return Ok(keys);
+        }
+        let keys = CircuitKeys::generate(info, params);
+        self.write(info, &keys)?;
+        Ok(keys)
+    }
+
+    /// `None` if the entry has not been written yet.
+    fn read(&self, info: &CircuitInfo) -> io::Result<Option<CircuitKeys>> {
+        let (pk_path, layout_path) = (self.path(info, "pk"), self.path(info, "json"));
+        if !pk_path.exists() || !layout_path.exists() {
+            return Ok(None);
+        }
+        let layout: CircuitLayout =
+            serde_json::from_reader(BufReader::new(File::open(layout_path)?))
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let pk = ProvingKey::read::<_, BaseCircuitBuilder<Fr>>(
+            &mut BufReader::new(File::open(pk_path)?),
+            SerdeFormat::RawBytes,
+            layout.config.clone(),
+        )?;
+        Ok(Some(CircuitKeys { pk, layout }))
+    }
+
+    fn write(&self, info: &CircuitInfo, keys: &CircuitKeys) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        keys.pk.write(
+            &mut BufWriter::new(File::create(self.path(info, "pk"))?),
+            SerdeFormat::RawBytes,
+        )?;
+        serde_json::to_writer(
+            BufWriter::new(File::create(self.path(info, "json"))?),
+            &keys.layout,
+        )
+        .map_err(io::Error::other)
+    }
+}
@@ -0,0 +1,106 @@
+//! Uniform metadata for every circuit this crate can prove.
+//!
+//! Tools (the `circuits` CLI, key caches, verifier exporters) look circuits up
+//! by a stable id instead of naming their Rust types. Each [`CircuitInfo`]
+//! carries the default sizing, a description of the public instances and a
+//! loader that builds the circuit from a JSON witness. [`keys`] generates,
+//! caches and uses the keys of any registered circuit.
+
+mod builtin;
+pub mod keys;
+
+use std::io;
+
+use crate::circuit::{BuilderCircuit, CircuitParams};
+
+/// One field of a circuit's public instances, in instance order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InstanceField {
+    pub name: &'static str,
+    /// Instance words, or `None` when the length depends on the witness.
+    pub words: Option<usize>,
+}
+
+impl InstanceField {
+    pub const fn fixed(name: &'static str, words: usize) -> Self {
+        Self {
+            name,
+            words: Some(words),
+        }
+    }
+
+    pub const fn variable(name: &'static str) -> Self {
+        Self { name, words: None }
+    }
+}
+
+/// A registered circuit.
+#[derive(Clone, Copy, Debug)]
+pub struct CircuitInfo {
+    /// Stable identifier; key caches and generated verifiers are named by it.
+    pub id: &'static str,
+    pub description: &'static str,
+    /// Default sizing.
+    pub params: CircuitParams,
+    pub instances: &'static [InstanceField],
+    /// Builds the circuit from its JSON witness.
+    pub load_witness: fn(&str) -> io::Result<Box<dyn BuilderCircuit>>,
+    /// A representative circuit, enough to fix the layout for key
+    /// generation. Its witness need not satisfy the constraints.
+    pub example: fn() -> Box<dyn BuilderCircuit>,
+}
+
+impl CircuitInfo {
+    /// Number of instance words, if every field has a fixed width.
+    pub fn num_instance(&self) -> Option<usize> {
+        self.instances.iter().map(|field| field.words).sum()
+    }
+}
+
+/// Circuits by id, in registration order.
+#[derive(Clone, Debug, Default)]
+pub struct CircuitRegistry {
+    circuits: Vec<CircuitInfo>,
+}
+
+impl CircuitRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every circuit defined in this crate.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        for info in builtin::circuits() {
+            registry.register(info);
+        }
+        registry
+    }
+
+    /// Adds a circuit. Panics if the id is already taken.
+    pub fn register(&mut self, info: CircuitInfo) {
+        assert!(
+            self.get(info.id).is_none(),
+            "circuit id {} is registered twice",
+            info.id
+        );
+        self.circuits.push(info);
+    }
+
+    pub fn get(&self, id: &str) -> Option<&CircuitInfo> {
+        self.circuits.iter().find(|info| info.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CircuitInfo> {
+        self.circuits.iter()
+    }
+
+    /// Loads the witness of circuit `id`.
+    pub fn load_witness(&self, id: &str, json: &str) -> io::Result<Box<dyn BuilderCircuit>> {
+        let info = self.get(id).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("unknown circuit {id}"))
+        })?;
+        (info.load_witness)(json)
+    }
+}
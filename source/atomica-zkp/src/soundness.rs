@@ -0,0 +1,290 @@
+//! Soundness harness for SHPLONK proofs in the EVM encoding.
+//!
+//! Given an honest proof, [`SoundnessHarness`] derives a systematic set of
+//! [`Mutation`]s — bit flips in every proof word, truncation, substituted
+//! commitments, perturbed and swapped instances, and replay against other
+//! verifying keys — and checks that every verifier under test rejects each
+//! of them. Verifiers are the native halo2 verifier and, when bytecode is
+//! supplied, the Solidity verifier running in revm.
+//!
+//! Appending bytes to a proof is deliberately not a mutation: neither
+//! verifier checks that the transcript was fully consumed, and trailing bytes
+//! do not change what was proven.
+
+use std::fmt;
+
+use halo2_proofs_axiom::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::VerifyingKey,
+    poly::kzg::commitment::ParamsKZG,
+};
+use snark_verifier_sdk::snark_verifier::{
+    loader::evm::{deploy_and_call, encode_calldata},
+    system::halo2::{compile, Config},
+};
+
+use crate::verifier::verify_evm_proof;
+
+/// Bytes per scalar in the EVM proof encoding; points are two words.
+const WORD: usize = 32;
+
+/// Points at the end of a SHPLONK proof, after the evaluations.
+const NUM_OPENING_POINTS: usize = 2;
+
+/// A verifying key and the verifiers built from it.
+pub struct VerifierUnderTest<'a> {
+    pub name: String,
+    params: &'a ParamsKZG<Bn256>,
+    vk: &'a VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+    deployment_code: Option<Vec<u8>>,
+}
+
+impl<'a> VerifierUnderTest<'a> {
+    pub fn new(
+        name: impl Into<String>,
+        params: &'a ParamsKZG<Bn256>,
+        vk: &'a VerifyingKey<G1Affine>,
+        num_instance: Vec<usize>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            params,
+            vk,
+            num_instance,
+            deployment_code: None,
+        }
+    }
+
+    /// Also checks the Solidity verifier deployed from `deployment_code`, as
+    /// returned by `gen_evm_verifier_shplonk`.
+    pub fn with_evm(mut self, deployment_code: Vec<u8>) -> Self {
+        self.deployment_code = Some(deployment_code);
+        self
+    }
+
+    pub fn verify_native(&self, instances: &[Vec<Fr>], proof: &[u8]) -> bool {
+        verify_evm_proof(self.params, self.vk, instances, proof)
+    }
+
+    /// `None` if no EVM bytecode was supplied.
+    pub fn verify_evm(&self, instances: &[Vec<Fr>], proof: &[u8]) -> Option<bool> {
+        let deployment_code = self.deployment_code.clone()?;
+        let calldata = encode_calldata(instances, proof);
+        Some(deploy_and_call(deployment_code, calldata).is_ok())
+    }
+
+    /// Proof offsets of every G1 point: the witness and quotient commitments
+    /// up front, and the opening points after the evaluations.
+    fn commitment_offsets(&self, proof_len: usize) -> Vec<usize> {
+        let protocol = compile(
+            self.params,
+            self.vk,
+            Config::kzg().with_num_instance(self.num_instance.clone()),
+        );
+        let evaluations_len = protocol.evaluations.len() * WORD;
+        let num_points = proof_len.saturating_sub(evaluations_len) / (2 * WORD);
+        let num_commitments = num_points.saturating_sub(NUM_OPENING_POINTS);
+        let leading = (0..num_commitments).map(|i| i * 2 * WORD);
+        let opening_start = num_commitments * 2 * WORD + evaluations_len;
+        let trailing =
+            (0..NUM_OPENING_POINTS.min(num_points)).map(|i| opening_start + i * 2 * WORD);
+        leading.chain(trailing).collect()
+    }
+}
+
+/// A single change to an honest `(instances, proof)` pair.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    /// Flip bit `bit` of proof byte `byte`.
+    FlipBit { byte: usize, bit: u8 },
+    /// Drop the last proof word.
+    Truncate,
+    /// Overwrite the point at proof offset `target` with the (different)
+    /// point at offset `source`.
+    SubstituteCommitment { target: usize, source: usize },
+    /// Add one to the flattened instance at `index`.
+    IncrementInstance { index: usize },
+    /// Swap the flattened instances at `a` and `b`, which hold different values.
+    SwapInstances { a: usize, b: usize },
+    /// Submit the unmodified proof to the foreign verifier at `index`.
+    ForeignKey { index: usize },
+}
+
+impl fmt::Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mutation::FlipBit { byte, bit } => write!(f, "flip bit {bit} of proof byte {byte}"),
+            Mutation::Truncate => write!(f, "truncate the last proof word"),
+            Mutation::SubstituteCommitment { target, source } => {
+                write!(
+                    f,
+                    "replace the point at {target} with the point at {source}"
+                )
+            }
+            Mutation::IncrementInstance { index } => write!(f, "increment instance {index}"),
+            Mutation::SwapInstances { a, b } => write!(f, "swap instances {a} and {b}"),
+            Mutation::ForeignKey { index } => write!(f, "verify against foreign key {index}"),
+        }
+    }
+}
+
+/// A mutation some verifier accepted.
+#[derive(Debug)]
+pub struct Acceptance {
+    pub mutation: Mutation,
+    /// `"<verifier name> (native)"` or `"<verifier name> (evm)"`.
+    pub verifier: String,
+}
+
+/// Checks a verifier, and any foreign verifiers, reject every mutation of
+/// an honest proof.
+pub struct SoundnessHarness<'a> {
+    verifier: VerifierUnderTest<'a>,
+    foreign: Vec<VerifierUnderTest<'a>>,
+}
+
+impl<'a> SoundnessHarness<'a> {
+    pub fn new(verifier: VerifierUnderTest<'a>) -> Self {
+        Self {
+            verifier,
+            foreign: vec![],
+        }
+    }
+
+    /// Adds a verifier for a different circuit (or setup) the proof must not
+    /// verify under.
+    pub fn with_foreign(mut self, verifier: VerifierUnderTest<'a>) -> Self {
+        self.foreign.push(verifier);
+        self
+    }
+
+    /// The mutations applied to `(instances, proof)`.
+    ///
+    /// Every 32-byte proof word has its lowest and highest bit flipped: the
+    /// former keeps scalars canonical and exercises the verifier equations,
+    /// the latter produces non-canonical scalars and off-curve points.
+    pub fn mutations(&self, instances: &[Vec<Fr>], proof: &[u8]) -> Vec<Mutation> {
+        let mut mutations = vec![];
+        for word in (0..proof.len()).step_by(WORD) {
+            let last = (word + WORD).min(proof.len()) - 1;
+            mutations.push(Mutation::FlipBit { byte: last, bit: 0 });
+            mutations.push(Mutation::FlipBit { byte: word, bit: 7 });
+        }
+        if proof.len() >= WORD {
+            mutations.push(Mutation::Truncate);
+        }
+
+        let offsets = self.verifier.commitment_offsets(proof.len());
+        for (i, &target) in offsets.iter().enumerate() {
+            let source = offsets[(i + 1) % offsets.len()];
+            if proof[target..target + 2 * WORD] != proof[source..source + 2 * WORD] {
+                mutations.push(Mutation::SubstituteCommitment { target, source });
+            }
+        }
+
+        let flat: Vec<Fr> = instances.concat();
+        for index in 0..flat.len() {
+            mutations.push(Mutation::IncrementInstance { index });
+        }
+        for a in 0..flat.len() {
+            for b in a + 1..flat.len() {
+                if flat[a] != flat[b] {
+                    mutations.push(Mutation::SwapInstances { a, b });
+                }
+            }
+        }
+
+        mutations.extend((0..self.foreign.len()).map(|index| Mutation::ForeignKey { index }));
+        mutations
+    }
+
+    /// Applies `mutation`, returning the verifier to check it against.
+    fn apply(
+        &self,
+        mutation: &Mutation,
+        instances: &[Vec<Fr>],
+        proof: &[u8],
+    ) -> (&VerifierUnderTest<'a>, Vec<Vec<Fr>>, Vec<u8>) {
+        let mut instances = instances.to_vec();
+        let mut proof = proof.to_vec();
+        let mut verifier = &self.verifier;
+        match *mutation {
+            Mutation::FlipBit { byte, bit } => proof[byte] ^= 1 << bit,
+            Mutation::Truncate => proof.truncate(proof.len() - WORD),
+            Mutation::SubstituteCommitment { target, source } => {
+                proof.copy_within(source..source + 2 * WORD, target)
+            }
+            Mutation::IncrementInstance { index } => {
+                *flat_instance(&mut instances, index) += Fr::one()
+            }
+            Mutation::SwapInstances { a, b } => {
+                let value_a = *flat_instance(&mut instances, a);
+                let value_b = std::mem::replace(flat_instance(&mut instances, b), value_a);
+                *flat_instance(&mut instances, a) = value_b;
+            }
+            Mutation::ForeignKey { index } => verifier = &self.foreign[index],
+        }
+        (verifier, instances, proof)
+    }
+
+    /// Runs every mutation and returns those that some verifier accepted.
+    ///
+    /// Panics if the honest proof itself is rejected, since every rejection
+    /// would then be vacuous.
+    pub fn run(&self, instances: &[Vec<Fr>], proof: &[u8]) -> Vec<Acceptance> {
+        let name = &self.verifier.name;
+        assert!(
+            self.verifier.verify_native(instances, proof),
+            "{name} (native) rejects the honest proof"
+        );
+        assert_ne!(
+            self.verifier.verify_evm(instances, proof),
+            Some(false),
+            "{name} (evm) rejects the honest proof"
+        );
+
+        let mut accepted = vec![];
+        for mutation in self.mutations(instances, proof) {
+            let (verifier, instances, proof) = self.apply(&mutation, instances, proof);
+            if verifier.verify_native(&instances, &proof) {
+                accepted.push(Acceptance {
+                    mutation: mutation.clone(),
+                    verifier: format!("{} (native)", verifier.name),
+                });
+            }
+            if verifier.verify_evm(&instances, &proof) == Some(true) {
+                accepted.push(Acceptance {
+                    mutation,
+                    verifier: format!("{} (evm)", verifier.name),
+                });
+            }
+        }
+        accepted
+    }
+
+    /// Panics listing every mutation some verifier accepted.
+    pub fn assert_sound(&self, instances: &[Vec<Fr>], proof: &[u8]) {
+        let accepted = self.run(instances, proof);
+        assert!(
+            accepted.is_empty(),
+            "{} mutated proofs were accepted:\n{}",
+            accepted.len(),
+            accepted
+                .iter()
+                .map(|acceptance| format!("  {}: {}", acceptance.verifier, acceptance.mutation))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+}
+
+fn flat_instance(instances: &mut [Vec<Fr>], mut index: usize) -> &mut Fr {
+    for column in instances.iter_mut() {
+        if index < column.len() {
+            return &mut column[index];
+        }
+        index -= column.len();
+    }
+    panic!("instance index out of range")
+}
@@ -0,0 +1,120 @@
+//! Native verification of SHPLONK proofs.
+//!
+//! Everything here is pure computation over byte slices — no randomness and
+//! no filesystem — so it builds unchanged for `wasm32-unknown-unknown`.
+
+use std::io;
+
+use halo2_proofs_axiom::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::{verify_proof, VerifyingKey},
+    poly::{
+        commitment::Params,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::VerifierSHPLONK,
+            strategy::SingleStrategy,
+        },
+    },
+    transcript::TranscriptReadBuffer,
+    SerdeFormat,
+};
+use snark_verifier_sdk::{
+    halo2::PoseidonTranscript,
+    snark_verifier::{loader::native::NativeLoader, system::halo2::transcript::evm::EvmTranscript},
+};
+
+use crate::{
+    envelope::ProofEnvelope,
+    halo2_base::gates::circuit::{builder::BaseCircuitBuilder, BaseCircuitParams},
+    prover::TranscriptKind,
+};
+
+/// Reads `ParamsKZG` in the raw-bytes encoding of `hermez-raw-*` files.
+pub fn read_params(mut bytes: &[u8]) -> io::Result<ParamsKZG<Bn256>> {
+    ParamsKZG::read(&mut bytes)
+}
+
+/// Reads the verifying key of a `BaseCircuitBuilder` circuit, written with
+/// `VerifyingKey::write(.., SerdeFormat::RawBytes)`.
+///
+/// The circuit's `config_params` fix the column layout and must be the ones
+/// used at key generation.
+pub fn read_vk(mut bytes: &[u8], config: BaseCircuitParams) -> io::Result<VerifyingKey<G1Affine>> {
+    VerifyingKey::read::<_, BaseCircuitBuilder<Fr>>(&mut bytes, SerdeFormat::RawBytes, config)
+}
+
+/// Checks a proof produced by `gen_evm_proof_shplonk`, i.e. with the Keccak
+/// transcript the Solidity and Move verifiers use.
+pub fn verify_evm_proof(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[Vec<Fr>],
+    proof: &[u8],
+) -> bool {
+    let instances: Vec<&[Fr]> = instances.iter().map(Vec::as_slice).collect();
+    let mut transcript = TranscriptReadBuffer::<_, G1Affine, _>::init(proof);
+    verify_proof::<
+        KZGCommitmentScheme<Bn256>,
+        VerifierSHPLONK<'_, Bn256>,
+        _,
+        EvmTranscript<G1Affine, NativeLoader, _, _>,
+        _,
+    >(
+        params,
+        vk,
+        SingleStrategy::new(params),
+        &[instances.as_slice()],
+        &mut transcript,
+    )
+    .is_ok()
+}
+
+/// Checks a proof written with the Poseidon transcript, as produced for
+/// aggregation by [`crate::prover::prove`].
+pub fn verify_poseidon_proof(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[Vec<Fr>],
+    proof: &[u8],
+) -> bool {
+    let instances: Vec<&[Fr]> = instances.iter().map(Vec::as_slice).collect();
+    let mut transcript = TranscriptReadBuffer::<_, G1Affine, _>::init(proof);
+    verify_proof::<
+        KZGCommitmentScheme<Bn256>,
+        VerifierSHPLONK<'_, Bn256>,
+        _,
+        PoseidonTranscript<NativeLoader, _>,
+        _,
+    >(
+        params,
+        vk,
+        SingleStrategy::new(params),
+        &[instances.as_slice()],
+        &mut transcript,
+    )
+    .is_ok()
+}
+
+/// Checks a proof with the verifier matching its transcript.
+pub fn verify(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    instances: &[Vec<Fr>],
+    proof: &[u8],
+    transcript: TranscriptKind,
+) -> bool {
+    match transcript {
+        TranscriptKind::Poseidon => verify_poseidon_proof(params, vk, instances, proof),
+        TranscriptKind::Evm => verify_evm_proof(params, vk, instances, proof),
+    }
+}
+
+/// [`verify_evm_proof`] for a decoded envelope.
+pub fn verify_envelope(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    envelope: &ProofEnvelope,
+) -> bool {
+    verify_evm_proof(params, vk, &envelope.instances, &envelope.proof)
+}
@@ -0,0 +1,69 @@
+//! wasm-bindgen exports for checking proofs in the browser.
+//!
+//! Build with `./build-wasm.sh`, which compiles the crate as a cdylib for
+//! `wasm32-unknown-unknown` and runs `wasm-bindgen --target web` on it:
+//!
+//! ```sh
+//! cargo rustc --lib --release --target wasm32-unknown-unknown \
+//!     --no-default-features --features wasm --crate-type cdylib
+//! ```
+//!
+//! and verify from JavaScript with
+//!
+//! ```js
+//! const verifier = new Verifier(paramsBytes, vkBytes, configJson);
+//! verifier.verify(envelopeJson); // true or false
+//! ```
+//!
+//! The caller supplies every input as bytes or strings: nothing here touches
+//! the filesystem or asks for randomness.
+
+use halo2_proofs_axiom::{
+    halo2curves::bn256::{Bn256, G1Affine},
+    plonk::VerifyingKey,
+    poly::kzg::commitment::ParamsKZG,
+};
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    envelope::ProofEnvelope,
+    halo2_base::gates::circuit::BaseCircuitParams,
+    verifier::{read_params, read_vk, verify_envelope},
+};
+
+#[cfg(target_arch = "wasm32")]
+fn no_entropy(_: &mut [u8]) -> Result<(), getrandom::Error> {
+    Err(getrandom::Error::UNSUPPORTED)
+}
+
+#[cfg(target_arch = "wasm32")]
+getrandom::register_custom_getrandom!(no_entropy);
+
+/// A verifying key and the KZG parameters it was generated with.
+#[wasm_bindgen]
+pub struct Verifier {
+    params: ParamsKZG<Bn256>,
+    vk: VerifyingKey<G1Affine>,
+}
+
+#[wasm_bindgen]
+impl Verifier {
+    /// `params` are raw `ParamsKZG` bytes (e.g. `hermez-raw-9`), `vk` a
+    /// raw-bytes verifying key and `config` the circuit's `BaseCircuitParams`
+    /// as JSON.
+    #[wasm_bindgen(constructor)]
+    pub fn new(params: &[u8], vk: &[u8], config: &str) -> Result<Verifier, JsError> {
+        let config: BaseCircuitParams = serde_json::from_str(config)?;
+        Ok(Self {
+            params: read_params(params)?,
+            vk: read_vk(vk, config)?,
+        })
+    }
+
+    /// Verifies a JSON [`ProofEnvelope`]. Malformed envelopes are errors;
+    /// well-formed but invalid proofs return `false`.
+    pub fn verify(&self, envelope: &str) -> Result<bool, JsError> {
+        let envelope = ProofEnvelope::from_json(envelope)?;
+        Ok(verify_envelope(&self.params, &self.vk, &envelope))
+    }
+}
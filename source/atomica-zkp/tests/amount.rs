@@ -0,0 +1,204 @@
+use diem_prover_halo2::{
+    circuit::{BuilderCircuit, CircuitParams},
+    gadgets::amount::{AmountChip, LimbBits},
+    halo2_base::{
+        gates::{
+            circuit::{builder::BaseCircuitBuilder, CircuitBuilderStage},
+            RangeChip,
+        },
+        AssignedValue, Context,
+    },
+};
+use halo2_proofs_axiom::{dev::MockProver, halo2curves::bn256::Fr};
+use snark_verifier_sdk::CircuitExt;
+
+/// Runs `f` against an [`AmountChip`] and exposes whatever it returns.
+struct AmountCircuit<F> {
+    limb_bits: LimbBits,
+    f: F,
+}
+
+impl<F> BuilderCircuit for AmountCircuit<F>
+where
+    F: Fn(&mut Context<Fr>, &AmountChip<Fr>) -> Vec<AssignedValue<Fr>>,
+{
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let chip = AmountChip::new(range, self.limb_bits);
+        (self.f)(builder.main(0), &chip)
+    }
+}
+
+fn params(limb_bits: LimbBits) -> CircuitParams {
+    match limb_bits {
+        LimbBits::Eight => CircuitParams {
+            k: 10,
+            lookup_bits: 8,
+            minimum_rows: 20,
+        },
+        LimbBits::Sixteen => CircuitParams {
+            k: 17,
+            lookup_bits: 16,
+            minimum_rows: 20,
+        },
+    }
+}
+
+const LIMB_WIDTHS: [LimbBits; 2] = [LimbBits::Eight, LimbBits::Sixteen];
+
+/// Mock-proves the circuit and returns its public instances on success.
+fn mock_prove<F>(limb_bits: LimbBits, f: F) -> Result<Vec<Fr>, String>
+where
+    F: Fn(&mut Context<Fr>, &AmountChip<Fr>) -> Vec<AssignedValue<Fr>>,
+{
+    let params = params(limb_bits);
+    let builder = AmountCircuit { limb_bits, f }.build(CircuitBuilderStage::Mock, params);
+    let instances = builder.instances();
+    let prover = MockProver::run(params.k, &builder, instances.clone()).unwrap();
+    prover
+        .verify()
+        .map(|()| instances[0].clone())
+        .map_err(|failures| format!("{failures:?}"))
+}
+
+fn pow2(bits: u32) -> Fr {
+    (0..bits).fold(Fr::one(), |acc, _| acc + acc)
+}
+
+#[test]
+fn test_u64_bounds_accepted() {
+    for limb_bits in LIMB_WIDTHS {
+        mock_prove(limb_bits, |ctx, chip| {
+            for value in [0, 1, u64::MAX - 1, u64::MAX] {
+                chip.load_u64(ctx, value);
+            }
+            vec![]
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_u128_bounds_accepted() {
+    for limb_bits in LIMB_WIDTHS {
+        mock_prove(limb_bits, |ctx, chip| {
+            for value in [0, u64::MAX as u128 + 1, u128::MAX] {
+                chip.load_u128(ctx, value);
+            }
+            vec![]
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn test_u64_overflow_rejected() {
+    // 2^64, 2^64 + 1 and -1 (p - 1) all have low limbs that fit but do not
+    // recompose.
+    for limb_bits in LIMB_WIDTHS {
+        for value in [pow2(64), pow2(64) + Fr::one(), -Fr::one()] {
+            let result = mock_prove(limb_bits, |ctx, chip| {
+                let cell = ctx.load_witness(value);
+                chip.assert_u64(ctx, cell);
+                vec![]
+            });
+            assert!(result.is_err(), "{value:?} accepted as u64");
+        }
+    }
+}
+
+#[test]
+fn test_u128_overflow_rejected() {
+    for limb_bits in LIMB_WIDTHS {
+        for value in [pow2(128), pow2(128) + Fr::from(u64::MAX), -Fr::one()] {
+            let result = mock_prove(limb_bits, |ctx, chip| {
+                let cell = ctx.load_witness(value);
+                chip.assert_u128(ctx, cell);
+                vec![]
+            });
+            assert!(result.is_err(), "{value:?} accepted as u128");
+        }
+    }
+}
+
+#[test]
+fn test_compare_edge_cases() {
+    let max = u64::MAX;
+    let cases = [(0, 0), (0, 1), (1, 0), (max - 1, max), (max, max - 1), (max, max), (0, max), (max, 0)];
+    for limb_bits in LIMB_WIDTHS {
+        for (a, b) in cases {
+            let instances = mock_prove(limb_bits, |ctx, chip| {
+                let a = chip.load_u64(ctx, a);
+                let b = chip.load_u64(ctx, b);
+                let ordering = chip.compare(ctx, a, b, 64);
+                let le = chip.is_less_than_or_equal(ctx, a, b, 64);
+                let max = chip.max(ctx, a, b, 64);
+                vec![ordering.lt, ordering.eq, ordering.gt, le, max]
+            })
+            .unwrap();
+            let expected = [a < b, a == b, a > b, a <= b].map(|flag| Fr::from(flag as u64));
+            assert_eq!(instances[..4], expected, "compare({a}, {b})");
+            assert_eq!(instances[4], Fr::from(a.max(b)));
+        }
+    }
+}
+
+#[test]
+fn test_u128_less_than() {
+    let max = u128::MAX;
+    for (a, b) in [(max - 1, max), (max, max - 1), (u64::MAX as u128, u64::MAX as u128 + 1)] {
+        let instances = mock_prove(LimbBits::Sixteen, |ctx, chip| {
+            let a = chip.load_u128(ctx, a);
+            let b = chip.load_u128(ctx, b);
+            vec![chip.is_less_than(ctx, a, b, 128)]
+        })
+        .unwrap();
+        assert_eq!(instances, vec![Fr::from((a < b) as u64)]);
+    }
+}
+
+#[test]
+fn test_assert_less_than() {
+    for limb_bits in LIMB_WIDTHS {
+        let check = |a: u64, b: u64| {
+            mock_prove(limb_bits, |ctx, chip| {
+                let a = chip.load_u64(ctx, a);
+                let b = chip.load_u64(ctx, b);
+                chip.assert_less_than(ctx, a, b, 64);
+                vec![]
+            })
+        };
+        assert!(check(4, 5).is_ok());
+        assert!(check(u64::MAX - 1, u64::MAX).is_ok());
+        assert!(check(5, 5).is_err());
+        assert!(check(u64::MAX, 0).is_err());
+    }
+}
+
+#[test]
+fn test_less_than_rejects_out_of_range_operand() {
+    // With `a = 2^65` the shifted difference no longer fits in 65 bits, so the
+    // comparison cannot be satisfied even though `a > b`.
+    for limb_bits in LIMB_WIDTHS {
+        let result = mock_prove(limb_bits, |ctx, chip| {
+            let a = ctx.load_witness(pow2(64) + pow2(64));
+            let b = chip.load_u64(ctx, 1);
+            vec![chip.is_less_than(ctx, a, b, 64)]
+        });
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+#[should_panic(expected = "16-bit limbs need a lookup table of at least 16 bits")]
+fn test_limbs_wider_than_lookup_table() {
+    let params = params(LimbBits::Eight);
+    AmountCircuit {
+        limb_bits: LimbBits::Sixteen,
+        f: |_: &mut Context<Fr>, _: &AmountChip<Fr>| vec![],
+    }
+    .build(CircuitBuilderStage::Mock, params);
+}
@@ -0,0 +1,234 @@
+use ark_bls12_381::{Fr as BlsScalar, G1Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use diem_prover_halo2::{
+    auction::{clear_bids, clear_native, AuctionClearingCircuit, ClearingOutcome, SealedBid, AUCTION_CIRCUIT_PARAMS},
+    circuit::{BuilderCircuit, CircuitParams},
+    gadgets::amount::{AmountChip, LimbBits},
+    halo2_base::{
+        gates::{
+            circuit::{builder::BaseCircuitBuilder, CircuitBuilderStage},
+            RangeChip, RangeInstructions,
+        },
+        AssignedValue,
+    },
+    ibe::native,
+};
+use halo2_proofs_axiom::{dev::MockProver, halo2curves::bn256::Fr};
+use snark_verifier_sdk::CircuitExt;
+
+/// Runs only the clearing rule on already-decrypted amounts.
+struct ClearingCircuit {
+    amounts: Vec<u64>,
+    payments: Vec<u64>,
+}
+
+impl BuilderCircuit for ClearingCircuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let amount_chip = AmountChip::new(range, LimbBits::Eight);
+        let amounts: Vec<_> = self.amounts.iter().map(|v| amount_chip.load_u64(ctx, *v)).collect();
+        let payments: Vec<_> = self.payments.iter().map(|v| amount_chip.load_u64(ctx, *v)).collect();
+
+        let clearing = clear_bids(ctx, &amount_chip, &amounts, &payments);
+        vec![
+            clearing.has_winner,
+            clearing.winner_index,
+            clearing.price,
+            clearing.valid_mask(ctx, range.gate()),
+        ]
+    }
+}
+
+const CLEARING_PARAMS: CircuitParams = CircuitParams {
+    k: 10,
+    lookup_bits: 8,
+    minimum_rows: 20,
+};
+
+fn expected_instances(outcome: &ClearingOutcome) -> Vec<Fr> {
+    let mask = outcome
+        .valid
+        .iter()
+        .enumerate()
+        .filter(|(_, valid)| **valid)
+        .map(|(i, _)| 1u64 << i)
+        .sum::<u64>();
+    vec![
+        Fr::from(outcome.winner.is_some() as u64),
+        Fr::from(outcome.winner.unwrap_or(0) as u64),
+        Fr::from(outcome.price),
+        Fr::from(mask),
+    ]
+}
+
+/// Checks the circuit agrees with the native rule and returns the outcome.
+fn run_clearing(amounts: &[u64], payments: &[u64]) -> ClearingOutcome {
+    let outcome = clear_native(amounts, payments);
+    let circuit = ClearingCircuit {
+        amounts: amounts.to_vec(),
+        payments: payments.to_vec(),
+    };
+    let builder = circuit.build(CircuitBuilderStage::Mock, CLEARING_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], expected_instances(&outcome));
+
+    let prover = MockProver::run(CLEARING_PARAMS.k, &builder, instances).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+    outcome
+}
+
+#[test]
+fn test_highest_funded_bid_wins() {
+    let outcome = run_clearing(&[100, 300, 200], &[100, 300, 250]);
+    assert_eq!(outcome.winner, Some(1));
+    assert_eq!(outcome.price, 300);
+    assert_eq!(outcome.valid, vec![true, true, true]);
+}
+
+#[test]
+fn test_tie_goes_to_earliest_bid() {
+    let outcome = run_clearing(&[500, 700, 700, 700], &[500, 700, 900, 700]);
+    assert_eq!(outcome.winner, Some(1));
+    assert_eq!(outcome.price, 700);
+}
+
+#[test]
+fn test_underfunded_bid_is_skipped() {
+    // The highest bid locked less than it claims, so the runner-up wins.
+    let outcome = run_clearing(&[400, 1_000, 600], &[400, 999, 600]);
+    assert_eq!(outcome.winner, Some(2));
+    assert_eq!(outcome.price, 600);
+    assert_eq!(outcome.valid, vec![true, false, true]);
+}
+
+#[test]
+fn test_all_underfunded_has_no_winner() {
+    let outcome = run_clearing(&[10, 20], &[9, 19]);
+    assert_eq!(outcome.winner, None);
+    assert_eq!(outcome.price, 0);
+    assert_eq!(outcome.valid, vec![false, false]);
+}
+
+#[test]
+fn test_zero_bids_never_win() {
+    let outcome = run_clearing(&[0, 0], &[5, 0]);
+    assert_eq!(outcome.winner, None);
+    assert_eq!(outcome.valid, vec![true, true]);
+}
+
+#[test]
+fn test_empty_auction() {
+    let outcome = run_clearing(&[], &[]);
+    assert_eq!(outcome.winner, None);
+    assert_eq!(outcome.price, 0);
+    assert!(outcome.valid.is_empty());
+}
+
+#[test]
+fn test_u64_extremes() {
+    let outcome = run_clearing(&[u64::MAX, u64::MAX - 1], &[u64::MAX, u64::MAX]);
+    assert_eq!(outcome.winner, Some(0));
+    assert_eq!(outcome.price, u64::MAX);
+}
+
+#[test]
+fn test_wrong_winner_rejected() {
+    let circuit = ClearingCircuit {
+        amounts: vec![100, 300, 200],
+        payments: vec![100, 300, 250],
+    };
+    let builder = circuit.build(CircuitBuilderStage::Mock, CLEARING_PARAMS);
+    let mut instances = builder.instances();
+    // Claim bid #2 won at its own price.
+    instances[0][1] = Fr::from(2);
+    instances[0][2] = Fr::from(200);
+
+    let prover = MockProver::run(CLEARING_PARAMS.k, &builder, instances).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+fn sealed_bid(bidder: u8, amount: u64, payment: u64, r: u64) -> (SealedBid, ark_bls12_381::G2Affine) {
+    let s = BlsScalar::from(123456789u64);
+    let mpk = (G1Affine::generator() * s).into_affine();
+    let q_id = native::identity_point(b"epoch_100");
+    let signature = (q_id * s).into_affine();
+    let (u, ciphertext) = native::encrypt(&mpk, &q_id, BlsScalar::from(r), &amount.to_le_bytes());
+    let bid = SealedBid {
+        bidder: [bidder; 32],
+        u,
+        payment,
+        ciphertext: ciphertext.try_into().unwrap(),
+    };
+    (bid, signature)
+}
+
+#[test]
+fn test_auction_native_outcome() {
+    let (a, signature) = sealed_bid(0xa1, 300, 300, 11);
+    let (b, _) = sealed_bid(0xb2, 900, 800, 12);
+    let (c, _) = sealed_bid(0xc3, 500, 600, 13);
+    let circuit = AuctionClearingCircuit::new(vec![a, b, c], signature);
+
+    assert_eq!(circuit.amounts(), vec![300, 900, 500]);
+    assert_eq!(
+        circuit.outcome(),
+        ClearingOutcome {
+            winner: Some(2),
+            price: 500,
+            valid: vec![true, false, true],
+        }
+    );
+}
+
+/// Enough for the signature encoding and one Keccak permutation, but no pairing.
+const EMPTY_AUCTION_PARAMS: CircuitParams = CircuitParams {
+    k: 17,
+    lookup_bits: 8,
+    minimum_rows: 20,
+};
+
+#[test]
+fn test_empty_auction_clearing_mock_prover() {
+    let (_, signature) = sealed_bid(0xa1, 300, 300, 11);
+    let circuit = AuctionClearingCircuit::new(vec![], signature);
+
+    let builder = circuit.build(CircuitBuilderStage::Mock, EMPTY_AUCTION_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], circuit.instances());
+
+    let prover = MockProver::run(EMPTY_AUCTION_PARAMS.k, &builder, instances).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_empty_auction_rejects_winner() {
+    let (_, signature) = sealed_bid(0xa1, 300, 300, 11);
+    let circuit = AuctionClearingCircuit::new(vec![], signature);
+
+    let builder = circuit.build(CircuitBuilderStage::Mock, EMPTY_AUCTION_PARAMS);
+    let mut instances = circuit.instances();
+    // has_winner follows the commitment (2 words) and the signature (12 words).
+    instances[14] = Fr::one();
+
+    let prover = MockProver::run(EMPTY_AUCTION_PARAMS.k, &builder, vec![instances]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+#[ignore = "one non-native BLS12-381 pairing per bid; needs several GB of RAM"]
+fn test_auction_clearing_mock_prover() {
+    let (a, signature) = sealed_bid(0xa1, 300, 300, 11);
+    let (b, _) = sealed_bid(0xb2, 900, 800, 12);
+    let circuit = AuctionClearingCircuit::new(vec![a, b], signature);
+
+    let builder = circuit.build(CircuitBuilderStage::Mock, AUCTION_CIRCUIT_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], circuit.instances());
+
+    let prover = MockProver::run(AUCTION_CIRCUIT_PARAMS.k, &builder, instances).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
@@ -1,3 +1,13 @@
+#![allow(dead_code)]
+
+use diem_prover_halo2::{
+    circuit::{BuilderCircuit, CircuitParams},
+    gadgets::amount::{AmountChip, LimbBits},
+    halo2_base::{
+        gates::{circuit::builder::BaseCircuitBuilder, GateInstructions, RangeChip},
+        AssignedValue,
+    },
+};
 use halo2_proofs_axiom::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
     halo2curves::bn256::Fr,
@@ -77,3 +87,55 @@ impl CircuitExt<Fr> for EquivalenceCircuit {
         vec![]
     }
 }
+
+/// Proves `a < b` for public `a` and `b`, or `a <= b` when not `strict`.
+pub struct OrderedPair {
+    pub a: u64,
+    pub b: u64,
+    pub strict: bool,
+}
+
+impl OrderedPair {
+    pub const PARAMS: CircuitParams = CircuitParams {
+        k: 10,
+        lookup_bits: 8,
+        minimum_rows: 20,
+    };
+
+    pub fn new(a: u64, b: u64) -> Self {
+        Self { a, b, strict: true }
+    }
+}
+
+impl BuilderCircuit for OrderedPair {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let chip = AmountChip::new(range, LimbBits::Eight);
+        let a = chip.load_u64(ctx, self.a);
+        let b = chip.load_u64(ctx, self.b);
+        if self.strict {
+            chip.assert_less_than(ctx, a, b, 64);
+        } else {
+            let le = chip.is_less_than_or_equal(ctx, a, b, 64);
+            range.gate().assert_is_const(ctx, &le, &Fr::one());
+        }
+        vec![a, b]
+    }
+}
+
+/// EVM verifiers need `solc`. Their soundness is what the `revm` feature is
+/// for, so a missing compiler fails the test rather than skipping it.
+pub fn require_solc() {
+    let available = std::process::Command::new("solc")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    assert!(
+        available,
+        "solc not found; install it or build without the `revm` feature"
+    );
+}
@@ -0,0 +1,190 @@
+use aptos_crypto::Uniform;
+use aptos_types::{
+    dkg::{real_dkg::RealDKG, DKGSessionMetadata, DKGTrait},
+    on_chain_config::OnChainRandomnessConfig,
+    validator_signer::ValidatorSigner,
+    validator_verifier::{ValidatorConsensusInfo, ValidatorConsensusInfoMoveStruct},
+};
+use ark_bls12_381::{Fr as BlsScalar, G1Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::CanonicalSerialize;
+use diem_prover_halo2::{
+    circuit::BuilderCircuit,
+    dkg::{native, DkgAggregationCircuit, DKG_CIRCUIT_PARAMS},
+    halo2_base::gates::circuit::CircuitBuilderStage,
+};
+use halo2_proofs_axiom::{dev::MockProver, halo2curves::bn256::Fr};
+use rand07::{rngs::StdRng, SeedableRng};
+use sha3::{Digest, Keccak256};
+use snark_verifier_sdk::CircuitExt;
+
+/// Contributions `a_i0 G` of dealers with constant terms `secrets`.
+fn contributions(secrets: &[u64]) -> Vec<G1Affine> {
+    secrets
+        .iter()
+        .map(|secret| (G1Affine::generator() * BlsScalar::from(*secret)).into_affine())
+        .collect()
+}
+
+fn dkg_circuit(epoch: u64, secrets: &[u64]) -> DkgAggregationCircuit {
+    DkgAggregationCircuit::new(epoch, native::encode_soks(&contributions(secrets)))
+}
+
+/// A transcript as `0x1::dkg` records it: the aggregate of every dealer's
+/// transcript for a session in `epoch`, serialized by aptos-types.
+fn aptos_transcript(epoch: u64, dealers: u8) -> Vec<u8> {
+    let signers: Vec<_> = (0..dealers)
+        .map(|i| ValidatorSigner::random([i + 1; 32]))
+        .collect();
+    let validators: Vec<ValidatorConsensusInfoMoveStruct> = signers
+        .iter()
+        .map(|signer| ValidatorConsensusInfo::new(signer.author(), signer.public_key(), 1).into())
+        .collect();
+    let metadata = DKGSessionMetadata {
+        dealer_epoch: epoch,
+        randomness_config: OnChainRandomnessConfig::default_enabled().into(),
+        dealer_validator_set: validators.clone(),
+        target_validator_set: validators,
+    };
+    let params = RealDKG::new_public_params(&metadata);
+
+    let mut rng = StdRng::seed_from_u64(epoch);
+    let mut transcripts = signers.iter().enumerate().map(|(index, signer)| {
+        let secret = <RealDKG as DKGTrait>::InputSecret::generate(&mut rng);
+        RealDKG::generate_transcript(
+            &mut rng,
+            &params,
+            &secret,
+            index as u64,
+            signer.private_key(),
+            &signer.public_key(),
+        )
+    });
+    let mut aggregate = transcripts.next().unwrap();
+    for transcript in transcripts {
+        RealDKG::aggregate_transcripts(&params, &mut aggregate, transcript);
+    }
+    RealDKG::verify_transcript(&params, &aggregate).unwrap();
+    RealDKG::serialize_transcript(&aggregate)
+}
+
+#[test]
+fn test_native_group_key_is_sum_of_secrets() {
+    let group_key = native::aggregate(&contributions(&[11, 22, 33]));
+    assert_eq!(
+        group_key,
+        (G1Affine::generator() * BlsScalar::from(66u64)).into_affine()
+    );
+}
+
+#[test]
+fn test_soks_section_round_trips_contributions() {
+    let dealers = contributions(&[11, 22, 33]);
+    let soks = native::encode_soks(&dealers);
+    assert_eq!(soks.len(), 1 + 3 * native::SOK_BYTES);
+
+    let transcript = [soks.as_slice(), &[0xab; 64]].concat();
+    let section = native::soks_section(&transcript).unwrap();
+    assert_eq!(section, soks.as_slice());
+    assert_eq!(native::contributions(section).unwrap(), dealers);
+}
+
+#[test]
+fn test_soks_section_rejects_malformed_transcripts() {
+    let soks = native::encode_soks(&contributions(&[11, 22]));
+    let mut bad_signature = soks.clone();
+    bad_signature[1 + native::SOK_BYTES + native::SIGNATURE_LENGTH_OFFSET] = 95;
+    for transcript in [
+        vec![],
+        vec![0],
+        soks[..soks.len() - 1].to_vec(),
+        bad_signature,
+    ] {
+        let err = native::soks_section(&transcript).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn test_soks_digest_matches_aptos_transcript() {
+    let transcript = aptos_transcript(7, 3);
+    let circuit = DkgAggregationCircuit::from_transcript(7, &transcript).unwrap();
+    assert_eq!(circuit.contributions().len(), 3);
+
+    // What a contract recomputes from `DKGSessionState.transcript`.
+    let prefix = &transcript[..1 + 3 * native::SOK_BYTES];
+    assert_eq!(
+        circuit.soks_digest(),
+        <[u8; 32]>::from(Keccak256::digest(prefix))
+    );
+
+    let builder = circuit.build(CircuitBuilderStage::Mock, DKG_CIRCUIT_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], circuit.instances());
+    let prover = MockProver::run(DKG_CIRCUIT_PARAMS.k, &builder, instances).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_parse_group_key_from_rest_api() {
+    let group_key = native::aggregate(&contributions(&[11, 22, 33]));
+    let mut bytes = vec![];
+    group_key.serialize_compressed(&mut bytes).unwrap();
+    let value = format!("0x{}", hex::encode(&bytes));
+    assert_eq!(native::parse_group_key(&value).unwrap(), group_key);
+
+    assert!(native::parse_group_key("0xzz").is_err());
+    assert!(native::parse_group_key(&value[..value.len() - 2]).is_err());
+}
+
+#[test]
+fn test_dkg_aggregation_mock_prover() {
+    let circuit = dkg_circuit(7, &[11, 22, 33]);
+    let builder = circuit.build(CircuitBuilderStage::Mock, DKG_CIRCUIT_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], circuit.instances());
+
+    let prover = MockProver::run(DKG_CIRCUIT_PARAMS.k, &builder, instances).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_dkg_aggregation_rejects_other_group_key() {
+    let circuit = dkg_circuit(7, &[11, 22, 33]);
+    let builder = circuit.build(CircuitBuilderStage::Mock, DKG_CIRCUIT_PARAMS);
+
+    // Claim the key of a dealer set that left one contribution out.
+    let mut instances = circuit.instances();
+    let partial = dkg_circuit(7, &[11, 22]).instances();
+    let group_key_words = instances.len() - 6;
+    instances[group_key_words..].copy_from_slice(&partial[partial.len() - 6..]);
+
+    let prover = MockProver::run(DKG_CIRCUIT_PARAMS.k, &builder, vec![instances]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_dkg_aggregation_rejects_other_transcript() {
+    let circuit = dkg_circuit(7, &[11, 22, 33]);
+    let builder = circuit.build(CircuitBuilderStage::Mock, DKG_CIRCUIT_PARAMS);
+
+    // Same contributions, but a different proof of knowledge on chain.
+    let mut soks = circuit.soks().to_vec();
+    *soks.last_mut().unwrap() ^= 1;
+    let mut instances = circuit.instances();
+    instances[1..3].copy_from_slice(&DkgAggregationCircuit::new(7, soks).instances()[1..3]);
+
+    let prover = MockProver::run(DKG_CIRCUIT_PARAMS.k, &builder, vec![instances]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+fn test_dkg_aggregation_rejects_other_epoch() {
+    let circuit = dkg_circuit(7, &[11, 22, 33]);
+    let builder = circuit.build(CircuitBuilderStage::Mock, DKG_CIRCUIT_PARAMS);
+    let mut instances = circuit.instances();
+    instances[0] += Fr::one();
+
+    let prover = MockProver::run(DKG_CIRCUIT_PARAMS.k, &builder, vec![instances]).unwrap();
+    assert!(prover.verify().is_err());
+}
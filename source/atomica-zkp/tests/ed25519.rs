@@ -0,0 +1,251 @@
+use diem_prover_halo2::{
+    circuit::{BuilderCircuit, CircuitParams},
+    ed25519::{
+        chip::Ed25519Chip, native::EdwardsAffine, raw_transaction_prefix, Ed25519SignatureCircuit,
+        ED25519_CIRCUIT_PARAMS, LIMB_BITS, NUM_LIMBS,
+    },
+    gadgets::{
+        bytes::{load_bytes, pack_words, pack_words_native},
+        sha512::Sha512Chip,
+    },
+    halo2_base::{
+        gates::{
+            circuit::{builder::BaseCircuitBuilder, CircuitBuilderStage},
+            RangeChip, RangeInstructions,
+        },
+        AssignedValue,
+    },
+    halo2_ecc::fields::fp::FpChip,
+};
+use halo2_proofs_axiom::{dev::MockProver, halo2curves::bn256::Fr};
+use sha2::{Digest, Sha512};
+
+/// Hashes a fixed input with SHA-512 and exposes the digest as public words.
+struct Sha512Circuit {
+    input: Vec<u8>,
+}
+
+impl BuilderCircuit for Sha512Circuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let input = load_bytes(ctx, range, &self.input);
+        let digest = Sha512Chip::new(range.gate()).sha512(ctx, &input);
+        pack_words(ctx, range.gate(), &digest)
+    }
+}
+
+const HASH_PARAMS: CircuitParams = CircuitParams {
+    k: 18,
+    lookup_bits: 8,
+    minimum_rows: 20,
+};
+
+#[test]
+fn test_sha512_matches_native() {
+    // Empty, one byte short of a length suffix, exactly fills the padding,
+    // exactly one block, multi-block
+    for len in [0, 111, 112, 128, 200] {
+        let input: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+        let expected = Sha512::digest(&input).to_vec();
+        let circuit = Sha512Circuit { input };
+        let builder = circuit.build(CircuitBuilderStage::Mock, HASH_PARAMS);
+        let instances = builder.instances();
+        assert_eq!(instances[0], pack_words_native::<Fr>(&expected));
+
+        let prover = MockProver::run(HASH_PARAMS.k, &builder, instances).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
+
+/// RFC 8032 section 7.1, TEST 2.
+const RFC8032_PUBLIC_KEY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+const RFC8032_MESSAGE: &[u8] = &[0x72];
+const RFC8032_SIGNATURE: &str = "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+                                 085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00";
+
+#[test]
+fn test_native_point_encoding() {
+    let basepoint = EdwardsAffine::basepoint();
+    assert!(basepoint.is_on_curve());
+    // The RFC 8032 encoding of B is 0x58 followed by 0x66 bytes.
+    let mut encoded = [0x66; 32];
+    encoded[0] = 0x58;
+    assert_eq!(basepoint.compress(), encoded);
+
+    let public_key: [u8; 32] = hex::decode(RFC8032_PUBLIC_KEY).unwrap().try_into().unwrap();
+    let point = EdwardsAffine::decompress(&public_key).unwrap();
+    assert!(point.is_on_curve());
+    assert_eq!(point.compress(), public_key);
+
+    // y = p is not a canonical encoding.
+    let mut non_canonical = [0xff; 32];
+    non_canonical[0] = 0xed;
+    non_canonical[31] = 0x7f;
+    assert_eq!(EdwardsAffine::decompress(&non_canonical), None);
+}
+
+/// Verifies a signature over an arbitrary message, with the public key as
+/// the only public input.
+struct SignatureCircuit {
+    public_key: Vec<u8>,
+    signature: Vec<u8>,
+    message: Vec<u8>,
+}
+
+impl SignatureCircuit {
+    fn rfc8032() -> Self {
+        Self {
+            public_key: hex::decode(RFC8032_PUBLIC_KEY).unwrap(),
+            signature: hex::decode(RFC8032_SIGNATURE).unwrap(),
+            message: RFC8032_MESSAGE.to_vec(),
+        }
+    }
+}
+
+impl BuilderCircuit for SignatureCircuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let fq_chip = FpChip::new(range, LIMB_BITS, NUM_LIMBS);
+        let scalar_chip = FpChip::new(range, LIMB_BITS, NUM_LIMBS);
+        let chip = Ed25519Chip::new(&fq_chip, &scalar_chip);
+
+        let public_key = load_bytes(ctx, range, &self.public_key);
+        let signature = load_bytes(ctx, range, &self.signature);
+        let message = load_bytes(ctx, range, &self.message);
+        chip.verify(ctx, &public_key, &signature, &message);
+        pack_words(ctx, range.gate(), &public_key)
+    }
+}
+
+const SIGNATURE_PARAMS: CircuitParams = CircuitParams {
+    k: 20,
+    lookup_bits: 19,
+    minimum_rows: 20,
+};
+
+fn verifies(circuit: SignatureCircuit) -> bool {
+    let builder = circuit.build(CircuitBuilderStage::Mock, SIGNATURE_PARAMS);
+    let instances = builder.instances();
+    let prover = MockProver::run(SIGNATURE_PARAMS.k, &builder, instances).unwrap();
+    prover.verify().is_ok()
+}
+
+/// The smallest full verification: a one-byte message is a single SHA-512
+/// block, leaving only the two scalar multiplications.
+#[test]
+fn test_rfc8032_signature_mock_prover() {
+    assert!(verifies(SignatureCircuit::rfc8032()));
+}
+
+#[test]
+#[ignore = "two non-native scalar multiplications; needs several GB of RAM"]
+fn test_rejects_signature_over_other_message() {
+    let mut circuit = SignatureCircuit::rfc8032();
+    circuit.message = vec![0x73];
+    assert!(!verifies(circuit));
+}
+
+#[test]
+#[ignore = "two non-native scalar multiplications; needs several GB of RAM"]
+fn test_rejects_non_canonical_s() {
+    // S + L encodes the same scalar but verify_strict rejects it.
+    let mut circuit = SignatureCircuit::rfc8032();
+    let l =
+        hex::decode("edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010").unwrap();
+    let mut carry = 0u16;
+    for (byte, l_byte) in circuit.signature[32..].iter_mut().zip(l) {
+        let sum = *byte as u16 + l_byte as u16 + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+    assert_eq!(carry, 0);
+    assert!(!verifies(circuit));
+}
+
+/// `0x1::aptos_account::transfer` of 1000 octas to `0xb0b` at sequence number
+/// 0 on the test chain, BCS-encoded as aptos-types does, from the account of
+/// `APTOS_PUBLIC_KEY`.
+const APTOS_RAW_TRANSACTION: &str = "7df415e5b21bdaa8b2946e8f1f4278b39904e51a69627494cd3e6f2996732fbd\
+                                     0000000000000000\
+                                     02\
+                                     0000000000000000000000000000000000000000000000000000000000000001\
+                                     0d6170746f735f6163636f756e74\
+                                     087472616e73666572\
+                                     00\
+                                     02\
+                                     200000000000000000000000000000000000000000000000000000000000000b0b\
+                                     08e803000000000000\
+                                     80841e0000000000\
+                                     6400000000000000\
+                                     ffffffffffffffff\
+                                     04";
+/// Ed25519 key with secret `[1; 32]`.
+const APTOS_PUBLIC_KEY: &str = "8a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c";
+const APTOS_SIGNATURE: &str = "62d710124f6e5fecf8e2d6640bdf81cb360687445acbd2d697a72d6123e3cfc2\
+                               73aa2ee8562a5f963dd2e361dff6825c93b9bbdfd0d088f2d000f2afdc42a20f";
+
+fn aptos_signed_transaction() -> Ed25519SignatureCircuit {
+    Ed25519SignatureCircuit::new(
+        hex::decode(APTOS_PUBLIC_KEY).unwrap().try_into().unwrap(),
+        hex::decode(APTOS_SIGNATURE).unwrap().try_into().unwrap(),
+        hex::decode(APTOS_RAW_TRANSACTION).unwrap(),
+    )
+}
+
+fn verifies_transaction(circuit: &Ed25519SignatureCircuit) -> bool {
+    let builder = circuit.build(CircuitBuilderStage::Mock, ED25519_CIRCUIT_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], circuit.instances());
+    let prover = MockProver::run(ED25519_CIRCUIT_PARAMS.k, &builder, instances).unwrap();
+    prover.verify().is_ok()
+}
+
+#[test]
+fn test_signing_message_matches_aptos() {
+    // `aptos_crypto::traits::signing_message` salts with SHA3-256 of the
+    // type's name.
+    assert_eq!(
+        hex::encode(raw_transaction_prefix()),
+        "b5e97db07fa0bd0e5598aa3643a9bc6f6693bddc1a9fec9e674a461eaa00b193"
+    );
+    let circuit = aptos_signed_transaction();
+    let message = circuit.signing_message();
+    assert_eq!(message[..32], raw_transaction_prefix());
+    assert_eq!(message[32..], circuit.raw_transaction);
+}
+
+#[test]
+#[ignore = "two non-native scalar multiplications and three SHA-512 blocks; needs several GB of RAM"]
+fn test_accepts_aptos_signed_transaction() {
+    assert!(verifies_transaction(&aptos_signed_transaction()));
+}
+
+#[test]
+#[ignore = "two non-native scalar multiplications and three SHA-512 blocks; needs several GB of RAM"]
+fn test_rejects_signature_over_other_transaction() {
+    let mut circuit = aptos_signed_transaction();
+    // Transfer 1_000_000 octas instead.
+    let amount = circuit.raw_transaction.len() - 33;
+    circuit.raw_transaction[amount..amount + 8].copy_from_slice(&1_000_000u64.to_le_bytes());
+    assert!(!verifies_transaction(&circuit));
+}
+
+#[test]
+#[ignore = "two non-native scalar multiplications and three SHA-512 blocks; needs several GB of RAM"]
+fn test_rejects_other_public_key() {
+    let mut circuit = aptos_signed_transaction();
+    circuit.public_key =
+        hex::decode("8139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b394")
+            .unwrap()
+            .try_into()
+            .unwrap();
+    assert!(!verifies_transaction(&circuit));
+}
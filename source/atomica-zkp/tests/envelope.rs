@@ -0,0 +1,83 @@
+mod common;
+
+use common::OrderedPair;
+use diem_prover_halo2::{
+    circuit::BuilderCircuit,
+    envelope::ProofEnvelope,
+    halo2_base::gates::circuit::CircuitBuilderStage,
+    verifier::{read_params, read_vk, verify_envelope},
+};
+use halo2_proofs_axiom::{
+    halo2curves::bn256::{Bn256, Fr},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use snark_verifier_sdk::{evm::gen_evm_proof_shplonk, gen_pk, CircuitExt};
+
+#[test]
+fn test_envelope_json_round_trip() {
+    let envelope = ProofEnvelope::new(vec![vec![Fr::one(), -Fr::one()], vec![]], vec![0xab, 0xcd]);
+    let json = envelope.to_json();
+    assert_eq!(
+        json,
+        format!(
+            r#"{{"version":1,"instances":[["0x{}01","0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000000"],[]],"proof":"0xabcd"}}"#,
+            "00".repeat(31)
+        )
+    );
+    assert_eq!(ProofEnvelope::from_json(&json).unwrap(), envelope);
+}
+
+#[test]
+fn test_envelope_rejects_malformed_json() {
+    let zero = format!("0x{}", "00".repeat(32));
+    let envelope = |version: u32, instance: &str, proof: &str| {
+        format!(r#"{{"version":{version},"instances":[["{instance}"]],"proof":"{proof}"}}"#)
+    };
+    assert!(ProofEnvelope::from_json(&envelope(1, &zero, "0x")).is_ok());
+
+    // Unknown version.
+    assert!(ProofEnvelope::from_json(&envelope(2, &zero, "0x")).is_err());
+    // Missing prefix, bad hex and wrong width.
+    assert!(ProofEnvelope::from_json(&envelope(1, &zero[2..], "0x")).is_err());
+    assert!(ProofEnvelope::from_json(&envelope(1, &zero, "0xzz")).is_err());
+    assert!(ProofEnvelope::from_json(&envelope(1, "0x01", "0x")).is_err());
+    // The scalar field modulus is not a canonical instance.
+    let modulus = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+    assert!(ProofEnvelope::from_json(&envelope(1, modulus, "0x")).is_err());
+    assert!(ProofEnvelope::from_json("{}").is_err());
+}
+
+/// Verifies through the same byte-level inputs the wasm verifier receives.
+#[test]
+fn test_verify_envelope_from_serialized_keys() {
+    let params = ParamsKZG::<Bn256>::setup(OrderedPair::PARAMS.k, StdRng::seed_from_u64(32));
+    let circuit = OrderedPair::new(3, 5);
+    let keygen = circuit.build(CircuitBuilderStage::Keygen, OrderedPair::PARAMS);
+    let pk = gen_pk(&params, &keygen, None);
+
+    let prover = circuit.build_prover(keygen.config_params.clone(), keygen.break_points());
+    let instances = prover.instances();
+    let proof = gen_evm_proof_shplonk(&params, &pk, prover, instances.clone());
+    let json = ProofEnvelope::new(instances, proof).to_json();
+
+    let mut params_bytes = vec![];
+    params.write(&mut params_bytes).unwrap();
+    let params = read_params(&params_bytes).unwrap();
+    let vk_bytes = pk.get_vk().to_bytes(SerdeFormat::RawBytes);
+    let config =
+        serde_json::from_str(&serde_json::to_string(&keygen.config_params).unwrap()).unwrap();
+    let vk = read_vk(&vk_bytes, config).unwrap();
+
+    let envelope = ProofEnvelope::from_json(&json).unwrap();
+    assert!(verify_envelope(&params, &vk, &envelope));
+
+    let mut swapped = envelope.clone();
+    swapped.instances[0].swap(0, 1);
+    assert!(!verify_envelope(&params, &vk, &swapped));
+
+    let mut truncated = envelope;
+    truncated.proof.truncate(64);
+    assert!(!verify_envelope(&params, &vk, &truncated));
+}
@@ -0,0 +1,409 @@
+use ark_bls12_381::{Fr as BlsScalar, G1Affine, G2Affine, G2Projective};
+use ark_ec::{AffineRepr, CurveGroup};
+use diem_prover_halo2::{
+    circuit::{BuilderCircuit, CircuitParams},
+    epoch::{
+        chain::{EpochChainProver, ACCUMULATOR_LIMBS, CHAIN_CIRCUIT_PARAMS},
+        native::{self, LedgerInfo, ValidatorSet},
+        EpochChange, EpochChangeCircuit, EPOCH_CIRCUIT_PARAMS,
+    },
+    gadgets::{
+        bytes::{load_bytes, pack_words, pack_words_native},
+        sha256::Sha256Chip,
+    },
+    halo2_base::{
+        gates::{
+            circuit::{builder::BaseCircuitBuilder, CircuitBuilderStage},
+            GateInstructions, RangeChip, RangeInstructions,
+        },
+        AssignedValue,
+    },
+    halo2_ecc::{
+        bls12_381::{Fp2Chip, FpChip},
+        ecc::EccChip,
+    },
+    ibe::{chip::IbeChip, LIMB_BITS, NUM_LIMBS},
+    prover::TranscriptKind,
+    verifier::verify,
+};
+use halo2_proofs_axiom::{
+    dev::MockProver,
+    halo2curves::bn256::{Bn256, Fr},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use sha2::{Digest, Sha256};
+use snark_verifier_sdk::CircuitExt;
+
+const CAPACITY: usize = 4;
+
+/// Validators with secret keys `secrets` and equal voting power.
+fn validators(secrets: &[u64]) -> ValidatorSet {
+    ValidatorSet::new(
+        secrets
+            .iter()
+            .map(|secret| (G1Affine::generator() * BlsScalar::from(*secret)).into_affine())
+            .collect(),
+        vec![10; secrets.len()],
+    )
+}
+
+/// The ledger info ending `epoch` and handing over to `next`.
+fn ledger_info(epoch: u64, next: ValidatorSet) -> LedgerInfo {
+    LedgerInfo {
+        epoch,
+        round: 3 * epoch,
+        id: [epoch as u8; 32],
+        executed_state_id: [0xee; 32],
+        version: 1_000 * epoch,
+        timestamp_usecs: 1_700_000_000_000_000 + epoch,
+        next_addresses: (1..=next.len()).map(|i| [i as u8; 32]).collect(),
+        next_validators: next,
+        consensus_data_hash: [0xcd; 32],
+    }
+}
+
+/// The change from `secrets` to `next_secrets` at `epoch`, signed by the
+/// validators flagged in `signers`.
+fn change(epoch: u64, secrets: &[u64], signers: &[bool], next_secrets: &[u64]) -> EpochChange {
+    let ledger_info = ledger_info(epoch, validators(next_secrets));
+    let message = ledger_info.message_point();
+    let signature = secrets
+        .iter()
+        .zip(signers)
+        .filter(|(_, &signed)| signed)
+        .fold(G2Projective::default(), |acc, (secret, _)| {
+            acc + message * BlsScalar::from(*secret)
+        })
+        .into_affine();
+    EpochChange {
+        validators: validators(secrets),
+        signers: signers.to_vec(),
+        signature,
+        ledger_info,
+    }
+}
+
+/// Hashes whichever of `inputs` `selected` picks with SHA-256 and exposes
+/// the digest as public words.
+struct Sha256Circuit {
+    inputs: Vec<Vec<u8>>,
+    selected: usize,
+}
+
+impl BuilderCircuit for Sha256Circuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let gate = range.gate();
+        let inputs: Vec<_> = self
+            .inputs
+            .iter()
+            .map(|input| load_bytes(ctx, range, input))
+            .collect();
+        let chip = Sha256Chip::new(gate);
+        let digest = if let [input] = inputs.as_slice() {
+            chip.sha256(ctx, input)
+        } else {
+            let selected = ctx.load_witness(Fr::from(self.selected as u64));
+            let indicator = gate.idx_to_indicator(ctx, selected, inputs.len());
+            chip.sha256_any(ctx, &inputs, &indicator)
+        };
+        pack_words(ctx, gate, &digest)
+    }
+}
+
+const HASH_PARAMS: CircuitParams = CircuitParams {
+    k: 18,
+    lookup_bits: 8,
+    minimum_rows: 20,
+};
+
+/// Runs [`Sha256Circuit`] and checks its digest against the `sha2` crate.
+fn check_sha256(inputs: Vec<Vec<u8>>, selected: usize) {
+    let expected = Sha256::digest(&inputs[selected]).to_vec();
+    let circuit = Sha256Circuit { inputs, selected };
+    let builder = circuit.build(CircuitBuilderStage::Mock, HASH_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], pack_words_native::<Fr>(&expected));
+
+    let prover = MockProver::run(HASH_PARAMS.k, &builder, instances).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+/// Hashes the ledger info of `step` to G2 in-circuit and checks the result
+/// against `signed`, the point a signature was made over.
+struct MessageBindingCircuit {
+    step: EpochChangeCircuit,
+    signed: G2Affine,
+}
+
+impl BuilderCircuit for MessageBindingCircuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let fp_chip = FpChip::new(range, LIMB_BITS, NUM_LIMBS);
+        let epoch = load_bytes(ctx, range, &self.step.change.epoch().to_le_bytes());
+        let (state_out, message) = self.step.load_next_state(ctx, range, &fp_chip, &epoch);
+        let signed = IbeChip::new(&fp_chip).load_g2(ctx, &self.signed);
+        let fp2_chip = Fp2Chip::new(&fp_chip);
+        EccChip::new(&fp2_chip).assert_equal(ctx, message, signed);
+        vec![state_out]
+    }
+}
+
+/// Hash-to-G2 without the pairings fits a smaller circuit.
+const BINDING_PARAMS: CircuitParams = CircuitParams {
+    k: 20,
+    lookup_bits: 19,
+    minimum_rows: 20,
+};
+
+fn run_message_binding(change: EpochChange, signed: G2Affine) -> bool {
+    let circuit = MessageBindingCircuit {
+        step: EpochChangeCircuit::new(change, CAPACITY),
+        signed,
+    };
+    let builder = circuit.build(CircuitBuilderStage::Mock, BINDING_PARAMS);
+    let instances = vec![vec![circuit.step.state_out()]];
+    assert_eq!(builder.instances(), instances);
+    let prover = MockProver::run(BINDING_PARAMS.k, &builder, instances).unwrap();
+    prover.verify().is_ok()
+}
+
+#[test]
+fn test_native_quorum_is_strictly_two_thirds() {
+    let set = validators(&[1, 2, 3]);
+    assert!(native::has_quorum(&set, &[true, true, true]));
+    assert!(!native::has_quorum(&set, &[true, true, false]));
+
+    let set = validators(&[1, 2, 3, 4]);
+    assert!(native::has_quorum(&set, &[true, true, true, false]));
+    assert!(!native::has_quorum(&set, &[true, true, false, false]));
+}
+
+#[test]
+fn test_native_verify() {
+    let valid = change(5, &[1, 2, 3, 4], &[true, true, true, false], &[5, 6, 7]);
+    assert!(valid.verify());
+
+    let mut forged = valid.clone();
+    forged.signers = vec![true, true, true, true];
+    assert!(!forged.verify());
+
+    let mut other_ledger_info = valid.clone();
+    other_ledger_info.ledger_info.consensus_data_hash = [0; 32];
+    assert!(!other_ledger_info.verify());
+
+    let mut swapped = valid.clone();
+    swapped.ledger_info.next_validators.public_keys.swap(0, 1);
+    assert!(!swapped.verify());
+
+    let minority = change(5, &[1, 2, 3, 4], &[true, true, false, false], &[5, 6, 7]);
+    assert!(!minority.verify());
+}
+
+#[test]
+fn test_native_state_binds_epoch_and_validators() {
+    let set = validators(&[1, 2, 3]);
+    let state = native::state(7, &set);
+    assert_ne!(state, native::state(8, &set));
+    assert_ne!(state, native::state(7, &validators(&[1, 2, 4])));
+    let mut reweighted = set.clone();
+    reweighted.voting_powers[0] += 1;
+    assert_ne!(state, native::state(7, &reweighted));
+}
+
+#[test]
+fn test_native_signing_message_layout() {
+    let info = ledger_info(5, validators(&[5, 6, 7]));
+    let message = info.signing_message();
+    assert_eq!(&message[..32], native::ledger_info_prefix());
+    assert_eq!(message[32..40], 5u64.to_le_bytes());
+    // Fixed fields, then 89 bytes per validator.
+    assert_eq!(message.len(), 173 + 3 * 89);
+    assert_eq!(message[32 + 107], 3);
+    assert_eq!(
+        message[32 + 108 + 33..32 + 108 + 81],
+        native::compressed_g1(&info.next_validators.public_keys[0])
+    );
+}
+
+#[test]
+fn test_sha256_matches_native() {
+    // Empty, one byte short of a length suffix, exactly fills the padding,
+    // exactly one block, multi-block
+    for len in [0, 55, 56, 64, 200] {
+        let input: Vec<u8> = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+        check_sha256(vec![input], 0);
+    }
+}
+
+#[test]
+fn test_sha256_any_selects_candidate() {
+    // Candidates ending in the same block, in later blocks and past the
+    // longest padding of the others.
+    let inputs: Vec<Vec<u8>> = [10, 20, 100, 130]
+        .iter()
+        .map(|&len| (0..len).map(|i| (i * 5 + len) as u8).collect())
+        .collect();
+    for selected in 0..inputs.len() {
+        check_sha256(inputs.clone(), selected);
+    }
+}
+
+#[test]
+fn test_message_binding_mock_prover() {
+    for next_secrets in [&[5, 6, 7][..], &[5, 6, 7, 8], &[]] {
+        let valid = change(5, &[1, 2, 3, 4], &[true; 4], next_secrets);
+        let signed = valid.ledger_info.message_point();
+        assert!(run_message_binding(valid, signed));
+    }
+}
+
+#[test]
+fn test_message_binding_rejects_other_ledger_infos() {
+    let valid = change(5, &[1, 2, 3, 4], &[true; 4], &[5, 6, 7]);
+
+    // A signature over a ledger info that differs in a field the states do
+    // not commit to.
+    let mut other_ledger_info = valid.ledger_info.clone();
+    other_ledger_info.round += 1;
+    assert!(!run_message_binding(
+        valid.clone(),
+        other_ledger_info.message_point()
+    ));
+
+    // The signed ledger info names the next validators in another order.
+    let mut swapped = valid.clone();
+    swapped.ledger_info.next_validators.public_keys.swap(0, 2);
+    assert!(!run_message_binding(
+        swapped,
+        valid.ledger_info.message_point()
+    ));
+
+    // A trailing validator claimed as padding.
+    let mut truncated = valid.clone();
+    truncated.ledger_info.next_validators = validators(&[5, 6]);
+    truncated.ledger_info.next_addresses.pop();
+    assert!(!run_message_binding(
+        truncated,
+        valid.ledger_info.message_point()
+    ));
+}
+
+#[test]
+fn test_consecutive_changes_link_states() {
+    let first = EpochChangeCircuit::new(change(5, &[1, 2, 3], &[true; 3], &[4, 5, 6]), CAPACITY);
+    let second = EpochChangeCircuit::new(change(6, &[4, 5, 6], &[true; 3], &[7, 8]), CAPACITY);
+    assert_eq!(first.state_out(), second.state_in());
+    assert_ne!(first.state_in(), second.state_out());
+}
+
+/// A single-slot layout: the two pairings still dominate, but hashing and
+/// the message candidates shrink to one validator.
+const SINGLE_SLOT_PARAMS: CircuitParams = CircuitParams {
+    k: 19,
+    lookup_bits: 18,
+    minimum_rows: 20,
+};
+
+#[test]
+fn test_single_validator_epoch_change_mock_prover() {
+    let circuit = EpochChangeCircuit::new(change(5, &[1], &[true], &[2]), 1);
+    let builder = circuit.build(CircuitBuilderStage::Mock, SINGLE_SLOT_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], circuit.instances());
+    let prover = MockProver::run(SINGLE_SLOT_PARAMS.k, &builder, instances).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    let mut unsigned = change(5, &[1], &[true], &[2]);
+    unsigned.signers = vec![false];
+    let circuit = EpochChangeCircuit::new(unsigned, 1);
+    let builder = circuit.build(CircuitBuilderStage::Mock, SINGLE_SLOT_PARAMS);
+    let prover =
+        MockProver::run(SINGLE_SLOT_PARAMS.k, &builder, vec![circuit.instances()]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+#[ignore = "two non-native BLS12-381 pairings; needs several GB of RAM"]
+fn test_epoch_change_mock_prover() {
+    let circuit = EpochChangeCircuit::new(
+        change(5, &[1, 2, 3, 4], &[true, false, true, true], &[5, 6, 7]),
+        CAPACITY,
+    );
+    let builder = circuit.build(CircuitBuilderStage::Mock, EPOCH_CIRCUIT_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], circuit.instances());
+
+    let prover = MockProver::run(EPOCH_CIRCUIT_PARAMS.k, &builder, instances).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+#[ignore = "two non-native BLS12-381 pairings; needs several GB of RAM"]
+fn test_epoch_change_rejects_invalid_changes() {
+    let minority = change(5, &[1, 2, 3, 4], &[true, true, false, false], &[5, 6, 7]);
+    let mut forged = change(5, &[1, 2, 3, 4], &[true, true, true, false], &[5, 6, 7]);
+    forged.signers = vec![true; 4];
+    let mut other_ledger_info = change(5, &[1, 2, 3, 4], &[true; 4], &[5, 6, 7]);
+    other_ledger_info.ledger_info.consensus_data_hash = [0; 32];
+    let mut swapped = change(5, &[1, 2, 3, 4], &[true; 4], &[5, 6, 7]);
+    swapped.ledger_info.next_validators.public_keys.swap(0, 1);
+
+    for invalid in [minority, forged, other_ledger_info, swapped] {
+        let circuit = EpochChangeCircuit::new(invalid, CAPACITY);
+        let builder = circuit.build(CircuitBuilderStage::Mock, EPOCH_CIRCUIT_PARAMS);
+        let prover =
+            MockProver::run(EPOCH_CIRCUIT_PARAMS.k, &builder, vec![circuit.instances()]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[test]
+#[ignore = "recursive aggregation at k = 22; needs tens of GB of RAM"]
+fn test_chain_of_three_epochs() {
+    let chain_params = ParamsKZG::<Bn256>::setup(CHAIN_CIRCUIT_PARAMS.k, StdRng::seed_from_u64(38));
+    let mut step_params = chain_params.clone();
+    step_params.downsize(EPOCH_CIRCUIT_PARAMS.k);
+
+    let changes = [
+        change(5, &[1, 2, 3], &[true; 3], &[4, 5, 6, 7]),
+        change(6, &[4, 5, 6, 7], &[true, true, true, false], &[8, 9]),
+        change(7, &[8, 9], &[true, true], &[10, 11, 12]),
+    ];
+    let prover = EpochChainProver::setup(
+        step_params,
+        EPOCH_CIRCUIT_PARAMS,
+        EpochChangeCircuit::new(changes[0].clone(), CAPACITY),
+        chain_params.clone(),
+        CHAIN_CIRCUIT_PARAMS,
+    );
+
+    let (instances, proof) = prover.prove(&changes, TranscriptKind::Evm);
+    let first = EpochChangeCircuit::new(changes[0].clone(), CAPACITY);
+    let last = EpochChangeCircuit::new(changes[2].clone(), CAPACITY);
+    assert_eq!(
+        &instances[ACCUMULATOR_LIMBS..],
+        &[
+            prover.vk_digest(),
+            first.state_in(),
+            last.state_out(),
+            Fr::from(2)
+        ]
+    );
+    assert!(verify(
+        &chain_params,
+        prover.chain_vk(),
+        &[instances],
+        &proof,
+        TranscriptKind::Evm
+    ));
+}
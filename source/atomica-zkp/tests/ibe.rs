@@ -0,0 +1,206 @@
+use ark_bls12_381::{Bls12_381, Fr as BlsScalar, G1Affine, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use diem_prover_halo2::{
+    circuit::{BuilderCircuit, CircuitParams},
+    gadgets::{
+        bytes::{load_bytes, pack_words, pack_words_native},
+        keccak::KeccakChip,
+    },
+    halo2_base::{
+        gates::{
+            circuit::{builder::BaseCircuitBuilder, CircuitBuilderStage},
+            RangeChip, RangeInstructions,
+        },
+        AssignedValue,
+    },
+    halo2_ecc::bls12_381::FpChip,
+    ibe::{chip::IbeChip, native, IbeDecryptionCircuit, IBE_CIRCUIT_PARAMS, LIMB_BITS, NUM_LIMBS},
+};
+use halo2_proofs_axiom::{dev::MockProver, halo2curves::bn256::Fr};
+use sha3::{Digest, Keccak256, Sha3_256};
+use snark_verifier_sdk::CircuitExt;
+
+/// Hashes a fixed input and exposes the digest as public words.
+struct HashCircuit {
+    input: Vec<u8>,
+    sha3: bool,
+}
+
+impl BuilderCircuit for HashCircuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let input = load_bytes(ctx, range, &self.input);
+        let keccak = KeccakChip::new(range.gate());
+        let digest = if self.sha3 {
+            keccak.sha3_256(ctx, &input)
+        } else {
+            keccak.keccak256(ctx, &input)
+        };
+        pack_words(ctx, range.gate(), &digest)
+    }
+}
+
+const HASH_PARAMS: CircuitParams = CircuitParams {
+    k: 18,
+    lookup_bits: 8,
+    minimum_rows: 20,
+};
+
+fn check_hash(input: Vec<u8>, sha3: bool) {
+    let expected: Vec<u8> = if sha3 {
+        Sha3_256::digest(&input).to_vec()
+    } else {
+        Keccak256::digest(&input).to_vec()
+    };
+    let circuit = HashCircuit { input, sha3 };
+    let builder = circuit.build(CircuitBuilderStage::Mock, HASH_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], pack_words_native::<Fr>(&expected));
+
+    let prover = MockProver::run(HASH_PARAMS.k, &builder, instances).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_keccak256_matches_native() {
+    // Empty, single byte, one byte short of a block, exactly one block, multi-block
+    for len in [0, 1, 135, 136, 300] {
+        let input = (0..len).map(|i| (i * 7 + 3) as u8).collect();
+        check_hash(input, false);
+    }
+}
+
+#[test]
+fn test_sha3_256_matches_native() {
+    check_hash(b"APTOS::LedgerInfo".to_vec(), true);
+}
+
+#[test]
+fn test_keccak256_rejects_wrong_digest() {
+    let circuit = HashCircuit {
+        input: b"SecretBid100".to_vec(),
+        sha3: false,
+    };
+    let builder = circuit.build(CircuitBuilderStage::Mock, HASH_PARAMS);
+    let mut instances = builder.instances();
+    instances[0][0] += Fr::one();
+
+    let prover = MockProver::run(HASH_PARAMS.k, &builder, instances).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+/// The Move `test_ibe_flow` scenario: master secret `s`, randomness `r`.
+fn sealed_bid(message: &[u8]) -> (G1Affine, G2Affine, Vec<u8>) {
+    let s = BlsScalar::from(123456789u64);
+    let r = BlsScalar::from(987654321u64);
+    let mpk = (G1Affine::generator() * s).into_affine();
+    let q_id = native::identity_point(b"epoch_100");
+    let signature = (q_id * s).into_affine();
+    let (u, ciphertext) = native::encrypt(&mpk, &q_id, r, message);
+    (u, signature, ciphertext)
+}
+
+#[test]
+fn test_native_roundtrip() {
+    let message = b"SecretBid100";
+    let (u, signature, ciphertext) = sealed_bid(message);
+    assert_ne!(ciphertext, message.to_vec());
+    assert_eq!(native::decrypt(&u, &signature, &ciphertext), message.to_vec());
+}
+
+#[test]
+fn test_native_wrong_signature_garbles() {
+    let message = b"SecretBid100";
+    let (u, _, ciphertext) = sealed_bid(message);
+    let wrong = (native::identity_point(b"epoch_101") * BlsScalar::from(123456789u64)).into_affine();
+    assert_ne!(native::decrypt(&u, &wrong, &ciphertext), message.to_vec());
+}
+
+/// The decryption circuit with the pairing's output supplied as a witness.
+struct UnmaskCircuit {
+    shared: <Bls12_381 as Pairing>::TargetField,
+    ciphertext: Vec<u8>,
+}
+
+impl BuilderCircuit for UnmaskCircuit {
+    fn synthesize(
+        &self,
+        builder: &mut BaseCircuitBuilder<Fr>,
+        range: &RangeChip<Fr>,
+    ) -> Vec<AssignedValue<Fr>> {
+        let ctx = builder.main(0);
+        let fp_chip = FpChip::new(range, LIMB_BITS, NUM_LIMBS);
+        let chip = IbeChip::new(&fp_chip);
+        let shared = chip.load_gt(ctx, &self.shared);
+        let ciphertext = load_bytes(ctx, range, &self.ciphertext);
+        let plaintext = chip.unmask(ctx, &shared, &ciphertext);
+        pack_words(ctx, range.gate(), &plaintext)
+    }
+}
+
+#[test]
+fn test_unmask_matches_native() {
+    let message = 1_000u64.to_le_bytes();
+    let (u, signature, ciphertext) = sealed_bid(&message);
+    let circuit = UnmaskCircuit {
+        shared: native::shared_secret(&u, &signature),
+        ciphertext,
+    };
+    let builder = circuit.build(CircuitBuilderStage::Mock, HASH_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], pack_words_native::<Fr>(&message));
+
+    let prover = MockProver::run(HASH_PARAMS.k, &builder, instances).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+fn test_unmask_rejects_wrong_plaintext() {
+    let message = 1_000u64.to_le_bytes();
+    let (u, signature, ciphertext) = sealed_bid(&message);
+    let circuit = UnmaskCircuit {
+        shared: native::shared_secret(&u, &signature),
+        ciphertext,
+    };
+    let builder = circuit.build(CircuitBuilderStage::Mock, HASH_PARAMS);
+    let instances = vec![pack_words_native::<Fr>(&1_001u64.to_le_bytes())];
+
+    let prover = MockProver::run(HASH_PARAMS.k, &builder, instances).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[test]
+#[ignore = "non-native BLS12-381 pairing; needs several GB of RAM"]
+fn test_ibe_decryption_mock_prover() {
+    let message = 1_000u64.to_le_bytes();
+    let (u, signature, ciphertext) = sealed_bid(&message);
+    let circuit = IbeDecryptionCircuit::new(u, signature, ciphertext);
+    assert_eq!(circuit.plaintext(), message.to_vec());
+
+    let builder = circuit.build(CircuitBuilderStage::Mock, IBE_CIRCUIT_PARAMS);
+    let instances = builder.instances();
+    assert_eq!(instances[0], circuit.instances());
+
+    let prover = MockProver::run(IBE_CIRCUIT_PARAMS.k, &builder, instances).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+}
+
+#[test]
+#[ignore = "non-native BLS12-381 pairing; needs several GB of RAM"]
+fn test_ibe_decryption_rejects_wrong_plaintext() {
+    let message = 1_000u64.to_le_bytes();
+    let (u, signature, ciphertext) = sealed_bid(&message);
+    let circuit = IbeDecryptionCircuit::new(u, signature, ciphertext);
+
+    let builder = circuit.build(CircuitBuilderStage::Mock, IBE_CIRCUIT_PARAMS);
+    let mut instances = circuit.instances();
+    // Claim a different bid amount in the last (plaintext) word.
+    *instances.last_mut().unwrap() += Fr::one();
+
+    let prover = MockProver::run(IBE_CIRCUIT_PARAMS.k, &builder, vec![instances]).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -0,0 +1,169 @@
+//! The native epoch relation checked against `atomica-light-client` on data
+//! built and signed with aptos-types.
+
+use std::sync::Arc;
+
+use aptos_crypto::{bls12381, traits::signing_message, HashValue};
+use aptos_types::{
+    aggregate_signature::{AggregateSignature, PartialSignatures},
+    block_info::BlockInfo,
+    epoch_change::EpochChangeProof,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_signer::ValidatorSigner,
+    validator_verifier::{ValidatorConsensusInfo, ValidatorVerifier},
+    waypoint::Waypoint,
+};
+use ark_bls12_381::{G1Affine, G2Affine};
+use ark_serialize::CanonicalDeserialize;
+use atomica_light_client::LightClientStore;
+use diem_prover_halo2::epoch::{
+    native::{self, ValidatorSet},
+    EpochChange,
+};
+
+fn signers(seed: u8, count: u8) -> Vec<ValidatorSigner> {
+    (0..count)
+        .map(|i| ValidatorSigner::random([seed * 16 + i; 32]))
+        .collect()
+}
+
+fn verifier(signers: &[ValidatorSigner]) -> ValidatorVerifier {
+    ValidatorVerifier::new(
+        signers
+            .iter()
+            .map(|signer| ValidatorConsensusInfo::new(signer.author(), signer.public_key(), 1))
+            .collect(),
+    )
+}
+
+/// The ledger info ending `epoch` and handing over to `next`, signed by
+/// every validator in `signers`.
+fn epoch_ending(
+    epoch: u64,
+    next: &[ValidatorSigner],
+    signers: &[ValidatorSigner],
+) -> LedgerInfoWithSignatures {
+    let next_epoch_state = EpochState::new(epoch + 1, Arc::new(verifier(next)));
+    let block = BlockInfo::new(
+        epoch,
+        7,
+        HashValue::sha3_256_of(&epoch.to_le_bytes()),
+        HashValue::sha3_256_of(b"accumulator"),
+        100 * epoch,
+        1_000 * epoch,
+        Some(next_epoch_state),
+    );
+    let ledger_info = LedgerInfo::new(block, HashValue::sha3_256_of(b"consensus data"));
+    if signers.is_empty() {
+        return LedgerInfoWithSignatures::new(ledger_info, AggregateSignature::empty());
+    }
+    let mut partial = PartialSignatures::empty();
+    for signer in signers {
+        partial.add_signature(signer.author(), signer.sign(&ledger_info).unwrap());
+    }
+    let aggregated = verifier(signers)
+        .aggregate_signatures(partial.signatures_iter())
+        .unwrap();
+    LedgerInfoWithSignatures::new(ledger_info, aggregated)
+}
+
+/// A store trusting `current` as the validators of `epoch`.
+fn store(epoch: u64, current: &[ValidatorSigner]) -> LightClientStore {
+    let genesis = epoch_ending(epoch - 1, current, &[]);
+    let waypoint = Waypoint::new_epoch_boundary(genesis.ledger_info()).unwrap();
+    LightClientStore::new(waypoint, genesis).unwrap()
+}
+
+fn ark_g1(key: &bls12381::PublicKey) -> G1Affine {
+    G1Affine::deserialize_compressed(&key.to_bytes()[..]).unwrap()
+}
+
+fn ark_g2(signature: &bls12381::Signature) -> G2Affine {
+    G2Affine::deserialize_compressed(&signature.to_bytes()[..]).unwrap()
+}
+
+fn validator_set(signers: &[ValidatorSigner]) -> ValidatorSet {
+    ValidatorSet::new(
+        signers
+            .iter()
+            .map(|signer| ark_g1(&signer.public_key()))
+            .collect(),
+        vec![1; signers.len()],
+    )
+}
+
+fn bytes(hash: HashValue) -> [u8; 32] {
+    hash.to_vec().try_into().unwrap()
+}
+
+/// The native counterpart of an aptos-types ledger info handing over to
+/// `next`.
+fn native_ledger_info(ledger_info: &LedgerInfo, next: &[ValidatorSigner]) -> native::LedgerInfo {
+    let block = ledger_info.commit_info();
+    native::LedgerInfo {
+        epoch: block.epoch(),
+        round: block.round(),
+        id: bytes(block.id()),
+        executed_state_id: bytes(block.executed_state_id()),
+        version: block.version(),
+        timestamp_usecs: block.timestamp_usecs(),
+        next_addresses: next
+            .iter()
+            .map(|signer| signer.author().into_bytes())
+            .collect(),
+        next_validators: validator_set(next),
+        consensus_data_hash: bytes(ledger_info.consensus_data_hash()),
+    }
+}
+
+#[test]
+fn test_native_bls_agrees_with_light_client() {
+    let current = signers(1, 4);
+    let mut trusted = store(5, &current);
+    let change = epoch_ending(5, &signers(2, 3), &current);
+    trusted
+        .verify_epoch_change(&EpochChangeProof::new(vec![change.clone()], false))
+        .unwrap();
+    assert_eq!(trusted.epoch(), 6);
+
+    let validators = validator_set(&current);
+    let public_key = native::aggregate_public_key(&validators, &[true; 4]);
+    let message = native::message_point(&signing_message(change.ledger_info()).unwrap());
+    let signature = ark_g2(change.signatures().sig().as_ref().unwrap());
+    assert!(native::has_quorum(&validators, &[true; 4]));
+    assert!(native::verify_aggregate(&public_key, &message, &signature));
+
+    // A signature by other validators is rejected by both.
+    let forged = epoch_ending(5, &signers(2, 3), &signers(3, 4));
+    assert!(store(5, &current)
+        .verify_epoch_change(&EpochChangeProof::new(vec![forged.clone()], false))
+        .is_err());
+    let signature = ark_g2(forged.signatures().sig().as_ref().unwrap());
+    assert!(!native::verify_aggregate(&public_key, &message, &signature));
+}
+
+#[test]
+fn test_signing_message_matches_aptos_types() {
+    let current = signers(1, 4);
+    let next = signers(2, 3);
+    let signed = epoch_ending(5, &next, &current);
+    let ledger_info = native_ledger_info(signed.ledger_info(), &next);
+    // `SHA3-256("APTOS::LedgerInfo") || BCS(LedgerInfo)`, as Aptos signs it.
+    assert_eq!(
+        ledger_info.signing_message(),
+        signing_message(signed.ledger_info()).unwrap()
+    );
+
+    let change = EpochChange {
+        validators: validator_set(&current),
+        signers: vec![true; 4],
+        signature: ark_g2(signed.signatures().sig().as_ref().unwrap()),
+        ledger_info,
+    };
+    assert!(change.verify());
+
+    let mut swapped = change.clone();
+    swapped.ledger_info.next_validators.public_keys.swap(0, 1);
+    assert!(!swapped.verify());
+}
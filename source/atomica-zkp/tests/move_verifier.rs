@@ -0,0 +1,60 @@
+mod common;
+
+use common::OrderedPair;
+use diem_prover_halo2::{
+    circuit::BuilderCircuit,
+    halo2_base::gates::circuit::CircuitBuilderStage,
+    move_verifier::{gen_move_verifier_code, MoveLoader},
+};
+use halo2_proofs_axiom::{
+    halo2curves::bn256::{Bn256, Fr},
+    poly::kzg::commitment::ParamsKZG,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use snark_verifier_sdk::{
+    evm::gen_evm_proof_shplonk,
+    gen_pk,
+    snark_verifier::loader::{LoadedScalar, ScalarLoader},
+    CircuitExt,
+};
+
+#[test]
+fn test_move_verifier_matches_evm_proof_layout() {
+    let params = ParamsKZG::<Bn256>::setup(OrderedPair::PARAMS.k, StdRng::seed_from_u64(29));
+    let circuit = OrderedPair::new(3, 5);
+    let keygen = circuit.build(CircuitBuilderStage::Keygen, OrderedPair::PARAMS);
+    let pk = gen_pk(&params, &keygen, None);
+    let num_instance = keygen.num_instance();
+
+    let code = gen_move_verifier_code(&params, pk.get_vk(), num_instance.clone(), "verifier::ordered_pair");
+    assert!(code.starts_with("/// Halo2 KZG (SHPLONK) proof verifier"));
+    assert!(code.contains("module verifier::ordered_pair {"));
+    assert!(code.contains("const NUM_INSTANCES: u64 = 2;"));
+    assert!(!code.contains("__"), "unfilled template placeholder");
+    // Values are computed before they are pushed, so no push borrows the
+    // vector it pushes onto.
+    assert!(code
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("vector::push_back(&mut s") || line.starts_with("vector::push_back(&mut p"))
+        .all(|line| line == "vector::push_back(&mut s, fr_tmp);" || line == "vector::push_back(&mut p, g1_tmp);"));
+
+    let prover = circuit.build_prover(keygen.config_params.clone(), keygen.break_points());
+    let instances = prover.instances();
+    let proof = gen_evm_proof_shplonk(&params, &pk, prover, instances);
+    assert!(code.contains(&format!("const PROOF_LEN: u64 = {};", proof.len())));
+
+    // Regenerating from the same key yields the same module.
+    assert_eq!(
+        code,
+        gen_move_verifier_code(&params, pk.get_vk(), num_instance, "verifier::ordered_pair")
+    );
+}
+
+#[test]
+fn test_constant_powers_are_folded() {
+    let loader = MoveLoader::new();
+    let base = loader.load_const(&Fr::from(3));
+    let exp = loader.load_const(&Fr::from(5));
+    assert_eq!(base.pow_var(&exp, 8), loader.load_const(&Fr::from(243)));
+}
@@ -0,0 +1,157 @@
+use std::io::Cursor;
+
+use diem_prover_halo2::ptau::PowersOfTau;
+use halo2_proofs_axiom::{
+    halo2curves::{
+        bn256::{Bn256, Fq, Fq2, Fr, G1Affine, G2Affine},
+        ff::{Field, PrimeField},
+        group::{prime::PrimeCurveAffine, Curve},
+        serde::SerdeObject,
+        CurveAffine,
+    },
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+const POWER: u32 = 4;
+
+fn section(id: u32, data: Vec<u8>) -> Vec<u8> {
+    [
+        id.to_le_bytes().to_vec(),
+        (data.len() as u64).to_le_bytes().to_vec(),
+        data,
+    ]
+    .concat()
+}
+
+/// A snarkjs-layout `.ptau` file for `tau` with `2^POWER` powers.
+fn synthetic_ptau(tau: Fr, tamper: impl Fn(&mut Vec<G1Affine>)) -> Vec<u8> {
+    let num_g1 = (1 << (POWER + 1)) - 1;
+    let num_g2 = 1 << POWER;
+    let powers: Vec<Fr> = (0..num_g1)
+        .scan(Fr::one(), |acc, _| {
+            let power = *acc;
+            *acc *= tau;
+            Some(power)
+        })
+        .collect();
+
+    let mut g1: Vec<G1Affine> = powers
+        .iter()
+        .map(|power| (G1Affine::generator() * power).to_affine())
+        .collect();
+    tamper(&mut g1);
+    let g2: Vec<G2Affine> = powers[..num_g2]
+        .iter()
+        .map(|power| (G2Affine::generator() * power).to_affine())
+        .collect();
+
+    // q is stored in standard (not Montgomery) form.
+    let q = -Fq::one();
+    let mut q_bytes = q.to_repr();
+    q_bytes[0] += 1;
+    let header = [
+        32u32.to_le_bytes().to_vec(),
+        q_bytes.to_vec(),
+        POWER.to_le_bytes().to_vec(),
+        POWER.to_le_bytes().to_vec(),
+    ]
+    .concat();
+
+    [
+        b"ptau".to_vec(),
+        1u32.to_le_bytes().to_vec(),
+        3u32.to_le_bytes().to_vec(),
+        section(1, header),
+        section(2, g1.iter().flat_map(|p| p.to_raw_bytes()).collect()),
+        section(3, g2.iter().flat_map(|p| p.to_raw_bytes()).collect()),
+    ]
+    .concat()
+}
+
+fn params_bytes(params: &ParamsKZG<Bn256>) -> Vec<u8> {
+    let mut bytes = vec![];
+    params.write(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn test_ptau_matches_setup_with_same_tau() {
+    let tau = Fr::from(0x5eed);
+    let ptau = synthetic_ptau(tau, |_| {});
+
+    for k in 1..=POWER {
+        let powers = PowersOfTau::read(&mut Cursor::new(&ptau), k).unwrap();
+        assert_eq!(powers.k(), k);
+        powers.verify(StdRng::seed_from_u64(30)).unwrap();
+
+        let expected = ParamsKZG::<Bn256>::unsafe_setup_with_s(k, tau);
+        assert_eq!(params_bytes(&powers.params()), params_bytes(&expected));
+    }
+}
+
+#[test]
+fn test_ptau_rejects_inconsistent_power() {
+    let ptau = synthetic_ptau(Fr::from(0x5eed), |g1| {
+        g1[3] = (g1[3].to_curve() + G1Affine::generator()).to_affine();
+    });
+    let powers = PowersOfTau::read(&mut Cursor::new(&ptau), 3).unwrap();
+    assert!(powers.verify(StdRng::seed_from_u64(30)).is_err());
+
+    // Powers past 2^k are not read, so a smaller k is unaffected.
+    let powers = PowersOfTau::read(&mut Cursor::new(&ptau), 1).unwrap();
+    powers.verify(StdRng::seed_from_u64(30)).unwrap();
+}
+
+#[test]
+fn test_ptau_rejects_wrong_generator() {
+    let ptau = synthetic_ptau(Fr::from(0x5eed), |g1| {
+        for point in g1.iter_mut() {
+            *point = (*point * Fr::from(2)).to_affine();
+        }
+    });
+    let powers = PowersOfTau::read(&mut Cursor::new(&ptau), 2).unwrap();
+    assert!(powers.verify(StdRng::seed_from_u64(30)).is_err());
+}
+
+#[test]
+fn test_ptau_rejects_malformed_files() {
+    let ptau = synthetic_ptau(Fr::from(0x5eed), |_| {});
+    assert!(PowersOfTau::read(&mut Cursor::new(&ptau), POWER + 1).is_err());
+    assert!(PowersOfTau::read(&mut Cursor::new(&ptau), 0).is_err());
+
+    let mut bad_magic = ptau.clone();
+    bad_magic[0] = b'x';
+    assert!(PowersOfTau::read(&mut Cursor::new(&bad_magic), 1).is_err());
+
+    // Corrupt the y coordinate of tau * G1 so it falls off the curve.
+    let mut off_curve = ptau.clone();
+    let tau_g1 = 4 + 4 + 4 + (4 + 8 + 4 + 32 + 4 + 4) + (4 + 8) + 64;
+    off_curve[tau_g1 + 32] ^= 1;
+    assert!(PowersOfTau::read(&mut Cursor::new(&off_curve), 1).is_err());
+
+    assert!(PowersOfTau::read(&mut Cursor::new(&ptau[..100]), 1).is_err());
+}
+
+#[test]
+fn test_ptau_rejects_g2_outside_subgroup() {
+    // A point on the twist which, like almost all of them, is not in the
+    // order-r subgroup.
+    let point = (1u64..)
+        .find_map(|x| {
+            let x = Fq2 {
+                c0: Fq::from(x),
+                c1: Fq::ZERO,
+            };
+            Option::from((x.square() * x + G2Affine::b()).sqrt()).map(|y| G2Affine { x, y })
+        })
+        .unwrap();
+    assert!(bool::from(point.is_on_curve()));
+
+    // Replace tau * G2, the second point of the G2 section.
+    let mut ptau = synthetic_ptau(Fr::from(0x5eed), |_| {});
+    let num_g1 = (1 << (POWER + 1)) - 1;
+    let tau_g2 = 4 + 4 + 4 + (4 + 8 + 4 + 32 + 4 + 4) + (4 + 8 + 64 * num_g1) + (4 + 8) + 128;
+    ptau[tau_g2..tau_g2 + 128].copy_from_slice(&point.to_raw_bytes());
+    assert!(PowersOfTau::read(&mut Cursor::new(&ptau), 1).is_err());
+}
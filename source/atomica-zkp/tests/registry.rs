@@ -0,0 +1,214 @@
+mod common;
+
+use ark_bls12_381::{Fr as BlsScalar, G1Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::CanonicalSerialize;
+use diem_prover_halo2::{
+    dkg::{native, DkgAggregationCircuit},
+    equivalence::{EquivalenceCircuit, EQUIVALENCE_CIRCUIT_PARAMS},
+    halo2_base::gates::circuit::CircuitBuilderStage,
+    registry::{
+        keys::{CircuitKeys, KeyCache},
+        CircuitRegistry,
+    },
+    verifier::verify_evm_proof,
+};
+use halo2_proofs_axiom::{halo2curves::bn256::Bn256, poly::kzg::commitment::ParamsKZG};
+use rand::{rngs::StdRng, SeedableRng};
+use snark_verifier_sdk::CircuitExt;
+use std::{io, path::PathBuf};
+
+fn compressed(point: &G1Affine) -> Vec<u8> {
+    let mut bytes = vec![];
+    point.serialize_compressed(&mut bytes).unwrap();
+    bytes
+}
+
+fn contributions(secrets: &[u64]) -> Vec<G1Affine> {
+    secrets
+        .iter()
+        .map(|secret| (G1Affine::generator() * BlsScalar::from(*secret)).into_affine())
+        .collect()
+}
+
+#[test]
+fn test_builtin_ids_are_stable() {
+    let registry = CircuitRegistry::builtin();
+    let ids: Vec<_> = registry.iter().map(|info| info.id).collect();
+    assert_eq!(
+        ids,
+        [
+            "equivalence",
+            "ibe-decryption",
+            "auction-clearing",
+            "ed25519-raw-transaction",
+            "dkg-aggregation",
+            "epoch-change"
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "registered twice")]
+fn test_register_rejects_duplicate_ids() {
+    let mut registry = CircuitRegistry::builtin();
+    let info = *registry.get("dkg-aggregation").unwrap();
+    registry.register(info);
+}
+
+/// Examples build, and expose as many instances as their layout declares.
+#[test]
+fn test_examples_match_instance_layout() {
+    let registry = CircuitRegistry::builtin();
+    for info in registry.iter() {
+        let builder = (info.example)().build(CircuitBuilderStage::Mock, info.params);
+        let num_instance = builder.num_instance()[0];
+        let fixed: usize = info.instances.iter().filter_map(|field| field.words).sum();
+        match info.num_instance() {
+            Some(words) => assert_eq!(num_instance, words, "{}", info.id),
+            None => assert!(num_instance >= fixed, "{}", info.id),
+        }
+    }
+}
+
+#[test]
+fn test_load_dkg_witness() {
+    let registry = CircuitRegistry::builtin();
+    let soks = native::encode_soks(&contributions(&[11, 22, 33]));
+    // The rest of the transcript is not part of the witness.
+    let transcript = [soks.as_slice(), &[0xab; 64]].concat();
+    let json = format!(
+        r#"{{"epoch": 7, "transcript": "0x{}"}}"#,
+        hex::encode(&transcript)
+    );
+    let info = registry.get("dkg-aggregation").unwrap();
+    let circuit = registry.load_witness(info.id, &json).unwrap();
+    let builder = circuit.build(CircuitBuilderStage::Mock, info.params);
+    assert_eq!(
+        builder.instances()[0],
+        DkgAggregationCircuit::new(7, soks).instances()
+    );
+}
+
+#[test]
+fn test_load_witness_rejects_bad_input() {
+    let registry = CircuitRegistry::builtin();
+    let err = registry.load_witness("groth16", "{}").err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    let soks = native::encode_soks(&contributions(&[11]));
+    let mut infinity = soks.clone();
+    infinity[1 + native::CONTRIBUTION_OFFSET..1 + native::SIGNATURE_LENGTH_OFFSET]
+        .copy_from_slice(&compressed(&G1Affine::zero()));
+    for json in [
+        "{}".to_string(),
+        r#"{"epoch": 7, "transcript": "0x"}"#.to_string(),
+        r#"{"epoch": 7, "transcript": "0x00"}"#.to_string(),
+        format!(
+            r#"{{"epoch": 7, "transcript": "0x{}"}}"#,
+            hex::encode(&soks[..soks.len() - 1])
+        ),
+        format!(
+            r#"{{"epoch": 7, "transcript": "0x{}"}}"#,
+            hex::encode(&infinity)
+        ),
+    ] {
+        let err = registry
+            .load_witness("dkg-aggregation", &json)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{json}");
+    }
+
+    let short_key = r#"{"public_key": "0x00", "signature": "0x00", "raw_transaction": "0x"}"#;
+    let err = registry
+        .load_witness("ed25519-raw-transaction", short_key)
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+fn equivalence_params() -> ParamsKZG<Bn256> {
+    ParamsKZG::setup(EQUIVALENCE_CIRCUIT_PARAMS.k, StdRng::seed_from_u64(7))
+}
+
+/// A fresh key cache directory, unique to the test.
+fn key_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("atomica-zkp-{test}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_key_cache_reuses_keys() {
+    let registry = CircuitRegistry::builtin();
+    let info = registry.get("equivalence").unwrap();
+    let params = equivalence_params();
+    let dir = key_dir("key-cache");
+    let cache = KeyCache::new(&dir);
+
+    let generated = cache.keys(info, &params).unwrap();
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+    let cached = cache.keys(info, &params).unwrap();
+    assert_eq!(
+        cached.vk().transcript_repr(),
+        generated.vk().transcript_repr()
+    );
+    assert_eq!(
+        serde_json::to_string(&cached.layout).unwrap(),
+        serde_json::to_string(&generated.layout).unwrap()
+    );
+
+    let (instances, proof) = cached.prove(&params, &EquivalenceCircuit { private_input: 42 });
+    assert!(verify_evm_proof(&params, cached.vk(), &instances, &proof));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_key_cache_rejects_other_k() {
+    let registry = CircuitRegistry::builtin();
+    let info = registry.get("equivalence").unwrap();
+    let params = ParamsKZG::<Bn256>::setup(info.params.k + 1, StdRng::seed_from_u64(7));
+    let dir = key_dir("key-cache-k");
+
+    let err = KeyCache::new(&dir).keys(info, &params).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(!dir.exists());
+}
+
+#[test]
+fn test_move_verifier_for_registered_circuit() {
+    let registry = CircuitRegistry::builtin();
+    let info = registry.get("equivalence").unwrap();
+    let params = equivalence_params();
+    let keys = CircuitKeys::generate(info, &params);
+
+    let code = keys.move_verifier(&params, "verifier::equivalence");
+    assert!(code.contains("module verifier::equivalence {"));
+    assert!(code.contains("const NUM_INSTANCES: u64 = 0;"));
+    let (_, proof) = keys.prove(&params, (info.example)().as_ref());
+    assert!(code.contains(&format!("const PROOF_LEN: u64 = {};", proof.len())));
+}
+
+#[cfg(feature = "revm")]
+#[test]
+fn test_equivalence_verification_gas() {
+    common::require_solc();
+    let registry = CircuitRegistry::builtin();
+    let info = registry.get("equivalence").unwrap();
+    let params = equivalence_params();
+    let dir = key_dir("gas");
+    let keys = KeyCache::new(&dir).keys(info, &params).unwrap();
+
+    let (instances, proof) = keys.prove(&params, (info.example)().as_ref());
+    let gas = keys.verification_gas(&params, &instances, &proof).unwrap();
+    // Catches regressions in the generated verifier's cost.
+    assert!(gas < 450_000, "verification took {gas} gas");
+
+    let mut bad_proof = proof;
+    bad_proof[0] ^= 1;
+    assert!(keys
+        .verification_gas(&params, &instances, &bad_proof)
+        .is_err());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -173,3 +173,35 @@ fn test_solidity_verifier_local_setup() {
     let params_path = Path::new("dummy_path");
     run_solidity_verifier_test(params_path, false);
 }
+
+/// The registry-driven export behind `circuits solidity`: keys from the key
+/// cache and the verifier source from `CircuitKeys::solidity_verifier`, which
+/// must compile and accept a proof of the registered example.
+#[cfg(feature = "revm")]
+#[test]
+fn test_solidity_verifier_registry_export() {
+    use diem_prover_halo2::registry::{keys::KeyCache, CircuitRegistry};
+    use snark_verifier_sdk::snark_verifier::loader::evm::{
+        compile_solidity, deploy_and_call, encode_calldata,
+    };
+
+    common::require_solc();
+    let registry = CircuitRegistry::builtin();
+    let info = registry.get("equivalence").unwrap();
+    let params = ParamsKZG::<Bn256>::setup(info.params.k, OsRng);
+    let key_dir =
+        std::env::temp_dir().join(format!("atomica-zkp-solidity-registry-{}", std::process::id()));
+    let keys = KeyCache::new(&key_dir)
+        .keys(info, &params)
+        .expect("key generation failed");
+
+    let deployment_code = compile_solidity(&keys.solidity_verifier(&params));
+    let (instances, proof) = keys.prove(&params, (info.example)().as_ref());
+    std::fs::remove_dir_all(&key_dir).unwrap();
+
+    deploy_and_call(deployment_code.clone(), encode_calldata(&instances, &proof))
+        .expect("exported verifier rejected the proof");
+    let mut bad_proof = proof;
+    bad_proof[0] ^= 1;
+    assert!(deploy_and_call(deployment_code, encode_calldata(&instances, &bad_proof)).is_err());
+}
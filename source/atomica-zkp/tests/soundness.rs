@@ -0,0 +1,139 @@
+#![cfg(feature = "revm")]
+
+mod common;
+
+use common::{require_solc, OrderedPair};
+use diem_prover_halo2::{
+    circuit::BuilderCircuit,
+    halo2_base::gates::circuit::{builder::BaseCircuitBuilder, CircuitBuilderStage},
+    soundness::{Mutation, SoundnessHarness, VerifierUnderTest},
+};
+use halo2_proofs_axiom::{
+    halo2curves::bn256::{Bn256, Fr, G1Affine},
+    plonk::ProvingKey,
+    poly::kzg::commitment::ParamsKZG,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use snark_verifier_sdk::{
+    evm::{gen_evm_proof_shplonk, gen_evm_verifier_shplonk},
+    gen_pk, CircuitExt,
+};
+
+struct Keys {
+    pk: ProvingKey<G1Affine>,
+    builder: BaseCircuitBuilder<Fr>,
+}
+
+fn keygen(params: &ParamsKZG<Bn256>, circuit: &OrderedPair) -> Keys {
+    let builder = circuit.build(CircuitBuilderStage::Keygen, OrderedPair::PARAMS);
+    let pk = gen_pk(params, &builder, None);
+    Keys { pk, builder }
+}
+
+fn verifier<'a>(
+    name: &str,
+    params: &'a ParamsKZG<Bn256>,
+    keys: &'a Keys,
+    evm: bool,
+) -> VerifierUnderTest<'a> {
+    let num_instance = keys.builder.num_instance();
+    let verifier = VerifierUnderTest::new(name, params, keys.pk.get_vk(), num_instance.clone());
+    if evm {
+        let code = gen_evm_verifier_shplonk::<BaseCircuitBuilder<Fr>>(
+            params,
+            keys.pk.get_vk(),
+            num_instance,
+            None,
+        );
+        verifier.with_evm(code)
+    } else {
+        verifier
+    }
+}
+
+#[test]
+fn test_ordered_pair_rejects_every_mutation() {
+    let params = ParamsKZG::<Bn256>::setup(OrderedPair::PARAMS.k, StdRng::seed_from_u64(31));
+    require_solc();
+
+    let strict = OrderedPair {
+        a: 3,
+        b: 5,
+        strict: true,
+    };
+    let strict_keys = keygen(&params, &strict);
+    // Also holds for (3, 5), so only the verifying key tells the proofs apart.
+    let loose_keys = keygen(
+        &params,
+        &OrderedPair {
+            a: 3,
+            b: 5,
+            strict: false,
+        },
+    );
+
+    let prover = strict.build_prover(
+        strict_keys.builder.config_params.clone(),
+        strict_keys.builder.break_points(),
+    );
+    let instances = prover.instances();
+    let proof = gen_evm_proof_shplonk(&params, &strict_keys.pk, prover, instances.clone());
+
+    let harness = SoundnessHarness::new(verifier("strict", &params, &strict_keys, true))
+        .with_foreign(verifier("loose", &params, &loose_keys, true));
+
+    let mutations = harness.mutations(&instances, &proof);
+    assert!(mutations.contains(&Mutation::SwapInstances { a: 0, b: 1 }));
+    assert!(mutations.contains(&Mutation::ForeignKey { index: 0 }));
+    assert!(mutations
+        .iter()
+        .any(|mutation| matches!(mutation, Mutation::SubstituteCommitment { .. })));
+
+    harness.assert_sound(&instances, &proof);
+}
+
+#[test]
+fn test_swapped_instances_are_not_mutations_when_equal() {
+    let params = ParamsKZG::<Bn256>::setup(OrderedPair::PARAMS.k, StdRng::seed_from_u64(31));
+    let circuit = OrderedPair {
+        a: 4,
+        b: 4,
+        strict: false,
+    };
+    let keys = keygen(&params, &circuit);
+    let prover = circuit.build_prover(
+        keys.builder.config_params.clone(),
+        keys.builder.break_points(),
+    );
+    let instances = prover.instances();
+    let proof = gen_evm_proof_shplonk(&params, &keys.pk, prover, instances.clone());
+
+    let harness = SoundnessHarness::new(verifier("loose", &params, &keys, false));
+    let mutations = harness.mutations(&instances, &proof);
+    assert!(!mutations
+        .iter()
+        .any(|mutation| matches!(mutation, Mutation::SwapInstances { .. })));
+    harness.assert_sound(&instances, &proof);
+}
+
+#[test]
+#[should_panic(expected = "rejects the honest proof")]
+fn test_harness_requires_an_honest_proof() {
+    let params = ParamsKZG::<Bn256>::setup(OrderedPair::PARAMS.k, StdRng::seed_from_u64(31));
+    let circuit = OrderedPair {
+        a: 3,
+        b: 5,
+        strict: true,
+    };
+    let keys = keygen(&params, &circuit);
+    let prover = circuit.build_prover(
+        keys.builder.config_params.clone(),
+        keys.builder.break_points(),
+    );
+    let instances = prover.instances();
+    let mut proof = gen_evm_proof_shplonk(&params, &keys.pk, prover, instances.clone());
+    proof[0] ^= 1;
+
+    SoundnessHarness::new(verifier("strict", &params, &keys, false))
+        .assert_sound(&instances, &proof);
+}
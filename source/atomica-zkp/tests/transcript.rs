@@ -0,0 +1,145 @@
+mod common;
+
+use common::OrderedPair;
+use diem_prover_halo2::{
+    circuit::BuilderCircuit,
+    halo2_base::gates::circuit::CircuitBuilderStage,
+    prover::{poseidon_snark, prove, TranscriptKind},
+    verifier::verify,
+};
+use halo2_proofs_axiom::{
+    halo2curves::{
+        bn256::{Bn256, Fq, Fr, G1Affine},
+        ff::PrimeField,
+        pairing::Engine,
+    },
+    plonk::ProvingKey,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use num_bigint::BigUint;
+use rand::{rngs::StdRng, SeedableRng};
+use snark_verifier_sdk::{
+    gen_pk,
+    halo2::aggregation::{AggregationCircuit, AggregationConfigParams, VerifierUniversality},
+    CircuitExt, SHPLONK,
+};
+
+/// Keygen plus one proof of `OrderedPair::new(3, 5)` per transcript.
+fn prove_ordered_pair(
+    params: &ParamsKZG<Bn256>,
+    transcript: TranscriptKind,
+) -> (ProvingKey<G1Affine>, Vec<Vec<Fr>>, Vec<u8>) {
+    let circuit = OrderedPair::new(3, 5);
+    let keygen = circuit.build(CircuitBuilderStage::Keygen, OrderedPair::PARAMS);
+    let pk = gen_pk(params, &keygen, None);
+    let prover = circuit.build_prover(keygen.config_params.clone(), keygen.break_points());
+    let instances = prover.instances();
+    let proof = prove(params, &pk, prover, instances.clone(), transcript);
+    (pk, instances, proof)
+}
+
+#[test]
+fn test_proofs_verify_only_with_their_own_transcript() {
+    let params = ParamsKZG::<Bn256>::setup(OrderedPair::PARAMS.k, StdRng::seed_from_u64(35));
+    for (transcript, other) in [
+        (TranscriptKind::Poseidon, TranscriptKind::Evm),
+        (TranscriptKind::Evm, TranscriptKind::Poseidon),
+    ] {
+        let (pk, instances, proof) = prove_ordered_pair(&params, transcript);
+        let vk = pk.get_vk();
+        assert!(
+            verify(&params, vk, &instances, &proof, transcript),
+            "{transcript:?}"
+        );
+        assert!(
+            !verify(&params, vk, &instances, &proof, other),
+            "{transcript:?}"
+        );
+
+        let mut wrong = instances.clone();
+        wrong[0][0] += Fr::one();
+        assert!(
+            !verify(&params, vk, &wrong, &proof, transcript),
+            "{transcript:?}"
+        );
+    }
+}
+
+const AGGREGATION_K: u32 = 20;
+
+/// Decodes the KZG accumulator `AggregationCircuit` exposes in its first 12
+/// instances (two G1 points, coordinates as three 88-bit limbs) and runs the
+/// pairing check an on-chain verifier would.
+fn accumulator_holds(params: &ParamsKZG<Bn256>, instances: &[Fr]) -> bool {
+    let coordinate = |limbs: &[Fr]| {
+        let value = limbs.iter().rev().fold(BigUint::default(), |acc, limb| {
+            (acc << 88) + BigUint::from_bytes_le(limb.to_repr().as_ref())
+        });
+        let mut repr = <Fq as PrimeField>::Repr::default();
+        let bytes = value.to_bytes_le();
+        repr.as_mut()[..bytes.len()].copy_from_slice(&bytes);
+        Fq::from_repr(repr).unwrap()
+    };
+    let point = |limbs: &[Fr]| {
+        G1Affine::from_xy(coordinate(&limbs[..3]), coordinate(&limbs[3..6])).unwrap()
+    };
+    let (lhs, rhs) = (point(&instances[..6]), point(&instances[6..12]));
+    Bn256::pairing(&lhs, &params.g2()) == Bn256::pairing(&rhs, &params.s_g2())
+}
+
+#[test]
+#[ignore = "aggregation circuit at k = 20; needs several GB of RAM"]
+fn test_poseidon_proof_verified_by_halo2_loader() {
+    let agg_params = ParamsKZG::<Bn256>::setup(AGGREGATION_K, StdRng::seed_from_u64(35));
+    let mut params = agg_params.clone();
+    params.downsize(OrderedPair::PARAMS.k);
+
+    let (pk, instances, proof) = prove_ordered_pair(&params, TranscriptKind::Poseidon);
+    let snark = poseidon_snark(&params, pk.get_vk(), instances, proof);
+
+    let config = AggregationConfigParams {
+        degree: AGGREGATION_K,
+        lookup_bits: AGGREGATION_K as usize - 1,
+        ..Default::default()
+    };
+    let mut keygen = AggregationCircuit::new::<SHPLONK>(
+        CircuitBuilderStage::Keygen,
+        config,
+        &agg_params,
+        [snark.clone()],
+        VerifierUniversality::None,
+    );
+    keygen.expose_previous_instances(false);
+    let config = keygen.calculate_params(Some(OrderedPair::PARAMS.minimum_rows));
+    let agg_pk = gen_pk(&agg_params, &keygen, None);
+    let break_points = keygen.break_points();
+
+    let mut outer = AggregationCircuit::new::<SHPLONK>(
+        CircuitBuilderStage::Prover,
+        config,
+        &agg_params,
+        [snark],
+        VerifierUniversality::None,
+    )
+    .use_break_points(break_points);
+    outer.expose_previous_instances(false);
+    let outer_instances = outer.instances();
+    // The inner instances follow the accumulator.
+    assert_eq!(&outer_instances[0][12..], &[Fr::from(3), Fr::from(5)]);
+    assert!(accumulator_holds(&agg_params, &outer_instances[0]));
+
+    let outer_proof = prove(
+        &agg_params,
+        &agg_pk,
+        outer,
+        outer_instances.clone(),
+        TranscriptKind::Evm,
+    );
+    assert!(verify(
+        &agg_params,
+        agg_pk.get_vk(),
+        &outer_instances,
+        &outer_proof,
+        TranscriptKind::Evm
+    ));
+}
@@ -0,0 +1,81 @@
+//! Runs the browser verifier under node:
+//!
+//! ```sh
+//! wasm-pack test --node --test wasm -- --no-default-features --features wasm
+//! ```
+//!
+//! The proof is generated inside the test with a seeded RNG, since the wasm
+//! build has no entropy source.
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+mod common;
+
+use common::OrderedPair;
+use diem_prover_halo2::{
+    circuit::BuilderCircuit, envelope::ProofEnvelope,
+    halo2_base::gates::circuit::CircuitBuilderStage, wasm::Verifier,
+};
+use halo2_proofs_axiom::{
+    halo2curves::bn256::{Bn256, G1Affine},
+    plonk::create_proof,
+    poly::{
+        commitment::Params,
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::ProverSHPLONK,
+        },
+    },
+    transcript::TranscriptWriterBuffer,
+    SerdeFormat,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use snark_verifier_sdk::{
+    gen_pk,
+    snark_verifier::{loader::native::NativeLoader, system::halo2::transcript::evm::EvmTranscript},
+    CircuitExt,
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_wasm_verifier_accepts_and_rejects() {
+    let params = ParamsKZG::<Bn256>::setup(OrderedPair::PARAMS.k, StdRng::seed_from_u64(32));
+    let circuit = OrderedPair::new(3, 5);
+    let keygen = circuit.build(CircuitBuilderStage::Keygen, OrderedPair::PARAMS);
+    let pk = gen_pk(&params, &keygen, None);
+
+    let prover = circuit.build_prover(keygen.config_params.clone(), keygen.break_points());
+    let instances = prover.instances();
+    let mut transcript = TranscriptWriterBuffer::<_, G1Affine, _>::init(vec![]);
+    create_proof::<
+        KZGCommitmentScheme<Bn256>,
+        ProverSHPLONK<'_, Bn256>,
+        _,
+        _,
+        EvmTranscript<G1Affine, NativeLoader, Vec<u8>, Vec<u8>>,
+        _,
+    >(
+        &params,
+        &pk,
+        &[prover],
+        &[&[instances[0].as_slice()]],
+        StdRng::seed_from_u64(0),
+        &mut transcript,
+    )
+    .expect("proof generation failed");
+    let proof = transcript.finalize();
+
+    let mut params_bytes = vec![];
+    params.write(&mut params_bytes).unwrap();
+    let vk_bytes = pk.get_vk().to_bytes(SerdeFormat::RawBytes);
+    let config = serde_json::to_string(&keygen.config_params).unwrap();
+    let verifier = Verifier::new(&params_bytes, &vk_bytes, &config).unwrap();
+
+    let envelope = ProofEnvelope::new(instances.clone(), proof.clone());
+    assert!(verifier.verify(&envelope.to_json()).unwrap());
+
+    let swapped = ProofEnvelope::new(vec![vec![instances[0][1], instances[0][0]]], proof);
+    assert!(!verifier.verify(&swapped.to_json()).unwrap());
+
+    assert!(verifier.verify("not json").is_err());
+}